@@ -0,0 +1,58 @@
+//! ライブラリの色を付けない描画 (`Layout`) の速さ
+//!
+//! `cargo bench` で幅と文言の組ごとに 1 秒あたりのフレーム数を出す。色や中央寄せまで
+//! 含めた描画はバイナリの隠しサブコマンド `bench-render` で測る
+
+use fav_say_dragon::layout::{Layout, Overflow};
+use std::hint::black_box;
+use std::time::Instant;
+
+const WIDTHS: &[usize] = &[40, 80, 160];
+const ITERATIONS: usize = 5000;
+
+/// (名前, おかず, キャプション)
+const CASES: &[(&str, &str, &str)] = &[
+    ("short", "からあげ", "好きな総菜発表ドラゴン"),
+    (
+        "long",
+        "ポテトサラダとひじきの煮物と筑前煮と卯の花ときんぴらごぼう",
+        "好きな総菜をたくさん発表するドラゴン",
+    ),
+    ("ascii", "fried chicken", "Favorite side dish dragon"),
+];
+
+fn main() {
+    let ellipsis = Layout {
+        overflow: Overflow::Ellipsis,
+        ..Layout::default()
+    };
+    let layouts = [("truncate", Layout::default()), ("ellipsis", ellipsis)];
+
+    println!(
+        "{:>5}  {:<8}  {:<8}  {:>12}  {:>10}",
+        "width", "text", "overflow", "frames/s", "us/frame"
+    );
+    for &width in WIDTHS {
+        for &(case, side_dish, caption) in CASES {
+            for (overflow, layout) in &layouts {
+                let started = Instant::now();
+                for _ in 0..ITERATIONS {
+                    black_box(
+                        layout
+                            .frame(black_box(side_dish), black_box(caption), width)
+                            .unwrap(),
+                    );
+                }
+                let elapsed = started.elapsed().as_secs_f64();
+                println!(
+                    "{:>5}  {:<8}  {:<8}  {:>12.0}  {:>10.2}",
+                    width,
+                    case,
+                    overflow,
+                    ITERATIONS as f64 / elapsed,
+                    elapsed * 1e6 / ITERATIONS as f64
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+/* fav_say_dragon の描画を埋め込むための宣言 (`cargo build --lib --release --features ffi`) */
+#ifndef FAV_SAY_DRAGON_H
+#define FAV_SAY_DRAGON_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* NUL 終端の UTF-8 のおかずを描き、改行区切りの行を返す。各行は表示幅 width まで空白で埋める
+ * (0 なら埋めない)。text が NULL か UTF-8 でなければ NULL。戻り値は fsd_free で解放する */
+char *fsd_render(const char *text, size_t width);
+
+/* fsd_render の戻り値を解放する (NULL なら何もしない) */
+void fsd_free(char *frame);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
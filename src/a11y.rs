@@ -0,0 +1,80 @@
+//! `--a11y` のスクリーンリーダー向けの出力
+//!
+//! アートと画面の消去を使わず、フレームごとに「誰が何を言ったか」を短い文で出す。アニメーション
+//! では再生を始めてからの時刻を頭に付ける
+
+use crate::{i18n, markup, RenderOptions};
+use std::path::Path;
+use std::time::Duration;
+
+/// おかずとキャプションと選択肢を読み上げる行にする (空のものは出さない)
+pub fn narration(
+    side_dish: &str,
+    caption: &str,
+    choices: &[String],
+    opts: &RenderOptions,
+) -> Vec<String> {
+    let text = |text: &str| {
+        flatten(&markup::Styled::parse(&opts.normalize.apply(&opts.expand(text))).plain)
+    };
+    let says = || {
+        i18n::tr(&format!(
+            "{} のセリフ: {}",
+            speaker(&opts.character),
+            text(side_dish)
+        ))
+        .into_owned()
+    };
+    let mut lines = Vec::new();
+    if !text(side_dish).is_empty() {
+        lines.push(says());
+    }
+    // リンクは読み上げても押せないので `名前 (URL)` にする
+    let caption = flatten(
+        &markup::Styled::caption(&opts.normalize.apply(&opts.expand(caption)), false).plain,
+    );
+    if !caption.is_empty() {
+        lines.push(i18n::tr(&format!("キャプション: {}", caption)).into_owned());
+    }
+    for (n, choice) in choices.iter().enumerate() {
+        lines.push(i18n::tr(&format!("選択肢 {}: {}", n + 1, text(choice))).into_owned());
+    }
+    // 何も無いフレームも時刻だけは分かるようにする
+    if lines.is_empty() {
+        lines.push(says());
+    }
+    lines
+}
+
+/// 先頭の行に再生を始めてからの時刻 `[分:秒.1/10 秒]` を付ける
+pub fn stamp(mut lines: Vec<String>, at: Duration) -> Vec<String> {
+    let tenths = at.as_millis() / 100;
+    if let Some(first) = lines.first_mut() {
+        *first = format!(
+            "[{}:{:02}.{}] {}",
+            tenths / 600,
+            tenths / 10 % 60,
+            tenths % 10,
+            first
+        );
+    }
+    lines
+}
+
+/// キャラクターの名前 (`dragon-santa` や `chars/cat.toml` は `Dragon`、`Cat`)
+fn speaker(character: &str) -> String {
+    let stem = Path::new(character)
+        .file_stem()
+        .map_or_else(|| character.into(), |stem| stem.to_string_lossy());
+    let name = stem.split('-').next().unwrap_or_default();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// 改行や続く空白を 1 つの空白にまとめる
+fn flatten(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,608 @@
+use anyhow::{bail, Result};
+use fav_say_dragon::layout::DRAGON;
+use std::collections::BTreeMap;
+
+#[rustfmt::skip]
+const DRAGON_ASCII: &str = r#"                                  __
+                                 /o \__
++--------------------+          /     _)
+|                    |         /   _/
+|                    |   /|   /   /
+|$line1$ >  / |__/   /
+|$line2$|  /        /--.__
+|                    | (   ___      \
+|                    |  \_/   \  ,   )
++--------------------+       / /  \ \ \
+                            (_/   (_/\_\"#;
+
+pub const DEFAULT_CHARACTER: &str = "dragon";
+/// `--ascii` のときに組み込みのキャラクターの代わりに使う
+pub const ASCII_CHARACTER: &str = "dragon-ascii";
+/// 組み込みのキャラクターから `--seed` に従って選ぶ
+pub const RANDOM_CHARACTER: &str = "random";
+
+#[derive(Clone, Debug)]
+pub struct Art {
+    pub template: String,
+    /// 吹き出しが無いアートでは `None`
+    pub bubble: Option<Bubble>,
+    /// `say --idle` でときどき差し替える待機中のテンプレート (まばたきなど)。行数は同じ
+    pub idle: Vec<String>,
+    /// `anime --typewriter` で文字を出しながら交互に見せる口を開けたテンプレート。行数は同じ
+    pub mouth: Option<String>,
+    /// 名前付きの欄 (`$sign1$` など) の大きさ。書いていない欄は `Art::fields` で決める
+    pub fields: BTreeMap<String, Field>,
+}
+
+/// 名前付きの欄の大きさ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    /// 1 行の表示幅
+    pub width: usize,
+    /// 行数 (テンプレートの欄の行数まで)
+    pub height: usize,
+}
+
+impl Art {
+    /// テンプレートの名前付きの欄とその大きさ。大きさが書かれていなければ、幅は 1 行目の印の幅、
+    /// 行数はテンプレートの欄の行数
+    pub fn fields(&self) -> Vec<(String, Field)> {
+        crate::template::Template::parse(&self.template)
+            .fields()
+            .into_iter()
+            .map(|(name, rows)| {
+                let field = match self.fields.get(&name) {
+                    Some(field) => Field {
+                        height: field.height.min(rows),
+                        ..*field
+                    },
+                    None => Field {
+                        width: console::measure_text_width(&format!("${}1$", name)),
+                        height: rows,
+                    },
+                };
+                (name, field)
+            })
+            .collect()
+    }
+}
+
+/// テンプレート中の吹き出しが占める範囲
+#[derive(Clone, Copy, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bubble {
+    /// 上枠の行
+    pub top: usize,
+    /// 下枠の行
+    pub bottom: usize,
+    /// 枠を含む表示幅
+    pub width: usize,
+    /// しっぽのある行とその向き (無ければ `--tail` はおかずの 1 行目に描く)
+    #[serde(default)]
+    pub tail: Option<Tail>,
+}
+
+/// 吹き出しのしっぽ
+#[derive(Clone, Copy, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tail {
+    pub row: usize,
+    pub side: TailSide,
+}
+
+/// しっぽを出す吹き出しの辺
+#[derive(clap::ValueEnum, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TailSide {
+    /// 左の枠から左へ
+    Left,
+    /// 右の枠の外へ (組み込みのドラゴン)
+    Right,
+    /// しっぽを描かずに枠を閉じる
+    None,
+}
+
+#[rustfmt::skip]
+const SANTA_HAT: &str = "                                            ,-‐-､
+                                          ／     ヽ○
+                                         (＿＿＿＿)";
+
+#[rustfmt::skip]
+const KADOMATSU: &str = "  ／|  ／|
+ / ／| / |
+ | || | ||
+ | || | ||
+(＿＿＿＿＿)
+ |＃＃＃＃|
+ |＃＃＃＃|";
+
+/// 看板の中の表示幅
+const SIGN_WIDTH: usize = 12;
+
+#[rustfmt::skip]
+const SIGN: &str = "                                    +------------+
+                                    |$sign1$|
+                                    +-----+------+
+                                          |";
+
+const DRAGON_BUBBLE: Bubble = Bubble {
+    top: 2,
+    bottom: 9,
+    width: 22,
+    tail: Some(Tail {
+        row: 5,
+        side: TailSide::Right,
+    }),
+};
+
+/// 上に `rows` 行を足したテンプレートでの吹き出しの範囲
+fn shift(bubble: Bubble, rows: usize) -> Bubble {
+    Bubble {
+        top: bubble.top + rows,
+        bottom: bubble.bottom + rows,
+        tail: bubble.tail.map(|tail| Tail {
+            row: tail.row + rows,
+            ..tail
+        }),
+        ..bubble
+    }
+}
+
+type Builder = fn() -> Art;
+
+pub const BUILTIN: &[(&str, Builder)] = &[
+    ("dragon", dragon),
+    ("dragon-santa", dragon_santa),
+    ("dragon-newyear", dragon_newyear),
+    ("dragon-ascii", dragon_ascii),
+    ("dragon-color", dragon_color),
+    ("dragon-sign", dragon_sign),
+];
+
+fn dragon() -> Art {
+    Art {
+        template: DRAGON.to_string(),
+        bubble: Some(DRAGON_BUBBLE),
+        idle: vec![blink(DRAGON)],
+        mouth: Some(open_mouth(DRAGON)),
+        fields: BTreeMap::new(),
+    }
+}
+
+/// 目を閉じたドラゴン
+fn blink(template: &str) -> String {
+    template.replacen('∩', "‐", 1).replacen("/o ", "/- ", 1)
+}
+
+/// 口を開けたドラゴン
+fn open_mouth(template: &str) -> String {
+    template.replacen("｀＞", "｀＜", 1).replacen("_)", "_O", 1)
+}
+
+/// 頭の上にサンタ帽を重ねる
+fn dragon_santa() -> Art {
+    let hat_height = SANTA_HAT.lines().count();
+    Art {
+        template: format!("{}\n{}", SANTA_HAT, DRAGON),
+        bubble: Some(shift(DRAGON_BUBBLE, hat_height)),
+        idle: vec![format!("{}\n{}", SANTA_HAT, blink(DRAGON))],
+        mouth: Some(format!("{}\n{}", SANTA_HAT, open_mouth(DRAGON))),
+        fields: BTreeMap::new(),
+    }
+}
+
+/// 頭の上に `$sign1$` の欄のある看板を掲げる
+fn dragon_sign() -> Art {
+    let sign_height = SIGN.lines().count();
+    Art {
+        template: format!("{}\n{}", SIGN, DRAGON),
+        bubble: Some(shift(DRAGON_BUBBLE, sign_height)),
+        idle: vec![format!("{}\n{}", SIGN, blink(DRAGON))],
+        mouth: Some(format!("{}\n{}", SIGN, open_mouth(DRAGON))),
+        fields: BTreeMap::from([(
+            "sign".to_string(),
+            Field {
+                width: SIGN_WIDTH,
+                height: 1,
+            },
+        )]),
+    }
+}
+
+/// 右下に門松を置く
+fn dragon_newyear() -> Art {
+    Art {
+        template: with_backdrop(DRAGON, KADOMATSU, 64),
+        bubble: Some(DRAGON_BUBBLE),
+        idle: vec![with_backdrop(&blink(DRAGON), KADOMATSU, 64)],
+        mouth: Some(with_backdrop(&open_mouth(DRAGON), KADOMATSU, 64)),
+        fields: BTreeMap::new(),
+    }
+}
+
+/// ASCII だけで描いたドラゴン
+fn dragon_ascii() -> Art {
+    Art {
+        template: DRAGON_ASCII.to_string(),
+        bubble: Some(DRAGON_BUBBLE),
+        idle: vec![blink(DRAGON_ASCII)],
+        mouth: Some(open_mouth(DRAGON_ASCII)),
+        fields: BTreeMap::new(),
+    }
+}
+
+/// 上から下へ明るい緑から濃い緑にぼかしたドラゴン
+fn dragon_color() -> Art {
+    Art {
+        template: shade(DRAGON),
+        bubble: Some(DRAGON_BUBBLE),
+        idle: vec![shade(&blink(DRAGON))],
+        mouth: Some(shade(&open_mouth(DRAGON))),
+        fields: BTreeMap::new(),
+    }
+}
+
+/// 吹き出しの右のアートに、行ごとに上から下へ変わる色の指定を付ける (目は別の色)
+fn shade(template: &str) -> String {
+    const TOP: [u8; 3] = [0x9b, 0xe3, 0x7a];
+    const BOTTOM: [u8; 3] = [0x2f, 0x7d, 0x4a];
+    const EYE: &str = "#ffd23f{∩}";
+
+    let slot_width = fav_say_dragon::layout::BUBBLE_CHARS + BUBBLE_PADDING;
+    let lines: Vec<&str> = template.lines().collect();
+    let last = lines.len().saturating_sub(1).max(1) as f64;
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let Some((left, body)) = split_at_column(line, DRAGON_BUBBLE.width, slot_width) else {
+                return line.to_string();
+            };
+            let art = body.trim_start();
+            if art.is_empty() {
+                return line.to_string();
+            }
+            let t = i as f64 / last;
+            let [r, g, b] = [0, 1, 2]
+                .map(|c| (TOP[c] as f64 + (BOTTOM[c] as f64 - TOP[c] as f64) * t).round() as u8);
+            format!(
+                "{}{}#{:02x}{:02x}{:02x}{{{}}}",
+                left,
+                &body[..body.len() - art.len()],
+                r,
+                g,
+                b,
+                art.replacen('∩', EYE, 1)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// ロケールが UTF-8 でないと明示されているか (LC_ALL, LC_CTYPE, LANG の順に見る)
+pub fn legacy_locale() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            !(locale.contains("utf-8") || locale.contains("utf8"))
+        }
+        None => false,
+    }
+}
+
+/// `backdrop` を下揃えで `column` 列目から並べる
+fn with_backdrop(template: &str, backdrop: &str, column: usize) -> String {
+    let lines: Vec<&str> = template.lines().collect();
+    let backdrop: Vec<&str> = backdrop.lines().collect();
+    let offset = lines.len().saturating_sub(backdrop.len());
+    lines
+        .iter()
+        .enumerate()
+        .map(
+            |(i, line)| match i.checked_sub(offset).and_then(|j| backdrop.get(j)) {
+                Some(backdrop) => {
+                    let padding = column.saturating_sub(console::measure_text_width(line));
+                    format!("{}{}{}", line, " ".repeat(padding), backdrop)
+                }
+                None => line.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub use fav_say_dragon::layout::BUBBLE_PADDING;
+
+/// 吹き出しを 1 行 `columns` 文字、`rows` 行の大きさに描き直す
+///
+/// `slot_width` は今のテンプレートで 1 つの口が占める表示幅。吹き出しの右側のアートは
+/// 横にずらし、行が足りなければ下に伸ばす
+pub fn resize_bubble(art: &Art, columns: usize, rows: usize, slot_width: usize) -> Result<Art> {
+    let Some(bubble) = art.bubble else {
+        bail!("吹き出しの範囲がわからないので大きさを変えられません");
+    };
+    let template = crate::template::Template::parse(&art.template);
+    if template.has_repeat() {
+        bail!("繰り返しのあるテンプレートの吹き出しは大きさを変えられません");
+    }
+    let slot_row = |n: usize| {
+        template
+            .slot_positions()
+            .into_iter()
+            .find(|(_, _, name)| *name == format!("line{}", n))
+            .map(|(row, _, _)| row)
+    };
+    let (Some(first), Some(last)) = (slot_row(1), slot_row(template.line_slots())) else {
+        bail!("吹き出しにおかずを置く印がありません");
+    };
+
+    let mut lefts = Vec::new();
+    let mut bodies = Vec::new();
+    for (i, line) in art.template.lines().enumerate() {
+        let Some((left, body)) = split_at_column(line, bubble.width, slot_width) else {
+            bail!("{} 行目の吹き出しの境目で文字が切れています", i + 1);
+        };
+        let inside = (bubble.top..=bubble.bottom).contains(&i);
+        if !inside && !left.trim().is_empty() {
+            bail!("{} 行目の吹き出しの左にアートがあります", i + 1);
+        }
+        lefts.push(left);
+        bodies.push(body);
+    }
+
+    let width = columns + BUBBLE_PADDING + 2;
+    let bottom = first + rows - 1 + (bubble.bottom - last);
+    let skeleton: Vec<String> = (0..bodies.len().max(bottom + 1))
+        .map(|i| {
+            let left = match i {
+                // 上下の枠は apply_border で描く
+                _ if i <= bubble.top || i >= bottom => " ".repeat(width),
+                _ if (first..first + rows).contains(&i) => {
+                    // 元の吹き出しで右の枠が開いている行 (しっぽ) はそのまま開けておく
+                    let n = i - first + 1;
+                    let open = slot_row(n)
+                        .and_then(|row| lefts.get(row))
+                        .is_some_and(|left| left.ends_with(' '));
+                    format!("|$line{}${}", n, if open { " " } else { "|" })
+                }
+                _ => format!("|{}|", " ".repeat(width - 2)),
+            };
+            let body = bodies.get(i).map_or("", String::as_str);
+            let line = format!("{}{}", left, body);
+            match i == bubble.top || i == bottom {
+                true => line,
+                false => line.trim_end().to_string(),
+            }
+        })
+        .collect();
+
+    let bubble = Bubble {
+        top: bubble.top,
+        bottom,
+        width,
+        tail: bubble.tail.filter(|tail| tail.row < bottom),
+    };
+    let border = default_border(art.template.is_ascii());
+    // 待機中や口を開けたテンプレートも同じ大きさにする
+    let variant = |template: &String| {
+        let variant = Art {
+            template: template.clone(),
+            bubble: art.bubble,
+            idle: Vec::new(),
+            mouth: None,
+            fields: BTreeMap::new(),
+        };
+        resize_bubble(&variant, columns, rows, slot_width).map(|variant| variant.template)
+    };
+    Ok(Art {
+        template: crate::theme::apply_border(&skeleton.join("\n"), &bubble, &border),
+        bubble: Some(bubble),
+        idle: art.idle.iter().map(variant).collect::<Result<_>>()?,
+        mouth: art.mouth.as_ref().map(variant).transpose()?,
+        fields: art.fields.clone(),
+    })
+}
+
+/// 吹き出しのしっぽを `side` に描き直す (枠を描き直した後に使う)
+///
+/// 右のしっぽは吹き出しのすぐ右の桁に重ね、左のしっぽは左の枠と置き換える。`slot_width` は
+/// 1 つの口が占める表示幅
+pub fn point_tail(art: &Art, side: TailSide, slot_width: usize) -> Result<Art> {
+    let Some(bubble) = art.bubble else {
+        bail!("吹き出しの範囲がわからないのでしっぽを描き直せません");
+    };
+    let template = crate::template::Template::parse(&art.template);
+    let row = match bubble.tail {
+        Some(tail) => tail.row,
+        None => match template.slot_positions().first() {
+            Some((row, _, _)) => *row,
+            None => bail!("吹き出しにおかずを置く印がありません"),
+        },
+    };
+    let from = bubble.tail.map_or(TailSide::None, |tail| tail.side);
+    if from == side {
+        return Ok(art.clone());
+    }
+    let ascii = art.template.is_ascii();
+    let redraw = |template: &String| -> Result<String> {
+        let mut lines: Vec<String> = template.lines().map(str::to_string).collect();
+        // しっぽの無い行の枠をしっぽを消した後の枠にする
+        let plain = (bubble.top + 1..bubble.bottom)
+            .filter(|i| *i != row)
+            .find_map(|i| split_at_column(lines.get(i)?, bubble.width, slot_width));
+        let (left_edge, right_edge) = match &plain {
+            Some((left, _)) => (first_char(left), left.chars().last().unwrap_or('|')),
+            None => ('|', '|'),
+        };
+        let Some(line) = lines.get_mut(row) else {
+            bail!("しっぽの行 {} がテンプレートの範囲外です", row + 1);
+        };
+        let Some((left, body)) = split_at_column(line, bubble.width, slot_width) else {
+            bail!("{} 行目の吹き出しの境目で文字が切れています", row + 1);
+        };
+        let mut left: Vec<char> = left.chars().collect();
+        let mut body = body;
+        // 元のしっぽを消してから描く
+        match from {
+            TailSide::Left => left[0] = left_edge,
+            TailSide::Right => {
+                let width = body.chars().next().map_or(0, char_width);
+                body = overwrite(&body, &" ".repeat(width));
+                *left.last_mut().unwrap_or(&mut ' ') = right_edge;
+            }
+            TailSide::None => {}
+        }
+        match side {
+            TailSide::Left => left[0] = '<',
+            TailSide::Right => {
+                *left.last_mut().unwrap_or(&mut ' ') = ' ';
+                body = overwrite(&body, if ascii { ">" } else { "＞" });
+            }
+            TailSide::None => {}
+        }
+        *line = format!("{}{}", left.into_iter().collect::<String>(), body)
+            .trim_end()
+            .to_string();
+        Ok(lines.join("\n"))
+    };
+    Ok(Art {
+        template: redraw(&art.template)?,
+        bubble: Some(Bubble {
+            tail: Some(Tail { row, side }),
+            ..bubble
+        }),
+        idle: art.idle.iter().map(redraw).collect::<Result<_>>()?,
+        mouth: art.mouth.as_ref().map(redraw).transpose()?,
+        fields: art.fields.clone(),
+    })
+}
+
+fn first_char(text: &str) -> char {
+    text.chars().next().unwrap_or('|')
+}
+
+fn char_width(c: char) -> usize {
+    console::measure_text_width(c.encode_utf8(&mut [0; 4]))
+}
+
+/// `body` の先頭の桁を `text` で上書きする (色の指定 `#rrggbb{` の中から数える)
+fn overwrite(body: &str, text: &str) -> String {
+    let prefix = crate::markup::open(body).map_or(0, |(_, len)| len);
+    let (prefix, rest) = body.split_at(prefix);
+    let width = console::measure_text_width(text);
+    let mut covered = 0;
+    let mut chars = rest.chars();
+    while covered < width {
+        match chars.next() {
+            Some(c) => covered += char_width(c),
+            None => break,
+        }
+    }
+    // 全角文字の途中までを上書きしたら残りの桁を空白で埋める
+    let padding = " ".repeat(covered.saturating_sub(width));
+    format!("{}{}{}{}", prefix, text, padding, chars.as_str())
+}
+
+/// 表示幅 `column` の位置で行を分ける (口は `slot_width`、`$$` は 1 と数える)
+fn split_at_column(line: &str, column: usize, slot_width: usize) -> Option<(String, String)> {
+    let mut width = 0;
+    let mut rest = line;
+    while width < column && !rest.is_empty() {
+        let (len, w) = match (rest.strip_prefix("$$"), crate::template::slot_at(rest)) {
+            (Some(_), _) => (2, 1),
+            (None, Some(len)) => (len, slot_width),
+            (None, None) => {
+                let c = rest.chars().next()?;
+                (
+                    c.len_utf8(),
+                    console::measure_text_width(c.encode_utf8(&mut [0; 4])),
+                )
+            }
+        };
+        if width + w > column {
+            return None;
+        }
+        width += w;
+        rest = &rest[len..];
+    }
+    let left = &line[..line.len() - rest.len()];
+    Some((
+        format!("{}{}", left, " ".repeat(column - width)),
+        rest.to_string(),
+    ))
+}
+
+/// 組み込みのドラゴンと同じ形の枠
+fn default_border(ascii: bool) -> crate::theme::Border {
+    let [top_left, top, top_right, side, bottom_left, bottom, bottom_right] = match ascii {
+        true => ["+", "-", "+", "|", "+", "-", "+"],
+        false => ["r'", "￣", "ヽ", "|", "ゝ", "＿", "ノ"],
+    };
+    crate::theme::Border {
+        top_left: top_left.to_string(),
+        top: top.to_string(),
+        top_right: top_right.to_string(),
+        left: side.to_string(),
+        right: side.to_string(),
+        bottom_left: bottom_left.to_string(),
+        bottom: bottom.to_string(),
+        bottom_right: bottom_right.to_string(),
+    }
+}
+
+/// アートパック、組み込み、ponysay のポニーの順に探す
+pub fn load(name: &str) -> Result<Art> {
+    if let Some(art) = crate::pack::load(name)? {
+        return Ok(art);
+    }
+    if let Some((_, art)) = BUILTIN.iter().find(|(builtin, _)| *builtin == name) {
+        return Ok(art());
+    }
+    match crate::pony::load(name)? {
+        Some(art) => Ok(art),
+        None => bail!("キャラクター '{}' が見つかりません", name),
+    }
+}
+
+/// 既定の季節ごとのキャラクター (`MM-DD..MM-DD` は年をまたいでもよい)
+pub const DEFAULT_SEASONAL: &[(&str, &str)] = &[
+    ("12-01..12-25", "dragon-santa"),
+    ("12-31..01-07", "dragon-newyear"),
+];
+
+/// `month`/`day` が含まれる期間のキャラクターを返す
+pub fn seasonal<'a>(
+    mapping: impl IntoIterator<Item = (&'a str, &'a str)>,
+    month: u32,
+    day: u32,
+) -> Result<Option<&'a str>> {
+    let parse = |date: &str| -> Result<(u32, u32)> {
+        let (m, d) = date
+            .trim()
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("日付 '{}' は MM-DD 形式で指定してください", date))?;
+        Ok((m.parse()?, d.parse()?))
+    };
+    let today = (month, day);
+
+    for (range, character) in mapping {
+        let (start, end) = range.split_once("..").ok_or_else(|| {
+            anyhow::anyhow!("期間 '{}' は MM-DD..MM-DD 形式で指定してください", range)
+        })?;
+        let (start, end) = (parse(start)?, parse(end)?);
+        let contains = match start <= end {
+            true => start <= today && today <= end,
+            false => start <= today || today <= end,
+        };
+        if contains {
+            return Ok(Some(character));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Background {
+    Dark,
+    Light,
+}
+
+impl Background {
+    /// `(アートの色, キャプションの色)`
+    pub fn default_colors(self) -> (&'static str, &'static str) {
+        match self {
+            Background::Dark => ("white", "yellow.bold"),
+            Background::Light => ("black", "blue.bold"),
+        }
+    }
+}
+
+/// COLORFGBG を優先し、無ければ OSC 11 で端末に問い合わせる
+pub fn detect() -> Option<Background> {
+    if let Some(background) = from_colorfgbg() {
+        return Some(background);
+    }
+    if !console::Term::stdout().is_term() {
+        return None;
+    }
+    query_osc11()
+}
+
+fn from_colorfgbg() -> Option<Background> {
+    let value = std::env::var("COLORFGBG").ok()?;
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    // 0-6 と 8 は暗い色
+    match bg {
+        0..=6 | 8 => Some(Background::Dark),
+        _ => Some(Background::Light),
+    }
+}
+
+/// `rgb:RRRR/GGGG/BBBB` 形式の応答から明暗を判定する
+#[cfg(unix)]
+fn parse_osc11(response: &str) -> Option<Background> {
+    let rgb = response.split("rgb:").nth(1)?;
+    let components: Vec<f64> = rgb
+        .split('/')
+        .take(3)
+        .map(|c| {
+            let c: String = c.chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+            let max = 16f64.powi(c.len() as i32) - 1.0;
+            u32::from_str_radix(&c, 16).ok().map(|v| v as f64 / max)
+        })
+        .collect::<Option<_>>()?;
+    if components.len() != 3 {
+        return None;
+    }
+    let luminance = 0.2126 * components[0] + 0.7152 * components[1] + 0.0722 * components[2];
+    match luminance < 0.5 {
+        true => Some(Background::Dark),
+        false => Some(Background::Light),
+    }
+}
+
+#[cfg(unix)]
+fn query_osc11() -> Option<Background> {
+    let mut tty = crate::tty::RawTty::open()?;
+    let mut response = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x07").is_ok() {
+        let deadline = std::time::Instant::now() + QUERY_TIMEOUT;
+        let mut buf = [0u8; 64];
+        loop {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match tty.read_timeout(&mut buf, Some(remaining)) {
+                0 => break,
+                n => response.extend_from_slice(&buf[..n]),
+            }
+            if response.ends_with(b"\x07") || response.ends_with(b"\x1b\\") {
+                break;
+            }
+        }
+    }
+
+    drop(tty);
+    parse_osc11(&String::from_utf8_lossy(&response))
+}
+
+#[cfg(not(unix))]
+fn query_osc11() -> Option<Background> {
+    None
+}
@@ -0,0 +1,84 @@
+//! 隠しサブコマンド `bench-render` の描画の速さの計測
+//!
+//! 吹き出しの折り返しから飾りと中央寄せまで、`say` と同じ描画を控えを使わずに
+//! 何度もくり返し、端末の幅と文言の組ごとに 1 秒あたりのフレーム数を出す。
+//! 出力はしないので、端末への書き出しの速さは含まない
+
+use crate::{draw_say, RenderOptions};
+use anyhow::Result;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// 既定で測る端末の幅
+pub const WIDTHS: &[usize] = &[40, 80, 160];
+
+/// 測る文言 (名前, おかず, キャプション)
+const CASES: &[(&str, &str, &str)] = &[
+    ("short", "からあげ", "好きな総菜発表ドラゴン"),
+    (
+        "long",
+        "ポテトサラダとひじきの煮物と筑前煮と卯の花ときんぴらごぼう",
+        "好きな総菜をたくさん発表するドラゴン",
+    ),
+    (
+        "markup",
+        "*とんかつ* と #red{エビフライ}",
+        "#cyan{好きな総菜} [発表](https://example.com)",
+    ),
+    ("ascii", "fried chicken", "Favorite side dish dragon"),
+];
+
+/// 1 つの組の結果
+struct Sample {
+    width: usize,
+    case: &'static str,
+    elapsed: Duration,
+    frames: usize,
+}
+
+impl Sample {
+    fn fps(&self) -> f64 {
+        self.frames as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// 幅と文言の組ごとに `iterations` 回描き、結果の表を stdout に出す
+pub fn bench_render(widths: &[usize], iterations: usize, opts: &RenderOptions) -> Result<()> {
+    let iterations = iterations.max(1);
+    let mut samples = Vec::new();
+    for &width in widths {
+        for &(case, side_dish, caption) in CASES {
+            // 最初の 1 回は計らない (アートの読み込みなどを除くため)
+            draw_say(side_dish, Some(caption), width, opts)?;
+            let started = Instant::now();
+            for _ in 0..iterations {
+                black_box(draw_say(
+                    black_box(side_dish),
+                    Some(black_box(caption)),
+                    width,
+                    opts,
+                )?);
+            }
+            samples.push(Sample {
+                width,
+                case,
+                elapsed: started.elapsed(),
+                frames: iterations,
+            });
+        }
+    }
+    println!(
+        "{:>5}  {:<8}  {:>12}  {:>10}",
+        "width", "text", "frames/s", "us/frame"
+    );
+    for sample in &samples {
+        println!(
+            "{:>5}  {:<8}  {:>12.0}  {:>10.2}",
+            sample.width,
+            sample.case,
+            sample.fps(),
+            sample.elapsed.as_secs_f64() * 1e6 / sample.frames as f64
+        );
+    }
+    Ok(())
+}
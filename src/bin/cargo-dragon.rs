@@ -0,0 +1,125 @@
+//! `cargo dragon build` / `cargo dragon test`
+//!
+//! cargo をそのまま実行して stderr を流し、最後にドラゴンが結果を発表する
+
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitCode, Stdio};
+
+/// 発表に使う本体
+const DRAGON: &str = "fav_say_dragon";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // cargo の外部サブコマンドとして呼ばれると最初の引数が "dragon" になる
+    if args.first().map(String::as_str) == Some("dragon") {
+        args.remove(0);
+    }
+    if args.is_empty() {
+        args.push("build".to_string());
+    }
+    let english = english();
+
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = Command::new(cargo);
+    command.args(&args).stderr(Stdio::piped());
+    // パイプにすると cargo が色を付けなくなるので、端末なら元どおり付けさせる
+    if std::io::stderr().is_terminal() && std::env::var_os("CARGO_TERM_COLOR").is_none() {
+        command.env("CARGO_TERM_COLOR", "always");
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("cargo: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (mut errors, mut warnings) = (0, 0);
+    if let Some(stderr) = child.stderr.take() {
+        let mut out = std::io::stderr().lock();
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            writeln!(out, "{}", line).ok();
+            let plain = console::strip_ansi_codes(&line);
+            if is_diagnostic(&plain, "error") {
+                errors += 1;
+            } else if is_diagnostic(&plain, "warning") {
+                warnings += 1;
+            }
+        }
+    }
+    let success = child.wait().is_ok_and(|status| status.success());
+
+    let subcommand = format!("cargo {}", args[0]);
+    let (side_dish, color) = match (success, english) {
+        (true, false) => (format!("{} 成功！", subcommand), "green"),
+        (true, true) => (format!("{} passed!", subcommand), "green"),
+        (false, false) if errors > 0 => (format!("エラー {} 件…", errors), "red"),
+        (false, true) if errors > 0 => (format!("Errors: {}", errors), "red"),
+        (false, false) => (format!("{} 失敗…", subcommand), "red"),
+        (false, true) => (format!("{} failed...", subcommand), "red"),
+    };
+    let caption = match (warnings, english) {
+        (0, _) => String::new(),
+        (n, false) => format!("警告 {} 件", n),
+        (n, true) => format!("Warnings: {}", n),
+    };
+    let shown = Command::new(dragon())
+        .args([
+            "--overflow",
+            "wrap",
+            "--color",
+            color,
+            "say",
+            &side_dish,
+            &caption,
+        ])
+        .status();
+    if let Err(e) = shown {
+        eprintln!("{}: {}", DRAGON, e);
+    }
+
+    match success {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
+
+/// `error: ...` や `error[E0308]: ...` (まとめの行は数えない)
+fn is_diagnostic(line: &str, level: &str) -> bool {
+    let Some(rest) = line.strip_prefix(level) else {
+        return false;
+    };
+    let message = match rest.strip_prefix('[') {
+        Some(code) => code.split_once("]:").map(|(_, message)| message),
+        None => rest.strip_prefix(':'),
+    };
+    message.is_some_and(|message| {
+        let message = message.trim_start();
+        !(message.starts_with("could not compile")
+            || message.starts_with("aborting due to")
+            || message.starts_with("test failed")
+            || (message.starts_with('`') && message.contains(" generated ")))
+    })
+}
+
+/// 同じディレクトリにあればそれを、無ければ PATH から探す
+fn dragon() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(format!("{}{}", DRAGON, std::env::consts::EXE_SUFFIX)))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| DRAGON.into())
+}
+
+/// fav_say_dragon の言語の判定と同じく、日本語か C ロケール以外なら英語
+fn english() -> bool {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale.as_deref() {
+        None | Some("C" | "POSIX") => false,
+        Some(locale) => !(locale.starts_with("C.") || locale.starts_with("ja")),
+    }
+}
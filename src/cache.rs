@@ -0,0 +1,56 @@
+//! 同じおかずとキャプションを同じ幅で描き直すときの描画結果の控え
+//!
+//! `clock` や `daemon`、`serve` のように同じ文言を何度も描くモードで、吹き出しの折り返しや
+//! 埋め草を毎回やり直さないようにする。控えは `RenderOptions` ごとに持ち、複製した
+//! `RenderOptions` は空の控えから始める (色やアートを書き換えても古い結果を使わない)
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 控えておく描画結果の数 (超えたら全部捨てる)
+const CAPACITY: usize = 64;
+
+type Key = (String, Option<String>, usize);
+
+#[derive(Debug, Default)]
+pub struct RenderCache(Mutex<HashMap<Key, Vec<String>>>);
+
+impl Clone for RenderCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl RenderCache {
+    /// 控えがあればそれを、無ければ `render` の結果を控えて返す
+    pub fn get_or_try_insert<E>(
+        &self,
+        side_dish: &str,
+        caption: Option<&str>,
+        terminal_width: usize,
+        render: impl FnOnce() -> Result<Vec<String>, E>,
+    ) -> Result<Vec<String>, E> {
+        let key = (
+            side_dish.to_string(),
+            caption.map(str::to_string),
+            terminal_width,
+        );
+        if let Some(lines) = self.lock().get(&key) {
+            return Ok(lines.clone());
+        }
+        let lines = render()?;
+        let mut cache = self.lock();
+        if cache.len() >= CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, lines.clone());
+        Ok(lines)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Vec<String>>> {
+        // 描いている途中で panic しても控えが壊れるだけなので、そのまま使う
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
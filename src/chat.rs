@@ -0,0 +1,140 @@
+//! `say --format` のチャットに貼り付けるためのコードブロック
+//!
+//! 等幅で表示されるコードブロックに入れて、行末の空白を落とし、表示できない色を取り除く
+
+use crate::{i18n, RenderOptions};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// 色なし、80 桁まで
+    Slack,
+    /// `ansi` のコードブロックで 8 色まで、80 桁まで
+    Discord,
+    /// 色なし、幅の制限なし
+    Markdown,
+}
+
+impl Format {
+    /// 折り返されずに表示できる桁数
+    pub fn width(self) -> Option<usize> {
+        match self {
+            Format::Slack | Format::Discord => Some(80),
+            Format::Markdown => None,
+        }
+    }
+
+    /// 描く前の設定をこの形式に合わせる
+    pub fn adjust(self, mut opts: RenderOptions) -> RenderOptions {
+        if let Some(width) = self.width() {
+            opts.caption_width = opts.caption_width.min(width);
+        }
+        // 貼り付け先で色が付くので、端末でなくても色を出す
+        if self == Format::Discord {
+            opts.art_style = opts.art_style.force_styling(true);
+            opts.bubble_style = opts.bubble_style.force_styling(true);
+            opts.caption_style = opts.caption_style.force_styling(true);
+        }
+        opts
+    }
+
+    /// フレームの行をコードブロックにする
+    pub fn block(self, lines: &[String]) -> String {
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                let line = match self {
+                    Format::Discord => basic_colors(&crate::markup::strip_links(line)),
+                    Format::Slack | Format::Markdown => crate::markup::strip(line),
+                };
+                line.trim_end().to_string()
+            })
+            .collect();
+        if let Some(width) = self.width() {
+            if lines
+                .iter()
+                .any(|line| console::measure_text_width(line) > width)
+            {
+                eprintln!(
+                    "{}",
+                    i18n::tr(&format!(
+                        "警告: {} 桁を超える行は貼り付け先で折り返されます",
+                        width
+                    ))
+                );
+            }
+        }
+
+        let (fence, lines) = match self {
+            // Markdown ではフェンスを中身のどのバッククォートの並びより長くする
+            Format::Markdown => {
+                let longest = lines
+                    .iter()
+                    .flat_map(|line| line.split(|c| c != '`'))
+                    .map(str::len)
+                    .max()
+                    .unwrap_or(0);
+                ("`".repeat(longest.max(2) + 1), lines)
+            }
+            // Slack と Discord は長いフェンスを使えないので、中身の ``` を崩す
+            Format::Slack | Format::Discord => (
+                "```".to_string(),
+                lines
+                    .iter()
+                    .map(|line| line.replace("```", "`\u{200b}``"))
+                    .collect(),
+            ),
+        };
+        let info = match self {
+            Format::Discord => "ansi",
+            Format::Slack | Format::Markdown => "",
+        };
+        format!("{}{}\n{}\n{}\n", fence, info, lines.join("\n"), fence)
+    }
+}
+
+/// Discord の ansi ブロックが解釈する SGR (太字、下線、8 色の文字色と背景色) だけを残す
+fn basic_colors(line: &str) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("\x1b[") {
+        out.push_str(&rest[..start]);
+        let sequence = &rest[start + 2..];
+        let Some(end) = sequence.find(|c: char| c.is_ascii_alphabetic()) else {
+            rest = "";
+            break;
+        };
+        if sequence.as_bytes()[end] == b'm' {
+            let params = basic_params(&sequence[..end]);
+            if !params.is_empty() {
+                out.push_str(&format!("\x1b[{}m", params.join(";")));
+            }
+        }
+        rest = &sequence[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 明るい 8 色は普通の 8 色にし、256 色やフルカラーの指定は落とす
+fn basic_params(params: &str) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut params = params
+        .split(';')
+        .map(|param| param.parse::<u8>().unwrap_or(0));
+    while let Some(param) = params.next() {
+        match param {
+            0 | 1 | 4 | 30..=37 | 40..=47 => kept.push(param),
+            90..=97 | 100..=107 => kept.push(param - 60),
+            38 | 48 => {
+                let skip = match params.next() {
+                    Some(5) => 1,
+                    Some(2) => 3,
+                    _ => 0,
+                };
+                params.by_ref().take(skip).for_each(drop);
+            }
+            _ => {}
+        }
+    }
+    kept.iter().map(u8::to_string).collect()
+}
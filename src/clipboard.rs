@@ -0,0 +1,61 @@
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// 順に試すクリップボードのコマンド
+const BACKENDS: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+    &[("pbcopy", &[])]
+} else if cfg!(windows) {
+    &[("clip", &[])]
+} else {
+    &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ]
+};
+
+/// クリップボードへ書き込む
+///
+/// コマンドが見つからなければ端末へ OSC 52 で頼む
+pub fn copy(text: &str) -> Result<()> {
+    for (program, args) in BACKENDS {
+        let child = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            bail!("{} が失敗しました ({})", program, status);
+        }
+        return Ok(());
+    }
+
+    let term = console::Term::stdout();
+    if !term.is_term() {
+        bail!(
+            "クリップボードのコマンドが見つかりません ({})",
+            BACKENDS
+                .iter()
+                .map(|(program, _)| *program)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    write!(
+        &term,
+        "\x1b]52;c;{}\x07",
+        crate::graphics::base64(text.as_bytes())
+    )?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::placeholder::LocalTime;
+use crate::{clear_screen, redraw_in_place, render_say, RenderOptions};
+use anyhow::Result;
+use console::Term;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn clock(
+    format: &str,
+    date_format: Option<&str>,
+    caption: Option<&str>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let mut term = Term::stdout();
+    clear_screen(&mut term)?;
+
+    loop {
+        let now = LocalTime::now();
+        let text = match date_format {
+            Some(date_format) => format!("{}\n{}", now.format(format), now.format(date_format)),
+            None => now.format(format),
+        };
+        let terminal_width = crate::terminal::width(&term);
+        redraw_in_place(
+            &mut term,
+            &render_say(&text, caption, terminal_width, opts)?,
+            opts,
+        )?;
+
+        // 次の秒の境目まで待つ
+        let subsec = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        sleep(Duration::from_secs(1) - Duration::from_nanos(subsec as u64));
+    }
+}
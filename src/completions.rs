@@ -0,0 +1,353 @@
+use clap::builder::ValueHint;
+use clap::{Arg, Command, ValueEnum};
+use std::fmt::Write;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// clap の定義から補完スクリプトを組み立てる
+pub fn generate(shell: Shell, mut cmd: Command) -> String {
+    cmd.build();
+    match shell {
+        Shell::Bash => bash(&cmd),
+        Shell::Zsh => zsh(&cmd),
+        Shell::Fish => fish(&cmd),
+        Shell::Powershell => powershell(&cmd),
+    }
+}
+
+fn options(cmd: &Command) -> impl Iterator<Item = &Arg> {
+    cmd.get_arguments().filter(|arg| !arg.is_positional())
+}
+
+fn flags(arg: &Arg) -> Vec<String> {
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("--{}", long));
+    }
+    flags
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_num_args().is_some_and(|n| n.takes_values())
+}
+
+fn is_path(arg: &Arg) -> bool {
+    matches!(
+        arg.get_value_hint(),
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath
+    )
+}
+
+fn possible_values(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+fn help(arg: &Arg) -> String {
+    arg.get_help()
+        .map(|help| help.to_string())
+        .unwrap_or_default()
+}
+
+fn bash(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let fn_name = format!("_{}", name);
+    let subcommands: Vec<&str> = cmd.get_subcommands().map(|sub| sub.get_name()).collect();
+    let mut top_words: Vec<String> = subcommands.iter().map(|s| s.to_string()).collect();
+    top_words.extend(options(cmd).flat_map(flags));
+
+    let mut s = String::new();
+    writeln!(s, "{}() {{", fn_name).unwrap();
+    writeln!(s, "    local cur prev sub i").unwrap();
+    writeln!(s, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"").unwrap();
+    writeln!(s, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"").unwrap();
+    writeln!(s, "    sub=\"\"").unwrap();
+    writeln!(s, "    for ((i = 1; i < COMP_CWORD; i++)); do").unwrap();
+    writeln!(s, "        case \"${{COMP_WORDS[i]}}\" in").unwrap();
+    writeln!(s, "            {})", subcommands.join("|")).unwrap();
+    writeln!(s, "                sub=\"${{COMP_WORDS[i]}}\"").unwrap();
+    writeln!(s, "                break").unwrap();
+    writeln!(s, "                ;;").unwrap();
+    writeln!(s, "        esac").unwrap();
+    writeln!(s, "    done").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "    case \"$sub\" in").unwrap();
+    writeln!(s, "        \"\")").unwrap();
+    writeln!(
+        s,
+        "            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+        top_words.join(" ")
+    )
+    .unwrap();
+    writeln!(s, "            ;;").unwrap();
+    for sub in cmd.get_subcommands() {
+        writeln!(s, "        {})", sub.get_name()).unwrap();
+        writeln!(s, "            case \"$prev\" in").unwrap();
+        for arg in options(sub).filter(|arg| takes_value(arg)) {
+            writeln!(s, "                {})", flags(arg).join("|")).unwrap();
+            let values = possible_values(arg);
+            if !values.is_empty() {
+                writeln!(
+                    s,
+                    "                    COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                    values.join(" ")
+                )
+                .unwrap();
+            } else if is_path(arg) {
+                writeln!(
+                    s,
+                    "                    COMPREPLY=($(compgen -f -- \"$cur\"))"
+                )
+                .unwrap();
+            } else {
+                writeln!(s, "                    COMPREPLY=()").unwrap();
+            }
+            writeln!(s, "                    return").unwrap();
+            writeln!(s, "                    ;;").unwrap();
+        }
+        writeln!(s, "            esac").unwrap();
+        let mut words: Vec<String> = options(sub).flat_map(flags).collect();
+        words.extend(sub.get_arguments().flat_map(possible_values));
+        writeln!(
+            s,
+            "            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+            words.join(" ")
+        )
+        .unwrap();
+        writeln!(s, "            ;;").unwrap();
+    }
+    writeln!(s, "    esac").unwrap();
+    writeln!(s, "}}").unwrap();
+    writeln!(
+        s,
+        "complete -F {} -o bashdefault -o default {}",
+        fn_name, name
+    )
+    .unwrap();
+    s
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let mut s = String::new();
+    writeln!(s, "#compdef {}", name).unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "_{}() {{", name).unwrap();
+    writeln!(s, "    local line state").unwrap();
+    writeln!(s, "    _arguments -C \\").unwrap();
+    for arg in options(cmd) {
+        for flag in flags(arg) {
+            writeln!(s, "        '{}[{}]' \\", flag, zsh_escape(&help(arg))).unwrap();
+        }
+    }
+    writeln!(s, "        '1: :->subcommand' \\").unwrap();
+    writeln!(s, "        '*:: :->args'").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "    case $state in").unwrap();
+    writeln!(s, "        subcommand)").unwrap();
+    writeln!(s, "            local -a subcommands").unwrap();
+    writeln!(s, "            subcommands=(").unwrap();
+    for sub in cmd.get_subcommands() {
+        let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+        writeln!(
+            s,
+            "                '{}:{}'",
+            sub.get_name(),
+            zsh_escape(&about)
+        )
+        .unwrap();
+    }
+    writeln!(s, "            )").unwrap();
+    writeln!(s, "            _describe 'command' subcommands").unwrap();
+    writeln!(s, "            ;;").unwrap();
+    writeln!(s, "        args)").unwrap();
+    writeln!(s, "            case $line[1] in").unwrap();
+    for sub in cmd.get_subcommands() {
+        writeln!(s, "                {})", sub.get_name()).unwrap();
+        writeln!(s, "                    _arguments \\").unwrap();
+        for arg in sub.get_arguments() {
+            let action = if !possible_values(arg).is_empty() {
+                format!("({})", possible_values(arg).join(" "))
+            } else if is_path(arg) {
+                "_files".to_string()
+            } else {
+                String::new()
+            };
+            let help = zsh_escape(&help(arg));
+            if arg.is_positional() {
+                let spec = if arg.get_num_args().is_some_and(|n| n.max_values() > 1) {
+                    "*".to_string()
+                } else {
+                    format!("{}", arg.get_index().unwrap_or(1))
+                };
+                writeln!(
+                    s,
+                    "                        '{}:{}:{}' \\",
+                    spec, help, action
+                )
+                .unwrap();
+                continue;
+            }
+            for flag in flags(arg) {
+                if takes_value(arg) {
+                    writeln!(
+                        s,
+                        "                        '*{}[{}]:value:{}' \\",
+                        flag, help, action
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(s, "                        '{}[{}]' \\", flag, help).unwrap();
+                }
+            }
+        }
+        writeln!(s, "                        && return 0").unwrap();
+        writeln!(s, "                    ;;").unwrap();
+    }
+    writeln!(s, "            esac").unwrap();
+    writeln!(s, "            ;;").unwrap();
+    writeln!(s, "    esac").unwrap();
+    writeln!(s, "}}").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "_{} \"$@\"", name).unwrap();
+    s
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish_option(s: &mut String, name: &str, condition: &str, arg: &Arg) {
+    write!(s, "complete -c {} -n '{}'", name, condition).unwrap();
+    if let Some(short) = arg.get_short() {
+        write!(s, " -s {}", short).unwrap();
+    }
+    if let Some(long) = arg.get_long() {
+        write!(s, " -l {}", long).unwrap();
+    }
+    if takes_value(arg) {
+        let values = possible_values(arg);
+        if !values.is_empty() {
+            write!(s, " -x -a '{}'", values.join(" ")).unwrap();
+        } else if is_path(arg) {
+            write!(s, " -r -F").unwrap();
+        } else {
+            write!(s, " -x").unwrap();
+        }
+    }
+    writeln!(s, " -d '{}'", fish_escape(&help(arg))).unwrap();
+}
+
+fn fish(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let mut s = String::new();
+    for arg in options(cmd) {
+        fish_option(&mut s, name, "__fish_use_subcommand", arg);
+    }
+    for sub in cmd.get_subcommands() {
+        let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+        writeln!(
+            s,
+            "complete -c {} -n '__fish_use_subcommand' -f -a {} -d '{}'",
+            name,
+            sub.get_name(),
+            fish_escape(&about)
+        )
+        .unwrap();
+    }
+    for sub in cmd.get_subcommands() {
+        let condition = format!("__fish_seen_subcommand_from {}", sub.get_name());
+        for arg in sub.get_arguments() {
+            if arg.is_positional() {
+                let values = possible_values(arg);
+                if !values.is_empty() {
+                    writeln!(
+                        s,
+                        "complete -c {} -n '{}' -f -a '{}'",
+                        name,
+                        condition,
+                        values.join(" ")
+                    )
+                    .unwrap();
+                }
+                continue;
+            }
+            fish_option(&mut s, name, &condition, arg);
+        }
+    }
+    s
+}
+
+fn powershell(cmd: &Command) -> String {
+    let name = cmd.get_name();
+    let mut s = String::new();
+    writeln!(
+        s,
+        "Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{",
+        name
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "    param($wordToComplete, $commandAst, $cursorPosition)"
+    )
+    .unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "    $options = @{{").unwrap();
+    let top: Vec<String> = cmd
+        .get_subcommands()
+        .map(|sub| format!("'{}'", sub.get_name()))
+        .chain(options(cmd).flat_map(flags).map(|f| format!("'{}'", f)))
+        .collect();
+    writeln!(s, "        '' = @({})", top.join(", ")).unwrap();
+    for sub in cmd.get_subcommands() {
+        let words: Vec<String> = options(sub)
+            .flat_map(flags)
+            .chain(sub.get_arguments().flat_map(possible_values))
+            .map(|f| format!("'{}'", f))
+            .collect();
+        writeln!(s, "        '{}' = @({})", sub.get_name(), words.join(", ")).unwrap();
+    }
+    writeln!(s, "    }}").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "    $sub = ''").unwrap();
+    writeln!(
+        s,
+        "    foreach ($element in $commandAst.CommandElements | Select-Object -Skip 1) {{"
+    )
+    .unwrap();
+    writeln!(s, "        if ($options[''] -contains $element.ToString() -and -not $element.ToString().StartsWith('-')) {{").unwrap();
+    writeln!(s, "            $sub = $element.ToString()").unwrap();
+    writeln!(s, "            break").unwrap();
+    writeln!(s, "        }}").unwrap();
+    writeln!(s, "    }}").unwrap();
+    writeln!(s).unwrap();
+    writeln!(
+        s,
+        "    $options[$sub] | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
+    )
+    .unwrap();
+    writeln!(s, "        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)").unwrap();
+    writeln!(s, "    }}").unwrap();
+    writeln!(s, "}}").unwrap();
+    s
+}
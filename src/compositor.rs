@@ -0,0 +1,252 @@
+//! フレームの重ね合わせ
+//!
+//! キャラクター (吹き出しを含むアート)、その横に並べるもの (QR コードや `--layout side` の文)、
+//! キャプションをそれぞれ `Layer` にし、`z` の小さいものから順に桁ごとに重ねて 1 フレームの
+//! 行にする。上のレイヤーの文字は下の文字を隠し、`Blend::Transparent` のレイヤーでは
+//! 色の無い空白だけ下を透かす。背景や効果を足すときも `z` を選んでレイヤーにする
+
+use crate::markup;
+use crate::transition::{self, Cell};
+
+/// 重ねる順番 (小さいものほど下)。背景は `CHARACTER` より下、効果は上に置く
+pub mod z {
+    pub const CHARACTER: i32 = 20;
+    pub const BESIDE: i32 = 40;
+    pub const CAPTION: i32 = 50;
+}
+
+/// レイヤーの空白の扱い
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blend {
+    /// 行の範囲を空白も含めてすべて上書きする
+    Opaque,
+    /// 色の無い空白は下のレイヤーを透かす
+    Transparent,
+}
+
+/// `x` 桁目、`y` 行目を左上にして置く行
+#[derive(Clone, Debug)]
+pub struct Layer {
+    pub z: i32,
+    pub x: usize,
+    pub y: usize,
+    pub lines: Vec<String>,
+    pub blend: Blend,
+}
+
+impl Layer {
+    /// 左上に置く、空白を透かすレイヤー
+    pub fn new(z: i32, lines: Vec<String>) -> Self {
+        Self {
+            z,
+            x: 0,
+            y: 0,
+            lines,
+            blend: Blend::Transparent,
+        }
+    }
+
+    pub fn at(mut self, x: usize, y: usize) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    pub fn opaque(mut self) -> Self {
+        self.blend = Blend::Opaque;
+        self
+    }
+
+    /// 末尾の埋め草の空白を除いた最大の表示幅
+    pub fn width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| markup::width(line.trim_end_matches(' ')))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 置いたときの右端の桁
+    pub fn right(&self) -> usize {
+        self.x + self.width()
+    }
+
+    /// 置いたときの下端の行
+    pub fn bottom(&self) -> usize {
+        self.y + self.lines.len()
+    }
+}
+
+/// 重ねる前のレイヤーの集まり
+#[derive(Clone, Debug, Default)]
+pub struct Compositor {
+    layers: Vec<Layer>,
+    /// 少なくともこの行数のフレームにする (足りない行は空行)
+    height: usize,
+}
+
+impl Compositor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, layer: Layer) -> &mut Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn with_height(mut self, height: usize) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// いま置いてあるレイヤーをすべて `dx` 桁右へずらす
+    pub fn shift(&mut self, dx: usize) -> &mut Self {
+        for layer in &mut self.layers {
+            layer.x += dx;
+        }
+        self
+    }
+
+    /// いま置いてあるレイヤーの右端
+    pub fn right(&self) -> usize {
+        self.layers.iter().map(Layer::right).max().unwrap_or(0)
+    }
+
+    /// いま置いてあるレイヤーの下端
+    pub fn bottom(&self) -> usize {
+        self.layers.iter().map(Layer::bottom).max().unwrap_or(0)
+    }
+
+    /// 重ねた行。各行は末尾の色の無い空白を除いてから表示幅 `width` まで空白で埋める
+    pub fn render(&self, width: usize) -> Vec<String> {
+        let mut layers: Vec<&Layer> = self.layers.iter().collect();
+        // 同じ z なら先に足したものが下
+        layers.sort_by_key(|layer| layer.z);
+        let mut rows: Vec<Vec<Segment>> = vec![Vec::new(); self.bottom().max(self.height)];
+        for layer in layers {
+            let lines: Vec<(&str, usize)> = layer
+                .lines
+                .iter()
+                .map(|line| {
+                    let line = line.trim_end_matches(' ');
+                    (line, markup::width(line))
+                })
+                .collect();
+            let layer_width = lines.iter().map(|(_, width)| *width).max().unwrap_or(0);
+            for (i, (line, width)) in lines.into_iter().enumerate() {
+                rows[layer.y + i].push(Segment {
+                    x: layer.x,
+                    width,
+                    line,
+                    opaque: (layer.blend == Blend::Opaque).then_some(layer_width),
+                });
+            }
+        }
+        rows.iter()
+            .map(|segments| {
+                let (mut line, used) = match overlaps(segments) {
+                    true => overlay(segments),
+                    false => concat(segments),
+                };
+                line.push_str(&" ".repeat(width.saturating_sub(used)));
+                line
+            })
+            .collect()
+    }
+}
+
+/// 1 つのレイヤーの 1 行
+#[derive(Clone)]
+struct Segment<'a> {
+    x: usize,
+    width: usize,
+    /// 末尾の空白を除いた行
+    line: &'a str,
+    /// `Blend::Opaque` ならレイヤーの幅
+    opaque: Option<usize>,
+}
+
+impl Segment<'_> {
+    fn right(&self) -> usize {
+        self.x + self.width.max(self.opaque.unwrap_or(0))
+    }
+}
+
+/// 同じ行で重なっているレイヤーがあるか
+fn overlaps(segments: &[Segment]) -> bool {
+    let mut spans: Vec<(usize, usize)> = segments
+        .iter()
+        .map(|segment| (segment.x, segment.right()))
+        .filter(|(x, right)| x < right)
+        .collect();
+    spans.sort_unstable();
+    spans.windows(2).any(|pair| pair[1].0 < pair[0].1)
+}
+
+/// 重ならない行を左から順につなぐ (文字ごとに分けずに済む)
+fn concat(segments: &[Segment]) -> (String, usize) {
+    let mut segments: Vec<&Segment> = segments.iter().filter(|s| s.width > 0).collect();
+    segments.sort_by_key(|segment| segment.x);
+    let mut out = String::new();
+    let mut used = 0;
+    for segment in segments {
+        out.push_str(&" ".repeat(segment.x - used));
+        out.push_str(segment.line);
+        used = segment.x + segment.width;
+    }
+    (out, used)
+}
+
+/// 重なる行を桁ごとに下から重ねる
+fn overlay(segments: &[Segment]) -> (String, usize) {
+    let mut row: Vec<Option<Cell>> = Vec::new();
+    for segment in segments {
+        let mut cells = transition::columns(segment.line);
+        if let Some(layer_width) = segment.opaque {
+            cells.resize_with(cells.len().max(layer_width), || Some(Cell::blank()));
+        }
+        for (offset, cell) in cells.into_iter().enumerate() {
+            let Some(cell) = cell else {
+                continue;
+            };
+            let see_through = cell.c == ' ' && cell.style.is_empty();
+            if segment.opaque.is_none() && see_through {
+                continue;
+            }
+            put(&mut row, segment.x + offset, cell);
+        }
+    }
+    let mut cells: Vec<Cell> = row.into_iter().flatten().collect();
+    let end = cells
+        .iter()
+        .rposition(|cell| cell.c != ' ' || !cell.style.is_empty())
+        .map_or(0, |i| i + 1);
+    cells.truncate(end);
+    let used = cells.iter().map(|cell| cell.width).sum();
+    (transition::join(&cells), used)
+}
+
+/// `row` の `column` 桁目に `cell` を置く。半分だけ隠れる全角文字は空白にする
+fn put(row: &mut Vec<Option<Cell>>, column: usize, cell: Cell) {
+    let end = column + cell.width;
+    if row.len() < end {
+        row.resize_with(end, || Some(Cell::blank()));
+    }
+    // 左にはみ出している全角文字
+    if row[column].is_none() {
+        if let Some(start) = (0..column).rev().find(|&i| row[i].is_some()) {
+            let width = row[start].as_ref().map_or(1, |cell| cell.width);
+            row[start..start + width].fill(Some(Cell::blank()));
+        }
+    }
+    // 右にはみ出す全角文字
+    if let Some(last) = (column..end).rev().find(|&i| row[i].is_some()) {
+        let width = row[last].as_ref().map_or(1, |cell| cell.width);
+        if last + width > end {
+            row[end..last + width].fill(Some(Cell::blank()));
+        }
+    }
+    row[column] = Some(cell);
+    row[column + 1..end].fill(None);
+}
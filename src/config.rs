@@ -0,0 +1,215 @@
+use crate::log;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const CONFIG_TEMPLATE: &str = r#"# fav_say_dragon の設定ファイル
+# コマンドラインで指定した値が優先されます
+
+# anime のインターバル (ms。"1.5s" のような文字列も可)
+# interval = 1000
+
+# キャラクター
+# character = "dragon"
+
+# テーマ (retro, neon, mono または themes/ 以下のファイル名)
+# theme = "neon"
+
+# キャプションの幅
+# caption_width = 60
+
+# アニメーションせずに全フレームを順に出力する
+# no_anim = false
+
+# 色とエスケープシーケンスを使わずに描く (VT を使えない古い Windows のコンソール向け)
+# legacy_console = false
+
+# --bell で出力するシーケンス (既定は BEL)
+# bell_sequence = "\u001b]777;notify;fav_say_dragon;done\u0007"
+
+# --graphics で使うビットマップフォント (BDF または GNU Unifont の .hex)
+# font = "/usr/share/fonts/X11/misc/unifont.bdf"
+
+# ASCII だけのアートと吹き出しを使う (省略時はロケールが UTF-8 でなければ使う)
+# ascii = false
+
+# スクリーンリーダー向けに、アートの代わりにセリフを文で出す
+# a11y = false
+
+# say のおかずを履歴に残す (history と again で使う)
+# history = true
+
+# おかずとキャプションの全角/半角をそろえる (nfkc, none)
+# normalize = "nfkc"
+
+# 吹き出しに収まらないおかずの扱い (truncate, wrap, error, ellipsis)
+# overflow = "error"
+
+# おかずを描く場所 (bubble, side。side ではアートの右に長い文を折り返して並べる)
+# layout = "side"
+
+# メッセージの言語 (ja, en。省略時はロケールに従う。ヘルプには --lang か LANG を使う)
+# lang = "en"
+
+# --seasonal で使う期間とキャラクターの対応 (指定すると既定の対応を置き換える)
+# [seasonal]
+# "12-01..12-25" = "dragon-santa"
+# "12-31..01-07" = "dragon-newyear"
+
+# 色 (例: "green", "cyan.bold", "red.on_black")
+[colors]
+# art = "green"
+# caption = "yellow"
+
+# remind で知らせる時刻 (いくつでも書ける。script を書くとおかずの代わりに再生する)
+# [[reminders]]
+# at = "12:00"
+# message = "お昼ごはん"
+# notify = true
+"#;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(deserialize_with = "crate::duration::deserialize_millis")]
+    pub interval: Option<u64>,
+    pub character: Option<String>,
+    pub theme: Option<String>,
+    pub caption_width: Option<usize>,
+    pub no_anim: Option<bool>,
+    pub legacy_console: Option<bool>,
+    pub bell_sequence: Option<String>,
+    pub font: Option<PathBuf>,
+    pub ascii: Option<bool>,
+    pub a11y: Option<bool>,
+    pub history: Option<bool>,
+    pub normalize: Option<crate::normalize::Normalize>,
+    pub overflow: Option<crate::Overflow>,
+    pub layout: Option<crate::Placement>,
+    pub lang: Option<crate::i18n::Lang>,
+    pub seasonal: Option<BTreeMap<String, String>>,
+    pub colors: Colors,
+    pub reminders: Vec<crate::remind::Reminder>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    pub art: Option<String>,
+    pub caption: Option<String>,
+}
+
+impl Config {
+    /// 設定ファイルを読み込み、環境変数で上書きする
+    pub fn load() -> Result<Self> {
+        let mut config = Self::load_file()?;
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    /// 設定ファイルが無ければ既定値を返す
+    fn load_file() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            log::debug!("設定ファイル {} はありません", path.display());
+            return Ok(Self::default());
+        }
+        log::info!("設定ファイル {} を読み込みます", path.display());
+
+        let config = std::fs::read_to_string(&path)?;
+        let config: Self = toml::from_str(&config)
+            .with_context(|| format!("{} の読み込みに失敗しました", path.display()))?;
+        if let Some(interval) = config.interval {
+            if interval < crate::duration::MIN_INTERVAL {
+                bail!("{}: interval は 10 以上にしてください", path.display());
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(interval) = env("INTERVAL") {
+            let interval = crate::duration::parse_millis(&interval)
+                .with_context(|| format!("{}INTERVAL が不正です", ENV_PREFIX))?;
+            if interval < crate::duration::MIN_INTERVAL {
+                bail!("{}INTERVAL は 10 以上にしてください", ENV_PREFIX);
+            }
+            self.interval = Some(interval);
+        }
+        if let Some(character) = env("CHARACTER") {
+            self.character = Some(character);
+        }
+        if let Some(theme) = env("THEME") {
+            self.theme = Some(theme);
+        }
+        if let Some(caption_width) = env("CAPTION_WIDTH") {
+            self.caption_width = Some(
+                caption_width
+                    .parse()
+                    .with_context(|| format!("{}CAPTION_WIDTH が不正です", ENV_PREFIX))?,
+            );
+        }
+        if let Some(no_anim) = env("NO_ANIM") {
+            self.no_anim = Some(!matches!(
+                no_anim.to_ascii_lowercase().as_str(),
+                "" | "0" | "false" | "no" | "off"
+            ));
+        }
+        if let Some(color) = env("COLOR") {
+            self.colors.art = Some(color);
+        }
+        if let Some(color) = env("CAPTION_COLOR") {
+            self.colors.caption = Some(color);
+        }
+
+        Ok(())
+    }
+}
+
+const ENV_PREFIX: &str = "FAV_SAY_DRAGON_";
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(format!("{}{}", ENV_PREFIX, name)).ok()
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ if cfg!(windows) => PathBuf::from(std::env::var_os("APPDATA")?),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("fav_say_dragon"))
+}
+
+/// アートパックなど設定以外のデータを置く場所
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ if cfg!(windows) => PathBuf::from(std::env::var_os("LOCALAPPDATA")?),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(base.join("fav_say_dragon"))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+pub fn init(force: bool) -> Result<PathBuf> {
+    let Some(path) = config_path() else {
+        bail!("設定ディレクトリを特定できません (HOME が未設定です)");
+    };
+    if path.exists() && !force {
+        bail!("{} は既に存在します (--force で上書き)", path.display());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, CONFIG_TEMPLATE)?;
+
+    Ok(path)
+}
@@ -0,0 +1,65 @@
+use crate::tty::RawTty;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+
+/// `kiosk` で受け付けるただ 1 つの終了のキー (0 なら締めていない)
+static LOCKED: AtomicU8 = AtomicU8::new(0);
+/// 締めてから終了のキーが押された
+static QUIT: AtomicBool = AtomicBool::new(false);
+
+/// `quit` のキーのほかはすべて (q や Ctrl-C も) 読み捨てる
+pub fn lock(quit: u8) {
+    LOCKED.store(quit, Ordering::SeqCst);
+}
+
+pub fn locked() -> bool {
+    LOCKED.load(Ordering::SeqCst) != 0
+}
+
+/// `lock` の終了のキーが押されたか
+pub fn quit_requested() -> bool {
+    QUIT.load(Ordering::SeqCst)
+}
+
+/// anime の再生中に受け付けるキー
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// スペース: 一時停止/再開
+    Pause,
+    /// n, →: 次のフレーム
+    Next,
+    /// p, ←: 前のフレーム
+    Previous,
+    /// +: 速く
+    Faster,
+    /// -: 遅く
+    Slower,
+    /// q, Ctrl-C: 終了
+    Quit,
+    /// 1-9: 選択肢
+    Choice(usize),
+}
+
+/// `timeout` まで待ってキーを 1 つ読む (知らないキーは読み捨てる)
+pub fn read_key(tty: &mut RawTty, timeout: Option<Duration>) -> Option<Key> {
+    let mut buf = [0u8; 8];
+    let n = tty.read_timeout(&mut buf, timeout);
+    let quit = LOCKED.load(Ordering::SeqCst);
+    if quit != 0 {
+        let pressed = buf[..n] == [quit];
+        if pressed {
+            QUIT.store(true, Ordering::SeqCst);
+        }
+        return pressed.then_some(Key::Quit);
+    }
+    match &buf[..n] {
+        b" " => Some(Key::Pause),
+        b"n" | b"\x1b[C" => Some(Key::Next),
+        b"p" | b"\x1b[D" => Some(Key::Previous),
+        b"+" | b"=" => Some(Key::Faster),
+        b"-" => Some(Key::Slower),
+        b"q" | b"\x03" => Some(Key::Quit),
+        [digit @ b'1'..=b'9'] => Some(Key::Choice((digit - b'0') as usize)),
+        _ => None,
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{duration, redraw, render_say, RenderOptions};
+use anyhow::Result;
+use console::Term;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+pub fn countdown(
+    length: Duration,
+    message: &str,
+    caption: Option<&str>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let mut term = Term::stdout();
+    tick(length, |remaining| {
+        let terminal_width = crate::terminal::width(&term);
+        let text = match remaining.is_zero() {
+            true => message.to_string(),
+            false => duration::format_clock(remaining),
+        };
+        redraw(
+            &mut term,
+            &render_say(&text, caption, terminal_width, opts)?,
+            opts,
+        )
+    })
+}
+
+/// 残り時間 (秒単位に切り上げ) を渡しながら 1 秒ごとに `on_tick` を呼ぶ
+///
+/// 最後に残り時間 0 で一度呼んでから戻る
+pub fn tick(length: Duration, mut on_tick: impl FnMut(Duration) -> Result<()>) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        let elapsed = start.elapsed();
+        let remaining = length.saturating_sub(elapsed);
+        let rounded =
+            Duration::from_secs(remaining.as_secs() + (remaining.subsec_nanos() > 0) as u64);
+        on_tick(rounded)?;
+        if remaining.is_zero() {
+            return Ok(());
+        }
+
+        // 次の秒の境目まで待つ
+        let subsec = Duration::from_nanos(remaining.subsec_nanos() as u64);
+        let wait = match subsec.is_zero() {
+            true => Duration::from_secs(1),
+            false => subsec,
+        };
+        sleep(wait.min(remaining));
+    }
+}
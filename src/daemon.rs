@@ -0,0 +1,124 @@
+use crate::events::{Event, Events};
+use crate::{i18n, redraw, render_say, RenderOptions};
+use anyhow::Result;
+use console::Term;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// side_dish とキャプションの区切り
+const SEPARATOR: char = '\0';
+
+pub fn default_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("fav_say_dragon.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+            std::env::temp_dir().join(format!("fav_say_dragon-{}.sock", user))
+        }
+    }
+}
+
+/// daemon がソケットのほかに待つ入力
+#[derive(Clone, Debug, Default)]
+pub struct Sources {
+    /// 標準入力の各行もおかずとして表示する
+    pub stdin: bool,
+    /// 変更されるたびに内容を表示するファイル
+    pub watch: Option<PathBuf>,
+    /// 表示してからこの時間が過ぎたら空の吹き出しに戻す
+    pub clear_after: Option<Duration>,
+}
+
+#[cfg(unix)]
+pub fn daemon(socket: &Path, sources: &Sources, opts: &RenderOptions) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+
+    // 前の daemon が残したソケットだけ消す (ほかのファイルを指定されても消さない)
+    if let Ok(metadata) = std::fs::symlink_metadata(socket) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{} はソケットではありません", socket.display());
+        }
+        std::fs::remove_file(socket)?;
+    }
+    let events = Events::new();
+    events.socket(UnixListener::bind(socket)?, SEPARATOR);
+    if sources.stdin {
+        events.stdin_lines();
+    }
+    if let Some(path) = &sources.watch {
+        events.watch(path.clone());
+    }
+
+    let mut term = Term::stdout();
+    draw(&mut term, "", None, opts)?;
+    let mut shown = false;
+    loop {
+        let timeout = sources.clear_after.filter(|_| shown);
+        // 描けなかったメッセージはエラーを出して読み捨て、次のメッセージを待つ
+        let drawn = match events.next(timeout) {
+            Some(Event::Message(side_dish, caption)) => draw(
+                &mut term,
+                &opts.sanitize(&side_dish),
+                caption
+                    .map(|caption| opts.sanitize(&caption).into_owned())
+                    .as_deref(),
+                opts,
+            ),
+            Some(Event::Line(line)) => draw(&mut term, &opts.sanitize(&line), None, opts),
+            Some(Event::Changed(text)) => {
+                draw(&mut term, opts.sanitize(&text).trim_end(), None, opts)
+            }
+            None => {
+                draw(&mut term, "", None, opts)?;
+                shown = false;
+                continue;
+            }
+        };
+        match drawn {
+            Ok(()) => shown = true,
+            Err(e) => eprintln!("{:#}", i18n::error(e)),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn daemon(_socket: &Path, _sources: &Sources, _opts: &RenderOptions) -> Result<()> {
+    anyhow::bail!("daemon は Unix 系 OS でのみ使えます")
+}
+
+#[cfg(unix)]
+pub fn send(socket: &Path, side_dish: &str, caption: Option<&str>) -> Result<()> {
+    use anyhow::Context;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("{} に接続できません", socket.display()))?;
+    write!(stream, "{}", side_dish)?;
+    if let Some(caption) = caption {
+        write!(stream, "{}{}", SEPARATOR, caption)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn send(_socket: &Path, _side_dish: &str, _caption: Option<&str>) -> Result<()> {
+    anyhow::bail!("send は Unix 系 OS でのみ使えます")
+}
+
+#[cfg(unix)]
+fn draw(
+    term: &mut Term,
+    side_dish: &str,
+    caption: Option<&str>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let terminal_width = crate::terminal::width(term);
+    redraw(
+        term,
+        &render_say(side_dish, caption, terminal_width, opts)?,
+        opts,
+    )
+}
@@ -0,0 +1,89 @@
+//! `anime --dry-run` の再生せずに確かめる一覧
+//!
+//! 再生と同じ順番、分割、表示時間を解決して、フレームごとの行と合計時間を表にする
+
+use crate::{fit_frames, i18n, progress, waits_after, Frame, PlayOptions, RenderOptions, Script};
+use anyhow::Result;
+use console::Alignment;
+
+/// テキストの列の表示幅
+const PREVIEW_WIDTH: usize = 32;
+
+pub fn dry_run(script: Script, play_opts: &PlayOptions, opts: &RenderOptions) -> Result<()> {
+    let frames = play_opts
+        .order
+        .arrange(fit_frames(script.timeline(), opts)?);
+    let transitions = frames.len().saturating_sub(1);
+    let header = [
+        i18n::tr("#").into_owned(),
+        i18n::tr("種類").into_owned(),
+        i18n::tr("テキスト").into_owned(),
+        i18n::tr("時間").into_owned(),
+    ];
+    let mut rows = vec![header];
+    let mut total = 0.0;
+    for (i, frame) in frames.iter().enumerate() {
+        let duration = match waits_after(&frames, i) {
+            true => {
+                let duration = play_opts.frame_duration(frame, i, progress(i, transitions));
+                total += duration.as_secs_f64();
+                format!("{:.2}s", duration.as_secs_f64())
+            }
+            false => "-".to_string(),
+        };
+        let text = opts.expand(frame.text(opts.lang));
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        rows.push([
+            (i + 1).to_string(),
+            i18n::tr(kind(frame, opts.lang)).into_owned(),
+            console::truncate_str(&text, PREVIEW_WIDTH, "…").into_owned(),
+            duration,
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..4)
+        .map(|column| {
+            rows.iter()
+                .map(|row| console::measure_text_width(&row[column]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                // 番号と時間は右に揃える
+                let align = match column {
+                    0 | 3 => Alignment::Right,
+                    _ => Alignment::Left,
+                };
+                console::pad_str(cell, *width, align, None).into_owned()
+            })
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+    println!(
+        "{}",
+        i18n::tr(&format!(
+            "合計: {} フレーム, {:.2} 秒 (goto と選択肢は先頭から順にたどった場合)",
+            frames.len(),
+            total
+        ))
+    );
+    Ok(())
+}
+
+fn kind(frame: &Frame, lang: i18n::Lang) -> &'static str {
+    match (
+        frame.side_dish.get(lang).is_empty(),
+        frame.caption.get(lang).is_empty(),
+    ) {
+        _ if !frame.choices.is_empty() => "選択肢",
+        (false, _) => "おかず",
+        (true, false) => "キャプション",
+        (true, true) => "空",
+    }
+}
@@ -0,0 +1,109 @@
+//! `anime --dump-json` で出力するフレームの一覧
+//!
+//! 再生と同じ順番、分割、インターバルを解決したものを JSON にして、別の表示側で再生できるようにする
+
+use crate::{
+    bubble_capacity, fit_frames, frame_lines, json, progress, template, waits_after, Cast,
+    PlayOptions, RenderOptions, Script,
+};
+use anyhow::Result;
+use serde::Serialize;
+
+/// 形式を変えたら上げる
+const VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Dump {
+    version: u32,
+    lang: &'static str,
+    /// goto と選択肢を無視して先頭から順に再生したときの長さ
+    total_ms: u64,
+    bubble: Option<Bubble>,
+    frames: Vec<Frame>,
+}
+
+#[derive(Serialize)]
+struct Bubble {
+    /// アートの中の上枠と下枠の行 (0 始まり)
+    top: usize,
+    bottom: usize,
+    /// 枠を含む表示幅
+    width: usize,
+    /// 中に入る文字数と行数
+    columns: usize,
+    rows: usize,
+}
+
+#[derive(Serialize)]
+struct Frame {
+    side_dish: String,
+    caption: String,
+    label: Option<String>,
+    goto: Option<String>,
+    choices: Vec<Choice>,
+    /// 次のフレームまでの時間。選択肢を待つフレームと最後のフレームは 0
+    duration_ms: u64,
+    /// 色を除いた表示内容
+    lines: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Choice {
+    label: String,
+    goto: String,
+}
+
+pub fn dump(script: Script, play_opts: &PlayOptions, opts: &RenderOptions) -> Result<()> {
+    let frames = play_opts
+        .order
+        .arrange(fit_frames(script.timeline(), opts)?);
+    let cast = Cast::new(&script, &frames, opts)?;
+    let transitions = frames.len().saturating_sub(1);
+    let mut dumped = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let duration_ms = match waits_after(&frames, i) {
+            true => play_opts
+                .frame_duration(frame, i, progress(i, transitions))
+                .as_millis() as u64,
+            false => 0,
+        };
+        let opts = cast.opts(frame, opts);
+        let lines = frame_lines(&frames, i, 0, play_opts, opts)?
+            .iter()
+            .map(|line| crate::markup::strip(line).trim_end().to_string())
+            .collect();
+        dumped.push(Frame {
+            side_dish: opts.expand(frame.side_dish.get(opts.lang)).into_owned(),
+            caption: opts.expand(frame.caption.get(opts.lang)).into_owned(),
+            label: frame.label.clone(),
+            goto: frame.goto.clone(),
+            choices: frame
+                .choices
+                .iter()
+                .map(|choice| Choice {
+                    label: choice.label.get(opts.lang).to_string(),
+                    goto: choice.goto.clone(),
+                })
+                .collect(),
+            duration_ms,
+            lines,
+        });
+    }
+
+    let capacity = bubble_capacity(&template::Template::parse(&opts.art.template));
+    let dump = Dump {
+        version: VERSION,
+        lang: opts.lang.code(),
+        total_ms: dumped.iter().map(|frame| frame.duration_ms).sum(),
+        bubble: opts.art.bubble.map(|bubble| Bubble {
+            top: bubble.top,
+            bottom: bubble.bottom,
+            width: bubble.width,
+            columns: opts.bubble_chars,
+            rows: capacity,
+        }),
+        frames: dumped,
+    };
+    println!("{}", json::to_string(&dump)?);
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// `25m`, `1m30s`, `1.5s`, `500ms`, `2h` のような表記を解釈する
+pub fn parse(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        bail!("時間が空です");
+    }
+
+    let mut total = 0f64;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if number_len == 0 {
+            bail!("時間 '{}' を解釈できません", s);
+        }
+        let number: f64 = rest[..number_len]
+            .parse()
+            .map_err(|_| anyhow!("時間 '{}' の数値が不正です", s))?;
+        rest = &rest[number_len..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let unit = rest[..unit_len].trim();
+        rest = &rest[unit_len..];
+
+        let scale = match unit {
+            "h" => 3600.0,
+            "m" | "min" => 60.0,
+            "s" | "sec" | "" => 1.0,
+            "ms" => 0.001,
+            _ => bail!("時間 '{}' の単位 '{}' は使えません (h, m, s, ms)", s, unit),
+        };
+        total += number * scale;
+    }
+
+    Duration::try_from_secs_f64(total).map_err(|_| anyhow!("時間 '{}' が長すぎます", s))
+}
+
+/// インターバルの表記をミリ秒にする。単位の無い数はミリ秒 (`1000`, `1.5s`, `500ms`, `2m`)
+pub fn parse_millis(s: &str) -> Result<u64> {
+    let trimmed = s.trim();
+    let millis = match trimmed.parse::<u64>() {
+        Ok(millis) => millis,
+        Err(_) => parse(trimmed)?.as_millis() as u64,
+    };
+    Ok(millis)
+}
+
+/// `parse_millis` に加えて、インターバルとして短すぎないか確かめる
+pub fn parse_interval(s: &str) -> Result<u64> {
+    let millis = parse_millis(s)?;
+    if millis < MIN_INTERVAL {
+        bail!("インターバル '{}' は 10ms 以上にしてください", s.trim());
+    }
+    Ok(millis)
+}
+
+/// これより短いインターバルは端末の描画が追いつかない (ms)
+pub const MIN_INTERVAL: u64 = 10;
+
+/// スクリプトや設定の、ミリ秒の整数か `"1.5s"` のような文字列
+pub fn deserialize_millis<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Millis {
+        Number(u64),
+        Text(String),
+    }
+
+    match Option::<Millis>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Millis::Number(millis)) => Ok(Some(millis)),
+        Some(Millis::Text(text)) => parse_millis(&text)
+            .map(Some)
+            .map_err(|e| serde::de::Error::custom(crate::i18n::error(e))),
+    }
+}
+
+/// `mm:ss` 形式 (1 時間以上なら `h:mm:ss`)
+pub fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs / 3600 {
+        0 => format!("{:02}:{:02}", secs / 60, secs % 60),
+        hours => format!("{}:{:02}:{:02}", hours, secs / 60 % 60, secs % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units_and_combinations() {
+        assert_eq!(parse("25m").unwrap(), Duration::from_secs(25 * 60));
+        assert_eq!(parse("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse(" 1.5s ").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse("1h 2min 3sec").unwrap(), Duration::from_secs(3723));
+        assert_eq!(parse("3").unwrap(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for s in ["", "  ", "m", "1x", "1.2.3s", ".s", "-1s", "1d"] {
+            assert!(parse(s).is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn rejects_too_long_durations() {
+        let e = parse("99999999999999999999h").unwrap_err();
+        assert_eq!(e.to_string(), "時間 '99999999999999999999h' が長すぎます");
+        assert!(parse_millis("99999999999999999999s").is_err());
+        assert!(parse(&format!("{}s", "9".repeat(400))).is_err());
+    }
+
+    #[test]
+    fn parses_millis_and_intervals() {
+        assert_eq!(parse_millis("1000").unwrap(), 1000);
+        assert_eq!(parse_millis("1.5s").unwrap(), 1500);
+        assert_eq!(parse_millis("2m").unwrap(), 120_000);
+        assert_eq!(parse_interval("10").unwrap(), 10);
+        assert!(parse_interval("9ms").is_err());
+    }
+
+    #[test]
+    fn formats_clocks() {
+        assert_eq!(format_clock(Duration::from_secs(65)), "01:05");
+        assert_eq!(format_clock(Duration::from_secs(3725)), "1:02:05");
+    }
+}
@@ -0,0 +1,496 @@
+//! `edit` のスクリプトの編集画面
+//!
+//! 左にフレームの一覧、右に選んでいるフレームの描画を出し、キーでフレームを並べ替えたり
+//! 足したり消したり、表示時間を変えたりする。保存するとスクリプトと同じ形式 (TOML か JSON) で
+//! 書き戻す。先頭からキャプションだけ、おかずだけ、キャプションだけのフレームの順に並んでいる分は
+//! `pre_captions` / `side_dishes` / `after_captions` にまとめ、残りを `frames` にする
+//!
+//! 文言の入力では `\n` と書くと改行になる
+
+use crate::compositor::{z, Compositor, Layer};
+use crate::{
+    i18n, json, render_frame, sanitize, screen, terminal, tty, Cast, Frame, PlayOptions,
+    RenderOptions, Script, ScriptFormat, Text, DEFAULT_INTERVAL,
+};
+use anyhow::{bail, Context, Result};
+use console::{Style, Term};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 一覧の幅の下限と上限
+const LIST_MIN: usize = 16;
+const LIST_MAX: usize = 40;
+/// `+` / `-` で変える表示時間 (ms)
+const STEP: u64 = 100;
+/// 端末の大きさが変わっていないか見る間隔
+const POLL: Duration = Duration::from_millis(250);
+
+/// 読み取ったキー
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Input {
+    Char(char),
+    Up,
+    Down,
+    Enter,
+    Backspace,
+    Escape,
+    /// Ctrl-C
+    Interrupt,
+}
+
+/// 一度に読めたバイト列をキーに分ける (知らないエスケープシーケンスは読み捨てる)
+fn inputs(bytes: &[u8]) -> Vec<Input> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    let mut inputs = Vec::new();
+    while let Some(c) = chars.next() {
+        inputs.push(match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let end = chars.find(|c| !c.is_ascii_digit() && *c != ';');
+                match end {
+                    Some('A') => Input::Up,
+                    Some('B') => Input::Down,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Input::Escape,
+            '\r' | '\n' => Input::Enter,
+            '\x7f' | '\x08' => Input::Backspace,
+            '\x03' => Input::Interrupt,
+            c if c.is_control() => continue,
+            c => Input::Char(c),
+        });
+    }
+    inputs
+}
+
+/// 入力中の文言
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    SideDish,
+    Caption,
+}
+
+impl Field {
+    fn label(self) -> &'static str {
+        match self {
+            Field::SideDish => "おかず",
+            Field::Caption => "キャプション",
+        }
+    }
+}
+
+/// `lang` の文言を書き換える (言語ごとに書いてあればその言語の分だけ)
+fn set_text(text: &mut Text, value: String, lang: i18n::Lang) {
+    match text {
+        Text::Plain(text) => *text = value,
+        Text::Translated(texts) => {
+            texts.insert(lang.code().to_string(), value);
+        }
+    }
+}
+
+/// 文言のほかに何も指定していないフレーム
+fn bare(frame: &Frame) -> bool {
+    frame.label.is_none()
+        && frame.goto.is_none()
+        && frame.choices.is_empty()
+        && frame.duration.is_none()
+        && frame.fields.is_empty()
+        && frame.character.is_none()
+        && frame.sound.is_none()
+        && frame.mood.is_none()
+        && frame.color.is_none()
+        && frame.effect.is_none()
+}
+
+/// `script` の文言の並びと `frames` をすべて 1 つの並びにする (`script` の並びは空になる)
+fn unpack(script: &mut Script) -> Vec<Frame> {
+    // スクリプト全体の fields はフレームに写さずに残す
+    let fields = std::mem::take(&mut script.fields);
+    let frames = script.timeline();
+    script.fields = fields;
+    script.pre_captions.clear();
+    script.side_dishes.clear();
+    script.after_captions.clear();
+    script.frames.clear();
+    frames
+}
+
+/// `unpack` の逆。まとめられる分だけ文言の並びに戻す
+fn pack(script: &Script, frames: &[Frame]) -> Script {
+    let caption_only =
+        |frame: &Frame| bare(frame) && frame.side_dish.is_empty() && !frame.caption.is_empty();
+    let side_dish_only =
+        |frame: &Frame| bare(frame) && frame.caption.is_empty() && !frame.side_dish.is_empty();
+    let mut rest = frames;
+    let mut take = |wanted: &dyn Fn(&Frame) -> bool| {
+        let n = rest.iter().take_while(|frame| wanted(frame)).count();
+        let (taken, left) = rest.split_at(n);
+        rest = left;
+        taken.to_vec()
+    };
+    let pre_captions = take(&caption_only);
+    let side_dishes = take(&side_dish_only);
+    let after_captions = take(&caption_only);
+    Script {
+        pre_captions: pre_captions.into_iter().map(|f| f.caption).collect(),
+        side_dishes: side_dishes.into_iter().map(|f| f.side_dish).collect(),
+        after_captions: after_captions.into_iter().map(|f| f.caption).collect(),
+        frames: rest.to_vec(),
+        ..script.clone()
+    }
+}
+
+/// 表示時間を `1.5s` のように
+fn seconds(millis: u64) -> String {
+    format!("{:.1}s", millis as f64 / 1000.0)
+}
+
+struct Editor {
+    path: PathBuf,
+    format: ScriptFormat,
+    /// 文言の並びを `frames` に移したスクリプト
+    script: Script,
+    frames: Vec<Frame>,
+    cursor: usize,
+    /// 一覧の一番上に出すフレーム
+    scroll: usize,
+    modified: bool,
+    /// 入力中の欄といまの文字列
+    input: Option<(Field, String)>,
+    /// 最下行に出すお知らせ (次のキーまで)
+    message: Option<String>,
+    /// 保存していない変更があるときに 1 度 q を押した
+    quitting: bool,
+}
+
+impl Editor {
+    /// `path` が無ければ空のスクリプトにする (拡張子が .json なら JSON で保存する)
+    fn open(path: &Path) -> Result<Self> {
+        let (format, mut script) = match path.exists() {
+            true => {
+                let source = std::fs::read_to_string(path)
+                    .with_context(|| format!("{} を読み込めません", path.display()))?;
+                let format = ScriptFormat::detect(path, &source);
+                if matches!(format, ScriptFormat::Srt | ScriptFormat::Lrc) {
+                    bail!(
+                        "edit で開けるのは TOML か JSON のスクリプトだけです: {}",
+                        path.display()
+                    );
+                }
+                (format, Script::read(path, Some(format))?)
+            }
+            false => {
+                let json = path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+                let format = match json {
+                    true => ScriptFormat::Json,
+                    false => ScriptFormat::Toml,
+                };
+                (format, Script::default())
+            }
+        };
+        let frames = unpack(&mut script);
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            script,
+            frames,
+            cursor: 0,
+            scroll: 0,
+            modified: false,
+            input: None,
+            message: None,
+            quitting: false,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let script = pack(&self.script, &self.frames);
+        script.validate()?;
+        let text = match self.format {
+            ScriptFormat::Json => json::to_string(&script)? + "\n",
+            _ => toml::to_string_pretty(&script)?,
+        };
+        std::fs::write(&self.path, text)
+            .with_context(|| format!("{} に書き込めません", self.path.display()))
+    }
+
+    /// `+` / `-` で変える前の表示時間
+    fn interval(&self, frame: &Frame) -> u64 {
+        let caption_only = frame.side_dish.is_empty() && !frame.caption.is_empty();
+        let caption_interval = self.script.caption_interval.filter(|_| caption_only);
+        caption_interval
+            .or(self.script.interval)
+            .unwrap_or(DEFAULT_INTERVAL)
+    }
+
+    fn start_input(&mut self, field: Field, opts: &RenderOptions) {
+        let text = self
+            .frames
+            .get(self.cursor)
+            .map_or("", |frame| match field {
+                Field::SideDish => frame.side_dish.get(opts.lang),
+                Field::Caption => frame.caption.get(opts.lang),
+            });
+        self.input = Some((field, text.replace('\n', "\\n")));
+    }
+
+    /// 入力中のキー。Enter で選んでいるフレームに入れる
+    fn type_key(&mut self, input: Input, opts: &RenderOptions) {
+        let Some((field, text)) = &mut self.input else {
+            return;
+        };
+        match input {
+            Input::Char(c) => text.push(c),
+            Input::Backspace => {
+                text.pop();
+            }
+            Input::Enter => {
+                let value = opts.sanitize(&text.replace("\\n", "\n")).into_owned();
+                let field = *field;
+                if let Some(frame) = self.frames.get_mut(self.cursor) {
+                    let text = match field {
+                        Field::SideDish => &mut frame.side_dish,
+                        Field::Caption => &mut frame.caption,
+                    };
+                    set_text(text, value, opts.lang);
+                    self.modified = true;
+                }
+                self.input = None;
+            }
+            Input::Escape | Input::Interrupt => self.input = None,
+            Input::Up | Input::Down => {}
+        }
+    }
+
+    /// 1 つのキーを処理する。終わるなら true
+    fn key(&mut self, input: Input, opts: &RenderOptions) -> bool {
+        if self.input.is_some() {
+            self.type_key(input, opts);
+            return false;
+        }
+        self.message = None;
+        let quitting = std::mem::take(&mut self.quitting);
+        let last = self.frames.len().saturating_sub(1);
+        match input {
+            Input::Char('j') | Input::Down => self.cursor = (self.cursor + 1).min(last),
+            Input::Char('k') | Input::Up => self.cursor = self.cursor.saturating_sub(1),
+            Input::Char('J') if self.cursor < last => {
+                self.frames.swap(self.cursor, self.cursor + 1);
+                self.cursor += 1;
+                self.modified = true;
+            }
+            Input::Char('K') if self.cursor > 0 => {
+                self.frames.swap(self.cursor, self.cursor - 1);
+                self.cursor -= 1;
+                self.modified = true;
+            }
+            Input::Char('a') => {
+                let at = match self.frames.is_empty() {
+                    true => 0,
+                    false => self.cursor + 1,
+                };
+                self.frames.insert(at, Frame::default());
+                self.cursor = at;
+                self.modified = true;
+                self.start_input(Field::SideDish, opts);
+            }
+            Input::Char('d') if !self.frames.is_empty() => {
+                self.frames.remove(self.cursor);
+                self.cursor = self.cursor.min(self.frames.len().saturating_sub(1));
+                self.modified = true;
+            }
+            Input::Char('e') | Input::Enter if !self.frames.is_empty() => {
+                self.start_input(Field::SideDish, opts)
+            }
+            Input::Char('c') if !self.frames.is_empty() => self.start_input(Field::Caption, opts),
+            Input::Char(c @ ('+' | '=' | '-' | '0')) if !self.frames.is_empty() => {
+                let interval = self.interval(&self.frames[self.cursor]);
+                let frame = &mut self.frames[self.cursor];
+                let current = frame.duration.unwrap_or(interval);
+                frame.duration = match c {
+                    '+' | '=' => Some(current + STEP),
+                    '-' => Some(current.saturating_sub(STEP).max(STEP)),
+                    _ => None,
+                };
+                self.modified = true;
+            }
+            Input::Char('s') => {
+                self.message = Some(match self.save() {
+                    Ok(()) => {
+                        self.modified = false;
+                        i18n::tr(&format!("{} に保存しました", self.path.display())).into_owned()
+                    }
+                    Err(e) => format!("{:#}", i18n::error(e)),
+                });
+            }
+            Input::Char('q') | Input::Interrupt => {
+                if !self.modified || quitting {
+                    return true;
+                }
+                self.quitting = true;
+                self.message = Some(
+                    i18n::tr("保存していない変更があります (もう一度 q で終了、s で保存)")
+                        .into_owned(),
+                );
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// 一覧の `i` 番目の行 (表示幅 `width`)
+    fn list_line(&self, i: usize, width: usize, opts: &RenderOptions) -> String {
+        let frame = &self.frames[i];
+        let side_dish = frame.side_dish.get(opts.lang);
+        let text = match side_dish.is_empty() {
+            true => format!("({})", frame.caption.get(opts.lang)),
+            false => side_dish.to_string(),
+        };
+        let text = sanitize::strip(&text).replace('\n', "↵");
+        let head = format!("{:>3} ", i + 1);
+        let time = frame
+            .duration
+            .map(|millis| format!(" {}", seconds(millis)))
+            .unwrap_or_default();
+        let room = width.saturating_sub(head.len() + time.len());
+        let text = console::truncate_str(&text, room, "…");
+        let line = format!(
+            "{}{}{}",
+            head,
+            console::pad_str(&text, room, console::Alignment::Left, None),
+            time
+        );
+        match i == self.cursor {
+            true => Style::new().reverse().apply_to(line).to_string(),
+            false => line,
+        }
+    }
+
+    /// 選んでいるフレームを `opts` とスクリプトの配役と `fields` で描く
+    fn preview(&self, width: usize, opts: &RenderOptions) -> Result<Vec<String>> {
+        let Some(frame) = self.frames.get(self.cursor) else {
+            return Ok(vec![i18n::tr("a でフレームを足します").into_owned()]);
+        };
+        let mut frame = frame.clone();
+        for (name, text) in &self.script.fields {
+            frame
+                .fields
+                .entry(name.clone())
+                .or_insert_with(|| text.clone());
+        }
+        let cast = Cast::new(&self.script, std::slice::from_ref(&frame), opts)?;
+        render_frame(
+            &frame,
+            self.cursor,
+            self.frames.len(),
+            width,
+            &PlayOptions::default(),
+            cast.opts(&frame, opts),
+        )
+    }
+
+    fn draw(&mut self, opts: &RenderOptions) -> Result<()> {
+        let (rows, columns) = Term::stdout().size();
+        let (rows, columns) = (usize::from(rows), usize::from(columns));
+        let body = rows.saturating_sub(2).max(1);
+        let list_width = (columns / 3).clamp(LIST_MIN, LIST_MAX).min(columns);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + body {
+            self.scroll = self.cursor + 1 - body;
+        }
+
+        let list: Vec<String> = (self.scroll..self.frames.len().min(self.scroll + body))
+            .map(|i| self.list_line(i, list_width, opts))
+            .collect();
+        let preview_width = columns.saturating_sub(list_width + 1);
+        let mut preview = self
+            .preview(preview_width, opts)
+            .unwrap_or_else(|e| vec![format!("{:#}", i18n::error(e))]);
+        preview.truncate(body);
+        let mut panes = Compositor::new().with_height(body);
+        panes.push(Layer::new(z::BESIDE, list));
+        panes.push(Layer::new(z::BESIDE, vec!["│".to_string(); body]).at(list_width, 0));
+        panes.push(Layer::new(z::CHARACTER, preview).at(list_width + 1, 0));
+
+        let header = format!(
+            "{}{}  {}/{}",
+            self.path.display(),
+            if self.modified { " *" } else { "" },
+            (self.cursor + 1).min(self.frames.len()),
+            self.frames.len()
+        );
+        let footer = match (&self.input, &self.message) {
+            (Some((field, text)), _) => format!("{}: {}▏", i18n::tr(field.label()), text),
+            (None, Some(message)) => message.clone(),
+            (None, None) => i18n::tr(
+                "j/k 選ぶ  J/K 並べ替え  a 足す  d 消す  e おかず  c キャプション  +/- 表示時間  0 時間を戻す  s 保存  q 終了",
+            )
+            .into_owned(),
+        };
+        let bold = Style::new().bold();
+        let dim = Style::new().dim();
+        let mut lines = vec![bold
+            .apply_to(console::truncate_str(&header, columns, "…"))
+            .to_string()];
+        lines.extend(panes.render(columns));
+        lines.push(
+            dim.apply_to(console::truncate_str(&footer, columns, "…"))
+                .to_string(),
+        );
+
+        let mut out = Vec::new();
+        terminal::home(&mut out)?;
+        for (n, line) in lines.iter().enumerate() {
+            out.extend_from_slice(line.as_bytes());
+            out.extend_from_slice(terminal::clear_line_end().as_bytes());
+            if n + 1 < lines.len() {
+                out.push(b'\n');
+            }
+        }
+        let mut stdout = std::io::stdout();
+        stdout.write_all(&out)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// `path` のスクリプトを編集する
+pub fn edit(path: &Path, opts: &RenderOptions) -> Result<()> {
+    if opts.graphics.is_some() {
+        bail!("edit は --graphics と一緒に使えません");
+    }
+    let mut editor = Editor::open(path)?;
+    let keyboard = std::io::stdout()
+        .is_terminal()
+        .then(tty::RawTty::open)
+        .flatten();
+    let Some(mut keyboard) = keyboard else {
+        bail!("edit は端末でしか使えません");
+    };
+    let _screen = screen::Screen::enter(true);
+    let mut size = None;
+    let mut dirty = true;
+    loop {
+        let now = Term::stdout().size();
+        if dirty || size != Some(now) {
+            size = Some(now);
+            editor.draw(opts)?;
+            dirty = false;
+        }
+        let mut buf = [0u8; 64];
+        let n = keyboard.read_timeout(&mut buf, Some(POLL));
+        for input in inputs(&buf[..n]) {
+            if editor.key(input, opts) {
+                return Ok(());
+            }
+            dirty = true;
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! 複数の入力を 1 つのチャンネルにまとめる
+//!
+//! ソケット、標準入力、ファイルの変更はそれぞれのスレッドで待ち、表示する側は
+//! `Events::next` で次の出来事かタイムアウトまで待つだけにする
+//!
+//! 使うのは今のところ `daemon` だけ。`anime` は入力がキーだけで `controls::read_key` の
+//! タイムアウトで足り、`serve --tcp` は接続ごとのスレッドで済むので移していない。
+//! tokio ではなくスレッドと `mpsc` にしているのは、オフラインでもビルドできるよう依存を
+//! 増やさないため (入力は数本なのでスレッドで足りる)
+
+use crate::i18n;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// ファイルの変更を確かめる間隔
+const WATCH_POLL: Duration = Duration::from_millis(500);
+/// ソケットで受け取るメッセージの上限 (バイト)
+const MAX_MESSAGE: u64 = 64 * 1024;
+/// 読み込みを待つ上限 (黙ったままの接続にほかの接続を待たせ続けないため)
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum Event {
+    /// ソケットで受け取ったおかずとキャプション
+    Message(String, Option<String>),
+    /// 標準入力の 1 行
+    Line(String),
+    /// 監視しているファイルの新しい内容
+    Changed(String),
+}
+
+pub struct Events {
+    tx: Sender<Event>,
+    rx: Receiver<Event>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (tx, rx) = channel();
+        Self { tx, rx }
+    }
+
+    /// `timeout` (None なら無期限) まで次の出来事を待つ。時間切れなら None
+    pub fn next(&self, timeout: Option<Duration>) -> Option<Event> {
+        match timeout {
+            Some(timeout) => self.rx.recv_timeout(timeout).ok(),
+            None => self.rx.recv().ok(),
+        }
+    }
+
+    /// 標準入力の各行 (閉じたらスレッドも終わる)
+    pub fn stdin_lines(&self) {
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+                if tx.send(Event::Line(line)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// `path` の更新日時が変わるたびに内容を送る (最初の内容も送る)
+    pub fn watch(&self, path: PathBuf) {
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let mut last: Option<SystemTime> = None;
+            loop {
+                let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+                if modified.is_some() && modified != last {
+                    last = modified;
+                    match std::fs::read_to_string(&path) {
+                        Ok(text) => {
+                            if tx.send(Event::Changed(text)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => eprintln!(
+                            "{}",
+                            i18n::tr(&format!("{} を読み込めません: {}", path.display(), e))
+                        ),
+                    }
+                }
+                thread::sleep(WATCH_POLL);
+            }
+        });
+    }
+
+    /// ソケットへの接続ごとに、`separator` の前をおかず、後をキャプションとして送る
+    #[cfg(unix)]
+    pub fn socket(&self, listener: std::os::unix::net::UnixListener, separator: char) {
+        use std::io::{ErrorKind, Read};
+
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut message = String::new();
+                let received = stream.and_then(|stream| {
+                    stream.set_read_timeout(Some(RECEIVE_TIMEOUT))?;
+                    stream.take(MAX_MESSAGE + 1).read_to_string(&mut message)
+                });
+                match received {
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                        eprintln!("{}", i18n::tr("受信が時間切れになりました"));
+                        continue;
+                    }
+                    Err(e) => {
+                        eprintln!("{}", i18n::tr(&format!("受信エラー: {}", e)));
+                        continue;
+                    }
+                    Ok(_) => {}
+                }
+                if message.len() as u64 > MAX_MESSAGE {
+                    eprintln!(
+                        "{}",
+                        i18n::tr(&format!(
+                            "メッセージが大きすぎます (最大 {} バイト)",
+                            MAX_MESSAGE
+                        ))
+                    );
+                    continue;
+                }
+                let event = match message.split_once(separator) {
+                    Some((side_dish, caption)) => {
+                        Event::Message(side_dish.to_string(), Some(caption.to_string()))
+                    }
+                    None => Event::Message(message, None),
+                };
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,44 @@
+use crate::font::Font;
+use crate::quantize::Quantize;
+use crate::renderer::{self, Renderer};
+use crate::{play, render_say, PlayOptions, RenderOptions, Script};
+use anyhow::Result;
+use std::path::Path;
+
+/// `say` と同じフレームを PNG 画像として `image` に書き出す
+pub fn png(
+    side_dish: &str,
+    caption: Option<&str>,
+    mut image: renderer::Image,
+    opts: RenderOptions,
+) -> Result<()> {
+    let opts = colored(opts);
+    image.frame(&render_say(side_dish, caption, 0, &opts)?)?;
+    image.finish()
+}
+
+/// `anime --export` で、再生するフレームを待たずに拡張子の形式のファイルへ書き出す
+pub fn anime(
+    script: Script,
+    play_opts: PlayOptions,
+    output: &Path,
+    font: impl FnOnce() -> Result<Font>,
+    quantize: Quantize,
+    opts: RenderOptions,
+) -> Result<()> {
+    let mut renderer = renderer::open(output, font, quantize)?;
+    let play_opts = PlayOptions {
+        animate: false,
+        interactive: false,
+        ..play_opts
+    };
+    play(renderer.as_mut(), script, play_opts, || 0, &colored(opts))
+}
+
+/// 色指定は端末かどうかに関係なくファイルへ反映する
+fn colored(mut opts: RenderOptions) -> RenderOptions {
+    opts.art_style = opts.art_style.force_styling(true);
+    opts.bubble_style = opts.bubble_style.force_styling(true);
+    opts.caption_style = opts.caption_style.force_styling(true);
+    opts
+}
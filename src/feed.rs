@@ -0,0 +1,146 @@
+//! RSS と Atom の見出し
+//!
+//! XML の解析器は使わず、`<item>`/`<entry>` と `<title>` だけを拾う
+
+use crate::fetch;
+use anyhow::{bail, Result};
+
+/// フィードの本文の上限 (バイト)
+const MAX_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+pub struct Feed {
+    pub title: String,
+    pub entries: Vec<String>,
+}
+
+pub fn fetch(url: &str) -> Result<Feed> {
+    parse(&fetch::text(url, MAX_BYTES)?)
+}
+
+pub fn parse(xml: &str) -> Result<Feed> {
+    let starts: Vec<usize> = ["<item", "<entry"]
+        .iter()
+        .flat_map(|tag| element_starts(xml, tag))
+        .collect();
+    let first = starts.iter().copied().min().unwrap_or(xml.len());
+    let feed_title = title(&xml[..first]).unwrap_or_default();
+
+    let mut starts = starts;
+    starts.sort_unstable();
+    let entries: Vec<String> = starts
+        .iter()
+        .filter_map(|&start| {
+            let (tag, end_tag) = match xml[start..].starts_with("<item") {
+                true => ("<item", "</item>"),
+                false => ("<entry", "</entry>"),
+            };
+            let body = &xml[start + tag.len()..];
+            let end = body.find(end_tag).unwrap_or(body.len());
+            title(&body[..end])
+        })
+        .filter(|title| !title.is_empty())
+        .collect();
+    if feed_title.is_empty() && entries.is_empty() {
+        bail!("RSS か Atom のフィードではありません");
+    }
+    Ok(Feed {
+        title: feed_title,
+        entries,
+    })
+}
+
+/// `<tag>` や `<tag ...>` の開始位置 (`<items>` などは除く)
+fn element_starts<'a>(xml: &'a str, tag: &'a str) -> impl Iterator<Item = usize> + 'a {
+    xml.match_indices(tag)
+        .filter_map(move |(i, _)| match xml[i + tag.len()..].chars().next() {
+            Some('>' | ' ' | '\t' | '\r' | '\n' | '/') => Some(i),
+            _ => None,
+        })
+}
+
+/// 最初の `<title>` の中身
+fn title(xml: &str) -> Option<String> {
+    let start = element_starts(xml, "<title").next()?;
+    let open = xml[start..].find('>')? + start;
+    if xml[..open].ends_with('/') {
+        return Some(String::new());
+    }
+    let close = xml[open..].find("</title>")? + open;
+    Some(text(&xml[open + 1..close]))
+}
+
+/// CDATA を外し、中のタグを除いて文字参照を戻し、空白をまとめる
+fn text(content: &str) -> String {
+    let mut out = String::new();
+    let mut rest = content;
+    while !rest.is_empty() {
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            // Atom の type="html" などで CDATA の中にタグがあることもある
+            out.push_str(&strip_tags(&cdata[..end]));
+            rest = cdata.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+        let end = rest.find("<![CDATA[").unwrap_or(rest.len());
+        out.push_str(&unescape(&strip_tags(&rest[..end])));
+        rest = &rest[end..];
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn strip_tags(text: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match (c, in_tag) {
+            ('<', _) => in_tag = true,
+            ('>', true) => in_tag = false,
+            (c, false) => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let Some(end) = rest.find(';').filter(|end| *end <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,71 @@
+use anyhow::{bail, Context, Result};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// say --url で取得する本文の上限 (バイト)
+pub const MAX_BYTES: u64 = 64 * 1024;
+/// 接続から読み終わるまでの上限 (秒)
+pub const TIMEOUT_SECS: u64 = 10;
+/// `download` で読み終わるまでの上限 (秒)
+pub const DOWNLOAD_TIMEOUT_SECS: u64 = 300;
+
+/// `url` の本文を curl で取得する
+///
+/// http と https だけを許し、`max_bytes` を超えたり `TIMEOUT_SECS` 秒を過ぎたりしたら失敗する
+pub fn text(url: &str, max_bytes: u64) -> Result<String> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        bail!("{} は http か https の URL ではありません", url);
+    }
+    let mut child = Command::new("curl")
+        .args(["-fsSL", "--proto", "=http,https", "--max-time"])
+        .arg(TIMEOUT_SECS.to_string())
+        .arg("--max-filesize")
+        .arg(max_bytes.to_string())
+        .arg(url)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("curl を実行できません")?;
+
+    let mut body = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        // Content-Length の無い応答は --max-filesize では止まらないので読む量も制限する
+        stdout.take(max_bytes + 1).read_to_end(&mut body)?;
+    }
+    if body.len() as u64 > max_bytes {
+        child.kill().ok();
+        child.wait()?;
+        bail!("{} の本文が大きすぎます (最大 {} バイト)", url, max_bytes);
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("{} を取得できません ({})", url, status);
+    }
+    String::from_utf8(body).with_context(|| format!("{} の本文が UTF-8 ではありません", url))
+}
+
+/// `url` を `dest` に curl で保存する (`max_bytes` を超えたら失敗する)
+///
+/// 大きなファイル向けなので、時間の上限は `DOWNLOAD_TIMEOUT_SECS` 秒にする
+pub fn download(url: &str, dest: &Path, max_bytes: u64) -> Result<()> {
+    if !url.starts_with("https://") {
+        bail!("{} は https の URL ではありません", url);
+    }
+    let status = Command::new("curl")
+        .args(["-fsSL", "--proto", "=https", "--max-time"])
+        .arg(DOWNLOAD_TIMEOUT_SECS.to_string())
+        .arg("--max-filesize")
+        .arg(max_bytes.to_string())
+        .arg("-o")
+        .arg(dest)
+        .arg(url)
+        .status()
+        .context("curl を実行できません")?;
+    if !status.success() {
+        bail!("{} を取得できません ({})", url, status);
+    }
+    if std::fs::metadata(dest)?.len() > max_bytes {
+        bail!("{} の本文が大きすぎます (最大 {} バイト)", url, max_bytes);
+    }
+    Ok(())
+}
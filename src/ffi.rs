@@ -0,0 +1,44 @@
+//! C から呼ぶための関数
+//!
+//! ```c
+//! char *frame = fsd_render("からあげ", 0);
+//! puts(frame);
+//! fsd_free(frame);
+//! ```
+//!
+//! 宣言は `include/fav_say_dragon.h` にある
+
+use std::ffi::{c_char, CStr, CString};
+
+/// NUL 終端の UTF-8 のおかずを描き、改行区切りの行を NUL 終端の UTF-8 で返す
+///
+/// 各行は表示幅 `width` まで空白で埋める (0 なら埋めない)。`text` が NULL か UTF-8 でなければ
+/// NULL を返す。戻り値は `fsd_free` で解放する
+///
+/// # Safety
+///
+/// `text` は NULL か NUL 終端の文字列を指すこと
+#[no_mangle]
+pub unsafe extern "C" fn fsd_render(text: *const c_char, width: usize) -> *mut c_char {
+    if text.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(text) = CStr::from_ptr(text).to_str() else {
+        return std::ptr::null_mut();
+    };
+    // おかずに NUL は入らないので、描いた結果にも入らない
+    CString::new(crate::render(text, width).join("\n"))
+        .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// `fsd_render` の戻り値を解放する (NULL なら何もしない)
+///
+/// # Safety
+///
+/// `frame` は `fsd_render` の戻り値で、まだ解放していないこと
+#[no_mangle]
+pub unsafe extern "C" fn fsd_free(frame: *mut c_char) {
+    if !frame.is_null() {
+        drop(CString::from_raw(frame));
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// `--font` も設定も無いときに探すビットマップフォント
+const DEFAULT_FONTS: &[&str] = &[
+    "/usr/share/fonts/X11/misc/unifont.bdf",
+    "/usr/share/fonts/misc/unifont.bdf",
+    "/usr/share/unifont/unifont.hex",
+    "/usr/local/share/fonts/unifont.bdf",
+    "/opt/homebrew/share/fonts/unifont.bdf",
+];
+
+/// BDF または GNU Unifont の .hex 形式のビットマップフォント
+#[derive(Debug)]
+pub struct Font {
+    /// 半角 1 文字分の幅 (px)
+    pub cell_width: usize,
+    pub height: usize,
+    glyphs: HashMap<char, Glyph>,
+}
+
+/// `height` 行 × `width` 列のビットマップ (ベースラインをそろえた状態)
+#[derive(Debug)]
+pub struct Glyph {
+    pub width: usize,
+    bits: Vec<bool>,
+}
+
+impl Glyph {
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && self.bits.get(y * self.width + x).copied().unwrap_or(false)
+    }
+}
+
+impl Font {
+    /// 指定が無ければ既定の場所からフォントを探す
+    pub fn find(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            return Self::load(path);
+        }
+        match DEFAULT_FONTS.iter().map(PathBuf::from).find(|path| path.exists()) {
+            Some(path) => Self::load(&path),
+            None => bail!(
+                "ビットマップフォントが見つかりません (--font で BDF か .hex ファイルを指定してください)"
+            ),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("フォント {} を読み込めません", path.display()))?;
+        let font = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("hex") => parse_hex(&source),
+            _ => parse_bdf(&source),
+        };
+        font.with_context(|| format!("フォント {} を解釈できません", path.display()))
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c)
+    }
+}
+
+fn parse_bdf(source: &str) -> Result<Font> {
+    let mut ascent = None;
+    let mut descent = None;
+    let mut bounding_box = None;
+    let mut raw = Vec::new();
+
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("FONTBOUNDINGBOX") => bounding_box = Some(numbers::<4>(words)?),
+            Some("FONT_ASCENT") => ascent = Some(numbers::<1>(words)?[0]),
+            Some("FONT_DESCENT") => descent = Some(numbers::<1>(words)?[0]),
+            Some("STARTCHAR") => raw.push(parse_bdf_char(&mut lines)?),
+            _ => {}
+        }
+    }
+
+    let [_, bbox_height, _, bbox_yoff] =
+        bounding_box.ok_or_else(|| anyhow!("FONTBOUNDINGBOX がありません"))?;
+    let ascent = ascent.unwrap_or(bbox_height + bbox_yoff);
+    let descent = descent.unwrap_or(-bbox_yoff);
+    let height = (ascent + descent).max(1) as usize;
+
+    let mut glyphs = HashMap::new();
+    for glyph in raw {
+        let Some(c) = glyph.encoding.and_then(|e| char::from_u32(e as u32)) else {
+            continue;
+        };
+        let width = glyph.advance.max(0) as usize;
+        let mut bits = vec![false; width * height];
+        let top = ascent - (glyph.height + glyph.yoff);
+        for (row, line) in glyph.rows.iter().enumerate() {
+            let y = top + row as i64;
+            if y < 0 || y >= height as i64 {
+                continue;
+            }
+            for col in 0..glyph.width {
+                let x = glyph.xoff + col;
+                if x < 0 || x >= width as i64 {
+                    continue;
+                }
+                let byte = line.get(col as usize / 8).copied().unwrap_or(0);
+                if byte & (0x80 >> (col % 8)) != 0 {
+                    bits[y as usize * width + x as usize] = true;
+                }
+            }
+        }
+        glyphs.insert(c, Glyph { width, bits });
+    }
+
+    Ok(Font {
+        cell_width: cell_width(&glyphs),
+        height,
+        glyphs,
+    })
+}
+
+struct BdfChar {
+    encoding: Option<i64>,
+    advance: i64,
+    width: i64,
+    height: i64,
+    xoff: i64,
+    yoff: i64,
+    rows: Vec<Vec<u8>>,
+}
+
+fn parse_bdf_char<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<BdfChar> {
+    let mut glyph = BdfChar {
+        encoding: None,
+        advance: 0,
+        width: 0,
+        height: 0,
+        xoff: 0,
+        yoff: 0,
+        rows: Vec::new(),
+    };
+    let mut in_bitmap = false;
+    for line in lines {
+        if line.starts_with("ENDCHAR") {
+            return Ok(glyph);
+        }
+        if in_bitmap {
+            glyph.rows.push(hex_bytes(line.trim())?);
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("ENCODING") => glyph.encoding = Some(numbers::<1>(words)?[0]).filter(|e| *e >= 0),
+            Some("DWIDTH") => glyph.advance = numbers::<1>(words)?[0],
+            Some("BBX") => {
+                let [width, height, xoff, yoff] = numbers::<4>(words)?;
+                glyph.width = width;
+                glyph.height = height;
+                glyph.xoff = xoff;
+                glyph.yoff = yoff;
+                if glyph.advance == 0 {
+                    glyph.advance = width;
+                }
+            }
+            Some("BITMAP") => in_bitmap = true,
+            _ => {}
+        }
+    }
+    bail!("ENDCHAR がありません")
+}
+
+/// `0041:0000000018242442427E424242420000` のような行の並び
+fn parse_hex(source: &str) -> Result<Font> {
+    const HEIGHT: usize = 16;
+
+    let mut glyphs = HashMap::new();
+    for line in source.lines().filter(|line| !line.trim().is_empty()) {
+        let (code, bitmap) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("不正な行です: {}", line))?;
+        let code = u32::from_str_radix(code.trim(), 16)?;
+        let Some(c) = char::from_u32(code) else {
+            continue;
+        };
+        let bytes = hex_bytes(bitmap.trim())?;
+        if bytes.is_empty() || !bytes.len().is_multiple_of(HEIGHT) {
+            bail!("U+{:04X} のビットマップの長さが不正です", code);
+        }
+        let width = bytes.len() * 8 / HEIGHT;
+        let row_bytes = width / 8;
+        let mut bits = vec![false; width * HEIGHT];
+        for y in 0..HEIGHT {
+            for x in 0..width {
+                let byte = bytes[y * row_bytes + x / 8];
+                bits[y * width + x] = byte & (0x80 >> (x % 8)) != 0;
+            }
+        }
+        glyphs.insert(c, Glyph { width, bits });
+    }
+
+    Ok(Font {
+        cell_width: cell_width(&glyphs),
+        height: HEIGHT,
+        glyphs,
+    })
+}
+
+/// 半角文字の幅を代表の文字から決める
+fn cell_width(glyphs: &HashMap<char, Glyph>) -> usize {
+    ['0', 'M', ' ']
+        .iter()
+        .find_map(|c| glyphs.get(c).map(|glyph| glyph.width))
+        .or_else(|| glyphs.values().map(|glyph| glyph.width).min())
+        .unwrap_or(8)
+        .max(1)
+}
+
+fn numbers<'a, const N: usize>(words: impl Iterator<Item = &'a str>) -> Result<[i64; N]> {
+    let numbers = words
+        .take(N)
+        .map(|word| word.parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()?;
+    numbers
+        .try_into()
+        .map_err(|_| anyhow!("数値が {} 個必要です", N))
+}
+
+fn hex_bytes(hex: &str) -> Result<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        bail!("不正な 16 進数です: {}", hex);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| anyhow!("不正な 16 進数です: {}", hex))
+        })
+        .collect()
+}
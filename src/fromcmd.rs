@@ -0,0 +1,75 @@
+//! `anime --from-cmd` のコマンドの出力のフレーム
+//!
+//! フレームを出す直前にシェルのコマンド (`fortune` など) を実行し、標準出力をおかずにする。
+//! 書き出しや `--dump-json` のように再生しないときは先にすべて実行する
+
+use crate::{Frame, RenderOptions, Script, Text};
+use anyhow::{bail, Context, Result};
+use std::process::{Command, Stdio};
+
+/// 既定のフレームの数
+pub const DEFAULT_FRAMES: usize = 10;
+
+/// `command` を実行する `frames` 枚のフレームを、プレキャプションとアフターキャプションの間に並べる
+pub fn script(
+    command: &str,
+    frames: usize,
+    pre_captions: Vec<String>,
+    after_captions: Vec<String>,
+) -> Script {
+    let caption = |caption: String| Frame {
+        caption: Text::Plain(caption),
+        ..Frame::default()
+    };
+    let commands = (0..frames).map(|_| Frame {
+        command: Some(command.to_string()),
+        ..Frame::default()
+    });
+    Script {
+        frames: pre_captions
+            .into_iter()
+            .map(caption)
+            .chain(commands)
+            .chain(after_captions.into_iter().map(caption))
+            .collect(),
+        ..Script::default()
+    }
+}
+
+/// `frame` のコマンドを実行して、その出力をおかずにする (コマンドの無いフレームはそのまま)
+pub fn resolve(frame: &mut Frame, opts: &RenderOptions) -> Result<()> {
+    let Some(command) = frame.command.take() else {
+        return Ok(());
+    };
+    let mut shell = match cfg!(windows) {
+        true => {
+            let mut shell = Command::new("cmd");
+            shell.args(["/C", &command]);
+            shell
+        }
+        false => {
+            let mut shell = Command::new("sh");
+            shell.args(["-c", &command]);
+            shell
+        }
+    };
+    let output = shell
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("コマンド '{}' を実行できません", command))?;
+    if !output.status.success() {
+        bail!("コマンド '{}' が失敗しました ({})", command, output.status);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    frame.side_dish = Text::Plain(opts.sanitize(stdout.trim_end()).into_owned());
+    Ok(())
+}
+
+/// スクリプトのすべてのフレームのコマンドを実行する
+pub fn resolve_all(script: &mut Script, opts: &RenderOptions) -> Result<()> {
+    script
+        .frames
+        .iter_mut()
+        .try_for_each(|frame| resolve(frame, opts))
+}
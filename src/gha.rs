@@ -0,0 +1,35 @@
+//! GitHub Actions のジョブのまとめと注釈
+//!
+//! `say --gha-summary` はフレームを `$GITHUB_STEP_SUMMARY` に Markdown のコードブロックとして足し、
+//! `--gha-notice` はキャプションを `::notice::` の注釈にする
+
+use crate::chat::Format;
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+
+/// `$GITHUB_STEP_SUMMARY` のファイルに追記する
+pub fn summary(lines: &[String]) -> Result<()> {
+    let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY").filter(|path| !path.is_empty()) else {
+        bail!("GITHUB_STEP_SUMMARY が設定されていません (GitHub Actions の中で実行してください)");
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("{} を開けません", path.to_string_lossy()))?;
+    file.write_all(Format::Markdown.block(lines).as_bytes())?;
+    Ok(())
+}
+
+/// ワークフローコマンドの `::notice::` の行
+pub fn notice(message: &str) -> String {
+    format!("::notice title=fav_say_dragon::{}", escape(message))
+}
+
+/// ワークフローコマンドのメッセージで使えない文字を置き換える
+fn escape(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
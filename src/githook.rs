@@ -0,0 +1,92 @@
+//! git のフックからコミットメッセージを吹き出しに出す
+
+use crate::{i18n, say, tty::RawTty, RenderOptions};
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const HOOK: &str = "commit-msg";
+
+/// コミットメッセージのファイルを読み、コメント以外の最初の行を表示する
+///
+/// `confirm` なら y/n を押すまで待ち、n ならフックを失敗させてコミットを止める
+pub fn commit_msg(file: &Path, confirm: bool, opts: &RenderOptions) -> Result<()> {
+    let message = std::fs::read_to_string(file)
+        .with_context(|| format!("{} を読み込めません", file.display()))?;
+    // 空のメッセージは git が自分で中止する
+    let Some(subject) = message
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+    else {
+        return Ok(());
+    };
+
+    // フックの標準入力は端末とは限らないので、制御端末から直接読む
+    let tty = match confirm {
+        true => RawTty::open(),
+        false => None,
+    };
+    let caption = match tty {
+        Some(_) => i18n::tr("このメッセージでコミットしますか？ (y/n)"),
+        None => i18n::tr("コミットメッセージ"),
+    };
+    say(subject, Some(&caption), None, opts)?;
+    println!();
+
+    let Some(mut tty) = tty else {
+        if confirm {
+            eprintln!("{}", i18n::tr("端末が無いので確認を省略しました"));
+        }
+        return Ok(());
+    };
+    let mut buf = [0u8; 1];
+    loop {
+        // 端末が閉じられたときも 0 が返る
+        if tty.read_timeout(&mut buf, None) == 0 {
+            bail!("コミットを中止しました");
+        }
+        match buf[0] {
+            b'y' | b'Y' => return Ok(()),
+            // Ctrl-C と Esc も中止として扱う
+            b'n' | b'N' | 3 | 0x1b => bail!("コミットを中止しました"),
+            _ => {}
+        }
+    }
+}
+
+/// このリポジトリの commit-msg フックにこのプログラムを登録する
+pub fn install(confirm: bool, force: bool) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .stderr(Stdio::null())
+        .output()
+        .context("git を実行できません")?;
+    if !output.status.success() {
+        bail!("git のリポジトリではありません");
+    }
+    let hooks = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let path = hooks.join(HOOK);
+    if path.exists() && !force {
+        bail!(
+            "{} は既にあります (上書きするには --force を指定してください)",
+            path.display()
+        );
+    }
+
+    let exe = std::env::current_exe().context("実行ファイルのパスを特定できません")?;
+    let script = format!(
+        "#!/bin/sh\n# fav_say_dragon git-hook install で作成\nexec '{}' git-hook {}{} \"$1\"\n",
+        exe.display().to_string().replace('\'', r"'\''"),
+        HOOK,
+        if confirm { " --confirm" } else { "" }
+    );
+    std::fs::create_dir_all(&hooks)?;
+    std::fs::write(&path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,193 @@
+use crate::font::Font;
+use crate::raster::{self, Image, Palette, Rgb};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Kitty のエスケープシーケンス 1 つに載せる base64 の長さ
+const KITTY_CHUNK: usize = 4096;
+/// 画像の余白の既定値 (px)
+pub const PADDING: usize = 8;
+/// sixel の色数の上限
+const SIXEL_COLORS: usize = 256;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Sixel,
+    Kitty,
+}
+
+pub fn encode(image: &Image, protocol: Protocol) -> String {
+    match protocol {
+        Protocol::Sixel => sixel(image),
+        Protocol::Kitty => kitty(image),
+    }
+}
+
+/// 生の RGB を分割して送る
+fn kitty(image: &Image) -> String {
+    let data: Vec<u8> = image.pixels.iter().flatten().copied().collect();
+    let data = base64(&data);
+    let chunks: Vec<&str> = data
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        match i {
+            0 => write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},m={};{}\x1b\\",
+                image.width, image.height, more, chunk
+            ),
+            _ => write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk),
+        }
+        .unwrap_or_default();
+    }
+    out.push('\n');
+    out
+}
+
+fn sixel(image: &Image) -> String {
+    let (palette, indices) = quantize(image);
+
+    let mut out = String::new();
+    write!(out, "\x1bPq\"1;1;{};{}", image.width, image.height).unwrap_or_default();
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        let percent = |c: u8| c as u32 * 100 / 255;
+        write!(
+            out,
+            "#{};2;{};{};{}",
+            i,
+            percent(*r),
+            percent(*g),
+            percent(*b)
+        )
+        .unwrap_or_default();
+    }
+
+    for band in (0..image.height).step_by(6) {
+        let rows = band..(band + 6).min(image.height);
+        let mut used: Vec<usize> = rows
+            .clone()
+            .flat_map(|y| {
+                indices[y * image.width..(y + 1) * image.width]
+                    .iter()
+                    .copied()
+            })
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+
+        for (n, color) in used.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            write!(out, "#{}", color).unwrap_or_default();
+            let sixels = (0..image.width).map(|x| {
+                let bits = rows.clone().fold(0u8, |bits, y| {
+                    match indices[y * image.width + x] == *color {
+                        true => bits | 1 << (y - band),
+                        false => bits,
+                    }
+                });
+                (0x3f + bits) as char
+            });
+            push_run_length(&mut out, sixels);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\\n");
+    out
+}
+
+/// 同じ文字が続く部分を `!回数文字` にまとめる
+fn push_run_length(out: &mut String, chars: impl Iterator<Item = char>) {
+    let flush = |out: &mut String, c: char, count: usize| match count {
+        0 => {}
+        1..=3 => out.extend(std::iter::repeat_n(c, count)),
+        _ => write!(out, "!{}{}", count, c).unwrap_or_default(),
+    };
+    let mut current = None;
+    let mut count = 0;
+    for c in chars {
+        if current == Some(c) {
+            count += 1;
+            continue;
+        }
+        if let Some(current) = current {
+            flush(out, current, count);
+        }
+        current = Some(c);
+        count = 1;
+    }
+    if let Some(current) = current {
+        flush(out, current, count);
+    }
+}
+
+/// 使われている色を並べ、多すぎれば 6x6x6 の色立方体へ丸める
+fn quantize(image: &Image) -> (Vec<Rgb>, Vec<usize>) {
+    let mut palette = Vec::new();
+    let mut lookup = HashMap::new();
+    for pixel in &image.pixels {
+        lookup.entry(*pixel).or_insert_with(|| {
+            palette.push(*pixel);
+            palette.len() - 1
+        });
+    }
+    if palette.len() <= SIXEL_COLORS {
+        let indices = image.pixels.iter().map(|pixel| lookup[pixel]).collect();
+        return (palette, indices);
+    }
+
+    let level = |c: u8| (c as usize * 5 + 127) / 255;
+    let palette = (0..216)
+        .map(|i| [i / 36, i / 6 % 6, i % 6].map(|l| (l * 51) as u8))
+        .collect();
+    let indices = image
+        .pixels
+        .iter()
+        .map(|[r, g, b]| level(*r) * 36 + level(*g) * 6 + level(*b))
+        .collect();
+    (palette, indices)
+}
+
+pub fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - i * 8));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(TABLE[(n >> (18 - i * 6)) as usize & 0x3f] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+/// `--graphics` で画像として出力するときの設定
+#[derive(Clone, Debug)]
+pub struct Graphics {
+    pub protocol: Protocol,
+    pub font: Arc<Font>,
+    pub palette: Palette,
+}
+
+impl Graphics {
+    pub fn frame(&self, lines: &[String]) -> String {
+        encode(
+            &raster::render(lines, &self.font, self.palette, PADDING),
+            self.protocol,
+        )
+    }
+}
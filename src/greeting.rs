@@ -0,0 +1,71 @@
+//! 新しいシェルを開いたときのあいさつ
+//!
+//! `shell-init` が rc ファイルに足す断片を出力し、その断片が `greet` を呼ぶ。最後にあいさつした
+//! 時刻をデータディレクトリに残し、`--every` の間は何も表示しない
+
+use crate::{config, placeholder::LocalTime, rng::Rng, say, scripting, RenderOptions};
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// rc ファイルに足す断片
+pub fn init_script(shell: Shell, every: Duration) -> Result<String> {
+    let exe = std::env::current_exe().context("実行ファイルのパスを特定できません")?;
+    let exe = format!("'{}'", exe.display().to_string().replace('\'', r"'\''"));
+    let every = format!("{}s", every.as_secs());
+    Ok(match shell {
+        Shell::Bash | Shell::Zsh => format!(
+            "# fav_say_dragon のあいさつ (fav_say_dragon shell-init で作成)\n\
+             case $- in\n    *i*) {} greet --every {} ;;\nesac\n",
+            exe, every
+        ),
+        Shell::Fish => format!(
+            "# fav_say_dragon のあいさつ (fav_say_dragon shell-init で作成)\n\
+             if status is-interactive\n    {} greet --every {}\nend\n",
+            exe, every
+        ),
+    })
+}
+
+/// 前のあいさつから `every` 以上たっていれば、時間帯に合わせたあいさつとおかずを表示する
+pub fn greet(every: Duration, opts: &RenderOptions) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let state = state_path();
+    let last = state
+        .as_ref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| text.trim().parse::<u64>().ok());
+    if last.is_some_and(|last| now.saturating_sub(last) < every.as_secs()) {
+        return Ok(());
+    }
+    if let Some(state) = &state {
+        // 記録できなくてもあいさつは続ける
+        let _ = state
+            .parent()
+            .map(std::fs::create_dir_all)
+            .transpose()
+            .and_then(|_| std::fs::write(state, now.to_string()));
+    }
+
+    let caption = match LocalTime::now().hour {
+        5..=10 => "おはようございます",
+        11..=17 => "こんにちは",
+        _ => "こんばんは",
+    };
+    let dishes = scripting::DEFAULT_DISHES;
+    let dish = dishes[Rng::stream(opts.seed, "greeting").below(dishes.len())];
+    say(dish, Some(&crate::i18n::tr(caption)), None, opts)
+}
+
+fn state_path() -> Option<PathBuf> {
+    Some(config::data_dir()?.join("last_greeting"))
+}
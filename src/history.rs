@@ -0,0 +1,135 @@
+//! `say` の履歴とお気に入り
+//!
+//! どちらもデータディレクトリの TOML (`history.toml`, `favorites.toml`) に置き、
+//! `history` と `fav list` の番号 (1 から) で `again` から出し直せる
+
+use crate::{config, i18n};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 履歴に残す数 (古いものから捨てる)
+const MAX_HISTORY: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub side_dish: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+}
+
+impl Entry {
+    /// 一覧に出す 1 行 (改行は ⏎ にする)
+    pub fn summary(&self) -> String {
+        let line = |text: &str| text.replace('\n', "⏎");
+        match &self.caption {
+            Some(caption) if !caption.is_empty() => {
+                format!("{} — {}", line(&self.side_dish), line(caption))
+            }
+            _ => line(&self.side_dish),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct List {
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+/// 履歴かお気に入りのファイル
+#[derive(Clone, Copy)]
+pub enum Store {
+    History,
+    Favorites,
+}
+
+impl Store {
+    fn path(self) -> Option<PathBuf> {
+        let name = match self {
+            Store::History => "history.toml",
+            Store::Favorites => "favorites.toml",
+        };
+        Some(config::data_dir()?.join(name))
+    }
+
+    /// 古いものから順に (ファイルが無ければ空)
+    pub fn load(self) -> Result<Vec<Entry>> {
+        let Some(path) = self.path().filter(|path| path.exists()) else {
+            return Ok(Vec::new());
+        };
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| format!("{} を読み込めません", path.display()))?;
+        let list: List = toml::from_str(&source)
+            .with_context(|| format!("{} の読み込みに失敗しました", path.display()))?;
+        Ok(list.entries)
+    }
+
+    fn save(self, entries: Vec<Entry>) -> Result<()> {
+        let Some(path) = self.path() else {
+            bail!("データディレクトリを特定できません (HOME が未設定です)");
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(&List { entries })?)?;
+        Ok(())
+    }
+
+    /// `number` 番 (1 から) のもの。`None` なら最新のもの
+    pub fn get(self, number: Option<usize>) -> Result<Entry> {
+        let entries = self.load()?;
+        let entry = match number {
+            Some(number) => number.checked_sub(1).and_then(|i| entries.get(i)),
+            None => entries.last(),
+        };
+        match (entry, number) {
+            (Some(entry), _) => Ok(entry.clone()),
+            (None, Some(number)) => bail!("{} 番はありません", number),
+            (None, None) => bail!("まだ何もありません"),
+        }
+    }
+
+    /// 最後に足す。履歴では直前と同じなら足さず、お気に入りでは同じものがあれば足さない
+    pub fn push(self, entry: Entry) -> Result<()> {
+        let mut entries = self.load()?;
+        let duplicate = match self {
+            Store::History => entries.last() == Some(&entry),
+            Store::Favorites => entries.contains(&entry),
+        };
+        if duplicate {
+            return Ok(());
+        }
+        entries.push(entry);
+        if let Store::History = self {
+            let excess = entries.len().saturating_sub(MAX_HISTORY);
+            entries.drain(..excess);
+        }
+        self.save(entries)
+    }
+
+    /// `number` 番 (1 から) を取り除く
+    pub fn remove(self, number: usize) -> Result<Entry> {
+        let mut entries = self.load()?;
+        if !(1..=entries.len()).contains(&number) {
+            bail!("{} 番はありません", number);
+        }
+        let entry = entries.remove(number - 1);
+        self.save(entries)?;
+        Ok(entry)
+    }
+}
+
+/// 履歴に残す (残せなくても表示は止めない)
+pub fn record(side_dish: &str, caption: Option<&str>) {
+    let entry = Entry {
+        side_dish: side_dish.to_string(),
+        caption: caption.map(str::to_string),
+    };
+    if let Err(e) = Store::History.push(entry) {
+        eprintln!(
+            "{}",
+            i18n::tr(&format!("履歴に残せませんでした: {:#}", i18n::error(e)))
+        );
+    }
+}
@@ -0,0 +1,727 @@
+//! ヘルプとメッセージの翻訳
+//!
+//! 日本語の文言そのものをキーにして英語の文言を引く。`{}` の部分は任意の文字列に
+//! 一致し、取り出した値も翻訳してから英語の文言に埋め込む
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(clap::ValueEnum, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// 日本語
+    #[default]
+    Ja,
+    /// English
+    En,
+}
+
+static LANG: AtomicU8 = AtomicU8::new(Lang::Ja as u8);
+
+/// `--lang`、ロケールの順に言語を決める
+///
+/// ヘルプを出す前に決める必要があるので、clap より先にコマンドラインを覗く
+pub fn detect(args: &[String]) -> Lang {
+    let from_args = args
+        .iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.strip_prefix("--lang") {
+            Some("") => args.get(i + 1).cloned(),
+            Some(value) => value.strip_prefix('=').map(String::from),
+            None => None,
+        });
+    from_args
+        .and_then(|lang| parse(&lang))
+        .unwrap_or_else(from_locale)
+}
+
+impl Lang {
+    /// スクリプトで訳を書くときの名前
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::Ja => "ja",
+            Lang::En => "en",
+        }
+    }
+}
+
+pub fn set(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        n if n == Lang::En as u8 => Lang::En,
+        _ => Lang::Ja,
+    }
+}
+
+fn parse(lang: &str) -> Option<Lang> {
+    match lang.to_ascii_lowercase().get(..2)? {
+        "ja" => Some(Lang::Ja),
+        "en" => Some(Lang::En),
+        _ => None,
+    }
+}
+
+/// 日本語以外の言語が明示されていれば英語にする (C や未設定は日本語のまま)
+pub fn from_locale() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale.as_deref() {
+        None | Some("C" | "POSIX") => Lang::Ja,
+        Some(locale) if locale.starts_with("C.") || locale.starts_with("ja") => Lang::Ja,
+        Some(_) => Lang::En,
+    }
+}
+
+/// 現在の言語に訳す (訳が無ければそのまま)
+pub fn tr(message: &str) -> Cow<'_, str> {
+    match current() {
+        Lang::Ja => Cow::Borrowed(message),
+        Lang::En => translate(message),
+    }
+}
+
+fn translate(message: &str) -> Cow<'_, str> {
+    for (ja, en) in CATALOG {
+        if let Some(values) = matches(ja, message) {
+            let mut out = String::new();
+            let mut values = values.into_iter();
+            for (i, part) in en.split("{}").enumerate() {
+                if i > 0 {
+                    out.push_str(&translate(values.next().unwrap_or_default()));
+                }
+                out.push_str(part);
+            }
+            return Cow::Owned(out);
+        }
+    }
+    Cow::Borrowed(message)
+}
+
+/// `pattern` の `{}` に当たる部分を取り出す
+fn matches<'a>(pattern: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let parts: Vec<&str> = pattern.split("{}").collect();
+    let (first, rest) = parts.split_first()?;
+    let mut remaining = message.strip_prefix(first)?;
+    if rest.is_empty() {
+        return remaining.is_empty().then(Vec::new);
+    }
+    let mut values = Vec::new();
+    for (i, part) in rest.iter().enumerate() {
+        let end = match i + 1 == rest.len() {
+            true => remaining.strip_suffix(part).map(str::len)?,
+            false if part.is_empty() => return None,
+            false => remaining.find(part)?,
+        };
+        values.push(&remaining[..end]);
+        remaining = &remaining[end + part.len()..];
+    }
+    Some(values)
+}
+
+/// clap のコマンド定義の説明を訳す
+pub fn localize(mut cmd: clap::Command) -> clap::Command {
+    if current() == Lang::Ja {
+        return cmd;
+    }
+    if let Some(about) = cmd
+        .get_about()
+        .map(|about| tr(&about.to_string()).into_owned())
+    {
+        cmd = cmd.about(about);
+    }
+    if let Some(about) = cmd
+        .get_long_about()
+        .map(|about| tr(&about.to_string()).into_owned())
+    {
+        cmd = cmd.long_about(about);
+    }
+    cmd = cmd.mut_args(|mut arg| {
+        if let Some(help) = arg
+            .get_help()
+            .map(|help| tr(&help.to_string()).into_owned())
+        {
+            arg = arg.help(help);
+        }
+        if let Some(help) = arg
+            .get_long_help()
+            .map(|help| tr(&help.to_string()).into_owned())
+        {
+            arg = arg.long_help(help);
+        }
+        arg
+    });
+    let names: Vec<String> = cmd
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in names {
+        cmd = cmd.mut_subcommand(name, localize);
+    }
+    cmd
+}
+
+/// 原因の連鎖ごとに訳したエラー
+pub fn error(error: anyhow::Error) -> anyhow::Error {
+    match current() {
+        Lang::Ja => error,
+        Lang::En => {
+            let messages: Vec<String> = error
+                .chain()
+                .map(|cause| tr(&cause.to_string()).into_owned())
+                .collect();
+            anyhow::anyhow!(messages.join(": "))
+        }
+    }
+}
+
+/// clap の値の解析で出たエラーも訳す
+pub fn parser<T: 'static>(
+    parse: fn(&str) -> anyhow::Result<T>,
+) -> impl Fn(&str) -> anyhow::Result<T> + Clone + Send + Sync + 'static {
+    move |s| parse(s).map_err(error)
+}
+
+#[rustfmt::skip]
+const CATALOG: &[(&str, &str)] = &[
+    // ヘルプ
+    ("好きな総菜発表ドラゴンsay", "The dragon that announces its favorite side dishes"),
+    (
+        "キャラクター (random なら組み込みのキャラクターからランダムに選ぶ)",
+        "Character (random picks one of the built-in characters)",
+    ),
+    ("日付に応じて季節のキャラクターを選ぶ", "Pick a seasonal character for today's date"),
+    ("テーマ (retro, neon, mono など)", "Theme (retro, neon, mono, ...)"),
+    ("アートの色 (例: green, cyan.bold)", "Art color (e.g. green, cyan.bold)"),
+    ("キャプションの色", "Caption color"),
+    ("キャプションの幅", "Caption width"),
+    ("暗い背景向けの色を使う", "Use colors for dark backgrounds"),
+    ("明るい背景向けの色を使う", "Use colors for light backgrounds"),
+    ("端末の画像表示機能で出力する", "Output through the terminal's inline image protocol"),
+    ("吹き出しの 1 行に入る文字数 (指定すると吹き出しを描き直す) [既定値: 16]", "Characters per bubble line (redraws the bubble) [default: 16]"),
+    ("吹き出しに入る行数 (指定すると吹き出しを描き直す) [既定値: 2]", "Lines in the bubble (redraws the bubble) [default: 2]"),
+    ("吹き出しに収まらないおかずの扱い (省略時は truncate)", "What to do with side dishes that overflow the bubble (truncate if omitted)"),
+    ("おかずを描く場所 (省略時は bubble。side ではアートの右に長い文を折り返して並べる)", "Where to draw the side dish (bubble if omitted; side wraps long text in a column right of the art)"),
+    ("ドラゴンと吹き出しを端末の中央に寄せる", "Center the dragon and bubble in the terminal"),
+    ("ASCII だけのアートと吹き出しを使う (ロケールが UTF-8 でなければ自動で使う)", "Use ASCII-only art and bubble (automatic on non-UTF-8 locales)"),
+    ("スクリーンリーダー向けに、アートと画面の消去を使わずフレームごとのセリフを文で出す", "For screen readers, print each frame as a short sentence without art or screen clearing"),
+    ("おかずとキャプションの全角/半角をそろえてから配置する", "Normalize full-width/half-width text before layout"),
+    ("--graphics で使うビットマップフォント (BDF, .hex)", "Bitmap font for --graphics (BDF, .hex)"),
+    ("アニメーションせずに全フレームを順に出力する (画面の消去やカーソル移動、待ち時間なし)", "Print every frame in order without animating (no screen clearing, cursor movement or waiting)"),
+    ("tmux のステータスライン向けに、小さなドラゴンとおかずを改行の無い 1 行で出力する", "Print a tiny dragon and the side dish as a single line without newlines, for the tmux status line"),
+    ("--oneline の最大の表示幅", "Maximum display width of --oneline"),
+    ("シェルのプロンプト向けの、色付きの小さなドラゴンと時間ごとに替わるおかず", "A small colored dragon and a side dish that changes over time, for shell prompts"),
+    ("おかずの候補 (省略時は組み込みの候補)", "Candidate side dishes (built-in ones if omitted)"),
+    ("おかずを替える間隔 (例: 10m, 1h)", "How often to change the side dish (e.g. 10m, 1h)"),
+    ("色のシーケンスを囲む書き方", "How to wrap color sequences"),
+    ("新しいシェルであいさつするために rc ファイルへ足す断片を出力する", "Print a snippet to add to your rc file for a greeting in new shells"),
+    ("あいさつする間隔 (例: 8h)", "How often to greet (e.g. 8h)"),
+    ("時間帯に合わせたあいさつ (前のあいさつから --every たっていなければ何もしない)", "A greeting for the time of day (does nothing if --every has not passed since the last one)"),
+    ("チャットに貼り付けられるようにコードブロックで出力する", "Print inside a code block that can be pasted into chat"),
+    ("GitHub Actions のジョブのまとめ ($GITHUB_STEP_SUMMARY) にもフレームを書き込む", "Also write the frame to the GitHub Actions job summary ($GITHUB_STEP_SUMMARY)"),
+    ("GitHub Actions の ::notice:: の注釈でキャプション (無ければおかず) も伝える", "Also report the caption (or the side dish) as a GitHub Actions ::notice:: annotation"),
+    ("SubRip 字幕 (.srt) の各字幕を、その表示時間どおりのフレームにして再生する", "Play each cue of a SubRip subtitle file (.srt) as a frame shown for the cue's duration"),
+    ("歌詞ファイル (.lrc) の各行をその時刻に表示するカラオケ", "Karaoke that shows each line of a lyrics file (.lrc) at its timestamp"),
+    ("再生を始めるときに実行する音楽のプレーヤーのコマンド (例: \"mpv --no-video song.mp3\")", "Music player command to run when playback starts (e.g. \"mpv --no-video song.mp3\")"),
+    ("再生せずに、フレームごとの種類、テキスト、表示時間と合計時間を表で出力する", "Print a table of each frame's type, text and duration plus the total runtime, without playing"),
+    ("キャプションだけのフレームのインターバル (省略時は --interval と同じ)", "Interval for caption-only frames (same as --interval if omitted)"),
+    ("インターバルをフレームごとにこの割合の範囲でばらつかせる (例: 20%)。 --seed で再現できる", "Randomize each interval within this fraction (e.g. 20%). Reproducible with --seed"),
+    ("表示したまま終わらずに、ときどきまばたきなどの待機中のフレームを見せる (q で終了)", "Keep running and show idle frames such as blinking now and then (q to quit)"),
+    ("おかずを 1 文字ずつ出し、キャラクターの口を動かす (1 文字の時間、既定値: 50ms)", "Reveal the side dish one character at a time, moving the character's mouth (time per character, default: 50ms)"),
+    ("おかずを Markdown (見出し、太字、斜体、インラインコード、箇条書き) として読み、 吹き出しを中身の大きさに合わせる", "Read the side dish as Markdown (headings, bold, italics, inline code, bullet lists) and size the bubble to fit"),
+    (
+        "標準入力、ファイル、URL、ソケットから受け取った文言のエスケープシーケンスを取り除かない",
+        "keep escape sequences in text read from stdin, files, URLs and sockets",
+    ),
+    (
+        "外から受け取った文言のエスケープシーケンスと制御文字を取り除きました (--allow-ansi でそのまま使えます)",
+        "removed escape sequences and control characters from external text (use --allow-ansi to keep them)",
+    ),
+    (
+        "再生せずに、フレームを拡張子の形式 (.txt, .html, .png, .cast) のファイルへ書き出す。 PNG は 2 フレーム以上なら連番になる",
+        "write the frames to a file without playing, in the format given by its extension (.txt, .html, .png, .cast). PNG files are numbered when there are two or more frames",
+    ),
+    ("フレームの切り替え方", "how to switch from one frame to the next"),
+    (
+        "乱数のシード。シャッフル、ばらつき、ランダムなキャラクター、ルーレットなどを毎回同じにする",
+        "random seed that makes shuffling, jitter, the random character, roulette and so on repeatable",
+    ),
+    ("キャラクターの名前付きの欄 (看板など) に入れる文言。何度でも指定できる (例: sign=営業中)", "Text for a named field of the character, such as a sign. Repeatable (e.g. sign=Open)"),
+    ("-f のスクリプトの形式 (省略時は拡張子、- なら中身から推測する)", "Format of the -f script (guessed from the extension, or from the contents for -)"),
+    ("おかずとキャラクターを画面のあちこちに出し続けるスクリーンセーバー (何かキーを押すと終了)", "Screensaver that keeps showing side dishes and characters around the screen (press any key to quit)"),
+    ("おかず (省略時は組み込みのおかず)", "Side dishes (built-in ones if omitted)"),
+    ("次を出すまでの間隔 (単位が無ければ ms) [既定値: 5s]", "Time until the next one (ms without a unit) [default: 5s]"),
+    ("最初のフレームの前に流す画面全体の効果 (--export の書き出しにも入る)", "Full-screen effect played before the first frame (also included in --export output)"),
+    ("画面を消さず、今のカーソルの位置から下だけを描き直す (前からある出力を残す)", "Redraw only below the current cursor position instead of clearing the screen (keeps earlier output)"),
+    ("ドラゴンを N 匹並べる (端末の幅に収まるだけ横に並べ、残りは下に続ける)", "Print N dragons (side by side as far as the terminal width allows, then on further rows)"),
+    ("--count のドラゴンごとに組み込みのおかずからランダムに選ぶ", "Pick a random built-in side dish for each dragon of --count"),
+    ("吹き出しのしっぽの向き (省略時はキャラクターのまま)", "Direction of the speech bubble's tail (defaults to the character's own)"),
+    ("このラベル (または 1 から数えたフレームの番号) のフレームから再生する", "Start playback at the frame with this label (or 1-based frame number)"),
+    ("このラベル (または 1 から数えたフレームの番号) のフレームまで再生する", "Stop playback after the frame with this label (or 1-based frame number)"),
+    (
+        "色数。16 と 256 はパレット形式になり、 504x272 の例ではフルカラー約 14 KB、256 色で約 10 KB、16 色で約 8 KB になる (グラデーションは近い色に丸まる)",
+        "Number of colors. 16 and 256 write palette PNGs; for a 504x272 image, about 14 KB in truecolor becomes about 10 KB with 256 colors and about 8 KB with 16 (gradients snap to the nearest color)",
+    ),
+    (
+        "--export の PNG の色数。16 と 256 はパレット形式になり、 504x272 の例ではフルカラー約 14 KB、256 色で約 10 KB、16 色で約 8 KB になる (グラデーションは近い色に丸まる)",
+        "Number of colors in --export PNGs. 16 and 256 write palette PNGs; for a 504x272 image, about 14 KB in truecolor becomes about 10 KB with 256 colors and about 8 KB with 16 (gradients snap to the nearest color)",
+    ),
+    ("--palette で色を減らすときの中間の色の表し方", "How to render in-between colors when --palette reduces them"),
+    ("最後のフレーム (--end-card があればその文言) を出したまま待つ時間。 forever ならキーを押すまで待つ", "How long to keep the last frame (or the --end-card text) on screen; forever waits for a key press"),
+    ("最後のフレームの後に、エンドロールのように出す文言", "Text shown like closing credits after the last frame"),
+    ("文章を単語ごと (日本語は文節ごと) に吹き出しへ出す速読の練習", "Speed-reading practice: flash text word by word (bunsetsu by bunsetsu for Japanese) in the bubble"),
+    ("読む文章のファイル (- なら標準入力)", "File with the text to read (- for stdin)"),
+    ("1 分あたりに出す単語 (文節) の数", "Words (bunsetsu) shown per minute"),
+    ("CPU・メモリ・バッテリーの使用率をキャプションに出し続け、負荷に合わせて機嫌を変える (--no-anim なら一度だけ)", "Keep showing CPU, memory and battery usage in the caption, with the mood following the load (once with --no-anim)"),
+    ("更新の間隔 (単位が無ければ ms) [既定値: 2s]", "Refresh interval (ms if no unit) [default: 2s]"),
+    ("CPU の使用率がこれ (%) 未満なら眠そうにする", "Look sleepy while CPU usage is below this (%)"),
+    ("CPU の使用率がこれ (%) 以上ならあわてる", "Panic when CPU usage is at or above this (%)"),
+    ("決めた時刻 (毎日) におかずを出すかスクリプトを再生して知らせる", "Remind at set times (every day) by showing a side dish or playing a script"),
+    ("知らせるおかず (--at の時刻に使う)", "Side dish to show (for the --at times)"),
+    ("知らせる時刻 (HH:MM)。何度でも指定でき、設定ファイルの [[reminders]] に足す", "Time to remind at (HH:MM). Can be repeated; added to [[reminders]] in the config file"),
+    ("キャプション (--at の時刻に使う)", "Caption (for the --at times)"),
+    ("おかずの代わりに再生するスクリプト (--at の時刻に使う)", "Script to play instead of the side dish (for the --at times)"),
+    ("デスクトップ通知も出す (--at の時刻に使う)", "Also send a desktop notification (for the --at times)"),
+    ("それぞれの時刻に一度ずつ知らせたら終わる", "Exit after reminding once at each time"),
+    ("端末から切り離してバックグラウンドで待つ", "Detach from the terminal and wait in the background"),
+    ("このおかずを履歴に残さない", "Don't record this side dish in the history"),
+    ("say の履歴を番号付きで表示する (番号は again で使う)", "List the say history with numbers (for again)"),
+    ("表示する数 (新しいものから)", "How many to show (newest first)"),
+    ("履歴かお気に入りのおかずをもう一度出す", "Show a side dish from the history or favorites again"),
+    ("history か fav list の番号 (省略時は最新のもの)", "Number from history or fav list (the latest if omitted)"),
+    ("お気に入りから選ぶ", "Pick from the favorites"),
+    ("お気に入りのおかずの操作", "Manage favorite side dishes"),
+    ("お気に入りに足す", "Add to the favorites"),
+    ("おかず (省略時は履歴の最新のもの)", "Side dish (the latest in the history if omitted)"),
+    ("お気に入りを番号付きで表示する (番号は again --fav で使う)", "List the favorites with numbers (for again --fav)"),
+    ("お気に入りから取り除く", "Remove from the favorites"),
+    ("fav list の番号", "Number from fav list"),
+    ("キャプションを ■ で伏せて出し、この時間の後に 1 文字ずつ明かす", "Show the caption hidden as ■ and reveal it character by character after this long"),
+    ("フレームを出すたびにシェルのコマンドを実行し、その標準出力をおかずにする (例: \"fortune\")", "Run a shell command for each frame and use its stdout as the side dish (e.g. \"fortune\")"),
+    ("--from-cmd で作るフレームの数 [既定値: 10]", "Number of frames to make with --from-cmd [default: 10]"),
+    ("インストールしたときと中身が違う (改ざんされたかもしれない) アートパックも使う", "Use art packs whose contents changed since install (possibly tampered with)"),
+    ("プレイリストのスクリプトをくり返し再生し続ける (常設の表示端末向け)", "Keep looping the scripts of a playlist (for always-on display terminals)"),
+    ("プレイリストのスクリプトをくり返し再生し続ける (常設の表示端末向け)\n\nプレイリストは変わるたびに読み直し、再生が失敗しても次のスクリプトに進む。 終了のキーのほかのキー操作と Ctrl-C は受け付けない", "Keep looping the scripts of a playlist (for always-on display terminals)\n\nThe playlist is reloaded whenever it changes, and a script that fails to play is skipped. Keys other than the quit key, and Ctrl-C, are ignored"),
+    ("プレイリスト (TOML の scripts = [\"a.toml\", ...])", "Playlist (TOML with scripts = [\"a.toml\", ...])"),
+    ("終了のキー (プレイリストの quit_key より優先。省略時は q)", "Quit key (overrides the playlist's quit_key; defaults to q)"),
+    ("スクリプトを一覧と描画を見ながら編集する (並べ替え、追加、削除、表示時間)", "Edit a script with a frame list and a live preview (reorder, add, delete, retime)"),
+    ("スクリプトのパス (無ければ新しく作る)", "Path to the script (created if missing)"),
+    ("GitHub の最新のリリースに入れ替える (チェックサムを確かめてから)", "Replace this binary with the latest GitHub release (after verifying its checksum)"),
+    ("新しい版があるかだけを確かめる", "Only check whether a newer version exists"),
+    ("同じ版でも入れ直す", "Reinstall even if the version is the same"),
+    ("描画の速さを端末の幅と文言ごとに測る (1 秒あたりのフレーム数)", "Measure rendering speed per terminal width and text (frames per second)"),
+    ("測る端末の幅 (カンマ区切り。省略時は 40,80,160)", "Terminal widths to measure (comma-separated; 40,80,160 if omitted)"),
+    ("組ごとに描く回数", "Renders per combination"),
+    ("色とエスケープシーケンスを使わずに描く (VT を使えない古い Windows のコンソール向け)", "Draw without colors or escape sequences (for old Windows consoles without VT support)"),
+    ("診断のログを stderr に出す (-v で info, -vv で debug, -vvv で trace)", "Print diagnostic logs to stderr (-v for info, -vv for debug, -vvv for trace)"),
+    ("ログの詳しさ (-v より優先)", "Log level (takes precedence over -v)"),
+    ("メッセージとヘルプ、スクリプトの訳の言語 (省略時は LANG に従う)", "Language of messages, help and script translations (follows LANG if omitted)"),
+    ("一度に出力", "Print once"),
+    ("おかず", "Side dish"),
+    ("おかずをファイルから読み込む", "Read the side dish from a file"),
+    ("おかずを URL から取得する (プレーンテキスト、64 KiB と 10 秒まで)", "Fetch the side dish from a URL (plain text, up to 64 KiB and 10 seconds)"),
+    ("キャプションをファイルから読み込む", "Read the caption from a file"),
+    ("キャプション", "Caption"),
+    ("デスクトップ通知も送る", "Also send a desktop notification"),
+    ("おかずを読み上げる", "Read the side dish aloud"),
+    ("色と末尾の空白を除いたフレームをクリップボードへコピーする", "Copy the frame without colors and trailing spaces to the clipboard"),
+    ("URL などを QR コードにして横に並べる (キャプションはその下に表示)", "Show a QR code of a URL beside the dragon (caption goes below it)"),
+    ("アニメーション出力", "Animated output"),
+    ("アニメーション出力\n\n再生中はスペースで一時停止/再開、n/p (←/→) でコマ送り、+/- で速度変更、q で終了。 選択肢のあるフレームでは番号のキーで選ぶ", "Animated output\n\nWhile playing: space pauses/resumes, n/p (←/→) steps frames, +/- changes speed, q quits. On frames with choices, press the choice's number"),
+    ("プレキャプション", "Pre-captions"),
+    ("アフターキャプション", "After-captions"),
+    ("ファイルからスクリプトを読み込む。何度でも指定でき、フレームを順につなげる (interval はファイルごとに効く)", "Load a script from a file. Repeatable; the frames are played in order (each file keeps its own interval)"),
+    ("インターバル (単位が無ければ ms。1.5s, 500ms, 2m も可) [既定値: 1000]", "Interval (ms without a unit; 1.5s, 500ms, 2m also work) [default: 1000]"),
+    ("フレームを表示するたびにデスクトップ通知も送る", "Also send a desktop notification for every frame"),
+    ("フレームのテキストを読み上げる", "Read each frame's text aloud"),
+    ("ベルを鳴らすタイミング", "When to ring the bell"),
+    ("再生速度の倍率 (2.0 で倍速)", "Playback speed multiplier (2.0 is double speed)"),
+    ("インターバルの変化のさせ方", "How the interval changes over the animation"),
+    ("キャプションの下に進み具合を表示する", "Show a progress bar under the caption"),
+    ("逆順に再生する", "Play in reverse"),
+    ("最後まで再生したら逆順で最初まで戻る", "Play back to the start after reaching the end"),
+    ("おかずの順番をランダムにする (キャプションはそのまま)", "Shuffle the side dishes (captions stay in place)"),
+    ("再生した内容とインターバルをスクリプトとして保存する", "Save what was played and its interval as a script"),
+    ("Rhai 風のスクリプトで再生を組み立てる (say, wait, set_mood, set_character, random_dish など)。 おかずを指定すると random_dish の候補になる", "Drive the animation with a Rhai-like script (say, wait, set_mood, set_character, random_dish, ...). Side dishes given become random_dish candidates"),
+    ("再生の後に合計時間、フレーム数、描画時間、間に合わなかったフレームを stderr に出す", "After playing, print the total time, frame count, render times and late frames to stderr"),
+    ("再生せずに、解決したフレームの一覧 (テキスト、表示時間、レイアウト) を JSON で出力する", "Print the resolved frame list (text, durations, layout) as JSON instead of playing"),
+    ("ドラゴンがクイズを出す", "The dragon runs a quiz"),
+    ("問題ファイル", "Question file"),
+    ("候補を切り替えながら 1 つを選ぶ", "Cycle through candidates and pick one"),
+    ("候補", "Candidates"),
+    ("止まったときのキャプション [既定値: 決定！]", "Caption shown on the winner [default: 決定！]"),
+    ("スクリプトのおかずを打ち込むタイピング練習", "Typing practice with a script's side dishes"),
+    ("練習に使うスクリプト", "Script to practice with"),
+    ("キャラクターかスクリプトの最初のフレームとレイアウトの情報を表示", "Show the first frame of a character or script with layout diagnostics"),
+    ("キャラクター名またはスクリプトのパス", "Character name or script path"),
+    ("入力した行を表示しながらスクリプトに記録する (pre:, after: でキャプション)", "Record typed lines into a script while showing them (pre:, after: for captions)"),
+    ("保存先", "Output path"),
+    ("既存のスクリプトに追記する", "Append to an existing script"),
+    ("HTTP サーバーとして起動", "Run as an HTTP server"),
+    ("ポート番号", "Port"),
+    ("待ち受けるアドレス", "Address to listen on"),
+    ("HTTP の代わりに TCP/telnet でアニメーションを配信", "Stream the animation over TCP/telnet instead of HTTP"),
+    ("配信するスクリプト (--tcp 時)", "Script to stream (with --tcp)"),
+    ("インターバル (単位が無ければ ms、--tcp 時) [既定値: 1000]", "Interval (ms without a unit, with --tcp) [default: 1000]"),
+    ("RSS/Atom フィードの見出しを順に表示する", "Show the headlines of an RSS/Atom feed one by one"),
+    ("フィードの URL", "Feed URL"),
+    ("表示する見出しの数", "Number of headlines to show"),
+    ("インターバル (単位が無ければ ms) [既定値: 3000]", "Interval (ms without a unit) [default: 3000]"),
+    ("今日の天気を wttr.in から取得して知らせる", "Fetch today's weather from wttr.in and announce it"),
+    ("地名 (省略時は接続元から推定)", "Location (guessed from your connection if omitted)"),
+    ("wttr.in 互換の API の URL", "URL of a wttr.in compatible API"),
+    ("日付で選んだ今日の名言 (同じ日は同じ名言)", "Today's quote, chosen by date (the same all day)"),
+    ("名言ファイル (省略時は設定ディレクトリの quotes.toml があれば使う)", "Quotes file (defaults to quotes.toml in the config directory if it exists)"),
+    ("組み込みの名言を使わない", "Do not use the built-in quotes"),
+    ("git のフックとしてコミットメッセージを表示する", "Show commit messages from a git hook"),
+    ("コミットメッセージのファイルを吹き出しに表示 (commit-msg フックから呼ぶ)", "Show a commit message file in the bubble (called from the commit-msg hook)"),
+    ("コミットメッセージのファイル", "Commit message file"),
+    ("y/n を押すまで待ち、n ならコミットを中止する", "Wait for y/n and abort the commit on n"),
+    ("このリポジトリに commit-msg フックをインストール", "Install the commit-msg hook in this repository"),
+    ("コミットの前に y/n で確認する", "Ask y/n before committing"),
+    ("既存のフックを上書きする", "Overwrite an existing hook"),
+    ("ログインバナー向けの固定幅出力", "Fixed-width output for login banners"),
+    ("出力幅", "Output width"),
+    ("ポモドーロタイマー", "Pomodoro timer"),
+    ("作業時間 (例: 25m)", "Work length (e.g. 25m)"),
+    ("休憩時間 (例: 5m)", "Break length (e.g. 5m)"),
+    ("繰り返す回数 (省略時は無限)", "Number of cycles (endless if omitted)"),
+    ("カウントダウン", "Countdown"),
+    ("時間 (例: 10s, 3m)", "Length (e.g. 10s, 3m)"),
+    ("0 になったときに表示するおかず", "Side dish shown when it reaches zero"),
+    ("時計", "Clock"),
+    ("時刻の書式 (%H, %M, %S など)", "Time format (%H, %M, %S, ...)"),
+    ("2 行目に表示する日付の書式 (例: %Y-%m-%d(%a))", "Date format for the second line (e.g. %Y-%m-%d(%a))"),
+    ("シェル補完スクリプトを出力", "Print a shell completion script"),
+    ("シェルの種類", "Shell"),
+    ("man ページを出力", "Print the man page"),
+    ("フレームを画像として書き出す", "Export a frame as an image"),
+    ("PNG 画像 (フォントは --font で指定)", "PNG image (set the font with --font)"),
+    ("出力先", "Output path"),
+    ("文字色 (#rrggbb)", "Text color (#rrggbb)"),
+    ("背景色 (#rrggbb)", "Background color (#rrggbb)"),
+    ("余白 (px)", "Padding (px)"),
+    ("設定ファイルの操作", "Manage the config file"),
+    ("設定ファイルのパスを表示", "Print the config file path"),
+    ("設定ファイルの雛形を作成", "Create a config file template"),
+    ("既存のファイルを上書きする", "Overwrite an existing file"),
+    ("テーマの操作", "Manage themes"),
+    ("使えるテーマの一覧", "List available themes"),
+    ("アートパックの操作", "Manage art packs"),
+    ("使えるキャラクターの一覧", "List available characters"),
+    ("アートパックをインストール", "Install an art pack"),
+    ("git の URL、tar.gz (URL かファイル)、ディレクトリ", "git URL, tar.gz (URL or file) or directory"),
+    ("アートパックを削除", "Remove an art pack"),
+    ("パックの名前", "Pack name"),
+    ("インストール元からアートパックを入れ直す", "Reinstall art packs from where they were installed"),
+    ("パックの名前 (省略時はすべて)", "Pack name (all packs if omitted)"),
+    ("ソケットで待ち受けて受信したおかずを表示し続ける", "Listen on a socket and keep showing received side dishes"),
+    ("ソケットのパス", "Socket path"),
+    ("標準入力の各行もおかずとして表示する", "Also show each line of stdin as a side dish"),
+    ("変更されるたびに内容をおかずとして表示するファイル", "File whose contents are shown as the side dish whenever it changes"),
+    ("表示してからこの時間が過ぎたら空の吹き出しに戻す (例: 30s)", "Return to an empty bubble after this long (e.g. 30s)"),
+    ("起動中の daemon におかずを送る", "Send a side dish to a running daemon"),
+    // 出力
+    ("{} を作成しました", "Created {}"),
+    ("{} に保存しました", "Saved {}"),
+    ("保存していない変更があります (もう一度 q で終了、s で保存)", "There are unsaved changes (press q again to quit, s to save)"),
+    ("a でフレームを足します", "Press a to add a frame"),
+    ("j/k 選ぶ  J/K 並べ替え  a 足す  d 消す  e おかず  c キャプション  +/- 表示時間  0 時間を戻す  s 保存  q 終了", "j/k select  J/K move  a add  d delete  e side dish  c caption  +/- duration  0 reset duration  s save  q quit"),
+    ("最新版です ({})", "Already up to date ({})"),
+    ("{} が出ています (いまは {})", "{} is available (currently {})"),
+    ("{} を {} に更新しました", "Updated {} to {}"),
+    ("端末の幅が足りません ({} 桁以上必要です)", "Terminal too narrow (need ≥ {} columns)"),
+    ("お気に入りに足しました: {}", "Added to the favorites: {}"),
+    ("お気に入りから取り除きました: {}", "Removed from the favorites: {}"),
+    ("バックグラウンドで待ちます (PID {})", "Waiting in the background (PID {})"),
+    ("時間だよ！", "It's time!"),
+    ("{} のセリフ: {}", "{} says: {}"),
+    ("キャプション: {}", "Caption: {}"),
+    ("選択肢 {}: {}", "Choice {}: {}"),
+    ("{} (ユーザー定義)", "{} (user)"),
+    ("  推奨する吹き出し: {}x{}", "  recommended bubble: {}x{}"),
+    ("{} ({} キャラクター) をインストールしました", "Installed {} ({} characters)"),
+    ("{} を削除しました", "Removed {}"),
+    ("{} を更新しました", "Updated {}"),
+    ("{} に保存しました", "Saved to {}"),
+    ("http://{}:{} で待ち受けています", "Listening on http://{}:{}"),
+    ("telnet {} {} で配信しています", "Streaming on telnet {} {}"),
+    ("接続エラー: {}", "connection error: {}"),
+    ("リクエスト処理エラー: {}", "request error: {}"),
+    ("{} への配信を中断しました: {}", "stopped streaming to {}: {}"),
+    ("受信エラー: {}", "receive error: {}"),
+    ("受信が時間切れになりました", "timed out while receiving"),
+    ("メッセージが大きすぎます (最大 {} バイト)", "message is too large (at most {} bytes)"),
+    ("通知を送れませんでした: {}", "could not send a notification: {}"),
+    ("読み上げできませんでした: {}", "could not speak: {}"),
+    ("このメッセージでコミットしますか？ (y/n)", "Commit with this message? (y/n)"),
+    ("コミットメッセージ", "Commit message"),
+    ("端末が無いので確認を省略しました", "no terminal, skipped the confirmation"),
+    ("再生: {} フレーム, 合計 {} 秒", "played {} frames in {} s"),
+    ("描画: 平均 {} ms, 最大 {} ms", "render: {} ms on average, {} ms at most"),
+    ("警告: {} フレーム目の描画 ({} ms) がインターバル ({} ms) に間に合いませんでした", "warning: frame {} took {} ms to render, longer than its {} ms interval"),
+    ("{}の天気: {}", "Weather in {}: {}"),
+    ("いい天気だ！", "What a nice day!"),
+    ("今日はくもりだね", "Cloudy today"),
+    ("霧に気をつけて", "Watch out for the fog"),
+    ("傘を忘れずに", "Don't forget your umbrella"),
+    ("あったかくしてね", "Stay warm"),
+    ("今日はおうちにいよう…", "Better stay home today..."),
+    ("ひまだなあ… Zzz", "Nothing to do... Zzz"),
+    ("順調だよ", "All good"),
+    ("けっこう忙しい！", "Getting busy!"),
+    ("もう無理！ たすけて！", "I can't take it! Help!"),
+    ("おはようございます", "Good morning"),
+    ("こんにちは", "Hello"),
+    ("こんばんは", "Good evening"),
+    ("種類", "Type"),
+    ("テキスト", "Text"),
+    ("時間", "Duration"),
+    ("選択肢", "Choice"),
+    ("空", "Empty"),
+    ("合計: {} フレーム, {} 秒 (goto と選択肢は先頭から順にたどった場合)", "total: {} frames, {} s (following goto and choices in order from the start)"),
+    ("警告: {} 桁を超える行は貼り付け先で折り返されます", "warning: lines wider than {} columns will wrap where pasted"),
+    // ログ
+    ("季節のキャラクター {} を選びました", "chose the seasonal character {}"),
+    ("ランダムにキャラクター {} を選びました", "chose the character {} at random"),
+    ("キャラクター {} に欄 {} はありません", "character {} has no field {}"),
+    ("ロケールが UTF-8 ではないので ASCII のアートを使います", "the locale is not UTF-8, using ASCII art"),
+    ("背景: {}", "background: {}"),
+    ("背景の明るさを判定できませんでした", "could not detect the background brightness"),
+    ("キャラクター: {}", "character: {}"),
+    ("シンボリックリンク {} は入れません", "skipping symbolic link {}"),
+    ("壊れたパックを飛ばします: {}", "skipping a broken pack: {}"),
+    ("{} を読み直しました", "reloaded {}"),
+    ("{} を再生します", "playing {}"),
+    ("最新のリリース: {}", "latest release: {}"),
+    ("{} を取得します", "downloading {}"),
+    ("チェックサムが合いました: {}", "checksum verified: {}"),
+    ("端末の幅 ({} 桁) にアートが入らないので、小さなドラゴンで描きます", "the art does not fit in the terminal width ({} columns), drawing a mini dragon"),
+    ("端末の幅 {} 桁にフレーム ({} 桁) が入りません", "the terminal width of {} columns cannot fit the frame ({} columns)"),
+    ("アートパック '{}' の {} はインストールしたときと違いますが、--trust なので使います", "art pack '{}': {} differs from when it was installed, but using it because of --trust"),
+    ("{} のハッシュを {} に記録しました", "recorded hashes of {} in {}"),
+    ("{}:{} のお知らせ", "reminder for {}:{}"),
+    ("ラベル '{}' は再生する範囲の外なので終わります", "label '{}' is outside the playback range; stopping"),
+    ("配役 {}: キャラクター {}", "role {}: character {}"),
+    ("{}: {} として読み込みます", "{}: reading as {}"),
+    ("色の出力: stdout {}, stderr {}", "colors: stdout {}, stderr {}"),
+    ("リンク (OSC 8): {}", "hyperlinks (OSC 8): {}"),
+    ("端末の大きさ: {} 桁 x {} 行", "terminal size: {} columns x {} rows"),
+    ("コンソール: 色とエスケープシーケンスを使わない互換の描き方", "console: legacy drawing without colors or escape sequences"),
+    ("コンソール: VT", "console: VT"),
+    ("{}: おかず {} 個, フレーム {} 個", "{}: {} side dishes, {} frames"),
+    ("おかず '{}' を {} フレームに分けました", "split side dish '{}' into {} frames"),
+    ("おかず '{}' は吹き出しで {} 行 (最大 {} 文字 x {} 行)", "side dish '{}' takes {} rows in the bubble (up to {} characters x {} rows)"),
+    ("'{}' は吹き出しの幅 ({} 文字) を超えています", "'{}' is wider than the bubble ({} characters)"),
+    ("おかず '{}' は {} 行になるので、吹き出しの {} 行に切り詰めます (--overflow wrap で次のフレームへ送れます)", "side dish '{}' takes {} rows, truncating to the bubble's {} rows (--overflow wrap carries it over to the next frame)"),
+    ("フレーム {}/{}", "frame {}/{}"),
+    ("設定ファイル {} はありません", "no config file at {}"),
+    ("設定ファイル {} を読み込みます", "loading config file {}"),
+    // プレビュー
+    ("アート: 幅 {} x 高さ {}", "art: {} wide x {} high"),
+    ("吹き出し: {}-{} 行目, 幅 {}", "bubble: lines {}-{}, {} wide"),
+    ("吹き出し: なし (テーマの枠線は使われません)", "bubble: none (theme borders are not used)"),
+    ("容量: {} 行 + 繰り返し x 表示幅 {}", "capacity: {} lines + repeat x {} columns"),
+    ("容量: {} 行 x 表示幅 {} (1 行のおかずは {} 文字で折り返し)", "capacity: {} lines x {} columns (a single-line side dish wraps every {} characters)"),
+    ("欄 {}: 幅 {} x {} 行", "field {}: {} wide x {} lines"),
+    ("${}$: {} 行目 {} 列目", "${}$: line {}, column {}"),
+    ("キャプション: 幅 {}", "caption: {} wide"),
+    ("フレーム: {} (インターバル {})", "frames: {} (interval {})"),
+    ("指定なし", "not set"),
+    ("警告: {} 枚目のおかずは吹き出しに収まりません", "warning: side dish of frame {} does not fit in the bubble"),
+    ("警告: {} 枚目のキャプションは幅を超えます", "warning: caption of frame {} is wider than the caption width"),
+    // エラー
+    ("{} の読み込みに失敗しました", "failed to load {}"),
+    ("{} を読み込めません: {}", "cannot read {}: {}"),
+    ("{} を読み込めません", "cannot read {}"),
+    ("{} はソケットではありません", "{} is not a socket"),
+    ("パックを {} には入れられません", "cannot install a pack into {}"),
+    ("edit で開けるのは TOML か JSON のスクリプトだけです: {}", "edit can only open TOML or JSON scripts: {}"),
+    ("edit は --graphics と一緒に使えません", "edit cannot be used with --graphics"),
+    ("edit は端末でしか使えません", "edit needs a terminal"),
+    ("{}: scripts が空です", "{}: scripts is empty"),
+    ("終了のキーは ASCII の 1 文字にしてください: {}", "the quit key must be a single ASCII character: {}"),
+    ("{} を再生できません", "cannot play {}"),
+    ("描画中にパニックしました", "panicked while rendering"),
+    ("kiosk は --no-anim と一緒に使えません", "kiosk cannot be used with --no-anim"),
+    ("{} は https の URL ではありません", "{} is not an https URL"),
+    ("リリースの情報を読めません", "cannot read the release information"),
+    ("{} に {}-{} 向けの成果物がありません", "{} has no artifact for {}-{}"),
+    ("{} のチェックサムがリリースにありません ({} か SHA256SUMS)", "the release has no checksum for {} ({} or SHA256SUMS)"),
+    ("{} に {} のチェックサムが見つかりません", "{} has no checksum for {}"),
+    ("{} のチェックサムが合いません (期待: {}, 実際: {})", "checksum mismatch for {} (expected: {}, actual: {})"),
+    ("{} の中に {} が見つかりません", "{} does not contain {}"),
+    ("{} を退けられません", "cannot move {} aside"),
+    ("{} を置き換えられません", "cannot replace {}"),
+    ("アートパック '{}' の {} がインストールしたときと違います (改ざんされているかもしれません)。art update で入れ直すか、--trust を付けると使えます", "art pack '{}': {} differs from when it was installed (it may have been tampered with). Reinstall it with art update, or pass --trust to use it anyway"),
+    ("コマンド '{}' を実行できません", "cannot run command '{}'"),
+    ("コマンド '{}' が失敗しました ({})", "command '{}' failed ({})"),
+    ("効果音を鳴らせませんでした: {}", "could not play a sound: {}"),
+    ("{} がありません", "{} does not exist"),
+    ("再生コマンドを実行できません", "cannot run the audio player"),
+    ("再生コマンドが見つかりません (paplay, aplay, ffplay)", "no audio player found (paplay, aplay, ffplay)"),
+    ("audio 機能を付けずにビルドしたので音は鳴りません (cargo build --features audio)", "this build cannot play sounds (build with cargo build --features audio)"),
+    ("データディレクトリを特定できません (HOME が未設定です)", "cannot determine the data directory (HOME is not set)"),
+    ("{} 番はありません", "there is no number {}"),
+    ("まだ何もありません", "nothing yet"),
+    ("履歴に残せませんでした: {}", "could not record the history: {}"),
+    ("時刻 '{}' を解釈できません (HH:MM)", "cannot parse time '{}' (HH:MM)"),
+    ("知らせる時刻がありません (--at か設定ファイルの [[reminders]] で指定します)", "no times to remind at (use --at or [[reminders]] in the config file)"),
+    ("バックグラウンドに移れません: {}", "cannot move to the background: {}"),
+    ("remind --background は Unix 系 OS でのみ使えます", "remind --background is only available on Unix-like systems"),
+    ("CPU の使用率を読めません (/proc/stat)", "cannot read CPU usage (/proc/stat)"),
+    ("status は /proc のある環境 (Linux) でだけ使えます", "status only works where /proc is available (Linux)"),
+    ("標準入力を読み込めません", "cannot read stdin"),
+    ("--from のフレームが --to のフレームより後にあります", "the --from frame comes after the --to frame"),
+    ("吹き出しの範囲がわからないのでしっぽを描き直せません", "cannot redraw the tail because the bubble area is unknown"),
+    ("しっぽの行 {} がテンプレートの範囲外です", "tail row {} is outside the template"),
+    ("標準入力からスクリプトを読み込めません", "cannot read the script from stdin"),
+    ("標準入力 (-) は 1 度だけ指定できます", "stdin (-) can be given only once"),
+    ("screensaver は端末でだけ使えます", "screensaver only works in a terminal"),
+    ("screensaver は --graphics と一緒に使えません", "screensaver cannot be used with --graphics"),
+    (
+        "{} の形式が分かりません (.txt, .html, .png, .cast のどれかにしてください)",
+        "unknown format for {} (use .txt, .html, .png or .cast)",
+    ),
+    ("このキャラクターには待機中のフレーム (idle) がありません", "this character has no idle frames"),
+    ("--idle は --graphics と一緒に使えません", "--idle cannot be used with --graphics"),
+    ("{}: '{}' の {} はテンプレートと行数が違います", "{}: '{}': {} has a different number of lines than the template"),
+    ("{} を開けません", "cannot open {}"),
+    ("時刻の行がありません: {}", "missing timing line: {}"),
+    ("終了が開始より前です: {}", "end is before start: {}"),
+    ("時刻 '{}' を解釈できません", "cannot parse timestamp '{}'"),
+    ("offset '{}' を解釈できません", "cannot parse offset '{}'"),
+    ("時刻の付いた歌詞がありません", "no timestamped lyrics"),
+    ("プレーヤー '{}' を起動できません", "cannot start the player '{}'"),
+    ("GITHUB_STEP_SUMMARY が設定されていません (GitHub Actions の中で実行してください)", "GITHUB_STEP_SUMMARY is not set (run this inside GitHub Actions)"),
+    ("{}: interval は 10 以上にしてください", "{}: interval must be at least 10"),
+    ("{}: caption_interval は 10 以上にしてください", "{}: caption_interval must be at least 10"),
+    ("インターバル '{}' は 10ms 以上にしてください", "interval '{}' must be at least 10ms"),
+    ("{}INTERVAL が不正です", "{}INTERVAL is invalid"),
+    ("{}INTERVAL は 10 以上にしてください", "{}INTERVAL must be at least 10"),
+    ("{}CAPTION_WIDTH が不正です", "{}CAPTION_WIDTH is invalid"),
+    ("{} は既に存在します (--force で上書き)", "{} already exists (use --force to overwrite)"),
+    ("設定ディレクトリを特定できません (HOME が未設定です)", "cannot locate the config directory (HOME is not set)"),
+    ("データディレクトリを特定できません (HOME が未設定です)", "cannot locate the data directory (HOME is not set)"),
+    ("キャラクター '{}' が見つかりません", "character '{}' not found"),
+    (
+        "機嫌 '{}' はありません (happy, angry, sad, sleepy, surprised)",
+        "no such mood '{}' (happy, angry, sad, sleepy, surprised)",
+    ),
+    ("テーマ '{}' が見つかりません", "theme '{}' not found"),
+    ("日付 '{}' は MM-DD 形式で指定してください", "date '{}' must be MM-DD"),
+    ("期間 '{}' は MM-DD..MM-DD 形式で指定してください", "range '{}' must be MM-DD..MM-DD"),
+    ("色 '{}' を解釈できません (#rrggbb)", "cannot parse color '{}' (#rrggbb)"),
+    ("速度は {} から {} にしてください", "speed must be between {} and {}"),
+    ("ばらつきは 0% 以上 100% 未満にしてください", "jitter must be at least 0% and less than 100%"),
+    ("欄は NAME=TEXT の形で指定してください", "fields must be given as NAME=TEXT"),
+    ("時間が空です", "duration is empty"),
+    ("時間 '{}' を解釈できません", "cannot parse duration '{}'"),
+    ("時間 '{}' の数値が不正です", "duration '{}' has an invalid number"),
+    ("時間 '{}' が長すぎます", "duration '{}' is too long"),
+    ("時間 '{}' の単位 '{}' は使えません (h, m, s, ms)", "duration '{}' has an unknown unit '{}' (h, m, s, ms)"),
+    ("おかず '{}' は吹き出しに収まりません ({} 文字 x {} 行まで)", "side dish '{}' does not fit in the bubble (up to {} characters x {} lines)"),
+    ("キャラクター '{}'", "character '{}'"),
+    ("吹き出しの範囲がわからないので大きさを変えられません", "cannot resize the bubble because its area is unknown"),
+    ("繰り返しのあるテンプレートの吹き出しは大きさを変えられません", "cannot resize the bubble of a template with a repeat region"),
+    ("吹き出しにおかずを置く印がありません", "the bubble has no slot for the side dish"),
+    ("{} 行目の吹き出しの境目で文字が切れています", "a character straddles the bubble edge on line {}"),
+    ("{} 行目の吹き出しの左にアートがあります", "line {} has art left of the bubble"),
+    ("{} は http か https の URL ではありません", "{} is not an http or https URL"),
+    ("{} の本文が大きすぎます (最大 {} バイト)", "the body of {} is too large (at most {} bytes)"),
+    ("{} を取得できません ({})", "cannot fetch {} ({})"),
+    ("{} の本文が UTF-8 ではありません", "the body of {} is not UTF-8"),
+    ("RSS か Atom のフィードではありません", "not an RSS or Atom feed"),
+    ("名言がありません", "no quotes"),
+    ("コミットを中止しました", "commit aborted"),
+    ("git を実行できません", "cannot run git"),
+    ("git のリポジトリではありません", "not a git repository"),
+    ("{} は既にあります (上書きするには --force を指定してください)", "{} already exists (use --force to overwrite)"),
+    ("実行ファイルのパスを特定できません", "cannot determine the path of the executable"),
+    ("天気の応答に現在の天気がありません", "the weather response has no current conditions"),
+    ("ラベル '{}' のフレームがありません", "no frame has the label '{}'"),
+    ("ビットマップフォントが見つかりません (--font で BDF か .hex ファイルを指定してください)", "no bitmap font found (pass a BDF or .hex file with --font)"),
+    ("フォント {} を読み込めません", "cannot read font {}"),
+    ("フォント {} を解釈できません", "cannot parse font {}"),
+    ("FONTBOUNDINGBOX がありません", "FONTBOUNDINGBOX is missing"),
+    ("ENDCHAR がありません", "ENDCHAR is missing"),
+    ("不正な行です: {}", "invalid line: {}"),
+    ("U+{} のビットマップの長さが不正です", "bitmap of U+{} has an invalid length"),
+    ("数値が {} 個必要です", "{} numbers are required"),
+    ("不正な 16 進数です: {}", "invalid hex: {}"),
+    ("QR コードにするには長すぎます (最大 {} バイト)", "too long for a QR code (at most {} bytes)"),
+    ("クリップボードのコマンドが見つかりません ({})", "no clipboard command found ({})"),
+    ("通知コマンドを実行できません", "cannot run the notification command"),
+    ("通知コマンドが失敗しました ({})", "the notification command failed ({})"),
+    ("読み上げコマンドを実行できません", "cannot run the speech command"),
+    ("読み上げコマンドが失敗しました ({})", "the speech command failed ({})"),
+    ("読み上げコマンドが見つかりません ({})", "no speech command found ({})"),
+    ("daemon は Unix 系 OS でのみ使えます", "daemon is only available on Unix"),
+    ("send は Unix 系 OS でのみ使えます", "send is only available on Unix"),
+    ("{} に接続できません", "cannot connect to {}"),
+    ("{} に書き込めません", "cannot write to {}"),
+    ("{}: questions が空です", "{}: questions is empty"),
+    ("{}: 「{}」に answers がありません", "{}: \"{}\" has no answers"),
+    ("候補を 1 つ以上指定してください", "give at least one candidate"),
+    ("練習するおかずがありません", "no side dishes to practice"),
+    (
+        "{}: name '{}' は使えません (英数字と _ と - だけ)",
+        "{}: name '{}' is not allowed (only letters, digits, _ and -)",
+    ),
+    ("{}: characters がありません", "{}: characters is empty"),
+    ("{}: キャラクター '{}' が重複しています", "{}: character '{}' is defined twice"),
+    ("{}: '{}' の bubble がテンプレートの範囲外です", "{}: bubble of '{}' is outside the template"),
+    ("{}: '{}' の bubble の tail が吹き出しの枠の中にありません", "{}: the bubble tail of '{}' is not inside the bubble frame"),
+    ("{}: '{}' のテンプレートに欄 {} がありません", "{}: the template of '{}' has no field {}"),
+    ("{}: '{}' の欄 {} の height は 1 から {} にしてください", "{}: '{}': height of field {} must be between 1 and {}"),
+    ("テンプレート {} はパックの外を指しています", "template {} points outside the pack"),
+    ("テンプレート {} を読み込めません", "cannot read template {}"),
+    ("テンプレート {} におかずを置く印がありません", "template {} has no slot for the side dish"),
+    ("パック '{}' はインストールされていません", "pack '{}' is not installed"),
+    ("{} のインストール元がわかりません", "the source of {} is unknown"),
+    ("{} は git の URL、tar.gz、ディレクトリのどれでもありません", "{} is not a git URL, tar.gz or directory"),
+    ("{} に {} が見つかりません", "{} not found in {}"),
+    ("{} を実行できません", "cannot run {}"),
+    ("{} が失敗しました ({})", "{} failed ({})"),
+    ("{} 行目: {}", "line {}: {}"),
+    ("コメントが閉じていません", "unterminated comment"),
+    ("文字列が閉じていません", "unterminated string"),
+    ("数が大きすぎます", "number is too large"),
+    ("使えない文字 '{}' があります", "unexpected character '{}'"),
+    ("'{}' が必要です", "expected '{}'"),
+    ("名前が必要です", "expected a name"),
+    ("式が必要です", "expected an expression"),
+    ("条件が真偽値ではありません: {}", "condition is not a boolean: {}"),
+    ("整数ではありません: {}", "not an integer: {}"),
+    ("配列ではありません: {}", "not an array: {}"),
+    ("添字 {} が範囲外です", "index {} is out of range"),
+    ("変数 {} は定義されていません", "variable {} is not defined"),
+    ("変数 {} は let で宣言されていません", "variable {} is not declared with let"),
+    ("for で回せるのは範囲か配列です: {}", "for needs a range or an array: {}"),
+    ("0 で割ろうとしました", "division by zero"),
+    ("rand の引数は正の整数にしてください", "rand needs a positive integer"),
+    ("関数 {}({} 個の引数) はありません", "no function {} with {} arguments"),
+    ("{} {} {} は計算できません", "cannot compute {} {} {}"),
+    ("{} は {} に使えません", "{} cannot be applied to {}"),
+    ("JSON が途中で終わっています", "unexpected end of JSON"),
+    ("JSON の末尾に余分な文字があります (位置 {})", "trailing characters after JSON (at {})"),
+    ("JSON の位置 {} で ',' か ']' が必要です ('{}' でした)", "expected ',' or ']' in JSON at {} (found '{}')"),
+    ("JSON の位置 {} で ',' か '}' が必要です ('{}' でした)", "expected ',' or '}' in JSON at {} (found '{}')"),
+    ("JSON の位置 {} で '{}' が必要です ('{}' でした)", "expected '{}' in JSON at {} (found '{}')"),
+    ("JSON の不正なエスケープ '\\{}' があります", "invalid escape '\\{}' in JSON"),
+    ("JSON の \\u エスケープに不正な文字 '{}' があります", "invalid character '{}' in a JSON \\u escape"),
+    ("JSON の位置 {} に不正な文字 '{}' があります", "unexpected character '{}' in JSON at {}"),
+    ("JSON の数値 '{}' を解釈できません", "cannot parse JSON number '{}'"),
+    ("JSON の入れ子が深すぎます (位置 {}、{} 段まで)", "JSON is nested too deeply (at {}, at most {} levels)"),
+    ("JSON のトップレベルに null は使えません", "null is not allowed at the top level of JSON"),
+    ("JSON の配列に null は使えません", "null is not allowed in JSON arrays"),
+    ("本文が大きすぎます (最大 {} バイト)", "body is too large (at most {} bytes)"),
+    ("HTTP で受け取ったスクリプトでは {} は使えません", "{} is not allowed in scripts received over HTTP"),
+    ("ヘッダーが大きすぎます (最大 {} バイト)", "headers are too large (at most {} bytes)"),
+    ("リクエスト行が不正です", "invalid request line"),
+    ("不正なパーセントエンコーディングです", "invalid percent-encoding"),
+];
@@ -0,0 +1,95 @@
+//! `say --idle` の待機中のアニメーション
+//!
+//! 表示したフレームをそのまま残し、数秒ごとにアートの待機中のテンプレート (まばたきなど)
+//! へ一瞬だけ差し替える。q か Ctrl-C で終わる
+
+use crate::{art::Art, rng::Rng, say_lines, screen::Screen, terminal, tty, RenderOptions};
+use anyhow::{bail, Result};
+use console::Term;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+/// 差し替えるまでの間隔 (この範囲でばらつかせる)
+const INTERVAL_MS: std::ops::Range<usize> = 2000..6000;
+/// 待機中のフレームを見せる時間
+const BLINK: Duration = Duration::from_millis(150);
+
+/// `say` で描いた直後のフレームを描き直し続ける
+pub fn idle(
+    side_dish: &str,
+    caption: Option<&str>,
+    qr: Option<&str>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    if opts.art.idle.is_empty() {
+        bail!("このキャラクターには待機中のフレーム (idle) がありません");
+    }
+    if opts.graphics.is_some() {
+        bail!("--idle は --graphics と一緒に使えません");
+    }
+    if !std::io::stdout().is_terminal() {
+        return Ok(());
+    }
+
+    let _screen = Screen::enter(false);
+    let mut term = Term::stdout();
+    let terminal_width = terminal::width(&term);
+    let base = say_lines(side_dish, caption, qr, terminal_width, opts)?;
+    let variants = opts
+        .art
+        .idle
+        .iter()
+        .map(|template| {
+            let opts = RenderOptions {
+                art: Art {
+                    template: template.clone(),
+                    ..opts.art.clone()
+                },
+                ..opts.clone()
+            };
+            say_lines(side_dish, caption, qr, terminal_width, &opts)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut keyboard = tty::RawTty::open();
+    let mut rng = Rng::stream(opts.seed, "idle");
+    loop {
+        let interval =
+            Duration::from_millis((INTERVAL_MS.start + rng.below(INTERVAL_MS.len())) as u64);
+        if quit(keyboard.as_mut(), interval) {
+            break;
+        }
+        overwrite(&mut term, &variants[rng.below(variants.len())])?;
+        let quitting = quit(keyboard.as_mut(), BLINK);
+        overwrite(&mut term, &base)?;
+        if quitting {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 直前に出力した同じ行数のフレームの上に書き直す
+fn overwrite(term: &mut Term, lines: &[String]) -> Result<()> {
+    term.move_cursor_up(lines.len())?;
+    for line in lines {
+        writeln!(term, "{}{}", line, terminal::clear_line_end())?;
+    }
+    term.flush()?;
+    Ok(())
+}
+
+/// `timeout` まで待ち、その間に q、Esc、Ctrl-C が押されたら true
+fn quit(keyboard: Option<&mut tty::RawTty>, timeout: Duration) -> bool {
+    match keyboard {
+        Some(keyboard) => {
+            let mut buf = [0; 16];
+            let n = keyboard.read_timeout(&mut buf, Some(timeout));
+            buf[..n].iter().any(|key| matches!(key, b'q' | 0x1b | 0x03))
+        }
+        None => {
+            std::thread::sleep(timeout);
+            false
+        }
+    }
+}
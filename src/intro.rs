@@ -0,0 +1,212 @@
+//! `anime --intro` で最初のフレームの前に流す効果
+//!
+//! 空の画面から最初のフレームへの `Transition` として作り、`Renderer` に描くので、端末でも
+//! asciicast などの書き出しでも同じように出る
+
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+use crate::transition::{columns, join, Cell, Transition};
+use anyhow::Result;
+use console::Style;
+use std::time::Duration;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intro {
+    /// 文字の雨が降り、通った後に最初のフレームが残る
+    Matrix,
+    /// 幕が中央から左右に開く
+    Curtain,
+    /// 何も流さない
+    None,
+}
+
+impl Intro {
+    /// `style` は雨と幕の色 (色が無ければ緑)、`ascii` なら幕を ASCII で描く
+    pub fn build(self, seed: u64, style: &Style, ascii: bool) -> Option<Box<dyn Transition>> {
+        // 端末かどうかに関係なく色を付ける指定は `style` から引き継ぐ
+        let style = match sequence(style).is_empty() {
+            true => sequence(&style.clone().green()),
+            false => sequence(style),
+        };
+        match self {
+            Intro::Matrix => Some(Box::new(Matrix { seed, style })),
+            Intro::Curtain => Some(Box::new(Curtain {
+                style,
+                c: if ascii { '#' } else { '▒' },
+            })),
+            Intro::None => None,
+        }
+    }
+}
+
+/// 効果を描く間隔
+const STEP: Duration = Duration::from_millis(40);
+
+/// 空の画面から `to` になるまでを描く (`to` そのものは描かない)。`animate` でなければ待たずに
+/// フレームと経った時間だけを渡す
+pub fn run(
+    renderer: &mut dyn Renderer,
+    intro: &dyn Transition,
+    to: &[String],
+    speed: f64,
+    animate: bool,
+) -> Result<()> {
+    let blank = vec![String::new(); to.len()];
+    let duration = intro.duration().div_f64(speed);
+    let steps = (duration.as_millis() / STEP.as_millis()).max(1) as u32;
+    renderer.frame(&blank)?;
+    for n in 1..=steps {
+        renderer.elapse(duration / steps);
+        if animate {
+            std::thread::sleep(duration / steps);
+        }
+        if n < steps {
+            renderer.redraw(&intro.blend(&blank, to, n as f64 / steps as f64))?;
+        }
+    }
+    Ok(())
+}
+
+/// `style` の色のシーケンス (色を付けないなら空)
+fn sequence(style: &Style) -> String {
+    let styled = style.apply_to(' ').to_string();
+    styled.split(' ').next().unwrap_or_default().to_string()
+}
+
+/// `to` を桁ごとに並べ、各行を `width` 桁にした文字。文字ごとの `(開始の桁, 文字)`
+fn grid(to: &[String]) -> (usize, Vec<Vec<(usize, Cell)>>) {
+    let rows: Vec<Vec<(usize, Cell)>> = to
+        .iter()
+        .map(|line| {
+            columns(line)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(column, cell)| Some((column, cell?)))
+                .collect()
+        })
+        .collect();
+    let width = rows
+        .iter()
+        .filter_map(|row| row.last().map(|(column, cell)| column + cell.width))
+        .max()
+        .unwrap_or(0);
+    (width, rows)
+}
+
+/// 列ごとにずれて文字の雨が落ちる
+struct Matrix {
+    seed: u64,
+    style: String,
+}
+
+/// 雨の尾の長さ (行)
+const TAIL: f64 = 6.0;
+/// 雨に使う半角カナ
+const GLYPHS: std::ops::RangeInclusive<u32> = 0xff66..=0xff9d;
+
+impl Matrix {
+    fn random(&self, a: u64, b: u64) -> u64 {
+        Rng::new(self.seed ^ (a << 32 | b).wrapping_mul(0x2545_f491_4f6c_dd1d)).next_u64()
+    }
+
+    fn glyph(&self, row: usize, column: usize, t: f64) -> char {
+        // 時間とともに文字が入れ替わる
+        let tick = (t * 25.0) as u64;
+        let n = self.random(row as u64 ^ tick << 16, column as u64) as u32;
+        char::from_u32(GLYPHS.start() + n % (GLYPHS.end() - GLYPHS.start() + 1)).unwrap_or('0')
+    }
+}
+
+impl Transition for Matrix {
+    fn duration(&self) -> Duration {
+        Duration::from_millis(1600)
+    }
+
+    fn blend(&self, _from: &[String], to: &[String], t: f64) -> Vec<String> {
+        let (_, rows) = grid(to);
+        let height = rows.len() as f64;
+        rows.iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                let mut out = Vec::new();
+                for (column, cell) in cells {
+                    // 列ごとに 0.0-0.4 だけ遅れて落ち始め、t = 1.0 で全部が通り過ぎる
+                    let delay = (self.random(0, *column as u64) >> 11) as f64 / (1u64 << 53) as f64;
+                    let head = (t * 1.4 - delay * 0.4) * (height + TAIL);
+                    let row = row as f64;
+                    let shown = match row {
+                        _ if row < head - TAIL => None,
+                        _ if row <= head => Some(match head - row < 1.0 {
+                            true => "\x1b[1m",
+                            false => "",
+                        }),
+                        _ => {
+                            out.extend((0..cell.width).map(|_| Cell::blank()));
+                            continue;
+                        }
+                    };
+                    match shown {
+                        None => out.push(cell.clone()),
+                        Some(bold) => out.extend((0..cell.width).map(|i| Cell {
+                            style: format!("{}{}", self.style, bold),
+                            c: self.glyph(row as usize, column + i, t),
+                            ..Cell::blank()
+                        })),
+                    }
+                }
+                join(&out)
+            })
+            .collect()
+    }
+}
+
+/// 中央から左右に開く幕
+struct Curtain {
+    style: String,
+    c: char,
+}
+
+impl Transition for Curtain {
+    fn duration(&self) -> Duration {
+        Duration::from_millis(800)
+    }
+
+    fn blend(&self, _from: &[String], to: &[String], t: f64) -> Vec<String> {
+        let (width, rows) = grid(to);
+        let center = width as f64 / 2.0;
+        let open = t * center;
+        rows.iter()
+            .map(|cells| {
+                let mut out = Vec::new();
+                let mut end = 0;
+                for (column, cell) in cells {
+                    let middle = *column as f64 + cell.width as f64 / 2.0;
+                    match (middle - center).abs() < open {
+                        true => out.push(cell.clone()),
+                        false => out.extend((0..cell.width).map(|_| self.cell())),
+                    }
+                    end = column + cell.width;
+                }
+                // 短い行も幕は端まで引く
+                for column in end..width {
+                    let middle = column as f64 + 0.5;
+                    match (middle - center).abs() < open {
+                        true => out.push(Cell::blank()),
+                        false => out.push(self.cell()),
+                    }
+                }
+                join(&out)
+            })
+            .collect()
+    }
+}
+
+impl Curtain {
+    fn cell(&self) -> Cell {
+        Cell {
+            style: self.style.clone(),
+            c: self.c,
+            ..Cell::blank()
+        }
+    }
+}
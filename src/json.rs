@@ -0,0 +1,364 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use toml::{Table, Value};
+
+/// 配列とオブジェクトの入れ子の上限 (深すぎる入力でスタックを使い切らないため)
+const MAX_DEPTH: usize = 64;
+
+/// JSON を `toml::Value` 経由でデシリアライズする
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T> {
+    let mut parser = Parser {
+        chars: s.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser
+        .value()?
+        .ok_or_else(|| anyhow!("JSON のトップレベルに null は使えません"))?;
+    parser.skip_ws();
+    if parser.pos < parser.chars.len() {
+        bail!("JSON の末尾に余分な文字があります (位置 {})", parser.pos);
+    }
+    Ok(value.try_into()?)
+}
+
+/// `toml::Value` 経由で JSON にする (`None` のフィールドは出力しない)
+pub fn to_string<T: Serialize>(value: &T) -> Result<String> {
+    let mut out = String::new();
+    write(&mut out, &Value::try_from(value)?);
+    Ok(out)
+}
+
+fn write(out: &mut String, value: &Value) {
+    match value {
+        Value::String(s) => write_string(out, s),
+        Value::Integer(n) => out.push_str(&n.to_string()),
+        Value::Float(f) if f.is_finite() => out.push_str(&f.to_string()),
+        Value::Float(_) => out.push_str("null"),
+        Value::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Datetime(datetime) => write_string(out, &datetime.to_string()),
+        Value::Array(array) => {
+            out.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write(out, value);
+            }
+            out.push(']');
+        }
+        Value::Table(table) => {
+            out.push('{');
+            for (i, (key, value)) in table.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// いま開いている配列とオブジェクトの数
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<char> {
+        let c = self
+            .peek()
+            .ok_or_else(|| anyhow!("JSON が途中で終わっています"))?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        let c = self.next()?;
+        if c != expected {
+            bail!(
+                "JSON の位置 {} で '{}' が必要です ('{}' でした)",
+                self.pos - 1,
+                expected,
+                c
+            );
+        }
+        Ok(())
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> Result<()> {
+        for c in word.chars() {
+            self.expect(c)?;
+        }
+        Ok(())
+    }
+
+    /// null は TOML に存在しないため `None` として返す
+    fn value(&mut self) -> Result<Option<Value>> {
+        self.skip_ws();
+        let value = match self.peek() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth >= MAX_DEPTH {
+                    bail!(
+                        "JSON の入れ子が深すぎます (位置 {}、{} 段まで)",
+                        self.pos,
+                        MAX_DEPTH
+                    );
+                }
+                self.depth += 1;
+                let value = match c {
+                    '{' => Value::Table(self.object()?),
+                    _ => Value::Array(self.array()?),
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Value::String(self.string()?),
+            Some('t') => {
+                self.keyword("true")?;
+                Value::Boolean(true)
+            }
+            Some('f') => {
+                self.keyword("false")?;
+                Value::Boolean(false)
+            }
+            Some('n') => {
+                self.keyword("null")?;
+                return Ok(None);
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(c) => bail!("JSON の位置 {} に不正な文字 '{}' があります", self.pos, c),
+            None => bail!("JSON が途中で終わっています"),
+        };
+        Ok(Some(value))
+    }
+
+    fn object(&mut self) -> Result<Table> {
+        self.expect('{')?;
+        let mut table = Table::new();
+        self.skip_ws();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(table);
+        }
+        loop {
+            self.skip_ws();
+            let key = self.string()?;
+            self.skip_ws();
+            self.expect(':')?;
+            if let Some(value) = self.value()? {
+                table.insert(key, value);
+            }
+            self.skip_ws();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Ok(table),
+                c => bail!(
+                    "JSON の位置 {} で ',' か '}}' が必要です ('{}' でした)",
+                    self.pos - 1,
+                    c
+                ),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Vec<Value>> {
+        self.expect('[')?;
+        let mut array = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(array);
+        }
+        loop {
+            let value = self
+                .value()?
+                .ok_or_else(|| anyhow!("JSON の配列に null は使えません"))?;
+            array.push(value);
+            self.skip_ws();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Ok(array),
+                c => bail!(
+                    "JSON の位置 {} で ',' か ']' が必要です ('{}' でした)",
+                    self.pos - 1,
+                    c
+                ),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Ok(s),
+                '\\' => match self.next()? {
+                    '"' => s.push('"'),
+                    '\\' => s.push('\\'),
+                    '/' => s.push('/'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'u' => {
+                        let high = self.hex4()?;
+                        let code = if (0xD800..0xDC00).contains(&high) {
+                            self.keyword("\\u")?;
+                            let low = self.hex4()?;
+                            0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+                        } else {
+                            high
+                        };
+                        s.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                    }
+                    c => bail!("JSON の不正なエスケープ '\\{}' があります", c),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let c = self.next()?;
+            let digit = c
+                .to_digit(16)
+                .ok_or_else(|| anyhow!("JSON の \\u エスケープに不正な文字 '{}' があります", c))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' || c.is_ascii_digit())
+        {
+            self.pos += 1;
+        }
+        let literal: String = self.chars[start..self.pos].iter().collect();
+        if let Ok(n) = literal.parse::<i64>() {
+            return Ok(Value::Integer(n));
+        }
+        literal
+            .parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| anyhow!("JSON の数値 '{}' を解釈できません", literal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Sample {
+        name: String,
+        count: i64,
+        ratio: f64,
+        tags: Vec<String>,
+        child: Option<Box<Sample>>,
+    }
+
+    #[test]
+    fn parses_nested_values() {
+        let sample: Sample = from_str(
+            r#" { "name": "からあげ", "count": -3, "ratio": 1.5e1, "tags": ["a", "b"],
+                  "child": { "name": "x", "count": 0, "ratio": 0.0, "tags": [], "child": null } } "#,
+        )
+        .unwrap();
+        assert_eq!(sample.name, "からあげ");
+        assert_eq!(sample.count, -3);
+        assert_eq!(sample.ratio, 15.0);
+        assert_eq!(sample.tags, ["a", "b"]);
+        assert_eq!(sample.child.unwrap().name, "x");
+    }
+
+    #[test]
+    fn decodes_escapes_and_surrogate_pairs() {
+        let map: BTreeMap<String, String> = from_str(r#"{"s": "a\"b\\c\/d\n\tあ🐉"}"#).unwrap();
+        assert_eq!(map["s"], "a\"b\\c/d\n\tあ🐉");
+    }
+
+    #[test]
+    fn round_trips_through_to_string() {
+        let map: BTreeMap<String, Vec<String>> =
+            BTreeMap::from([("k".to_string(), vec!["\"\n\u{1}".to_string()])]);
+        let json = to_string(&map).unwrap();
+        assert_eq!(json, r#"{"k":["\"\n\u0001"]}"#);
+        assert_eq!(
+            from_str::<BTreeMap<String, Vec<String>>>(&json).unwrap(),
+            map
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for source in [
+            "",
+            "null",
+            "{",
+            "[1,]",
+            r#"{"a" 1}"#,
+            r#"{"a": 1} x"#,
+            r#""\q""#,
+            r#""\u12g4""#,
+            "[null]",
+            "tru",
+            "1.2.3",
+        ] {
+            assert!(from_str::<Value>(source).is_err(), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let ok = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+        assert!(from_str::<Value>(&ok).is_ok());
+        let deep = "[".repeat(MAX_DEPTH + 1) + &"]".repeat(MAX_DEPTH + 1);
+        assert!(from_str::<Value>(&deep).is_err());
+        // スタックを使い切らずにエラーになる
+        let huge = "[".repeat(60_000);
+        assert!(from_str::<Value>(&huge).is_err());
+        let objects = r#"{"a":"#.repeat(1000);
+        assert!(from_str::<Value>(&objects).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! `kiosk` の壁掛け表示
+//!
+//! プレイリスト (TOML) のスクリプトを順にくり返し再生し続ける。プレイリストは
+//! スクリプトの合間に更新日時を見て、変わっていれば読み直す。描画が失敗したりパニックしたり
+//! しても、エラーを出して `retry` だけ待ってから次のスクリプトに進む
+//!
+//! キーは終了のキー (既定は q) のほかはすべて読み捨て、Ctrl-C などの割り込みも無視する。
+//! 止めるときは終了のキーを押すか SIGTERM を送る
+//!
+//! ```toml
+//! scripts = ["opening.toml", "menu.json"]  # プレイリストのディレクトリからの相対パス
+//! quit_key = "x"                           # 省略時は q
+//! retry = "10s"                            # 失敗してから次に進むまで (省略時は 5s)
+//! ```
+
+use crate::controls::{self, Key};
+use crate::{duration, i18n, log, screen, tty};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_QUIT_KEY: char = 'q';
+/// 失敗してから次のスクリプトに進むまで (ms)
+const DEFAULT_RETRY: u64 = 5000;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Playlist {
+    scripts: Vec<PathBuf>,
+    quit_key: Option<char>,
+    #[serde(default, deserialize_with = "duration::deserialize_millis")]
+    retry: Option<u64>,
+}
+
+impl Playlist {
+    /// スクリプトのパスはプレイリストのディレクトリから解決する
+    fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("{} を読み込めません", path.display()))?;
+        let mut playlist: Self = toml::from_str(&source)
+            .with_context(|| format!("{} を読み込めません", path.display()))?;
+        if playlist.scripts.is_empty() {
+            bail!("{}: scripts が空です", path.display());
+        }
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for script in &mut playlist.scripts {
+            *script = dir.join(&*script);
+        }
+        Ok(playlist)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 終了のキー (コマンドラインの指定がプレイリストより優先)
+fn lock(quit_key: Option<char>, playlist: &Playlist) -> Result<()> {
+    let key = quit_key.or(playlist.quit_key).unwrap_or(DEFAULT_QUIT_KEY);
+    if !key.is_ascii() || key == '\0' {
+        bail!("終了のキーは ASCII の 1 文字にしてください: {:?}", key);
+    }
+    controls::lock(key as u8);
+    Ok(())
+}
+
+/// `duration` だけ待つ。その間に終了のキーが押されたら true
+fn pause(keyboard: Option<&mut tty::RawTty>, duration: Duration) -> bool {
+    let Some(keyboard) = keyboard else {
+        sleep(duration);
+        return false;
+    };
+    let until = Instant::now() + duration;
+    loop {
+        let remaining = until.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        if controls::read_key(keyboard, Some(remaining)) == Some(Key::Quit) {
+            return true;
+        }
+    }
+}
+
+/// プレイリスト `path` のスクリプトを `play` で再生し続ける。終了のキーが押されるまで戻らない
+pub fn kiosk(
+    path: &Path,
+    quit_key: Option<char>,
+    mut play: impl FnMut(&Path) -> Result<()>,
+) -> Result<()> {
+    let mut playlist = Playlist::load(path)?;
+    let mut last_modified = modified(path);
+    lock(quit_key, &playlist)?;
+    screen::ignore_interrupts();
+    // 再生の合間も端末を生のままにして、押されたキーを画面に出さない
+    let mut keyboard = tty::RawTty::open();
+
+    let mut next = 0;
+    loop {
+        // 再生の合間に変わっていれば読み直す (読めなければ前のものを使い続ける)
+        let now = modified(path);
+        if now != last_modified {
+            last_modified = now;
+            match Playlist::load(path).and_then(|new| lock(quit_key, &new).map(|()| new)) {
+                Ok(new) => {
+                    log::info!("{} を読み直しました", path.display());
+                    playlist = new;
+                    next = 0;
+                }
+                Err(e) => eprintln!("{:#}", i18n::error(e)),
+            }
+        }
+        let script = &playlist.scripts[next % playlist.scripts.len()];
+        next = (next + 1) % playlist.scripts.len();
+
+        log::info!("{} を再生します", script.display());
+        let played = panic::catch_unwind(AssertUnwindSafe(|| play(script)))
+            .unwrap_or_else(|_| Err(anyhow!("描画中にパニックしました")));
+        if controls::quit_requested() {
+            return Ok(());
+        }
+        if let Err(e) = played {
+            let e = e.context(format!("{} を再生できません", script.display()));
+            eprintln!("{:#}", i18n::error(e));
+            let retry = Duration::from_millis(playlist.retry.unwrap_or(DEFAULT_RETRY));
+            if pause(keyboard.as_mut(), retry) {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! 吹き出しのレイアウト
+//!
+//! おかずを吹き出しの行に分けてテンプレートの口に収める。色や端末には触らない
+
+use crate::template::{Piece, Template};
+use anyhow::{bail, Result};
+use console::Alignment;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// 吹き出しの 1 行に入る文字数
+pub const BUBBLE_CHARS: usize = 16;
+
+/// キャプションの行の幅
+pub const CAPTION_WIDTH: usize = 60;
+
+/// 吹き出しの 1 行の表示幅は、1 行に入る文字数にこの幅を足したもの
+pub const BUBBLE_PADDING: usize = 4;
+
+#[rustfmt::skip]
+pub const DRAGON: &str = "                                          ,. ､
+                                        く  r',ゝ
+r'￣￣￣￣￣￣￣￣￣ヽ                   ,ゝｰ'､
+|                    |          ､      ／      ヽ.
+|                    |        く、｀ヽ/  ∩       |
+|$line1$ ＞        ｀＞             |
+|$line2$|         く´ , -'7         レ个ー─┐
+|                    |          ｀´   //  /      ー个ー─'7
+|                    |               //  /         |    (
+ゝ＿＿＿＿＿＿＿＿__ノ              //  /'┤      |ヽv'⌒ヽ､ゝ
+                                   くﾉ  lｰ┤       ヽ.
+                                    ｀^^'ｰ┤          ▽_
+                                    ((    )          ヽ乙_
+                                    ((    )ヽ､          ヽレl
+                                    ≧＿_ゝ    ｀ﾞー-=､.＿_,ゝ";
+
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    /// 収まらない分を黙って切り捨てる
+    #[default]
+    Truncate,
+    /// 収まらない分を次のフレームに送る (say では続けて表示する)
+    Wrap,
+    /// エラーにする
+    Error,
+    /// 切り捨てて末尾に … を付ける
+    Ellipsis,
+}
+
+/// おかずを描く場所
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// キャラクターの吹き出しの中
+    #[default]
+    Bubble,
+    /// 吹き出しを消したアートの右に、端末の残りの幅で折り返して
+    Side,
+}
+
+/// テンプレートに入るおかずの行数
+pub fn bubble_capacity(template: &Template) -> usize {
+    match template.has_repeat() {
+        true => usize::MAX,
+        false => template.line_slots(),
+    }
+}
+
+/// おかずを吹き出しの行に分ける
+///
+/// 1 行のおかずは折り返す。truncate 以外では、複数行のおかずの長すぎる行も折り返す
+pub fn bubble_rows(side_dish: &str, columns: usize, overflow: Overflow) -> Vec<String> {
+    let wrap = |line: &str| -> Vec<String> {
+        let chars: Vec<char> = line.chars().collect();
+        match chars.is_empty() {
+            true => vec![String::new()],
+            false => chars
+                .chunks(columns)
+                .map(|chunk| chunk.iter().collect())
+                .collect(),
+        }
+    };
+    match (side_dish.lines().count(), overflow) {
+        (0, _) => Vec::new(),
+        (1, _) => wrap(side_dish),
+        (_, Overflow::Truncate) => side_dish.lines().map(|s| s.to_string()).collect(),
+        _ => side_dish.lines().flat_map(wrap).collect(),
+    }
+}
+
+/// 行を吹き出しに入る数に切り詰め、口の幅で中央に揃える
+///
+/// 入りきらないときの `Overflow::Error` は呼ぶ側で確かめる
+pub fn fit_rows(
+    mut rows: Vec<String>,
+    template: &Template,
+    columns: usize,
+    overflow: Overflow,
+) -> Vec<String> {
+    let capacity = bubble_capacity(template);
+    if rows.len() > capacity && overflow == Overflow::Ellipsis && capacity > 0 {
+        let last = &mut rows[capacity - 1];
+        if last.chars().count() >= columns {
+            last.pop();
+        }
+        last.push('…');
+    }
+    rows.truncate(capacity);
+    if rows.len() < template.line_slots() {
+        rows.resize(template.line_slots(), String::new());
+    }
+    rows.iter()
+        .map(|s| console::pad_str(s, columns + BUBBLE_PADDING, Alignment::Center, None).to_string())
+        .collect()
+}
+
+/// 名前付きの欄の文言を表示幅 `width` で折り返し、`height` 行に切り詰めて中央に揃える
+///
+/// 足りない行は空白で埋めるので、いつも `height` 行になる
+pub fn fit_field(text: &str, width: usize, height: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    for line in text.lines() {
+        let mut row = String::new();
+        for c in line.chars() {
+            let c_width = console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+            if console::measure_text_width(&row) + c_width > width && !row.is_empty() {
+                rows.push(std::mem::take(&mut row));
+            }
+            row.push(c);
+        }
+        rows.push(row);
+    }
+    rows.resize(height, String::new());
+    rows.iter()
+        .map(|row| console::pad_str(row, width, Alignment::Center, None).to_string())
+        .collect()
+}
+
+/// `Overflow::Error` のとき、行が吹き出しに入りきるか確かめる
+pub fn check(rows: &[String], side_dish: &str, columns: usize, capacity: usize) -> Result<()> {
+    if rows.len() > capacity {
+        bail!(
+            "おかず '{}' は吹き出しに収まりません ({} 文字 x {} 行まで)",
+            side_dish.lines().collect::<Vec<_>>().join(" "),
+            columns,
+            capacity
+        );
+    }
+    Ok(())
+}
+
+/// テンプレートに吹き出しの行を差し込み、アートと口をそれぞれ `art` と `slot` で飾った行にする
+///
+/// 名前付きの欄には `fields` の行を差し込む。各行は飾る前の表示幅で `width` まで空白で埋める
+pub fn compose(
+    template: &Template,
+    rows: &[String],
+    fields: &BTreeMap<String, Vec<String>>,
+    width: usize,
+    art: impl Fn(&str) -> String,
+    slot: impl Fn(&str) -> String,
+) -> Vec<String> {
+    template
+        .render_fields(rows, fields)
+        .into_iter()
+        .map(|pieces| {
+            let mut styled = String::new();
+            let mut line_width = 0;
+            let mut text = String::new();
+            for piece in pieces {
+                match piece {
+                    Piece::Art(piece) => text.push_str(&piece),
+                    Piece::Slot(piece) => {
+                        styled.push_str(&art(&text));
+                        styled.push_str(&slot(&piece));
+                        line_width += console::measure_text_width(&text)
+                            + console::measure_text_width(&piece);
+                        text.clear();
+                    }
+                }
+            }
+            styled.push_str(&art(&text));
+            line_width += console::measure_text_width(&text);
+            styled.push_str(&" ".repeat(width.saturating_sub(line_width)));
+            styled
+        })
+        .collect()
+}
+
+/// キャプションを幅 `width` の中央に置く
+pub fn caption(caption: &str, width: usize) -> String {
+    console::pad_str(caption, width, Alignment::Center, None).to_string()
+}
+
+/// 色を付けずに描くときの吹き出しとキャプションの形
+#[derive(Clone, Debug)]
+pub struct Layout {
+    pub template: Template,
+    /// 吹き出しの 1 行に入る文字数
+    pub columns: usize,
+    pub overflow: Overflow,
+    pub caption_width: usize,
+}
+
+impl Layout {
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: Template::parse(template),
+            columns: BUBBLE_CHARS,
+            overflow: Overflow::default(),
+            caption_width: CAPTION_WIDTH,
+        }
+    }
+
+    /// おかずを言うアートの行 (各行を表示幅 `width` まで空白で埋める)
+    pub fn dragon(&self, side_dish: &str, width: usize) -> Result<Vec<String>> {
+        let rows = bubble_rows(side_dish, self.columns, self.overflow);
+        if self.overflow == Overflow::Error {
+            check(
+                &rows,
+                side_dish,
+                self.columns,
+                bubble_capacity(&self.template),
+            )?;
+        }
+        let rows = fit_rows(rows, &self.template, self.columns, self.overflow);
+        let plain = |text: &str| text.to_string();
+        Ok(compose(
+            &self.template,
+            &rows,
+            &BTreeMap::new(),
+            width,
+            plain,
+            plain,
+        ))
+    }
+
+    /// アートの下にキャプションの行を付けた 1 フレーム
+    pub fn frame(&self, side_dish: &str, caption: &str, width: usize) -> Result<Vec<String>> {
+        let mut lines = self.dragon(side_dish, width)?;
+        lines.push(self::caption(caption, self.caption_width));
+        Ok(lines)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new(DRAGON)
+    }
+}
+
+/// 組み込みのドラゴンにおかずを言わせた、色の無い行 (各行を表示幅 `width` まで空白で埋める)
+pub fn render(text: &str, width: usize) -> Vec<String> {
+    // 切り詰めるだけなので失敗しない
+    Layout::default().dragon(text, width).unwrap_or_default()
+}
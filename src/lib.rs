@@ -0,0 +1,12 @@
+//! 表示の中心になるテンプレートと吹き出しのレイアウト
+//!
+//! 端末に依存しないので、`ffi` フィーチャーで C から、`wasm` フィーチャーで wasm32 向けにも使える
+
+#[cfg(feature = "ffi")]
+mod ffi;
+pub mod layout;
+pub mod template;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use layout::render;
@@ -0,0 +1,79 @@
+//! `-v` と `--log-level` で stderr に出す診断のログ
+//!
+//! スクリプトの解釈や吹き出しのレイアウト、端末の判定でなぜそうなったかを追えるようにする
+
+use crate::i18n;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// `-v` の数から (0 なら warn)
+    pub fn from_verbosity(count: u8) -> Self {
+        match count {
+            0 => Level::Warn,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+pub fn set(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+pub fn write(level: Level, message: &str) {
+    eprintln!("[{}] {}", level.name(), i18n::tr(message));
+}
+
+/// `enabled` のときだけ `format!` して出力する
+macro_rules! log {
+    ($level:ident, $($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::$level) {
+            $crate::log::write($crate::log::Level::$level, &format!($($arg)*));
+        }
+    };
+}
+
+// `warn` は組み込みの属性と名前がぶつかるので、別名で定義してから `warn` として出す
+macro_rules! warning {
+    ($($arg:tt)*) => { $crate::log::log!(Warn, $($arg)*) };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log::log!(Info, $($arg)*) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log::log!(Debug, $($arg)*) };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::log::log!(Trace, $($arg)*) };
+}
+
+pub(crate) use {debug, info, log, trace, warning as warn};
@@ -0,0 +1,166 @@
+//! `anime --lrc` の歌詞ファイルに合わせたカラオケ
+//!
+//! 歌詞の各行をその時刻から次の行の時刻まで表示する。吹き出しには今の行を ♪ 付きで、
+//! その下に次の行を出す。`--player` を付けると再生を始めるときに音楽のプレーヤーも起動する
+
+use crate::{Frame, Script, Text};
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+
+/// 今の行に付ける印
+const MARK: &str = "♪";
+
+struct Line {
+    time: u64,
+    text: String,
+}
+
+pub fn load(path: &Path) -> Result<Script> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("{} を読み込めません", path.display()))?;
+    script(&source).with_context(|| format!("{} の読み込みに失敗しました", path.display()))
+}
+
+/// 歌詞の各行をその時刻に表示するスクリプト
+pub fn script(source: &str) -> Result<Script> {
+    let (lines, title) = parse(source)?;
+    let caption = Text::Plain(title);
+    let mut frames = Vec::new();
+    if let Some(first) = lines.first().filter(|line| line.time > 0) {
+        frames.push(Frame {
+            caption: caption.clone(),
+            duration: Some(first.time),
+            ..Frame::default()
+        });
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let next = lines.get(i + 1);
+        let side_dish = match (line.text.is_empty(), next) {
+            (true, _) => String::new(),
+            (false, Some(next)) if !next.text.is_empty() => {
+                format!("{}{}\n{}", MARK, line.text, next.text)
+            }
+            (false, _) => format!("{}{}", MARK, line.text),
+        };
+        frames.push(Frame {
+            side_dish: Text::Plain(side_dish),
+            caption: caption.clone(),
+            duration: next.map(|next| next.time - line.time),
+            ..Frame::default()
+        });
+    }
+    Ok(Script {
+        frames,
+        ..Script::default()
+    })
+}
+
+/// 時刻の付いた行と、`[ti:]` と `[ar:]` から作ったタイトル
+fn parse(source: &str) -> Result<(Vec<Line>, String)> {
+    let mut lines = Vec::new();
+    let (mut title, mut artist, mut offset) = (None, None, 0i64);
+    for row in source.trim_start_matches('\u{feff}').lines() {
+        let mut rest = row.trim();
+        let mut times = Vec::new();
+        while let Some((tag, after)) = rest.strip_prefix('[').and_then(|tag| tag.split_once(']')) {
+            match tag.split_once(':') {
+                Some(("ti", value)) => title = Some(value.trim().to_string()),
+                Some(("ar", value)) => artist = Some(value.trim().to_string()),
+                Some(("offset", value)) => {
+                    offset = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow!("offset '{}' を解釈できません", value.trim()))?
+                }
+                _ => times.extend(timestamp(tag)),
+            }
+            rest = after;
+        }
+        let text = strip_word_times(rest).trim().to_string();
+        lines.extend(times.into_iter().map(|time| Line {
+            // offset が正なら歌詞を早める
+            time: (time as i64 - offset).max(0) as u64,
+            text: text.clone(),
+        }));
+    }
+    if lines.is_empty() {
+        anyhow::bail!("時刻の付いた歌詞がありません");
+    }
+    lines.sort_by_key(|line| line.time);
+    let title = match (title, artist) {
+        (Some(title), Some(artist)) => format!("{} / {}", title, artist),
+        (Some(title), None) => title,
+        (None, Some(artist)) => artist,
+        (None, None) => String::new(),
+    };
+    Ok((lines, title))
+}
+
+/// `mm:ss.xx` (`mm:ss` や `mm:ss:xx` も可) をミリ秒にする。時刻でなければ None
+fn timestamp(tag: &str) -> Option<u64> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let (seconds, fraction) = seconds.split_once(['.', ':']).unwrap_or((seconds, ""));
+    let seconds: u64 = seconds.parse().ok()?;
+    let millis = match fraction {
+        "" => 0,
+        // 1/100 秒 (2 桁) と 1/1000 秒 (3 桁) のどちらも使われる
+        fraction => format!("{:0<3}", fraction).get(..3)?.parse::<u64>().ok()?,
+    };
+    Some((minutes * 60 + seconds) * 1000 + millis)
+}
+
+/// 拡張 LRC の単語ごとの時刻 `<mm:ss.xx>` を取り除く
+fn strip_word_times(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        match rest[start..].find('>') {
+            Some(end) if timestamp(&rest[start + 1..start + end]).is_some() => {
+                rest = &rest[start + end + 1..]
+            }
+            _ => {
+                out.push('<');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 起動した音楽のプレーヤー
+pub struct Player(Child);
+
+impl Player {
+    /// シェルのコマンドとして起動する (出力は捨てる)
+    pub fn spawn(command: &str) -> Result<Self> {
+        let mut shell = match cfg!(windows) {
+            true => {
+                let mut shell = Command::new("cmd");
+                shell.args(["/C", command]);
+                shell
+            }
+            false => {
+                // 止めるときにプレーヤーそのものへ届くように、シェルをプレーヤーに置き換える
+                let mut shell = Command::new("sh");
+                shell.args(["-c", &format!("exec {}", command)]);
+                shell
+            }
+        };
+        let child = shell
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("プレーヤー '{}' を起動できません", command))?;
+        Ok(Self(child))
+    }
+
+    pub fn stop(mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
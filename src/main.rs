@@ -1,28 +1,341 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use console::{Alignment, Term};
-use serde::Deserialize;
+mod a11y;
+mod art;
+mod background;
+mod bench;
+mod cache;
+mod chat;
+mod clipboard;
+mod clock;
+mod completions;
+mod compositor;
+mod config;
+mod controls;
+mod countdown;
+mod daemon;
+mod dryrun;
+mod dump;
+mod duration;
+mod editor;
+mod events;
+mod export;
+mod feed;
+mod fetch;
+mod font;
+mod fromcmd;
+mod gha;
+mod githook;
+mod graphics;
+mod greeting;
+mod history;
+mod i18n;
+mod idle;
+mod intro;
+mod json;
+mod kiosk;
+mod log;
+mod lrc;
+mod mangen;
+mod markdown;
+mod markup;
+mod mood;
+mod motd;
+mod narrow;
+mod normalize;
+mod notify;
+mod oneline;
+mod pack;
+mod placeholder;
+mod png;
+mod pomodoro;
+mod pony;
+mod preview;
+mod prompt;
+mod qotd;
+mod qr;
+mod quantize;
+mod quiz;
+mod raster;
+mod record;
+mod remind;
+mod renderer;
+mod rng;
+mod roulette;
+mod rsvp;
+mod sanitize;
+mod schedule;
+mod screen;
+mod screensaver;
+mod scripting;
+mod selfupdate;
+mod serve;
+mod sha256;
+mod side;
+mod sound;
+mod speak;
+mod srt;
+mod stats;
+mod status;
+mod terminal;
+mod theme;
+mod transition;
+mod tty;
+mod typing;
+mod weather;
+
+use fav_say_dragon::layout::{
+    self, bubble_capacity, bubble_rows, Overflow, Placement, BUBBLE_CHARS,
+};
+use fav_say_dragon::template;
+
+use anyhow::{Context, Result};
+use art::Art;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use compositor::{z, Compositor, Layer};
+use config::Config;
+use console::{Style, Term};
+use controls::Key;
+use renderer::Renderer;
+use schedule::Schedule;
+use serde::{Deserialize, Serialize};
+use stats::Stats;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(version, about, flatten_help = true)]
 struct Args {
+    #[command(flatten)]
+    render: RenderArgs,
+
     #[command(subcommand)]
     sub: Command,
 }
 
+#[derive(clap::Args, Clone, Debug)]
+struct RenderArgs {
+    /// キャラクター (random なら組み込みのキャラクターからランダムに選ぶ)
+    #[arg(long, global = true)]
+    character: Option<String>,
+
+    /// 日付に応じて季節のキャラクターを選ぶ
+    #[arg(long, global = true)]
+    seasonal: bool,
+
+    /// テーマ (retro, neon, mono など)
+    #[arg(long, global = true)]
+    theme: Option<String>,
+
+    /// アートの色 (例: green, cyan.bold)
+    #[arg(long, global = true)]
+    color: Option<String>,
+
+    /// キャプションの色
+    #[arg(long, global = true)]
+    caption_color: Option<String>,
+
+    /// キャプションの幅
+    #[arg(long, global = true)]
+    caption_width: Option<usize>,
+
+    /// 暗い背景向けの色を使う
+    #[arg(long, global = true, conflicts_with = "force_light")]
+    force_dark: bool,
+
+    /// 明るい背景向けの色を使う
+    #[arg(long, global = true)]
+    force_light: bool,
+
+    /// 端末の画像表示機能で出力する
+    #[arg(long, global = true, value_enum)]
+    graphics: Option<graphics::Protocol>,
+
+    /// 吹き出しの 1 行に入る文字数 (指定すると吹き出しを描き直す) [既定値: 16]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u16).range(1..))]
+    bubble_cols: Option<u16>,
+
+    /// 吹き出しに入る行数 (指定すると吹き出しを描き直す) [既定値: 2]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u16).range(1..))]
+    bubble_rows: Option<u16>,
+
+    /// 吹き出しに収まらないおかずの扱い (省略時は truncate)
+    #[arg(long, global = true, value_enum)]
+    overflow: Option<Overflow>,
+
+    /// おかずを描く場所 (省略時は bubble。side ではアートの右に長い文を折り返して並べる)
+    #[arg(long, global = true, value_enum)]
+    layout: Option<Placement>,
+
+    /// 吹き出しのしっぽの向き (省略時はキャラクターのまま)
+    #[arg(long, global = true, value_enum)]
+    tail: Option<art::TailSide>,
+
+    /// ドラゴンと吹き出しを端末の中央に寄せる
+    #[arg(long, global = true)]
+    center: bool,
+
+    /// ASCII だけのアートと吹き出しを使う (ロケールが UTF-8 でなければ自動で使う)
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// スクリーンリーダー向けに、アートと画面の消去を使わずフレームごとのセリフを文で出す
+    #[arg(long, global = true, conflicts_with = "graphics")]
+    a11y: bool,
+
+    /// おかずとキャプションの全角/半角をそろえてから配置する
+    #[arg(long, global = true, value_enum)]
+    normalize: Option<normalize::Normalize>,
+
+    /// キャラクターの名前付きの欄 (看板など) に入れる文言。何度でも指定できる (例: sign=営業中)
+    #[arg(long = "field", value_name = "NAME=TEXT", global = true, value_parser = i18n::parser(parse_field))]
+    fields: Vec<(String, String)>,
+
+    /// 標準入力、ファイル、URL、ソケットから受け取った文言のエスケープシーケンスを取り除かない
+    #[arg(long, global = true)]
+    allow_ansi: bool,
+
+    /// 乱数のシード。シャッフル、ばらつき、ランダムなキャラクター、ルーレットなどを毎回同じにする
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// --graphics で使うビットマップフォント (BDF, .hex)
+    #[arg(long, global = true, value_hint = clap::ValueHint::FilePath)]
+    font: Option<PathBuf>,
+
+    /// メッセージとヘルプ、スクリプトの訳の言語 (省略時は LANG に従う)
+    #[arg(long, global = true, value_enum)]
+    lang: Option<i18n::Lang>,
+
+    /// アニメーションせずに全フレームを順に出力する (画面の消去やカーソル移動、待ち時間なし)
+    #[arg(long, global = true, visible_alias = "ci")]
+    no_anim: bool,
+
+    /// 色とエスケープシーケンスを使わずに描く (VT を使えない古い Windows のコンソール向け)
+    #[arg(long, global = true)]
+    legacy_console: bool,
+
+    /// インストールしたときと中身が違う (改ざんされたかもしれない) アートパックも使う
+    #[arg(long, global = true)]
+    trust: bool,
+
+    /// 診断のログを stderr に出す (-v で info, -vv で debug, -vvv で trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// ログの詳しさ (-v より優先)
+    #[arg(long, global = true, value_enum)]
+    log_level: Option<log::Level>,
+}
+
+// 引数を解析した直後に一度だけ作るので、大きさの違いは気にしない
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Clone, Debug)]
 enum Command {
     /// 一度に出力
     Say {
         /// おかず
-        side_dish: String,
+        #[arg(required_unless_present_any = ["file", "url", "random_dishes"])]
+        side_dish: Option<String>,
         /// キャプション
         caption: Option<String>,
+
+        /// おかずをファイルから読み込む
+        #[arg(short, long, conflicts_with = "side_dish", value_hint = clap::ValueHint::FilePath)]
+        file: Option<PathBuf>,
+
+        /// おかずを URL から取得する (プレーンテキスト、64 KiB と 10 秒まで)
+        #[arg(long, conflicts_with_all = ["side_dish", "file"], value_hint = clap::ValueHint::Url)]
+        url: Option<String>,
+
+        /// キャプションをファイルから読み込む
+        #[arg(long, conflicts_with = "caption", value_hint = clap::ValueHint::FilePath)]
+        caption_file: Option<PathBuf>,
+
+        /// デスクトップ通知も送る
+        #[arg(long)]
+        notify: bool,
+
+        /// おかずを読み上げる
+        #[arg(long)]
+        speak: bool,
+
+        /// 色と末尾の空白を除いたフレームをクリップボードへコピーする
+        #[arg(long)]
+        copy: bool,
+
+        /// URL などを QR コードにして横に並べる (キャプションはその下に表示)
+        #[arg(long, value_name = "URL")]
+        qr: Option<String>,
+
+        /// tmux のステータスライン向けに、小さなドラゴンとおかずを改行の無い 1 行で出力する
+        #[arg(long, conflicts_with_all = ["qr", "copy"])]
+        oneline: bool,
+
+        /// チャットに貼り付けられるようにコードブロックで出力する
+        #[arg(long, value_enum, conflicts_with = "oneline")]
+        format: Option<chat::Format>,
+
+        /// GitHub Actions のジョブのまとめ ($GITHUB_STEP_SUMMARY) にもフレームを書き込む
+        #[arg(long)]
+        gha_summary: bool,
+
+        /// GitHub Actions の ::notice:: の注釈でキャプション (無ければおかず) も伝える
+        #[arg(long)]
+        gha_notice: bool,
+
+        /// --oneline の最大の表示幅
+        #[arg(long, value_name = "COLUMNS", requires = "oneline", default_value_t = oneline::DEFAULT_WIDTH,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        oneline_width: usize,
+
+        /// おかずを Markdown (見出し、太字、斜体、インラインコード、箇条書き) として読み、
+        /// 吹き出しを中身の大きさに合わせる
+        #[arg(long)]
+        markdown: bool,
+
+        /// 表示したまま終わらずに、ときどきまばたきなどの待機中のフレームを見せる (q で終了)
+        #[arg(long, conflicts_with_all = ["oneline", "format"])]
+        idle: bool,
+
+        /// ドラゴンを N 匹並べる (端末の幅に収まるだけ横に並べ、残りは下に続ける)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with_all = ["oneline", "format", "idle"])]
+        count: Option<u16>,
+
+        /// --count のドラゴンごとに組み込みのおかずからランダムに選ぶ
+        #[arg(long, requires = "count")]
+        random_dishes: bool,
+
+        /// このおかずを履歴に残さない
+        #[arg(long)]
+        no_history: bool,
+    },
+    /// say の履歴を番号付きで表示する (番号は again で使う)
+    History {
+        /// 表示する数 (新しいものから)
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+    },
+    /// 履歴かお気に入りのおかずをもう一度出す
+    Again {
+        /// history か fav list の番号 (省略時は最新のもの)
+        number: Option<usize>,
+
+        /// お気に入りから選ぶ
+        #[arg(long)]
+        fav: bool,
+    },
+    /// お気に入りのおかずの操作
+    Fav {
+        #[command(subcommand)]
+        action: FavAction,
     },
     /// アニメーション出力
+    ///
+    /// 再生中はスペースで一時停止/再開、n/p (←/→) でコマ送り、+/- で速度変更、q で終了。
+    /// 選択肢のあるフレームでは番号のキーで選ぶ
     Anime {
         /// おかず
         side_dishes: Vec<String>,
@@ -35,206 +348,3441 @@ enum Command {
         #[arg(short = 'A', long)]
         after_captions: Vec<String>,
 
-        /// ファイルからスクリプトを読み込む
+        /// ファイルからスクリプトを読み込む。何度でも指定でき、フレームを順につなげる
+        /// (interval はファイルごとに効く)
         #[arg(short = 'f', long,
             conflicts_with_all(["side_dishes", "pre_captions", "after_captions"]))
         ]
-        script_file: Option<PathBuf>,
+        script_file: Vec<PathBuf>,
+
+        /// -f のスクリプトの形式 (省略時は拡張子、- なら中身から推測する)
+        #[arg(long, value_enum, requires = "script_file")]
+        format: Option<ScriptFormat>,
+
+        /// SubRip 字幕 (.srt) の各字幕を、その表示時間どおりのフレームにして再生する
+        #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath,
+            conflicts_with_all(["side_dishes", "pre_captions", "after_captions", "script_file", "shuffle"]))
+        ]
+        srt: Option<PathBuf>,
+
+        /// 歌詞ファイル (.lrc) の各行をその時刻に表示するカラオケ
+        #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath,
+            conflicts_with_all(["side_dishes", "pre_captions", "after_captions", "script_file", "srt", "shuffle"]))
+        ]
+        lrc: Option<PathBuf>,
+
+        /// 再生を始めるときに実行する音楽のプレーヤーのコマンド (例: "mpv --no-video song.mp3")
+        #[arg(long, value_name = "COMMAND", requires = "lrc")]
+        player: Option<String>,
+
+        /// フレームを出すたびにシェルのコマンドを実行し、その標準出力をおかずにする (例: "fortune")
+        #[arg(long, value_name = "COMMAND",
+            conflicts_with_all(["side_dishes", "script_file", "srt", "lrc", "shuffle"]))
+        ]
+        from_cmd: Option<String>,
+
+        /// --from-cmd で作るフレームの数 [既定値: 10]
+        #[arg(long, value_name = "N", requires = "from_cmd",
+            value_parser = clap::value_parser!(u16).range(1..))
+        ]
+        frames: Option<u16>,
+
+        /// インターバル (単位が無ければ ms。1.5s, 500ms, 2m も可) [既定値: 1000]
+        #[arg(short, long, value_parser = i18n::parser(duration::parse_interval))]
+        interval: Option<u64>,
+
+        /// キャプションだけのフレームのインターバル (省略時は --interval と同じ)
+        #[arg(long, value_parser = i18n::parser(duration::parse_interval))]
+        caption_interval: Option<u64>,
+
+        /// フレームを表示するたびにデスクトップ通知も送る
+        #[arg(long)]
+        notify: bool,
+
+        /// フレームのテキストを読み上げる
+        #[arg(long)]
+        speak: bool,
+
+        /// ベルを鳴らすタイミング
+        #[arg(long, value_enum, default_value_t = Bell::None)]
+        bell: Bell,
+
+        /// 再生速度の倍率 (2.0 で倍速)
+        #[arg(long, default_value_t = 1.0, value_parser = i18n::parser(parse_speed))]
+        speed: f64,
+
+        /// インターバルの変化のさせ方
+        #[arg(long, value_enum, default_value_t = Easing::Linear)]
+        easing: Easing,
 
-        /// インターバル (ms)
+        /// インターバルをフレームごとにこの割合の範囲でばらつかせる (例: 20%)。
+        /// --seed で再現できる
+        #[arg(long, value_parser = i18n::parser(parse_jitter))]
+        jitter: Option<f64>,
+
+        /// おかずを 1 文字ずつ出し、キャラクターの口を動かす (1 文字の時間、既定値: 50ms)
         #[arg(
-            short, long,
-            default_value_t = 1000,
-            value_parser = clap::value_parser!(u64).range(10..))
+            long,
+            value_name = "PER_CHAR",
+            num_args = 0..=1,
+            default_missing_value = "50",
+            value_parser = i18n::parser(duration::parse_millis)
+        )]
+        typewriter: Option<u64>,
+
+        /// キャプションを ■ で伏せて出し、この時間の後に 1 文字ずつ明かす
+        #[arg(long, value_parser = i18n::parser(duration::parse), conflicts_with = "typewriter")]
+        reveal_after: Option<Duration>,
+
+        /// フレームの切り替え方
+        #[arg(long, value_enum)]
+        transition: Option<transition::Kind>,
+
+        /// 最初のフレームの前に流す画面全体の効果 (--export の書き出しにも入る)
+        #[arg(long, value_enum)]
+        intro: Option<intro::Intro>,
+
+        /// 画面を消さず、今のカーソルの位置から下だけを描き直す (前からある出力を残す)
+        #[arg(long, conflicts_with = "graphics")]
+        inline: bool,
+
+        /// このラベル (または 1 から数えたフレームの番号) のフレームから再生する
+        #[arg(long, value_name = "LABEL")]
+        from: Option<String>,
+
+        /// このラベル (または 1 から数えたフレームの番号) のフレームまで再生する
+        #[arg(long, value_name = "LABEL")]
+        to: Option<String>,
+
+        /// 最後のフレーム (--end-card があればその文言) を出したまま待つ時間。
+        /// forever ならキーを押すまで待つ
+        #[arg(long, value_name = "DURATION|forever", value_parser = i18n::parser(parse_hold))]
+        hold_last: Option<Hold>,
+
+        /// 最後のフレームの後に、エンドロールのように出す文言
+        #[arg(long, value_name = "TEXT")]
+        end_card: Option<String>,
+
+        /// キャプションの下に進み具合を表示する
+        #[arg(long)]
+        progress: bool,
+
+        /// 逆順に再生する
+        #[arg(long, conflicts_with = "pingpong")]
+        reverse: bool,
+
+        /// 最後まで再生したら逆順で最初まで戻る
+        #[arg(long)]
+        pingpong: bool,
+
+        /// おかずの順番をランダムにする (キャプションはそのまま)
+        #[arg(long)]
+        shuffle: bool,
+
+        /// 再生した内容とインターバルをスクリプトとして保存する
+        #[arg(long, value_name = "OUT", value_hint = clap::ValueHint::FilePath)]
+        record: Option<PathBuf>,
+
+        /// Rhai 風のスクリプトで再生を組み立てる (say, wait, set_mood, set_character, random_dish など)。
+        /// おかずを指定すると random_dish の候補になる
+        #[arg(long, value_name = "SCRIPT", value_hint = clap::ValueHint::FilePath,
+            conflicts_with_all(["script_file", "srt", "lrc", "record"]))
         ]
-        interval: u64,
+        rhai: Option<PathBuf>,
+
+        /// 再生の後に合計時間、フレーム数、描画時間、間に合わなかったフレームを stderr に出す
+        #[arg(long, conflicts_with_all = ["rhai", "dump_json"])]
+        stats: bool,
+
+        /// 再生せずに、解決したフレームの一覧 (テキスト、表示時間、レイアウト) を JSON で出力する
+        #[arg(long, conflicts_with = "rhai")]
+        dump_json: bool,
+
+        /// 再生せずに、フレームごとの種類、テキスト、表示時間と合計時間を表で出力する
+        #[arg(long, conflicts_with_all = ["rhai", "dump_json", "stats", "player"])]
+        dry_run: bool,
+
+        /// 再生せずに、フレームを拡張子の形式 (.txt, .html, .png, .cast) のファイルへ書き出す。
+        /// PNG は 2 フレーム以上なら連番になる
+        #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath,
+            conflicts_with_all = ["rhai", "dump_json", "dry_run", "stats", "player"])]
+        export: Option<PathBuf>,
+
+        /// --export の PNG の色数。16 と 256 はパレット形式になり、
+        /// 504x272 の例ではフルカラー約 14 KB、256 色で約 10 KB、16 色で約 8 KB になる (グラデーションは近い色に丸まる)
+        #[arg(long, value_enum, default_value_t, requires = "export")]
+        palette: quantize::Depth,
+
+        /// --palette で色を減らすときの中間の色の表し方
+        #[arg(long, value_enum, default_value_t, requires = "export")]
+        dither: quantize::Dither,
+    },
+    /// ドラゴンがクイズを出す
+    Quiz {
+        /// 問題ファイル
+        #[arg(short = 'f', long, value_hint = clap::ValueHint::FilePath)]
+        quiz_file: PathBuf,
+    },
+    /// 候補を切り替えながら 1 つを選ぶ
+    Roulette {
+        /// 候補
+        #[arg(required = true)]
+        items: Vec<String>,
+
+        /// 止まったときのキャプション [既定値: 決定！]
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// おかずとキャラクターを画面のあちこちに出し続けるスクリーンセーバー (何かキーを押すと終了)
+    Screensaver {
+        /// おかず (省略時は組み込みのおかず)
+        side_dishes: Vec<String>,
+
+        /// 次を出すまでの間隔 (単位が無ければ ms) [既定値: 5s]
+        #[arg(short, long, value_parser = i18n::parser(duration::parse_interval))]
+        interval: Option<u64>,
+    },
+    /// 文章を単語ごと (日本語は文節ごと) に吹き出しへ出す速読の練習
+    Rsvp {
+        /// 読む文章のファイル (- なら標準入力)
+        #[arg(value_hint = clap::ValueHint::FilePath)]
+        file: PathBuf,
+
+        /// 1 分あたりに出す単語 (文節) の数
+        #[arg(long, default_value_t = rsvp::DEFAULT_WPM, value_parser = clap::value_parser!(u16).range(1..))]
+        wpm: u16,
+    },
+    /// スクリプトのおかずを打ち込むタイピング練習
+    Type {
+        /// 練習に使うスクリプト
+        #[arg(short = 'f', long, value_hint = clap::ValueHint::FilePath)]
+        script_file: PathBuf,
+    },
+    /// キャラクターかスクリプトの最初のフレームとレイアウトの情報を表示
+    Preview {
+        /// キャラクター名またはスクリプトのパス
+        target: String,
+    },
+    /// スクリプトを一覧と描画を見ながら編集する (並べ替え、追加、削除、表示時間)
+    Edit {
+        /// スクリプトのパス (無ければ新しく作る)
+        #[arg(value_hint = clap::ValueHint::FilePath)]
+        script_file: PathBuf,
+    },
+    /// 入力した行を表示しながらスクリプトに記録する (pre:, after: でキャプション)
+    Record {
+        /// 保存先
+        #[arg(value_hint = clap::ValueHint::FilePath)]
+        output: PathBuf,
+
+        /// 既存のスクリプトに追記する
+        #[arg(long)]
+        append: bool,
+    },
+    /// HTTP サーバーとして起動
+    Serve {
+        /// ポート番号
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+
+        /// 待ち受けるアドレス
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// HTTP の代わりに TCP/telnet でアニメーションを配信
+        #[arg(long, requires = "script_file")]
+        tcp: bool,
+
+        /// 配信するスクリプト (--tcp 時)
+        #[arg(short = 'f', long)]
+        script_file: Option<PathBuf>,
+
+        /// インターバル (単位が無ければ ms、--tcp 時) [既定値: 1000]
+        #[arg(short, long, value_parser = i18n::parser(duration::parse_interval))]
+        interval: Option<u64>,
+    },
+    /// RSS/Atom フィードの見出しを順に表示する
+    Feed {
+        /// フィードの URL
+        #[arg(value_hint = clap::ValueHint::Url)]
+        url: String,
+
+        /// 表示する見出しの数
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+
+        /// インターバル (単位が無ければ ms) [既定値: 3000]
+        #[arg(short, long, value_parser = i18n::parser(duration::parse_interval))]
+        interval: Option<u64>,
+    },
+    /// 今日の天気を wttr.in から取得して知らせる
+    Weather {
+        /// 地名 (省略時は接続元から推定)
+        location: Option<String>,
+
+        /// wttr.in 互換の API の URL
+        #[arg(long, default_value = "https://wttr.in", value_hint = clap::ValueHint::Url)]
+        api: String,
+    },
+    /// CPU・メモリ・バッテリーの使用率をキャプションに出し続け、負荷に合わせて機嫌を変える
+    /// (--no-anim なら一度だけ)
+    Status {
+        /// 更新の間隔 (単位が無ければ ms) [既定値: 2s]
+        #[arg(short, long, value_parser = i18n::parser(duration::parse_interval))]
+        interval: Option<u64>,
+
+        /// CPU の使用率がこれ (%) 未満なら眠そうにする
+        #[arg(long, value_name = "PERCENT", default_value_t = 10.0)]
+        sleepy: f64,
+
+        /// CPU の使用率がこれ (%) 以上ならあわてる
+        #[arg(long, value_name = "PERCENT", default_value_t = 90.0)]
+        panic: f64,
+    },
+    /// 日付で選んだ今日の名言 (同じ日は同じ名言)
+    Qotd {
+        /// 名言ファイル (省略時は設定ディレクトリの quotes.toml があれば使う)
+        #[arg(short = 'f', long, value_hint = clap::ValueHint::FilePath)]
+        quotes_file: Option<PathBuf>,
+
+        /// 組み込みの名言を使わない
+        #[arg(long)]
+        no_builtin: bool,
+    },
+    /// シェルのプロンプト向けの、色付きの小さなドラゴンと時間ごとに替わるおかず
+    PromptSegment {
+        /// おかずの候補 (省略時は組み込みの候補)
+        side_dishes: Vec<String>,
+
+        /// おかずを替える間隔 (例: 10m, 1h)
+        #[arg(long, default_value = "1h", value_parser = i18n::parser(duration::parse))]
+        rotate: Duration,
+
+        /// 色のシーケンスを囲む書き方
+        #[arg(long, value_enum, default_value_t = prompt::Shell::Plain)]
+        shell: prompt::Shell,
+    },
+    /// 新しいシェルであいさつするために rc ファイルへ足す断片を出力する
+    ShellInit {
+        shell: greeting::Shell,
+
+        /// あいさつする間隔 (例: 8h)
+        #[arg(long, default_value = "8h", value_parser = i18n::parser(duration::parse))]
+        every: Duration,
+    },
+    /// 時間帯に合わせたあいさつ (前のあいさつから --every たっていなければ何もしない)
+    Greet {
+        /// あいさつする間隔 (例: 8h)
+        #[arg(long, default_value = "0s", value_parser = i18n::parser(duration::parse))]
+        every: Duration,
+    },
+    /// ログインバナー向けの固定幅出力
+    Motd {
+        /// おかず
+        side_dish: String,
+        /// キャプション
+        caption: Option<String>,
+
+        /// 出力幅
+        #[arg(short, long, default_value_t = 80)]
+        width: usize,
+    },
+    /// 決めた時刻 (毎日) におかずを出すかスクリプトを再生して知らせる
+    Remind {
+        /// 知らせるおかず (--at の時刻に使う)
+        message: Option<String>,
+
+        /// 知らせる時刻 (HH:MM)。何度でも指定でき、設定ファイルの [[reminders]] に足す
+        #[arg(long, value_name = "HH:MM", value_parser = i18n::parser(remind::parse_at))]
+        at: Vec<remind::At>,
+
+        /// キャプション (--at の時刻に使う)
+        #[arg(short, long)]
+        caption: Option<String>,
+
+        /// おかずの代わりに再生するスクリプト (--at の時刻に使う)
+        #[arg(short = 'f', long, value_hint = clap::ValueHint::FilePath)]
+        script_file: Option<PathBuf>,
+
+        /// デスクトップ通知も出す (--at の時刻に使う)
+        #[arg(long)]
+        notify: bool,
+
+        /// それぞれの時刻に一度ずつ知らせたら終わる
+        #[arg(long)]
+        once: bool,
+
+        /// 端末から切り離してバックグラウンドで待つ
+        #[arg(long)]
+        background: bool,
+    },
+    /// ポモドーロタイマー
+    Pomodoro {
+        /// 作業時間 (例: 25m)
+        #[arg(long, default_value = "25m", value_parser = i18n::parser(duration::parse))]
+        work: Duration,
+
+        /// 休憩時間 (例: 5m)
+        #[arg(long = "break", default_value = "5m", value_parser = i18n::parser(duration::parse))]
+        rest: Duration,
+
+        /// 繰り返す回数 (省略時は無限)
+        #[arg(long)]
+        cycles: Option<u32>,
+    },
+    /// カウントダウン
+    Countdown {
+        /// 時間 (例: 10s, 3m)
+        #[arg(value_parser = i18n::parser(duration::parse))]
+        length: Duration,
+        /// 0 になったときに表示するおかず
+        #[arg(default_value = "")]
+        message: String,
+
+        /// キャプション
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// 時計
+    Clock {
+        /// 時刻の書式 (%H, %M, %S など)
+        #[arg(short, long, default_value = "%H:%M:%S")]
+        format: String,
+
+        /// 2 行目に表示する日付の書式 (例: %Y-%m-%d(%a))
+        #[arg(short, long)]
+        date_format: Option<String>,
+
+        /// キャプション
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// シェル補完スクリプトを出力
+    Completions {
+        /// シェルの種類
+        shell: completions::Shell,
+    },
+    /// man ページを出力
+    Mangen,
+    /// フレームを画像として書き出す
+    Export {
+        #[command(subcommand)]
+        format: ExportFormat,
+    },
+    /// 設定ファイルの操作
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// テーマの操作
+    Theme {
+        #[command(subcommand)]
+        action: ThemeAction,
+    },
+    /// アートパックの操作
+    Art {
+        #[command(subcommand)]
+        action: ArtAction,
+    },
+    /// git のフックとしてコミットメッセージを表示する
+    GitHook {
+        #[command(subcommand)]
+        action: GitHookAction,
+    },
+    /// ソケットで待ち受けて受信したおかずを表示し続ける
+    Daemon {
+        /// ソケットのパス
+        #[arg(short, long)]
+        socket: Option<PathBuf>,
+
+        /// 標準入力の各行もおかずとして表示する
+        #[arg(long)]
+        stdin: bool,
+
+        /// 変更されるたびに内容をおかずとして表示するファイル
+        #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+        watch: Option<PathBuf>,
+
+        /// 表示してからこの時間が過ぎたら空の吹き出しに戻す (例: 30s)
+        #[arg(long, value_parser = i18n::parser(duration::parse))]
+        clear_after: Option<Duration>,
+    },
+    /// 起動中の daemon におかずを送る
+    Send {
+        /// おかず
+        side_dish: String,
+        /// キャプション
+        caption: Option<String>,
+
+        /// ソケットのパス
+        #[arg(short, long)]
+        socket: Option<PathBuf>,
+    },
+    /// プレイリストのスクリプトをくり返し再生し続ける (常設の表示端末向け)
+    ///
+    /// プレイリストは変わるたびに読み直し、再生が失敗しても次のスクリプトに進む。
+    /// 終了のキーのほかのキー操作と Ctrl-C は受け付けない
+    Kiosk {
+        /// プレイリスト (TOML の scripts = ["a.toml", ...])
+        #[arg(short = 'f', long, value_hint = clap::ValueHint::FilePath)]
+        playlist: PathBuf,
+
+        /// 終了のキー (プレイリストの quit_key より優先。省略時は q)
+        #[arg(long)]
+        quit_key: Option<char>,
+    },
+    /// GitHub の最新のリリースに入れ替える (チェックサムを確かめてから)
+    SelfUpdate {
+        /// 新しい版があるかだけを確かめる
+        #[arg(long)]
+        check: bool,
+
+        /// 同じ版でも入れ直す
+        #[arg(long)]
+        force: bool,
+    },
+    /// 描画の速さを端末の幅と文言ごとに測る (1 秒あたりのフレーム数)
+    #[command(hide = true)]
+    BenchRender {
+        /// 測る端末の幅 (カンマ区切り。省略時は 40,80,160)
+        #[arg(long = "width", value_delimiter = ',')]
+        widths: Vec<usize>,
+
+        /// 組ごとに描く回数
+        #[arg(short = 'n', long, default_value_t = 1000)]
+        iterations: usize,
     },
 }
 
-#[derive(Deserialize, Debug)]
-struct Script {
-    side_dishes: Vec<String>,
-    pre_captions: Vec<String>,
-    after_captions: Vec<String>,
+#[derive(Subcommand, Clone, Debug)]
+enum ConfigAction {
+    /// 設定ファイルのパスを表示
+    Path,
+    /// 設定ファイルの雛形を作成
+    Init {
+        /// 既存のファイルを上書きする
+        #[arg(long)]
+        force: bool,
+    },
 }
 
-impl Script {
-    fn load(path: &Path) -> Result<Self> {
-        let script = std::fs::read_to_string(path)?;
-        Ok(toml::from_str(&script)?)
-    }
+#[derive(Subcommand, Clone, Debug)]
+enum FavAction {
+    /// お気に入りに足す
+    Add {
+        /// おかず (省略時は履歴の最新のもの)
+        side_dish: Option<String>,
+        /// キャプション
+        caption: Option<String>,
+    },
+    /// お気に入りを番号付きで表示する (番号は again --fav で使う)
+    List,
+    /// お気に入りから取り除く
+    Remove {
+        /// fav list の番号
+        number: usize,
+    },
 }
 
-fn main() -> Result<()> {
-    match Args::parse().sub {
-        Command::Say { side_dish, caption } => say(&side_dish, caption.as_deref())?,
-        Command::Anime {
-            side_dishes,
-            pre_captions,
-            after_captions,
-            script_file,
-            interval,
-        } => anime(
-            side_dishes,
-            pre_captions,
-            after_captions,
-            script_file,
-            interval,
-        )?,
-    }
+#[derive(Subcommand, Clone, Debug)]
+enum ThemeAction {
+    /// 使えるテーマの一覧
+    List,
+}
 
-    Ok(())
+#[derive(Subcommand, Clone, Debug)]
+enum ArtAction {
+    /// 使えるキャラクターの一覧
+    List,
+    /// アートパックをインストール
+    Install {
+        /// git の URL、tar.gz (URL かファイル)、ディレクトリ
+        source: String,
+    },
+    /// アートパックを削除
+    Remove {
+        /// パックの名前
+        name: String,
+    },
+    /// インストール元からアートパックを入れ直す
+    Update {
+        /// パックの名前 (省略時はすべて)
+        name: Option<String>,
+    },
 }
 
-fn say(side_dish: &str, caption: Option<&str>) -> Result<()> {
-    let term = Term::stdout();
-    let terminal_width = term.size().1 as usize;
+#[derive(Subcommand, Clone, Debug)]
+enum GitHookAction {
+    /// コミットメッセージのファイルを吹き出しに表示 (commit-msg フックから呼ぶ)
+    CommitMsg {
+        /// コミットメッセージのファイル
+        #[arg(value_hint = clap::ValueHint::FilePath)]
+        file: PathBuf,
 
-    let dragon = create_dragon(side_dish, terminal_width);
-    for line in dragon {
-        term.write_line(&line)?;
-    }
-    let caption = console::pad_str(caption.unwrap_or(""), 60, Alignment::Center, None);
-    term.write_line(&caption)?;
+        /// y/n を押すまで待ち、n ならコミットを中止する
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// このリポジトリに commit-msg フックをインストール
+    Install {
+        /// コミットの前に y/n で確認する
+        #[arg(long)]
+        confirm: bool,
 
-    Ok(())
+        /// 既存のフックを上書きする
+        #[arg(long)]
+        force: bool,
+    },
 }
 
-fn anime(
-    side_dishes: Vec<String>,
-    pre_captions: Vec<String>,
-    after_captions: Vec<String>,
-    script_file: Option<PathBuf>,
-    interval: u64,
-) -> Result<()> {
-    let (side_dishes, pre_captions, after_captions) = match script_file {
-        Some(path) => {
-            let Script {
-                side_dishes,
-                pre_captions,
-                after_captions,
-            } = Script::load(&path)?;
-            (side_dishes, pre_captions, after_captions)
-        }
-        None => (side_dishes, pre_captions, after_captions),
-    };
+#[derive(Subcommand, Clone, Debug)]
+enum ExportFormat {
+    /// PNG 画像 (フォントは --font で指定)
+    Png {
+        /// おかず
+        side_dish: String,
+        /// キャプション
+        caption: Option<String>,
 
-    let term = Term::stdout();
-    let terminal_width = term.size().1 as usize;
-    term.clear_screen()?;
-    let empty_dragon = create_dragon("", terminal_width);
-    let mut printed_flag = false;
+        /// 出力先
+        #[arg(short, long, default_value = "dragon.png", value_hint = clap::ValueHint::FilePath)]
+        output: PathBuf,
 
-    let mut pre_captions_iter = pre_captions.into_iter().peekable();
-    while let Some(pre_caption) = pre_captions_iter.next() {
-        for line in empty_dragon.iter() {
-            term.write_line(line)?;
-        }
-        let pre_caption = console::pad_str(&pre_caption, 60, Alignment::Center, None);
-        term.write_line(&pre_caption)?;
-        printed_flag = true;
+        /// 文字色 (#rrggbb)
+        #[arg(long, default_value = "#e5e5e5", value_parser = i18n::parser(raster::parse_color))]
+        foreground: raster::Rgb,
 
-        if pre_captions_iter.peek().is_some() {
-            clear_dragon(interval, &term, &mut printed_flag)?;
-        }
-    }
+        /// 背景色 (#rrggbb)
+        #[arg(long, default_value = "#000000", value_parser = i18n::parser(raster::parse_color))]
+        background: raster::Rgb,
 
-    let mut side_dish_iter = side_dishes.iter().peekable();
+        /// 余白 (px)
+        #[arg(long, default_value_t = graphics::PADDING)]
+        padding: usize,
 
-    if printed_flag && side_dish_iter.peek().is_some() {
-        clear_dragon(interval, &term, &mut printed_flag)?;
-    }
+        /// 色数。16 と 256 はパレット形式になり、
+        /// 504x272 の例ではフルカラー約 14 KB、256 色で約 10 KB、16 色で約 8 KB になる (グラデーションは近い色に丸まる)
+        #[arg(long, value_enum, default_value_t)]
+        palette: quantize::Depth,
+
+        /// --palette で色を減らすときの中間の色の表し方
+        #[arg(long, value_enum, default_value_t)]
+        dither: quantize::Dither,
+    },
+}
+
+const DEFAULT_INTERVAL: u64 = 1000;
+/// 見出しは読む時間が要るので長めにする
+const FEED_INTERVAL: u64 = 3000;
+const DEFAULT_BELL_SEQUENCE: &str = "\x07";
+/// `--markdown` の吹き出しの 1 行の最大の文字数
+const MARKDOWN_COLUMNS: usize = 48;
+/// `-f` にこの名前を渡すと標準入力から読む
+const STDIN_PATH: &str = "-";
+/// 再生中の +/- で速度を変える倍率と範囲
+const SPEED_STEP: f64 = 1.5;
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 16.0;
 
-    while let Some(side_dish) = side_dish_iter.next() {
-        let dragon = create_dragon(side_dish, terminal_width);
-        for line in dragon {
-            term.write_line(&line)?;
+#[derive(Clone, Debug)]
+struct RenderOptions {
+    /// 吹き出しの大きさを変えるときに読み込み直すキャラクター
+    character: String,
+    art: Art,
+    art_style: Style,
+    bubble_style: Style,
+    caption_style: Style,
+    caption_width: usize,
+    /// 指定されていればテキストの代わりに画像で出力する
+    graphics: Option<graphics::Graphics>,
+    /// キャラクターを差し替えたときにも使うテーマの枠
+    border: Option<theme::Border>,
+    /// 進み具合のバーなどにも ASCII だけを使う
+    ascii: bool,
+    /// フレーム全体を端末の幅に対して中央に寄せる
+    center: bool,
+    /// アートの代わりにセリフを文で出す (`--a11y`)
+    a11y: bool,
+    overflow: Overflow,
+    /// `--layout side` ならおかずを吹き出しではなくアートの右に描く
+    layout: Placement,
+    /// 吹き出しの 1 行に入る文字数
+    bubble_chars: usize,
+    /// 指定されていればキャラクターの吹き出しをこの文字数と行数に描き直す
+    bubble_size: Option<(usize, usize)>,
+    /// 指定されていれば吹き出しのしっぽをこの向きに描き直す
+    tail: Option<art::TailSide>,
+    normalize: normalize::Normalize,
+    /// スクリプトの文言に訳があればこの言語のものを使う
+    lang: i18n::Lang,
+    /// `{date}` などを置き換える (外から受け取った文言では無効にする)
+    placeholders: bool,
+    /// 外から受け取った文言のエスケープシーケンスをそのまま使う
+    allow_ansi: bool,
+    /// キャプションの `[名前](URL)` を OSC 8 のリンクにする (false なら `名前 (URL)` と出す)
+    hyperlinks: bool,
+    /// `--seed` (無ければ実行ごとにランダム)。ルーレットなどの乱数はここから作る
+    seed: u64,
+    /// 名前付きの欄の文言 (`--field` とスクリプトの `fields`)
+    fields: BTreeMap<String, String>,
+    /// `render_say` の結果の控え
+    cache: cache::RenderCache,
+}
+
+impl RenderOptions {
+    /// `detect_background` が true なら端末の背景色から既定の色を選ぶ
+    fn resolve(
+        args: &RenderArgs,
+        config: &Config,
+        seed: u64,
+        detect_background: bool,
+    ) -> Result<Self> {
+        let seasonal = match args.seasonal {
+            true => {
+                let today = placeholder::LocalTime::now();
+                match &config.seasonal {
+                    Some(mapping) => art::seasonal(
+                        mapping.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                        today.month,
+                        today.day,
+                    )?,
+                    None => art::seasonal(
+                        art::DEFAULT_SEASONAL.iter().copied(),
+                        today.month,
+                        today.day,
+                    )?,
+                }
+            }
+            false => None,
+        };
+        let character = args
+            .character
+            .as_deref()
+            .or(seasonal)
+            .or(config.character.as_deref())
+            .unwrap_or(art::DEFAULT_CHARACTER);
+        if let Some(seasonal) = seasonal {
+            log::info!("季節のキャラクター {} を選びました", seasonal);
+        }
+        let character = match character {
+            art::RANDOM_CHARACTER => {
+                let (name, _) =
+                    art::BUILTIN[rng::Rng::stream(seed, "character").below(art::BUILTIN.len())];
+                log::info!("ランダムにキャラクター {} を選びました", name);
+                name
+            }
+            character => character,
+        };
+        let ascii = args.ascii || config.ascii.unwrap_or_else(art::legacy_locale);
+        if ascii && !args.ascii && config.ascii.is_none() {
+            log::info!("ロケールが UTF-8 ではないので ASCII のアートを使います");
         }
-        let empty_line = console::pad_str("", 60, Alignment::Center, None);
-        term.write_line(&empty_line)?;
-        printed_flag = true;
+        let bubble_size = match (args.bubble_cols, args.bubble_rows) {
+            (None, None) => None,
+            (columns, rows) => Some((
+                columns.map_or(BUBBLE_CHARS, usize::from),
+                rows.map_or(2, usize::from),
+            )),
+        };
+        let character = match ascii && art::BUILTIN.iter().any(|(name, _)| *name == character) {
+            true => art::ASCII_CHARACTER,
+            false => character,
+        };
+        let theme = match args.theme.as_deref().or(config.theme.as_deref()) {
+            Some(name) => theme::Theme::load(name)?,
+            None => theme::Theme::default(),
+        };
+        let border = theme.border.filter(|border| !ascii || border.is_ascii());
+        let style = |colors: &[Option<&str>]| {
+            colors
+                .iter()
+                .find_map(|color| *color)
+                .map(Style::from_dotted_str)
+                .unwrap_or_default()
+        };
 
-        if side_dish_iter.peek().is_some() {
-            clear_dragon(interval, &term, &mut printed_flag)?;
+        let art_color = args
+            .color
+            .as_deref()
+            .or(config.colors.art.as_deref())
+            .or(theme.colors.art.as_deref());
+        let caption_color = args
+            .caption_color
+            .as_deref()
+            .or(config.colors.caption.as_deref())
+            .or(theme.colors.caption.as_deref());
+        let background = match (args.force_dark, args.force_light) {
+            (true, _) => Some(background::Background::Dark),
+            (_, true) => Some(background::Background::Light),
+            _ if detect_background && (art_color.is_none() || caption_color.is_none()) => {
+                background::detect()
+            }
+            _ => None,
+        };
+        match background {
+            Some(background) => log::debug!("背景: {:?}", background),
+            None if detect_background => log::debug!("背景の明るさを判定できませんでした"),
+            None => {}
         }
-    }
+        let (default_art_color, default_caption_color) = background
+            .map(|background| background.default_colors())
+            .unzip();
+
+        let graphics = match args.graphics {
+            Some(protocol) => Some(graphics::Graphics {
+                protocol,
+                font: std::sync::Arc::new(font::Font::find(
+                    args.font.as_deref().or(config.font.as_deref()),
+                )?),
+                palette: match background {
+                    Some(background::Background::Light) => raster::Palette::LIGHT,
+                    _ => raster::Palette::default(),
+                },
+            }),
+            None => None,
+        };
 
-    let mut after_captions_iter = after_captions.into_iter().peekable();
+        log::debug!("キャラクター: {}", character);
+        log::debug!(
+            "色の出力: stdout {}, stderr {}",
+            console::colors_enabled(),
+            console::colors_enabled_stderr()
+        );
+        if let Some((rows, columns)) = Term::stdout().size_checked() {
+            log::debug!("端末の大きさ: {} 桁 x {} 行", columns, rows);
+        }
+        let hyperlinks = graphics.is_none() && terminal::hyperlinks();
+        log::debug!("リンク (OSC 8): {}", hyperlinks);
 
-    if printed_flag && after_captions_iter.peek().is_some() {
-        clear_dragon(interval, &term, &mut printed_flag)?;
+        Ok(Self {
+            art: load_art(character, border.as_ref(), bubble_size, args.tail)?,
+            character: character.to_string(),
+            art_style: style(&[art_color, default_art_color]),
+            bubble_style: style(&[theme.colors.bubble.as_deref()]),
+            caption_style: style(&[caption_color, default_caption_color]),
+            caption_width: args
+                .caption_width
+                .or(config.caption_width)
+                .unwrap_or(layout::CAPTION_WIDTH),
+            graphics,
+            border,
+            ascii,
+            center: args.center,
+            a11y: args.a11y || config.a11y.unwrap_or(false),
+            overflow: args.overflow.or(config.overflow).unwrap_or_default(),
+            layout: args.layout.or(config.layout).unwrap_or_default(),
+            bubble_chars: bubble_size.map_or(BUBBLE_CHARS, |(columns, _)| columns),
+            bubble_size,
+            tail: args.tail,
+            normalize: args.normalize.or(config.normalize).unwrap_or_default(),
+            lang: i18n::current(),
+            placeholders: true,
+            allow_ansi: args.allow_ansi,
+            hyperlinks,
+            seed,
+            fields: args.fields.iter().cloned().collect(),
+            cache: cache::RenderCache::default(),
+        })
     }
 
-    while let Some(after_caption) = after_captions_iter.next() {
-        for line in empty_dragon.iter() {
-            term.write_line(line)?;
+    /// 表示する直前の文言。`{date}` などを置き換える
+    fn expand<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.placeholders {
+            true => Cow::Owned(placeholder::expand(text)),
+            false => Cow::Borrowed(text),
         }
-        let after_caption = console::pad_str(&after_caption, 60, Alignment::Center, None);
-        term.write_line(&after_caption)?;
+    }
 
-        if after_captions_iter.peek().is_some() {
-            clear_dragon(interval, &term, &mut printed_flag)?;
+    /// 標準入力や URL、ソケットから受け取った文言の制御文字を取り除く
+    fn sanitize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.allow_ansi {
+            true => Cow::Borrowed(text),
+            false => sanitize::strip(text),
         }
     }
 
-    Ok(())
-}
+    /// スクリプトの `fields` を足す (`--field` で指定した欄はそのまま)
+    fn with_fields(&self, fields: &BTreeMap<String, Text>) -> Cow<'_, Self> {
+        if fields.keys().all(|name| self.fields.contains_key(name)) {
+            return Cow::Borrowed(self);
+        }
+        let mut opts = self.clone();
+        for (name, text) in fields {
+            opts.fields
+                .entry(name.clone())
+                .or_insert_with(|| text.get(self.lang).to_string());
+        }
+        Cow::Owned(opts)
+    }
 
-fn clear_dragon(interval: u64, term: &Term, printed_flag: &mut bool) -> Result<()> {
-    sleep(Duration::from_millis(interval));
-    term.clear_screen()?;
-    *printed_flag = false;
+    /// 色やテーマはそのままでキャラクターだけ差し替える
+    fn with_character(&self, character: &str) -> Result<Self> {
+        Ok(Self {
+            art: load_art(character, self.border.as_ref(), self.bubble_size, self.tail)?,
+            character: character.to_string(),
+            ..self.clone()
+        })
+    }
 
-    Ok(())
+    /// テーマの枠と色に差し替える (テーマに無い色はそのまま)
+    fn with_theme(&self, name: &str) -> Result<Self> {
+        let theme = theme::Theme::load(name)?;
+        let border = theme
+            .border
+            .filter(|border| !self.ascii || border.is_ascii());
+        let style = |color: &Option<String>, current: &Style| {
+            color
+                .as_deref()
+                .map_or_else(|| current.clone(), Style::from_dotted_str)
+        };
+        Ok(Self {
+            art: load_art(
+                &self.character,
+                border.as_ref(),
+                self.bubble_size,
+                self.tail,
+            )?,
+            art_style: style(&theme.colors.art, &self.art_style),
+            bubble_style: style(&theme.colors.bubble, &self.bubble_style),
+            caption_style: style(&theme.colors.caption, &self.caption_style),
+            border,
+            ..self.clone()
+        })
+    }
+
+    /// 吹き出しを 1 行 `columns` 文字、`rows` 行に描き直す
+    fn with_bubble_size(&self, columns: usize, rows: usize) -> Result<Self> {
+        Ok(Self {
+            art: load_art(
+                &self.character,
+                self.border.as_ref(),
+                Some((columns, rows)),
+                self.tail,
+            )?,
+            bubble_chars: columns,
+            bubble_size: Some((columns, rows)),
+            ..self.clone()
+        })
+    }
+}
+
+fn load_art(
+    character: &str,
+    border: Option<&theme::Border>,
+    bubble_size: Option<(usize, usize)>,
+    tail: Option<art::TailSide>,
+) -> Result<Art> {
+    let mut art = art::load(character)?;
+    if let Some((columns, rows)) = bubble_size {
+        art = art::resize_bubble(&art, columns, rows, BUBBLE_CHARS + art::BUBBLE_PADDING)
+            .with_context(|| format!("キャラクター '{}'", character))?;
+    }
+    if let (Some(bubble), Some(border)) = (art.bubble, border) {
+        art.template = theme::apply_border(&art.template, &bubble, border);
+        for variant in art.idle.iter_mut().chain(&mut art.mouth) {
+            *variant = theme::apply_border(variant, &bubble, border);
+        }
+    }
+    if let Some(tail) = tail {
+        let columns = bubble_size.map_or(BUBBLE_CHARS, |(columns, _)| columns);
+        art = art::point_tail(&art, tail, columns + art::BUBBLE_PADDING)
+            .with_context(|| format!("キャラクター '{}'", character))?;
+    }
+    // 色の指定は吹き出しの外にあるので、大きさと枠を変えた後で置き換える
+    let colors = console::colors_enabled();
+    art.template = markup::art(&art.template, colors);
+    for variant in art.idle.iter_mut().chain(&mut art.mouth) {
+        *variant = markup::art(variant, colors);
+    }
+    Ok(art)
+}
+
+#[derive(Clone, Debug)]
+struct PlayOptions {
+    interval: u64,
+    /// キャプションだけのフレームのインターバル (無ければ interval)
+    caption_interval: Option<u64>,
+    /// false なら画面を消さず待たずに全フレームを順に出力する
+    animate: bool,
+    /// フレームごとにデスクトップ通知を送る
+    notify: bool,
+    /// フレームごとにテキストを読み上げる
+    speak: bool,
+    bell: Bell,
+    /// ベルの代わりに出力するシーケンス (OSC など)
+    bell_sequence: String,
+    /// インターバルを割る再生速度の倍率
+    speed: f64,
+    order: Order,
+    easing: Easing,
+    jitter: Option<Jitter>,
+    /// おかずを 1 文字ずつ出すときの 1 文字の時間
+    typewriter: Option<u64>,
+    /// キャプションを伏せておく時間
+    reveal_after: Option<Duration>,
+    /// フレームからフレームへの切り替え方 (無ければすぐに切り替える)
+    transition: Option<transition::Kind>,
+    /// 最初のフレームの前に流す効果
+    intro: Option<intro::Intro>,
+    /// 画面を消さず、カーソルの位置から下だけを描き直す
+    inline: bool,
+    /// 再生する範囲の最初と最後のフレーム (ラベルか 1 から数えた番号)
+    from: Option<String>,
+    to: Option<String>,
+    /// 最後に出したものを残して待つ時間
+    hold_last: Option<Hold>,
+    /// 最後のフレームの後に出す文言
+    end_card: Option<String>,
+    /// 切り替えなどの効果の乱数のシード
+    seed: u64,
+    /// 端末からのキー操作 (一時停止、コマ送り、速度変更、終了) を受け付ける
+    interactive: bool,
+    /// キャプションの下に進み具合のバーを出す
+    progress: bool,
+    /// 再生の後にフレーム数や描画時間を stderr に出す
+    stats: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Easing {
+    /// 一定の間隔
+    Linear,
+    /// だんだん速くする (2 倍から 0.5 倍へ)
+    Accelerate,
+    /// だんだん遅くする (0.5 倍から 2 倍へ)
+    Decelerate,
+}
+
+impl Easing {
+    /// 再生の進み具合 `progress` (0.0-1.0) でのインターバルの倍率
+    fn factor(self, progress: f64) -> f64 {
+        match self {
+            Easing::Linear => 1.0,
+            Easing::Accelerate => 2.0 - 1.5 * progress,
+            Easing::Decelerate => 0.5 + 1.5 * progress,
+        }
+    }
+}
+
+/// `--hold-last` の待ち方
+#[derive(Clone, Copy, Debug)]
+enum Hold {
+    For(Duration),
+    /// キーを押すまで
+    Forever,
+}
+
+/// インターバルのばらつき
+#[derive(Clone, Copy, Debug)]
+struct Jitter {
+    /// 上下にずらす割合 (0.2 なら 0.8 倍から 1.2 倍)
+    ratio: f64,
+    seed: u64,
+}
+
+impl Jitter {
+    /// `step` 回目に待つときの倍率。同じシードと回数なら同じ値になる
+    fn factor(self, step: usize) -> f64 {
+        let mut rng = rng::Rng::new(self.seed ^ (step as u64).wrapping_mul(0x2545_f491_4f6c_dd1d));
+        let unit = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        1.0 + self.ratio * (unit * 2.0 - 1.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Order {
+    Forward,
+    Reverse,
+    /// 最後まで進んでから最初へ戻る
+    PingPong,
 }
 
-fn create_dragon(side_dish: &str, terminal_width: usize) -> Vec<String> {
-    let lines: Vec<String> = match side_dish.lines().count() {
-        0 => vec!["".to_string(), "".to_string()],
-        1 => {
-            let empty = Vec::new();
-            let side_dish: Vec<char> = side_dish.chars().collect();
-            match side_dish.len() {
-                0..=16 => vec![side_dish.as_slice(), empty.as_slice()],
-                17..=32 => vec![&side_dish[..16], &side_dish[16..]],
-                _ => vec![&side_dish[..16], &side_dish[16..32]],
+impl Order {
+    fn arrange(self, mut frames: Vec<Frame>) -> Vec<Frame> {
+        match self {
+            Order::Forward => {}
+            Order::Reverse => frames.reverse(),
+            Order::PingPong => {
+                let back: Vec<Frame> = frames.iter().rev().skip(1).cloned().collect();
+                frames.extend(back);
             }
-            .into_iter()
-            .map(|s| s.iter().collect::<String>())
+        }
+        frames
+    }
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            caption_interval: None,
+            animate: true,
+            notify: false,
+            speak: false,
+            bell: Bell::None,
+            bell_sequence: DEFAULT_BELL_SEQUENCE.to_string(),
+            speed: 1.0,
+            order: Order::Forward,
+            easing: Easing::Linear,
+            jitter: None,
+            typewriter: None,
+            reveal_after: None,
+            transition: None,
+            intro: None,
+            inline: false,
+            from: None,
+            to: None,
+            hold_last: None,
+            end_card: None,
+            seed: 0,
+            interactive: false,
+            progress: false,
+            stats: false,
+        }
+    }
+}
+
+impl PlayOptions {
+    /// 再生速度とイージングを反映した、`frame` の次のフレームまでの時間
+    /// (`step` は何回目に待つか、`progress` は再生の進み具合)
+    fn frame_duration(&self, frame: &Frame, step: usize, progress: f64) -> Duration {
+        let interval = match (frame.interval, self.caption_interval) {
+            (Some(interval), _) => interval,
+            (None, Some(caption_interval))
+                if frame.side_dish.is_empty() && !frame.caption.is_empty() =>
+            {
+                caption_interval
+            }
+            _ => self.interval,
+        };
+        let millis = match frame.duration {
+            Some(duration) => duration as f64,
+            None => {
+                let jitter = self.jitter.map_or(1.0, |jitter| jitter.factor(step));
+                interval as f64 * self.easing.factor(progress) * jitter
+            }
+        };
+        Duration::from_secs_f64(millis / 1000.0 / self.speed)
+    }
+}
+
+/// `20%` か `0.2`
+fn parse_jitter(s: &str) -> Result<f64> {
+    let ratio = match s.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>()? / 100.0,
+        None => s.parse()?,
+    };
+    if !(0.0..1.0).contains(&ratio) {
+        anyhow::bail!("ばらつきは 0% 以上 100% 未満にしてください");
+    }
+    Ok(ratio)
+}
+
+/// `forever` かインターバルと同じ表記の時間
+fn parse_hold(s: &str) -> Result<Hold> {
+    match s {
+        "forever" => Ok(Hold::Forever),
+        s => Ok(Hold::For(Duration::from_millis(duration::parse_millis(s)?))),
+    }
+}
+
+fn parse_field(s: &str) -> Result<(String, String)> {
+    match s.split_once('=') {
+        Some((name, text)) if !name.is_empty() => Ok((name.to_string(), text.to_string())),
+        _ => anyhow::bail!("欄は NAME=TEXT の形で指定してください"),
+    }
+}
+
+fn parse_speed(s: &str) -> Result<f64> {
+    let speed: f64 = s.parse()?;
+    if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        anyhow::bail!("速度は {} から {} にしてください", MIN_SPEED, MAX_SPEED);
+    }
+    Ok(speed)
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Bell {
+    /// 最後のフレームの後に鳴らす
+    End,
+    /// フレームごとに鳴らす
+    Frame,
+    /// 鳴らさない
+    None,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Script {
+    #[serde(default)]
+    side_dishes: Vec<Text>,
+    #[serde(default)]
+    pre_captions: Vec<Text>,
+    #[serde(default)]
+    after_captions: Vec<Text>,
+    /// コマンドラインの指定が無ければこのインターバルで再生する (ms か "1.5s" のような文字列)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "duration::deserialize_millis"
+    )]
+    interval: Option<u64>,
+    /// キャプションだけのフレームのインターバル (省略時は interval)
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "duration::deserialize_millis"
+    )]
+    caption_interval: Option<u64>,
+    /// コマンドラインの指定が無ければこの切り替え方を使う
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transition: Option<transition::Kind>,
+    /// すべてのフレームで名前付きの欄に入れる文言 (フレームの `fields` が優先される)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, Text>,
+    /// フレームの `character` で呼ぶ配役の名前と、そのキャラクターとテーマ
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    characters: BTreeMap<String, Role>,
+    /// アフターキャプションの後に続く、ラベルや選択肢を持てるフレーム
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    frames: Vec<Frame>,
+}
+
+/// `anime -f` で読むスクリプトの形式
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ScriptFormat {
+    /// TOML (man ページのスクリプトの書き方)
+    Toml,
+    /// JSON (キーは TOML と同じ)
+    Json,
+    /// SubRip 字幕
+    Srt,
+    /// 時刻付きの歌詞
+    Lrc,
+}
+
+impl ScriptFormat {
+    /// 拡張子で決める。拡張子が無い (標準入力など) か知らないものなら中身から推測する
+    fn detect(path: &Path, source: &str) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("toml") => Self::Toml,
+            Some("json") => Self::Json,
+            Some("srt") => Self::Srt,
+            Some("lrc") => Self::Lrc,
+            _ => Self::sniff(source),
+        }
+    }
+
+    /// `{` で始まれば JSON、`[00:12.34]` や `[ti:...]` なら歌詞、番号の次の行に `-->` があれば字幕
+    fn sniff(source: &str) -> Self {
+        let mut lines = source
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        let first = lines.next().unwrap_or_default();
+        let tag = first
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .map(|(tag, _)| tag);
+        if first.starts_with('{') {
+            Self::Json
+        } else if tag.is_some_and(|tag| tag.contains(':') && !tag.contains(['"', '='])) {
+            Self::Lrc
+        } else if first.chars().all(|c| c.is_ascii_digit())
+            && lines.next().is_some_and(|line| line.contains("-->"))
+        {
+            Self::Srt
+        } else {
+            Self::Toml
+        }
+    }
+}
+
+impl Script {
+    fn load(path: &Path) -> Result<Self> {
+        Self::read(path, None)
+    }
+
+    /// `-` なら標準入力から読む。`format` が無ければ拡張子か中身で形式を決める
+    fn read(path: &Path, format: Option<ScriptFormat>) -> Result<Self> {
+        let source = match path == Path::new(STDIN_PATH) {
+            true => std::io::read_to_string(std::io::stdin())
+                .context("標準入力からスクリプトを読み込めません")?,
+            false => std::fs::read_to_string(path)?,
+        };
+        let format = format.unwrap_or_else(|| ScriptFormat::detect(path, &source));
+        log::debug!("{}: {:?} として読み込みます", path.display(), format);
+        let mut script: Self = match format {
+            ScriptFormat::Toml => toml::from_str(&source)?,
+            ScriptFormat::Json => json::from_str(&source)?,
+            ScriptFormat::Srt => srt::script(&source)?,
+            ScriptFormat::Lrc => lrc::script(&source)?,
+        };
+        if script
+            .interval
+            .is_some_and(|interval| interval < duration::MIN_INTERVAL)
+        {
+            anyhow::bail!("{}: interval は 10 以上にしてください", path.display());
+        }
+        if script
+            .caption_interval
+            .is_some_and(|interval| interval < duration::MIN_INTERVAL)
+        {
+            anyhow::bail!(
+                "{}: caption_interval は 10 以上にしてください",
+                path.display()
+            );
+        }
+        script
+            .validate()
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        if let Some(dir) = path.parent().filter(|_| path != Path::new(STDIN_PATH)) {
+            for sound in script
+                .frames
+                .iter_mut()
+                .filter_map(|frame| frame.sound.as_mut())
+            {
+                *sound = dir.join(&*sound);
+            }
+        }
+        log::info!(
+            "{}: おかず {} 個, フレーム {} 個",
+            path.display(),
+            script.side_dishes.len(),
+            script.frames.len()
+        );
+        Ok(script)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 飛び先のラベルがすべて存在するか確かめる
+    fn validate(&self) -> Result<()> {
+        let gotos = self.frames.iter().flat_map(|frame| {
+            frame
+                .goto
+                .iter()
+                .chain(frame.choices.iter().map(|choice| &choice.goto))
+        });
+        for goto in gotos {
+            if !self
+                .frames
+                .iter()
+                .any(|frame| frame.label.as_ref() == Some(goto))
+            {
+                anyhow::bail!("ラベル '{}' のフレームがありません", goto);
+            }
+        }
+        Ok(())
+    }
+
+    /// `scripts` のフレームを順につなげる。コマンドラインの `interval` と `caption_interval` が
+    /// 無ければ、それぞれのスクリプトの指定をそのフレームに使う
+    fn concat(scripts: Vec<Script>, interval: Option<u64>, caption_interval: Option<u64>) -> Self {
+        let mut concatenated = Script::default();
+        for script in scripts {
+            for mut frame in script.timeline() {
+                let caption_only = frame.side_dish.is_empty() && !frame.caption.is_empty();
+                frame.interval = match caption_only {
+                    true => caption_interval
+                        .or(script.caption_interval)
+                        .or(interval)
+                        .or(script.interval),
+                    false => interval.or(script.interval),
+                };
+                concatenated.frames.push(frame);
+            }
+            concatenated.transition = concatenated.transition.or(script.transition);
+            for (name, role) in script.characters {
+                concatenated.characters.entry(name).or_insert(role);
+            }
+        }
+        concatenated
+    }
+
+    /// おかずの順番を入れ替える (キャプションとラベルや選択肢のあるフレームはそのまま)
+    ///
+    /// `-f` を複数つなげたスクリプトはおかずも `frames` に入っているので、`frames` の中の
+    /// おかずだけのフレームも入れ替える
+    fn shuffle(&mut self, rng: &mut rng::Rng) {
+        rng.shuffle(&mut self.side_dishes);
+        let dishes: Vec<usize> = (0..self.frames.len())
+            .filter(|&i| {
+                let frame = &self.frames[i];
+                !frame.side_dish.is_empty()
+                    && frame.caption.is_empty()
+                    && frame.label.is_none()
+                    && frame.goto.is_none()
+                    && frame.choices.is_empty()
+            })
+            .collect();
+        let mut order = dishes.clone();
+        rng.shuffle(&mut order);
+        let frames = self.frames.clone();
+        for (&to, &from) in dishes.iter().zip(&order) {
+            self.frames[to] = frames[from].clone();
+        }
+    }
+
+    /// 外から受け取ったスクリプトのすべての文言に `sanitize` をかける
+    fn sanitize(&mut self, opts: &RenderOptions) {
+        let texts = self
+            .side_dishes
+            .iter_mut()
+            .chain(&mut self.pre_captions)
+            .chain(&mut self.after_captions)
+            .chain(self.fields.values_mut())
+            .chain(self.frames.iter_mut().flat_map(|frame| {
+                [&mut frame.side_dish, &mut frame.caption]
+                    .into_iter()
+                    .chain(frame.choices.iter_mut().map(|choice| &mut choice.label))
+                    .chain(frame.fields.values_mut())
+            }));
+        for text in texts {
+            match text {
+                Text::Plain(text) => *text = opts.sanitize(text).into_owned(),
+                Text::Translated(texts) => {
+                    for text in texts.values_mut() {
+                        *text = opts.sanitize(text).into_owned();
+                    }
+                }
+            }
+        }
+    }
+
+    /// プレキャプション、おかず、アフターキャプション、frames の順に並べる
+    fn timeline(&self) -> Vec<Frame> {
+        let caption = |caption: &Text| Frame {
+            caption: caption.clone(),
+            ..Frame::default()
+        };
+        self.pre_captions
+            .iter()
+            .map(caption)
+            .chain(self.side_dishes.iter().map(|side_dish| Frame {
+                side_dish: side_dish.clone(),
+                ..Frame::default()
+            }))
+            .chain(self.after_captions.iter().map(caption))
+            .chain(self.frames.iter().cloned())
+            .map(|mut frame| {
+                for (name, text) in &self.fields {
+                    frame
+                        .fields
+                        .entry(name.clone())
+                        .or_insert_with(|| text.clone());
+                }
+                frame
+            })
             .collect()
+    }
+}
+
+/// スクリプトの文言。`{ ja = "からあげ", en = "Fried chicken" }` のように言語ごとにも書ける
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+enum Text {
+    Plain(String),
+    Translated(std::collections::BTreeMap<String, String>),
+}
+
+impl Text {
+    /// どの言語でも空か
+    fn is_empty(&self) -> bool {
+        match self {
+            Text::Plain(text) => text.is_empty(),
+            Text::Translated(texts) => texts.values().all(String::is_empty),
+        }
+    }
+
+    /// `lang` の訳が無ければ日本語、それも無ければ最初に書かれた訳を使う
+    fn get(&self, lang: i18n::Lang) -> &str {
+        match self {
+            Text::Plain(text) => text,
+            Text::Translated(texts) => texts
+                .get(lang.code())
+                .or_else(|| texts.get(i18n::Lang::Ja.code()))
+                .or_else(|| texts.values().next())
+                .map_or("", String::as_str),
+        }
+    }
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text::Plain(String::new())
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Text::Plain(text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+struct Frame {
+    side_dish: Text,
+    caption: Text,
+    /// goto や選択肢で飛ぶときの名前
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    /// 次のフレームの代わりにこのラベルへ進む
+    #[serde(skip_serializing_if = "Option::is_none")]
+    goto: Option<String>,
+    /// 空でなければ一時停止して番号のキーで選ばせる
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    choices: Vec<Choice>,
+    /// 次のフレームまでの時間 (ms か "1.5s" のような文字列)。インターバルと --easing の代わりに使う
+    /// (--speed は効く)
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "duration::deserialize_millis"
+    )]
+    duration: Option<u64>,
+    /// このフレームで名前付きの欄に入れる文言
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, Text>,
+    /// このフレームを演じる配役 (スクリプトの `characters` の名前かキャラクターの名前)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    character: Option<String>,
+    /// このフレームを出すときに鳴らす音のファイル (スクリプトからの相対パス。`audio` 機能付きの
+    /// ビルドだけ)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sound: Option<PathBuf>,
+    /// このフレームだけの機嫌 (色と表情)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mood: Option<mood::Mood>,
+    /// このフレームだけのアートの色 (`--color` と同じ書き方。`mood` の色より優先)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+    /// このフレームへの切り替え方 (`--transition` より優先)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effect: Option<transition::Kind>,
+    /// `--from-cmd` の、出す直前に実行して標準出力をおかずにするコマンド
+    #[serde(skip)]
+    command: Option<String>,
+    /// `-f` でつなげたときの、元のスクリプトのインターバル
+    #[serde(skip)]
+    interval: Option<u64>,
+}
+
+/// スクリプトの配役
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct Role {
+    /// キャラクター (省略時はコマンドラインと設定ファイルのキャラクター)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    art: Option<String>,
+    /// テーマ (省略時はコマンドラインと設定ファイルのテーマ)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    theme: Option<String>,
+}
+
+/// フレームの配役、機嫌、色の組
+type Look = (Option<String>, Option<mood::Mood>, Option<String>);
+
+fn look(frame: &Frame) -> Look {
+    (frame.character.clone(), frame.mood, frame.color.clone())
+}
+
+/// フレームの `character` と `mood`、`color` の組ごとに、キャラクターとテーマと色を差し替えた
+/// 描き方
+struct Cast(BTreeMap<Look, RenderOptions>);
+
+impl Cast {
+    /// `frames` に出てくる配役のアートとテーマ、機嫌と色を先に読み込んでおく
+    fn new(script: &Script, frames: &[Frame], opts: &RenderOptions) -> Result<Self> {
+        let mut cast = BTreeMap::new();
+        for name in frames.iter().filter_map(|frame| frame.character.as_ref()) {
+            if cast.contains_key(name) {
+                continue;
+            }
+            let role = script.characters.get(name);
+            let art = role.map_or(Some(name), |role| role.art.as_ref());
+            let mut role_opts = match art {
+                Some(art) => opts.with_character(art)?,
+                None => opts.clone(),
+            };
+            if let Some(theme) = role.and_then(|role| role.theme.as_ref()) {
+                role_opts = role_opts.with_theme(theme)?;
+            }
+            log::debug!("配役 {}: キャラクター {}", name, role_opts.character);
+            cast.insert(name.clone(), role_opts);
+        }
+        let mut looks = BTreeMap::new();
+        for frame in frames {
+            let look = look(frame);
+            if looks.contains_key(&look) || look == (None, None, None) {
+                continue;
+            }
+            let role = frame.character.as_ref().and_then(|name| cast.get(name));
+            let mut look_opts = role.unwrap_or(opts).clone();
+            if let Some(mood) = frame.mood {
+                look_opts = mood.apply(&look_opts);
+            }
+            if let Some(color) = &frame.color {
+                look_opts.art_style = Style::from_dotted_str(color);
+            }
+            looks.insert(look, look_opts);
+        }
+        Ok(Self(looks))
+    }
+
+    /// `frame` を描くときの描き方 (配役も機嫌も色も無ければ `opts`)
+    fn opts<'a>(&'a self, frame: &Frame, opts: &'a RenderOptions) -> &'a RenderOptions {
+        self.0.get(&look(frame)).unwrap_or(opts)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Choice {
+    label: Text,
+    goto: String,
+}
+
+impl Frame {
+    /// 通知や読み上げに使うテキスト
+    fn text(&self, lang: i18n::Lang) -> &str {
+        match self.side_dish.get(lang) {
+            "" => self.caption.get(lang),
+            side_dish => side_dish,
+        }
+    }
+}
+
+/// `label` を持つフレームの位置
+fn find_label(frames: &[Frame], label: &str) -> Result<usize> {
+    frames
+        .iter()
+        .position(|frame| frame.label.as_deref() == Some(label))
+        .ok_or_else(|| anyhow::anyhow!("ラベル '{}' のフレームがありません", label))
+}
+
+/// `--from`/`--to` の範囲のフレーム (両端を含む)
+fn slice(frames: Vec<Frame>, from: Option<&str>, to: Option<&str>) -> Result<Vec<Frame>> {
+    let position = |spec: &str| {
+        find_label(&frames, spec).or_else(|error| match spec.parse::<usize>() {
+            Ok(n) if (1..=frames.len()).contains(&n) => Ok(n - 1),
+            _ => Err(error),
+        })
+    };
+    let start = from.map(position).transpose()?.unwrap_or(0);
+    let end = match to {
+        Some(to) => position(to)?,
+        None => frames.len().saturating_sub(1),
+    };
+    if start > end {
+        anyhow::bail!("--from のフレームが --to のフレームより後にあります");
+    }
+    Ok(frames
+        .into_iter()
+        .skip(start)
+        .take(end + 1 - start)
+        .collect())
+}
+
+/// `label` のフレームへ飛ぶ。`sliced` で範囲の外のラベルなら再生を終える (`None`)
+fn jump(frames: &[Frame], label: &str, sliced: bool) -> Result<Option<usize>> {
+    match find_label(frames, label) {
+        Ok(i) => Ok(Some(i)),
+        Err(_) if sliced => {
+            log::info!("ラベル '{}' は再生する範囲の外なので終わります", label);
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// ファイルの中身を末尾の改行を除いて読む
+fn read_text(path: &Path) -> Result<String> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("{} を読み込めません", path.display()))?;
+    Ok(text.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// 言語に合わせて説明を訳したコマンド定義
+fn command() -> clap::Command {
+    i18n::localize(Args::command())
+}
+
+fn main() -> Result<()> {
+    screen::install();
+    i18n::set(i18n::detect(&std::env::args().collect::<Vec<_>>()));
+    let args = Args::from_arg_matches(&command().get_matches()).unwrap_or_else(|e| e.exit());
+    run(args).map_err(i18n::error)
+}
+
+fn run(args: Args) -> Result<()> {
+    log::set(
+        args.render
+            .log_level
+            .unwrap_or_else(|| log::Level::from_verbosity(args.render.verbose)),
+    );
+    let config = Config::load()?;
+    terminal::init(args.render.legacy_console || config.legacy_console.unwrap_or(false));
+    pack::set_trusted(args.render.trust);
+    i18n::set(
+        args.render
+            .lang
+            .or(config.lang)
+            .unwrap_or_else(i18n::from_locale),
+    );
+    let play_opts = |interval: Option<u64>| PlayOptions {
+        interval: interval.or(config.interval).unwrap_or(DEFAULT_INTERVAL),
+        animate: !(args.render.no_anim || config.no_anim.unwrap_or(false)),
+        bell_sequence: config
+            .bell_sequence
+            .clone()
+            .unwrap_or_else(|| DEFAULT_BELL_SEQUENCE.to_string()),
+        ..PlayOptions::default()
+    };
+    // 指定が無ければ毎回違うシードにして、同じ実行の中ではすべての乱数をそこから作る
+    let seed = rng::Rng::from_seed(args.render.seed).next_u64();
+    let opts = || RenderOptions::resolve(&args.render, &config, seed, true);
+    let keep_history = config.history.unwrap_or(true);
+    let plain_opts = || RenderOptions::resolve(&args.render, &config, seed, false);
+
+    match args.sub {
+        Command::Say {
+            side_dish,
+            caption,
+            file,
+            url,
+            caption_file,
+            notify,
+            speak,
+            copy,
+            qr,
+            oneline,
+            oneline_width,
+            format,
+            gha_summary,
+            gha_notice,
+            markdown,
+            idle,
+            count,
+            random_dishes,
+            no_history,
+        } => {
+            let opts = match format {
+                Some(format) => format.adjust(opts()?),
+                None => opts()?,
+            };
+            let side_dish = match (file, url) {
+                (Some(path), _) => opts.sanitize(&read_text(&path)?).into_owned(),
+                (_, Some(url)) => opts
+                    .sanitize(fetch::text(&url, fetch::MAX_BYTES)?.trim_end())
+                    .into_owned(),
+                (None, None) => side_dish.unwrap_or_default(),
+            };
+            let caption = match caption_file {
+                Some(path) => Some(opts.sanitize(&read_text(&path)?).into_owned()),
+                None => caption,
+            };
+            let (side_dish, opts) = match markdown {
+                true => {
+                    let (side_dish, columns, rows) =
+                        fit_markdown(&markdown::to_markup(&side_dish, opts.ascii), &opts);
+                    let opts = RenderOptions {
+                        overflow: Overflow::Wrap,
+                        ..opts.with_bubble_size(columns, rows)?
+                    };
+                    (side_dish, opts)
+                }
+                false => (side_dish, opts),
+            };
+            match (oneline, format) {
+                (true, _) => println!(
+                    "{}",
+                    oneline::line(&side_dish, caption.as_deref(), oneline_width, &opts)
+                ),
+                (false, Some(format)) => print!(
+                    "{}",
+                    format.block(&say_lines(
+                        &side_dish,
+                        caption.as_deref(),
+                        qr.as_deref(),
+                        0,
+                        &opts
+                    )?)
+                ),
+                (false, None) => match count {
+                    Some(count) => {
+                        let mut rng = rng::Rng::stream(seed, "count");
+                        let side_dishes: Vec<String> = (0..count)
+                            .map(|_| match random_dishes {
+                                true => scripting::DEFAULT_DISHES
+                                    [rng.below(scripting::DEFAULT_DISHES.len())]
+                                .to_string(),
+                                false => side_dish.clone(),
+                            })
+                            .collect();
+                        say_many(&side_dishes, caption.as_deref(), qr.as_deref(), &opts)?
+                    }
+                    None => say(&side_dish, caption.as_deref(), qr.as_deref(), &opts)?,
+                },
+            }
+            if copy {
+                let lines = say_lines(&side_dish, caption.as_deref(), qr.as_deref(), 0, &opts)?;
+                clipboard::copy(&match format {
+                    // 貼り付ける先に合わせた形でコピーする
+                    Some(format) => format.block(&lines),
+                    None => plain_text(&lines),
+                })?;
+            }
+            if gha_summary {
+                gha::summary(&say_lines(
+                    &side_dish,
+                    caption.as_deref(),
+                    qr.as_deref(),
+                    0,
+                    &opts,
+                )?)?;
+            }
+            if gha_notice {
+                println!(
+                    "{}",
+                    gha::notice(
+                        caption
+                            .as_deref()
+                            .filter(|caption| !caption.is_empty())
+                            .unwrap_or(&side_dish)
+                    )
+                );
+            }
+            if notify {
+                notify::frame(&side_dish);
+            }
+            if speak {
+                speak::frame(&side_dish);
+            }
+            // tmux のステータスラインなどから何度も呼ばれるものは残さない
+            if keep_history && !no_history && !oneline && !random_dishes {
+                history::record(&side_dish, caption.as_deref());
+            }
+            if idle && !opts.a11y {
+                idle::idle(&side_dish, caption.as_deref(), qr.as_deref(), &opts)?;
+            }
+        }
+        Command::History { count } => {
+            let entries = history::Store::History.load()?;
+            let start = entries.len().saturating_sub(count);
+            for (i, entry) in entries.iter().enumerate().skip(start) {
+                println!("{:>5}  {}", i + 1, entry.summary());
+            }
+        }
+        Command::Again { number, fav } => {
+            let store = match fav {
+                true => history::Store::Favorites,
+                false => history::Store::History,
+            };
+            let entry = store.get(number)?;
+            say(&entry.side_dish, entry.caption.as_deref(), None, &opts()?)?;
+            if keep_history {
+                history::record(&entry.side_dish, entry.caption.as_deref());
+            }
+        }
+        Command::Fav { action } => match action {
+            FavAction::Add { side_dish, caption } => {
+                let entry = match side_dish {
+                    Some(side_dish) => history::Entry { side_dish, caption },
+                    None => history::Store::History.get(None)?,
+                };
+                println!(
+                    "{}",
+                    i18n::tr(&format!("お気に入りに足しました: {}", entry.summary()))
+                );
+                history::Store::Favorites.push(entry)?;
+            }
+            FavAction::List => {
+                for (i, entry) in history::Store::Favorites.load()?.iter().enumerate() {
+                    println!("{:>5}  {}", i + 1, entry.summary());
+                }
+            }
+            FavAction::Remove { number } => {
+                let entry = history::Store::Favorites.remove(number)?;
+                println!(
+                    "{}",
+                    i18n::tr(&format!(
+                        "お気に入りから取り除きました: {}",
+                        entry.summary()
+                    ))
+                );
+            }
+        },
+        Command::Anime {
+            side_dishes,
+            pre_captions,
+            after_captions,
+            script_file,
+            format,
+            srt,
+            lrc,
+            player,
+            from_cmd,
+            frames,
+            interval: anime_interval,
+            caption_interval,
+            notify,
+            speak,
+            bell,
+            speed,
+            easing,
+            jitter,
+            typewriter,
+            reveal_after,
+            transition,
+            intro,
+            inline,
+            from,
+            to,
+            hold_last,
+            end_card,
+            progress,
+            reverse,
+            pingpong,
+            shuffle,
+            record,
+            rhai,
+            stats,
+            dump_json,
+            dry_run,
+            export,
+            palette,
+            dither,
+        } => {
+            if let Some(path) = rhai {
+                let animate = play_opts(None).animate;
+                return scripting::run(&path, side_dishes, animate, &opts()?);
+            }
+            let stdin = Path::new(STDIN_PATH);
+            if script_file.iter().filter(|path| *path == stdin).count() > 1 {
+                anyhow::bail!("標準入力 (-) は 1 度だけ指定できます");
+            }
+            let mut script = match (script_file.as_slice(), srt, lrc) {
+                _ if from_cmd.is_some() => fromcmd::script(
+                    from_cmd.as_deref().unwrap_or_default(),
+                    frames.map_or(fromcmd::DEFAULT_FRAMES, usize::from),
+                    pre_captions,
+                    after_captions,
+                ),
+                ([path], _, _) => Script::read(path, format)?,
+                ([_, ..], _, _) => Script::concat(
+                    script_file
+                        .iter()
+                        .map(|path| Script::read(path, format))
+                        .collect::<Result<_>>()?,
+                    anime_interval,
+                    caption_interval,
+                ),
+                (_, Some(path), _) => srt::load(&path)?,
+                (_, _, Some(path)) => lrc::load(&path)?,
+                ([], None, None) => Script {
+                    side_dishes: side_dishes.into_iter().map(Text::from).collect(),
+                    pre_captions: pre_captions.into_iter().map(Text::from).collect(),
+                    after_captions: after_captions.into_iter().map(Text::from).collect(),
+                    ..Script::default()
+                },
+            };
+            if script_file.iter().any(|path| path == stdin) {
+                script.sanitize(&plain_opts()?);
+            }
+            if shuffle {
+                script.shuffle(&mut rng::Rng::stream(seed, "shuffle"));
+            }
+            // 再生しないときは --from-cmd のコマンドを先に実行しておく
+            if dump_json || dry_run || export.is_some() || record.is_some() {
+                fromcmd::resolve_all(&mut script, &plain_opts()?)?;
+            }
+            let play_opts = PlayOptions {
+                notify,
+                speak,
+                bell,
+                speed,
+                easing,
+                jitter: jitter.map(|ratio| Jitter {
+                    ratio,
+                    seed: rng::Rng::stream(seed, "jitter").next_u64(),
+                }),
+                typewriter,
+                reveal_after,
+                transition: transition.or(script.transition),
+                intro,
+                inline,
+                from,
+                to,
+                hold_last,
+                end_card,
+                seed: rng::Rng::stream(seed, "transition").next_u64(),
+                progress,
+                stats,
+                interactive: std::io::IsTerminal::is_terminal(&std::io::stdout()),
+                order: match (reverse, pingpong) {
+                    (true, _) => Order::Reverse,
+                    (_, true) => Order::PingPong,
+                    _ => Order::Forward,
+                },
+                caption_interval: caption_interval.or(script.caption_interval),
+                ..play_opts(anime_interval.or(script.interval))
+            };
+            if let Some(path) = record {
+                Script {
+                    interval: Some(play_opts.interval),
+                    caption_interval: play_opts.caption_interval,
+                    transition: play_opts.transition,
+                    ..script.clone()
+                }
+                .save(&path)?;
+            }
+            match (dump_json, dry_run, export) {
+                (true, _, _) => dump::dump(script, &play_opts, &plain_opts()?)?,
+                (_, true, _) => dryrun::dry_run(script, &play_opts, &plain_opts()?)?,
+                (_, _, Some(output)) => {
+                    let font =
+                        || font::Font::find(args.render.font.as_deref().or(config.font.as_deref()));
+                    let quantize = quantize::Quantize {
+                        depth: palette,
+                        dither,
+                    };
+                    export::anime(script, play_opts, &output, font, quantize, plain_opts()?)?;
+                    println!(
+                        "{}",
+                        i18n::tr(&format!("{} を作成しました", output.display()))
+                    );
+                }
+                _ => {
+                    let opts = opts()?;
+                    let player = player.as_deref().map(lrc::Player::spawn).transpose()?;
+                    let played = anime(script, play_opts, &opts);
+                    // 途中で止めたときも音楽を止める
+                    if let Some(player) = player {
+                        player.stop();
+                    }
+                    played?
+                }
+            }
+        }
+        Command::Quiz { quiz_file } => quiz::quiz(&quiz_file, &opts()?)?,
+        Command::Roulette { items, caption } => roulette::roulette(
+            &items,
+            caption.as_deref(),
+            play_opts(None).animate,
+            &opts()?,
+        )?,
+        Command::Screensaver {
+            side_dishes,
+            interval,
+        } => {
+            let side_dishes = match side_dishes.is_empty() {
+                true => scripting::DEFAULT_DISHES
+                    .iter()
+                    .map(|dish| dish.to_string())
+                    .collect(),
+                false => side_dishes,
+            };
+            screensaver::screensaver(
+                &side_dishes,
+                interval.unwrap_or(screensaver::DEFAULT_INTERVAL),
+                &opts()?,
+            )?
+        }
+        Command::Rsvp { file, wpm } => {
+            let mut script = rsvp::load(&file, wpm)?;
+            let opts = opts()?;
+            script.sanitize(&opts);
+            let play_opts = PlayOptions {
+                interactive: std::io::IsTerminal::is_terminal(&std::io::stdout()),
+                ..play_opts(None)
+            };
+            anime(script, play_opts, &opts)?
+        }
+        Command::Type { script_file } => typing::typing(&Script::load(&script_file)?, &opts()?)?,
+        Command::Preview { target } => preview::preview(&target, &opts()?)?,
+        Command::Edit { script_file } => editor::edit(&script_file, &opts()?)?,
+        Command::Record { output, append } => record::record(&output, append, &opts()?)?,
+        Command::Serve {
+            port,
+            host,
+            tcp,
+            script_file,
+            interval: serve_interval,
+        } => match script_file {
+            Some(path) if tcp => {
+                let script = Script::load(&path)?;
+                let play_opts = PlayOptions {
+                    caption_interval: script.caption_interval,
+                    transition: script.transition,
+                    ..play_opts(serve_interval.or(script.interval))
+                };
+                serve::serve_tcp(&host, port, script, play_opts, plain_opts()?)?
+            }
+            // 受け取った文言からホスト名などが漏れないようにする
+            _ => serve::serve(
+                &host,
+                port,
+                RenderOptions {
+                    placeholders: false,
+                    ..plain_opts()?
+                },
+            )?,
+        },
+        Command::Feed {
+            url,
+            count,
+            interval,
+        } => {
+            let feed = feed::fetch(&url)?;
+            let mut script = Script {
+                side_dishes: feed
+                    .entries
+                    .into_iter()
+                    .take(count)
+                    .map(Text::from)
+                    .collect(),
+                pre_captions: match feed.title.is_empty() {
+                    true => Vec::new(),
+                    false => vec![Text::from(feed.title)],
+                },
+                ..Script::default()
+            };
+            let opts = opts()?;
+            script.sanitize(&opts);
+            let play_opts = PlayOptions {
+                interactive: std::io::IsTerminal::is_terminal(&std::io::stdout()),
+                ..play_opts(interval.or(Some(FEED_INTERVAL)))
+            };
+            anime(script, play_opts, &opts)?
+        }
+        Command::Weather { location, api } => weather::weather(location.as_deref(), &api, opts()?)?,
+        Command::Status {
+            interval,
+            sleepy,
+            panic,
+        } => status::status(
+            Duration::from_millis(interval.unwrap_or(status::DEFAULT_INTERVAL)),
+            sleepy,
+            panic,
+            !play_opts(None).animate,
+            &opts()?,
+        )?,
+        Command::Qotd {
+            quotes_file,
+            no_builtin,
+        } => qotd::qotd(quotes_file.as_deref(), !no_builtin, &opts()?)?,
+        Command::PromptSegment {
+            side_dishes,
+            rotate,
+            shell,
+        } => {
+            let dishes = match side_dishes.is_empty() {
+                true => scripting::DEFAULT_DISHES
+                    .iter()
+                    .map(|dish| dish.to_string())
+                    .collect(),
+                false => side_dishes,
+            };
+            let segment = prompt::Segment {
+                dishes: &dishes,
+                rotate,
+                shell,
+                ascii: args.render.ascii || config.ascii.unwrap_or_else(art::legacy_locale),
+                art_color: args
+                    .render
+                    .color
+                    .as_deref()
+                    .or(config.colors.art.as_deref())
+                    .unwrap_or("green"),
+                dish_color: args
+                    .render
+                    .caption_color
+                    .as_deref()
+                    .or(config.colors.caption.as_deref())
+                    .unwrap_or("yellow"),
+            };
+            println!("{}", segment.render());
+        }
+        Command::ShellInit { shell, every } => {
+            print!("{}", greeting::init_script(shell, every)?)
+        }
+        Command::Greet { every } => greeting::greet(every, &opts()?)?,
+        Command::Motd {
+            side_dish,
+            caption,
+            width,
+        } => motd::motd(&side_dish, caption.as_deref(), width, plain_opts()?)?,
+        Command::Remind {
+            message,
+            at,
+            caption,
+            script_file,
+            notify,
+            once,
+            background,
+        } => {
+            let mut reminders: Vec<remind::Reminder> = at
+                .into_iter()
+                .map(|at| remind::Reminder {
+                    at,
+                    message: message.clone().unwrap_or_default(),
+                    caption: caption.clone(),
+                    script: script_file.clone(),
+                    notify,
+                })
+                .collect();
+            reminders.extend(config.reminders.iter().cloned());
+            // 時刻になってから失敗しないよう、スクリプトは先に読めるか確かめる
+            for path in reminders.iter().filter_map(|r| r.script.as_ref()) {
+                Script::load(path)
+                    .with_context(|| format!("{} を読み込めません", path.display()))?;
+            }
+            let opts = opts()?;
+            if background && remind::detach()? {
+                return Ok(());
+            }
+            remind::run(&reminders, once, |reminder| {
+                if reminder.notify {
+                    notify::frame(&reminder.text());
+                }
+                match &reminder.script {
+                    Some(path) => {
+                        let script = Script::load(path)?;
+                        let play_opts = PlayOptions {
+                            transition: script.transition,
+                            caption_interval: script.caption_interval,
+                            ..play_opts(script.interval)
+                        };
+                        anime(script, play_opts, &opts)
+                    }
+                    None => say(
+                        &opts.sanitize(&reminder.text()),
+                        reminder.caption.as_deref(),
+                        None,
+                        &opts,
+                    ),
+                }
+            })?
+        }
+        Command::Pomodoro { work, rest, cycles } => {
+            pomodoro::pomodoro(work, rest, cycles, &opts()?)?
+        }
+        Command::Countdown {
+            length,
+            message,
+            caption,
+        } => countdown::countdown(length, &message, caption.as_deref(), &opts()?)?,
+        Command::Clock {
+            format,
+            date_format,
+            caption,
+        } => clock::clock(
+            &format,
+            date_format.as_deref(),
+            caption.as_deref(),
+            &opts()?,
+        )?,
+        Command::Completions { shell } => {
+            print!("{}", completions::generate(shell, command()))
+        }
+        Command::Mangen => print!("{}", mangen::generate(command())),
+        Command::Export { format } => match format {
+            ExportFormat::Png {
+                side_dish,
+                caption,
+                output,
+                foreground,
+                background,
+                padding,
+                palette,
+                dither,
+            } => {
+                let font =
+                    font::Font::find(args.render.font.as_deref().or(config.font.as_deref()))?;
+                let image = renderer::Image::new(
+                    &output,
+                    font,
+                    raster::Palette {
+                        foreground,
+                        background,
+                    },
+                    padding,
+                    quantize::Quantize {
+                        depth: palette,
+                        dither,
+                    },
+                );
+                export::png(&side_dish, caption.as_deref(), image, plain_opts()?)?;
+                println!(
+                    "{}",
+                    i18n::tr(&format!("{} を作成しました", output.display()))
+                );
+            }
+        },
+        Command::Config { action } => match action {
+            ConfigAction::Path => match config::config_path() {
+                Some(path) => println!("{}", path.display()),
+                None => anyhow::bail!("設定ディレクトリを特定できません (HOME が未設定です)"),
+            },
+            ConfigAction::Init { force } => {
+                let path = config::init(force)?;
+                println!(
+                    "{}",
+                    i18n::tr(&format!("{} を作成しました", path.display()))
+                );
+            }
+        },
+        Command::Theme { action } => match action {
+            ThemeAction::List => {
+                for (name, builtin) in theme::list()? {
+                    match builtin {
+                        true => println!("{}", name),
+                        false => println!("{}", i18n::tr(&format!("{} (ユーザー定義)", name))),
+                    }
+                }
+            }
+        },
+        Command::GitHook { action } => match action {
+            GitHookAction::CommitMsg { file, confirm } => {
+                githook::commit_msg(&file, confirm, &opts()?)?
+            }
+            GitHookAction::Install { confirm, force } => {
+                let path = githook::install(confirm, force)?;
+                println!(
+                    "{}",
+                    i18n::tr(&format!("{} を作成しました", path.display()))
+                );
+            }
+        },
+        Command::Art { action } => match action {
+            ArtAction::List => {
+                for (name, _) in art::BUILTIN {
+                    println!("{}", name);
+                }
+                for (_, manifest) in pack::list()? {
+                    println!();
+                    print!("{}", manifest.name);
+                    if let Some(version) = &manifest.version {
+                        print!(" {}", version);
+                    }
+                    if let Some(author) = &manifest.author {
+                        print!(" ({})", author);
+                    }
+                    println!();
+                    if let Some(description) = &manifest.description {
+                        println!("  {}", description);
+                    }
+                    if let Some(size) = manifest.bubble_size {
+                        let size = format!("  推奨する吹き出し: {}x{}", size.columns, size.rows);
+                        println!("{}", i18n::tr(&size));
+                    }
+                    for character in &manifest.characters {
+                        println!("  {}/{}", manifest.name, character.name);
+                    }
+                }
+            }
+            ArtAction::Install { source } => {
+                let manifest = pack::install(&source)?;
+                let installed = format!(
+                    "{} ({} キャラクター) をインストールしました",
+                    manifest.name,
+                    manifest.characters.len()
+                );
+                println!("{}", i18n::tr(&installed));
+            }
+            ArtAction::Remove { name } => {
+                pack::remove(&name)?;
+                println!("{}", i18n::tr(&format!("{} を削除しました", name)));
+            }
+            ArtAction::Update { name } => {
+                for manifest in pack::update(name.as_deref())? {
+                    println!("{}", i18n::tr(&format!("{} を更新しました", manifest.name)));
+                }
+            }
+        },
+        Command::Daemon {
+            socket,
+            stdin,
+            watch,
+            clear_after,
+        } => daemon::daemon(
+            &socket.unwrap_or_else(daemon::default_socket_path),
+            &daemon::Sources {
+                stdin,
+                watch,
+                clear_after,
+            },
+            &opts()?,
+        )?,
+        Command::Send {
+            side_dish,
+            caption,
+            socket,
+        } => daemon::send(
+            &socket.unwrap_or_else(daemon::default_socket_path),
+            &side_dish,
+            caption.as_deref(),
+        )?,
+        Command::Kiosk { playlist, quit_key } => {
+            if !play_opts(None).animate {
+                anyhow::bail!("kiosk は --no-anim と一緒に使えません");
+            }
+            let opts = opts()?;
+            kiosk::kiosk(&playlist, quit_key, |path| {
+                let script = Script::load(path)?;
+                let play_opts = PlayOptions {
+                    transition: script.transition,
+                    caption_interval: script.caption_interval,
+                    interactive: std::io::IsTerminal::is_terminal(&std::io::stdout()),
+                    ..play_opts(script.interval)
+                };
+                // 次のスクリプトに移る前に最後のフレームも 1 インターバル見せる
+                let play_opts = PlayOptions {
+                    hold_last: Some(Hold::For(Duration::from_millis(play_opts.interval))),
+                    ..play_opts
+                };
+                anime(script, play_opts, &opts)
+            })?
+        }
+        Command::SelfUpdate { check, force } => selfupdate::self_update(check, force)?,
+        Command::BenchRender { widths, iterations } => {
+            let widths = match widths.is_empty() {
+                true => bench::WIDTHS.to_vec(),
+                false => widths,
+            };
+            bench::bench_render(&widths, iterations, &opts()?)?
+        }
+    }
+
+    Ok(())
+}
+
+fn say(
+    side_dish: &str,
+    caption: Option<&str>,
+    qr: Option<&str>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let terminal_width = terminal::width(&Term::stdout());
+    let mut term = renderer::Terminal::new(Term::stdout(), false, opts);
+    term.frame(&say_lines(side_dish, caption, qr, terminal_width, opts)?)?;
+    term.finish()
+}
+
+/// `say --count` のドラゴンを、端末の幅に収まるだけ横に並べて出力する
+fn say_many(
+    side_dishes: &[String],
+    caption: Option<&str>,
+    qr: Option<&str>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let terminal_width = terminal::width(&Term::stdout());
+    let frames = side_dishes
+        .iter()
+        .map(|side_dish| say_lines(side_dish, caption, qr, 0, opts))
+        .collect::<Result<Vec<_>>>()?;
+    let mut term = renderer::Terminal::new(Term::stdout(), false, opts);
+    term.frame(&center(
+        tile(&frames, terminal_width, opts),
+        terminal_width,
+        opts,
+    ))?;
+    term.finish()
+}
+
+/// ドラゴンとドラゴンの間の桁数
+const TILE_GAP: usize = 2;
+
+/// `frames` を `terminal_width` に収まるだけ横に並べ、入りきらない分は下の段に続ける
+fn tile(frames: &[Vec<String>], terminal_width: usize, opts: &RenderOptions) -> Compositor {
+    // 末尾の空白は埋め草なので幅に数えない
+    let width = |line: &str| markup::width(line.trim_end_matches(' '));
+    let frame_width = frames
+        .iter()
+        .flatten()
+        .map(|line| width(line))
+        .max()
+        .unwrap_or(0);
+    let per_row = match opts.a11y || opts.graphics.is_some() {
+        true => 1,
+        false => ((terminal_width + TILE_GAP) / (frame_width + TILE_GAP)).max(1),
+    };
+    let mut tiles = Compositor::new();
+    for (n, row) in frames.chunks(per_row).enumerate() {
+        // 段の間は 1 行あける
+        let y = tiles.bottom() + usize::from(n > 0);
+        for (k, frame) in row.iter().enumerate() {
+            let x = k * (frame_width + TILE_GAP);
+            tiles.push(Layer::new(z::CHARACTER, frame.clone()).at(x, y));
         }
-        _ => {
-            let mut lines: Vec<String> = side_dish.lines().rev().map(|s| s.to_string()).collect();
-            let line0 = lines.pop().unwrap_or("".to_string());
-            let line1 = lines.pop().unwrap_or("".to_string());
-            vec![line0, line1]
-        }
-    }
-    .into_iter()
-    .map(|s| console::pad_str(&s, 20, Alignment::Center, None).to_string())
-    .collect();
-
-    #[rustfmt::skip]
-    let dragon = "                                          ,. ､
-                                        く  r',ゝ
-r'￣￣￣￣￣￣￣￣￣ヽ                   ,ゝｰ'､
-|                    |          ､      ／      ヽ.
-|                    |        く、｀ヽ/  ∩       |
-|$line1$ ＞        ｀＞             |
-|$line2$|         く´ , -'7         レ个ー─┐
-|                    |          ｀´   //  /      ー个ー─'7
-|                    |               //  /         |    (
-ゝ＿＿＿＿＿＿＿＿__ノ              //  /'┤      |ヽv'⌒ヽ､ゝ
-                                   くﾉ  lｰ┤       ヽ.
-                                    ｀^^'ｰ┤          ▽_
-                                    ((    )          ヽ乙_
-                                    ((    )ヽ､          ヽレl
-                                    ≧＿_ゝ    ｀ﾞー-=､.＿_,ゝ";
-
-    dragon
-        .replace("$line1$", &lines[0])
-        .replace("$line2$", &lines[1])
+    }
+    tiles
+}
+
+/// `--markdown` のおかずの各行を同じ幅にそろえ (中央寄せでも左端がそろう)、
+/// 吹き出しの大きさ `(文字数, 行数)` と合わせて返す。長い行は折り返す
+fn fit_markdown(side_dish: &str, opts: &RenderOptions) -> (String, usize, usize) {
+    let width = |line: &str| console::measure_text_width(&markup::Styled::parse(line).plain);
+    let columns = side_dish
+        .lines()
+        .map(width)
+        .max()
+        .unwrap_or(0)
+        .clamp(1, MARKDOWN_COLUMNS);
+    let side_dish = side_dish
         .lines()
-        .map(|line| console::pad_str(line, terminal_width, Alignment::Left, None).to_string())
+        .map(|line| {
+            format!(
+                "{}{}",
+                line,
+                " ".repeat(columns.saturating_sub(width(line)))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let plain = markup::Styled::parse(&opts.normalize.apply(&side_dish)).plain;
+    let rows = bubble_rows(&plain, columns, Overflow::Wrap).len().max(1);
+    (side_dish, columns, rows)
+}
+
+/// `qr` があればキャプションの代わりに QR コードとラベルを横に並べる
+///
+/// `--overflow wrap` で吹き出しに収まらなければ、続きのフレームを下に並べる
+fn say_lines(
+    side_dish: &str,
+    caption: Option<&str>,
+    qr: Option<&str>,
+    terminal_width: usize,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    if opts.a11y {
+        return render_say(side_dish, caption, terminal_width, opts);
+    }
+    let mut lines = Vec::new();
+    for page in pages(side_dish, opts) {
+        lines.extend(match qr {
+            Some(qr) => center(
+                qr::beside(
+                    create_dragon(&page, 0, opts)?,
+                    qr::render(qr)?,
+                    caption
+                        .map(|caption| opts.caption_style.apply_to(caption).to_string())
+                        .as_deref(),
+                ),
+                terminal_width,
+                opts,
+            ),
+            None => render_say(&page, caption, terminal_width, opts)?,
+        });
+    }
+    Ok(lines)
+}
+
+fn render_say(
+    side_dish: &str,
+    caption: Option<&str>,
+    terminal_width: usize,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    if opts.a11y {
+        return Ok(a11y::narration(side_dish, caption.unwrap_or(""), &[], opts));
+    }
+    let render = || draw_say(side_dish, caption, terminal_width, opts);
+    // `{time}` などは描くたびに変わるので控えない
+    let placeholders = [Some(side_dish), caption]
+        .into_iter()
+        .flatten()
+        .any(|text| opts.placeholders && text.contains('{'));
+    match placeholders {
+        true => render(),
+        false => opts
+            .cache
+            .get_or_try_insert(side_dish, caption, terminal_width, render),
+    }
+}
+
+/// 控えを使わずに `render_say` のフレームを描く
+fn draw_say(
+    side_dish: &str,
+    caption: Option<&str>,
+    terminal_width: usize,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    let dragon = create_dragon(side_dish, terminal_width, opts)?;
+    let mut frame = Compositor::new();
+    let bottom = dragon.len();
+    frame.push(Layer::new(z::CHARACTER, dragon));
+    let caption = caption_line(caption.unwrap_or(""), opts);
+    frame.push(Layer::new(z::CAPTION, vec![caption]).at(0, bottom));
+    Ok(center(frame, terminal_width, opts))
+}
+
+/// `frame` を重ねた行。`--center` なら、レイヤーの相対位置を保ったままフレーム全体を
+/// 端末の中央へずらす
+fn center(mut frame: Compositor, terminal_width: usize, opts: &RenderOptions) -> Vec<String> {
+    if opts.center && opts.graphics.is_none() {
+        frame.shift(terminal_width.saturating_sub(frame.right()) / 2);
+    }
+    frame.render(terminal_width)
+}
+
+/// 色と行末の空白を取り除いて 1 つの文字列にする
+fn plain_text(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| markup::strip(line).trim_end().to_string() + "\n")
+        .collect()
+}
+
+fn caption_line(caption: &str, opts: &RenderOptions) -> String {
+    let caption = opts.expand(caption);
+    let styled = markup::Styled::caption(&opts.normalize.apply(&caption), opts.hyperlinks);
+    let line = layout::caption(&styled.plain, opts.caption_width);
+    match styled.is_plain() {
+        true => opts.caption_style.apply_to(line).to_string(),
+        false => styled.restyle(&line, 0, styled.plain.chars().count(), &opts.caption_style),
+    }
+}
+
+fn anime(script: Script, play_opts: PlayOptions, opts: &RenderOptions) -> Result<()> {
+    // --a11y では待つだけで、画面は消さずに続けて出力する
+    let redraws = play_opts.animate && !opts.a11y;
+    let _screen = redraws.then(|| screen::Screen::enter(false)).flatten();
+    let mut term = match play_opts.inline && redraws {
+        true => renderer::Terminal::inline(Term::stdout(), opts),
+        false => renderer::Terminal::new(Term::stdout(), redraws, opts),
+    };
+    // 端末の大きさが変わっても次のフレームから合わせる
+    let terminal_width = || terminal::width(&Term::stdout());
+    let play_opts = PlayOptions {
+        intro: play_opts.intro.filter(|_| redraws),
+        typewriter: play_opts.typewriter.filter(|_| redraws),
+        reveal_after: play_opts.reveal_after.filter(|_| redraws),
+        transition: play_opts.transition.filter(|_| redraws),
+        ..play_opts
+    };
+    play(&mut term, script, play_opts, terminal_width, opts)
+}
+
+fn play(
+    renderer: &mut dyn Renderer,
+    script: Script,
+    mut play_opts: PlayOptions,
+    terminal_width: impl Fn() -> usize,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let frames = slice(
+        fit_frames(script.timeline(), opts)?,
+        play_opts.from.as_deref(),
+        play_opts.to.as_deref(),
+    )?;
+    let sliced = play_opts.from.is_some() || play_opts.to.is_some();
+    let mut frames = play_opts.order.arrange(frames);
+    let cast = Cast::new(&script, &frames, opts)?;
+    let mut keyboard = match play_opts.interactive && play_opts.animate {
+        true => tty::RawTty::open(),
+        false => None,
+    };
+
+    let transitions = frames.len().saturating_sub(1);
+    let mut stats = play_opts.stats.then(Stats::start);
+    let intro = play_opts
+        .intro
+        .and_then(|intro| intro.build(play_opts.seed, &opts.art_style, opts.ascii));
+    if let (Some(_), Some(first)) = (&intro, frames.first_mut()) {
+        fromcmd::resolve(first, opts)?;
+    }
+    if let (Some(intro), Some(first)) = (intro, frames.first()) {
+        let mut lines = frame_lines(
+            &frames,
+            0,
+            terminal_width(),
+            &play_opts,
+            cast.opts(first, opts),
+        )?;
+        // 端末なら画面全体に流す (--inline ならフレームの大きさのまま)
+        if play_opts.animate && !play_opts.inline {
+            let (rows, _) = Term::stdout().size();
+            let width = terminal_width();
+            lines.resize(
+                lines.len().max((rows as usize).saturating_sub(1)),
+                String::new(),
+            );
+            for line in &mut lines {
+                let padding = width.saturating_sub(markup::width(line));
+                line.push_str(&" ".repeat(padding));
+            }
+        }
+        intro::run(
+            renderer,
+            intro.as_ref(),
+            &lines,
+            play_opts.speed,
+            play_opts.animate,
+        )?;
+    }
+    let mut i = 0;
+    let mut step = 0;
+    let mut schedule = Schedule::start();
+    let mut previous: Option<Vec<String>> = None;
+    // 予定どおりに再生したときの、今のフレームを出す時刻 (--a11y で付ける)
+    let mut at = Duration::ZERO;
+    // 最後のフレームまで再生した (q や選択肢で終えたのでなく)
+    let mut finished = false;
+    while i < frames.len() {
+        log::trace!("フレーム {}/{}", i + 1, frames.len());
+        // --from-cmd のフレームは出す直前にコマンドを実行する
+        fromcmd::resolve(&mut frames[i], opts)?;
+        let frame = &frames[i];
+        let opts = cast.opts(frame, opts);
+        let typewriter = play_opts.typewriter.filter(|_| play_opts.animate);
+        if let Some(per_char) = typewriter {
+            typewrite(
+                renderer,
+                &frames,
+                i,
+                terminal_width(),
+                per_char,
+                &play_opts,
+                opts,
+            )?;
+            // 打ち出す時間はインターバルに含めない
+            schedule.reset();
+        }
+        let reveal = play_opts
+            .reveal_after
+            .filter(|_| play_opts.animate && !frame.caption.get(opts.lang).is_empty());
+        if let Some(after) = reveal {
+            self::reveal(
+                renderer,
+                &frames,
+                i,
+                terminal_width(),
+                after,
+                &play_opts,
+                opts,
+            )?;
+            // 明かす時間もインターバルに含めない
+            schedule.reset();
+        }
+        let started = Instant::now();
+        let mut lines = frame_lines(&frames, i, terminal_width(), &play_opts, opts)?;
+        if opts.a11y {
+            lines = a11y::stamp(lines, at);
+        }
+        // --a11y では切り替えの効果を出さない
+        let effect = frame.effect.filter(|_| !opts.a11y);
+        let transition = effect
+            .or(play_opts.transition)
+            .filter(|_| play_opts.animate && typewriter.is_none() && reveal.is_none())
+            .and_then(|kind| kind.build(play_opts.seed ^ step as u64));
+        let mut transitioned = Duration::ZERO;
+        match (typewriter.or(reveal.map(|_| 0)), transition, &previous) {
+            (Some(_), _, _) => renderer.redraw(&lines)?,
+            (None, Some(transition), Some(previous)) => {
+                let started = Instant::now();
+                transition::run(
+                    renderer,
+                    transition.as_ref(),
+                    previous,
+                    &lines,
+                    play_opts.speed,
+                )?;
+                transitioned = started.elapsed();
+                renderer.redraw(&lines)?;
+            }
+            (None, _, _) => renderer.frame(&lines)?,
+        }
+        if let Some(stats) = &mut stats {
+            let interval = (play_opts.animate && waits_after(&frames, i))
+                .then(|| play_opts.frame_duration(frame, step, progress(i, transitions)));
+            stats.frame(i, started.elapsed().saturating_sub(transitioned), interval);
+        }
+        previous = Some(lines);
+        frame_shown(renderer, &play_opts, frame.text(opts.lang))?;
+        if let Some(sound) = frame.sound.as_deref().filter(|_| play_opts.animate) {
+            sound::play(sound);
+        }
+        if play_opts.speak {
+            schedule.reset();
+        }
+        if !frame.choices.is_empty() {
+            match choose(renderer, keyboard.as_mut(), &frame.choices)? {
+                Some(choice) => match jump(&frames, &choice.goto, sliced)? {
+                    Some(next) => i = next,
+                    None => break,
+                },
+                None => break,
+            }
+            schedule.reset();
+            continue;
+        }
+        if i == transitions && frame.goto.is_none() {
+            finished = true;
+            break;
+        }
+
+        let duration = play_opts.frame_duration(frame, step, progress(i, transitions));
+        step += 1;
+        at += duration;
+        renderer.elapse(duration);
+        match wait(
+            renderer,
+            &mut play_opts,
+            keyboard.as_mut(),
+            &mut schedule,
+            duration,
+        )? {
+            Step::Next => match &frame.goto {
+                Some(goto) => match jump(&frames, goto, sliced)? {
+                    Some(next) => i = next,
+                    None => break,
+                },
+                None => i += 1,
+            },
+            Step::Previous => i = i.saturating_sub(1),
+            Step::Quit => break,
+        }
+    }
+    if let (true, Some(text)) = (finished, play_opts.end_card.clone()) {
+        let duration = play_opts.frame_duration(&frames[i], step, 1.0);
+        at += duration;
+        renderer.elapse(duration);
+        let step = wait(
+            renderer,
+            &mut play_opts,
+            keyboard.as_mut(),
+            &mut schedule,
+            duration,
+        )?;
+        finished = !matches!(step, Step::Quit);
+        if finished {
+            let lines = match opts.a11y {
+                true => a11y::stamp(vec![opts.expand(&text).into_owned()], at),
+                false => end_card(
+                    &text,
+                    previous.as_deref().unwrap_or_default(),
+                    terminal_width(),
+                    opts,
+                ),
+            };
+            renderer.frame(&lines)?;
+        }
+    }
+    if play_opts.bell == Bell::End {
+        renderer.bell(&play_opts.bell_sequence)?;
+    }
+    if let (true, Some(hold)) = (finished, play_opts.hold_last) {
+        match hold {
+            Hold::For(duration) => {
+                renderer.elapse(duration);
+                wait(
+                    renderer,
+                    &mut play_opts,
+                    keyboard.as_mut(),
+                    &mut schedule,
+                    duration,
+                )?;
+            }
+            Hold::Forever => hold_forever(renderer, &play_opts, keyboard.as_mut())?,
+        }
+    }
+    renderer.finish()?;
+    if let Some(stats) = stats {
+        for line in stats.report() {
+            eprintln!("{}", line);
+        }
+    }
+
+    Ok(())
+}
+
+/// `--end-card` の文言を、最後のフレーム `last` と同じ大きさの中央に置いた行
+///
+/// 端末の幅が分かれば (`terminal_width` が 0 でなければ) 横は端末の中央にする
+fn end_card(
+    text: &str,
+    last: &[String],
+    terminal_width: usize,
+    opts: &RenderOptions,
+) -> Vec<String> {
+    let text = opts.expand(text);
+    let card: Vec<&str> = text.lines().collect();
+    let width = match terminal_width {
+        0 => last
+            .iter()
+            .map(|line| markup::width(line.trim_end_matches(' ')))
+            .max()
+            .unwrap_or(0),
+        width => width,
+    };
+    let top = last.len().saturating_sub(card.len()) / 2;
+    let mut lines = vec![String::new(); top];
+    lines.extend(card.iter().map(|line| {
+        let line = console::pad_str(line, width, console::Alignment::Center, None);
+        opts.caption_style.apply_to(line.trim_end()).to_string()
+    }));
+    lines.resize(lines.len().max(last.len()), String::new());
+    lines
+}
+
+/// `--hold-last forever` で、キーを押すまで (キーを読めなければ止められるまで) 待つ
+fn hold_forever(
+    renderer: &mut dyn Renderer,
+    play_opts: &PlayOptions,
+    keyboard: Option<&mut tty::RawTty>,
+) -> Result<()> {
+    renderer.flush()?;
+    if !play_opts.animate {
+        return Ok(());
+    }
+    match keyboard {
+        Some(keyboard) => while controls::read_key(keyboard, None).is_none() {},
+        // パイプの先で止まり続けないよう、端末に出しているときだけ待つ
+        None if std::io::IsTerminal::is_terminal(&std::io::stdout()) => loop {
+            sleep(Duration::from_secs(3600));
+        },
+        None => {}
+    }
+    Ok(())
+}
+
+/// `frames[i]` の後にインターバルを待つか (選択肢を待つフレームと最後のフレームは待たない)
+fn waits_after(frames: &[Frame], i: usize) -> bool {
+    let frame = &frames[i];
+    frame.choices.is_empty() && (i + 1 < frames.len() || frame.goto.is_some())
+}
+
+/// `frames[i]` を表示する行 (キャプション、選択肢、進み具合のバーまで)
+fn frame_lines(
+    frames: &[Frame],
+    i: usize,
+    terminal_width: usize,
+    play_opts: &PlayOptions,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    render_frame(&frames[i], i, frames.len(), terminal_width, play_opts, opts)
+}
+
+/// `total` フレーム中の `i` 番目として `frame` を描く
+fn render_frame(
+    frame: &Frame,
+    i: usize,
+    total: usize,
+    terminal_width: usize,
+    play_opts: &PlayOptions,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    let opts = &*opts.with_fields(&frame.fields);
+    if opts.a11y {
+        let choices: Vec<String> = frame
+            .choices
+            .iter()
+            .map(|choice| choice.label.get(opts.lang).to_string())
+            .collect();
+        return Ok(a11y::narration(
+            frame.side_dish.get(opts.lang),
+            frame.caption.get(opts.lang),
+            &choices,
+            opts,
+        ));
+    }
+    let dragon = create_dragon(frame.side_dish.get(opts.lang), terminal_width, opts)?;
+    let mut lines = vec![caption_line(frame.caption.get(opts.lang), opts)];
+    for (n, choice) in frame.choices.iter().enumerate() {
+        let label = format!("{}. {}", n + 1, choice.label.get(opts.lang));
+        lines.push(caption_line(&label, opts));
+    }
+    if play_opts.progress {
+        lines.push(progress_line(i + 1, total, opts));
+    }
+    let mut layers = Compositor::new();
+    let bottom = dragon.len();
+    layers.push(Layer::new(z::CHARACTER, dragon));
+    layers.push(Layer::new(z::CAPTION, lines).at(0, bottom));
+    Ok(center(layers, terminal_width, opts))
+}
+
+/// `--typewriter` で何文字ごとに口を開け閉めするか
+const MOUTH_CHARS: usize = 2;
+
+/// `i` 番目のフレームのおかずを 1 文字ずつ出す。口を開けたアートがあれば交互に見せる
+///
+/// 最後の 1 文字を出したフレームは描かないので、続けて全体を描き直す
+fn typewrite(
+    renderer: &mut dyn Renderer,
+    frames: &[Frame],
+    i: usize,
+    terminal_width: usize,
+    per_char: u64,
+    play_opts: &PlayOptions,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let frame = &frames[i];
+    let text: Vec<char> = opts
+        .expand(frame.side_dish.get(opts.lang))
+        .chars()
+        .collect();
+    // 置き換え済みの文字列を途中で切るので、もう一度置き換えない
+    let closed = RenderOptions {
+        placeholders: false,
+        ..opts.clone()
+    };
+    let open = opts.art.mouth.as_ref().map(|mouth| RenderOptions {
+        art: Art {
+            template: mouth.clone(),
+            ..closed.art.clone()
+        },
+        ..closed.clone()
+    });
+    let delay = Duration::from_secs_f64(per_char as f64 / 1000.0 / play_opts.speed);
+    for n in 0..text.len() {
+        let opts = match &open {
+            Some(open) if (n / MOUTH_CHARS).is_multiple_of(2) => open,
+            _ => &closed,
+        };
+        let partial = Frame {
+            side_dish: Text::Plain(text[..n].iter().collect()),
+            choices: Vec::new(),
+            ..frame.clone()
+        };
+        let lines = render_frame(&partial, i, frames.len(), terminal_width, play_opts, opts)?;
+        renderer.redraw(&lines)?;
+        std::thread::sleep(delay);
+    }
+    Ok(())
+}
+
+/// `--reveal-after` で 1 文字を明かす間隔
+const REVEAL_CHAR: Duration = Duration::from_millis(80);
+
+/// `i` 番目のフレームをキャプションを伏せて描き、`after` の後に前から 1 文字ずつ明かす
+///
+/// 明かしている間は色の指定を外し、すべて明かしたフレームは描かない (続けて全体を描き直す)
+fn reveal(
+    renderer: &mut dyn Renderer,
+    frames: &[Frame],
+    i: usize,
+    terminal_width: usize,
+    after: Duration,
+    play_opts: &PlayOptions,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let frame = &frames[i];
+    let caption = opts.expand(frame.caption.get(opts.lang));
+    let plain: Vec<char> =
+        markup::Styled::caption(&opts.normalize.apply(&caption), opts.hyperlinks)
+            .plain
+            .chars()
+            .collect();
+    let side_dish = Text::Plain(opts.expand(frame.side_dish.get(opts.lang)).into_owned());
+    // 置き換え済みの文字列を描くので、もう一度置き換えない
+    let opts = RenderOptions {
+        placeholders: false,
+        ..opts.clone()
+    };
+    let mark = if opts.ascii { "#" } else { "■" };
+    let lines = |n: usize| {
+        // 伏せた文字も同じ幅にして、明かしても位置がずれないようにする
+        let hidden = plain[n..].iter().map(|c| match c.is_whitespace() {
+            true => c.to_string(),
+            false => mark.repeat(console::measure_text_width(c.encode_utf8(&mut [0; 4]))),
+        });
+        let partial = Frame {
+            side_dish: side_dish.clone(),
+            caption: Text::Plain(
+                plain[..n]
+                    .iter()
+                    .map(char::to_string)
+                    .chain(hidden)
+                    .collect(),
+            ),
+            ..frame.clone()
+        };
+        render_frame(&partial, i, frames.len(), terminal_width, play_opts, &opts)
+    };
+    renderer.redraw(&lines(0)?)?;
+    sleep(after.div_f64(play_opts.speed));
+    for n in 1..plain.len() {
+        if plain[n - 1].is_whitespace() {
+            continue;
+        }
+        renderer.redraw(&lines(n)?)?;
+        sleep(REVEAL_CHAR.div_f64(play_opts.speed));
+    }
+    Ok(())
+}
+
+/// `i` 番目のフレームの後のインターバルにかけるイージングの進み具合 (0.0-1.0)
+fn progress(i: usize, transitions: usize) -> f64 {
+    match transitions {
+        0 | 1 => 0.0,
+        _ => (i as f64 / (transitions - 1) as f64).min(1.0),
+    }
+}
+
+/// `--overflow` に合わせて、再生を始める前にフレームを分けるか確かめる
+fn fit_frames(frames: Vec<Frame>, opts: &RenderOptions) -> Result<Vec<Frame>> {
+    match opts.overflow {
+        Overflow::Wrap => Ok(frames
+            .into_iter()
+            .flat_map(|frame| {
+                let pages = pages(frame.side_dish.get(opts.lang), opts);
+                if pages.len() == 1 {
+                    return vec![frame];
+                }
+                log::debug!(
+                    "おかず '{}' を {} フレームに分けました",
+                    frame.side_dish.get(opts.lang).replace('\n', " "),
+                    pages.len()
+                );
+                // ラベルは最初、飛び先と選択肢は最後のフレームに付け、表示時間は等分する
+                let last = pages.len() - 1;
+                let duration = frame.duration.map(|duration| duration / pages.len() as u64);
+                pages
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, page)| Frame {
+                        side_dish: Text::Plain(page),
+                        caption: frame.caption.clone(),
+                        label: frame.label.clone().filter(|_| i == 0),
+                        goto: frame.goto.clone().filter(|_| i == last),
+                        choices: match i == last {
+                            true => frame.choices.clone(),
+                            false => Vec::new(),
+                        },
+                        duration,
+                        fields: frame.fields.clone(),
+                        character: frame.character.clone(),
+                        sound: frame.sound.clone().filter(|_| i == 0),
+                        mood: frame.mood,
+                        color: frame.color.clone(),
+                        effect: frame.effect.filter(|_| i == 0),
+                        command: None,
+                        interval: frame.interval,
+                    })
+                    .collect()
+            })
+            .collect()),
+        Overflow::Error => {
+            for frame in &frames {
+                create_dragon(frame.side_dish.get(opts.lang), 0, opts)?;
+            }
+            Ok(frames)
+        }
+        Overflow::Truncate | Overflow::Ellipsis => Ok(frames),
+    }
+}
+
+/// `━━━━─────── 3/12` のような進み具合のバー
+fn progress_line(current: usize, total: usize, opts: &RenderOptions) -> String {
+    let counter = format!(" {}/{}", current, total);
+    let width = opts.caption_width.saturating_sub(counter.len());
+    let done = width * current / total.max(1);
+    let (done_bar, rest_bar) = match opts.ascii {
+        true => ("#", "-"),
+        false => ("━", "─"),
+    };
+    format!(
+        "{}{}{}",
+        done_bar.repeat(done),
+        rest_bar.repeat(width - done),
+        counter
+    )
+}
+
+enum Step {
+    Next,
+    Previous,
+    Quit,
+}
+
+/// 次のフレームを `schedule` の予定の `duration` 後まで待つ。`keyboard` があればキー操作を受け付ける
+fn wait(
+    renderer: &mut dyn Renderer,
+    play_opts: &mut PlayOptions,
+    keyboard: Option<&mut tty::RawTty>,
+    schedule: &mut Schedule,
+    duration: Duration,
+) -> Result<Step> {
+    renderer.flush()?;
+    if !play_opts.animate {
+        return Ok(Step::Next);
+    }
+    let mut remaining = schedule.advance(duration);
+    let Some(keyboard) = keyboard else {
+        sleep(remaining);
+        return Ok(Step::Next);
+    };
+
+    let mut paused = false;
+    // 止めたり速さを変えたり、キーで進めたりしたら次のフレームからは予定を数え直す
+    let mut on_schedule = true;
+    let step = loop {
+        let started = Instant::now();
+        let key = controls::read_key(keyboard, (!paused).then_some(remaining));
+        if !paused {
+            remaining = remaining.saturating_sub(started.elapsed());
+        }
+        match key {
+            Some(Key::Pause) => {
+                paused = !paused;
+                on_schedule = false;
+            }
+            Some(Key::Next) => break Step::Next,
+            Some(Key::Previous) => break Step::Previous,
+            Some(Key::Quit) => return Ok(Step::Quit),
+            Some(key @ (Key::Faster | Key::Slower)) => {
+                let speed = play_opts.speed;
+                play_opts.speed = match key {
+                    Key::Faster => speed * SPEED_STEP,
+                    _ => speed / SPEED_STEP,
+                }
+                .clamp(MIN_SPEED, MAX_SPEED);
+                remaining = remaining.mul_f64(speed / play_opts.speed);
+                on_schedule = false;
+            }
+            None if !paused && remaining.is_zero() && on_schedule => return Ok(Step::Next),
+            None if !paused && remaining.is_zero() => break Step::Next,
+            None | Some(Key::Choice(_)) => {}
+        }
+    };
+    schedule.reset();
+    Ok(step)
+}
+
+/// 番号のキーで選択肢を選ばせる (q なら None)。端末が無いか `kiosk` でキーを締めていれば最初の選択肢に進む
+fn choose<'a>(
+    renderer: &mut dyn Renderer,
+    keyboard: Option<&mut tty::RawTty>,
+    choices: &'a [Choice],
+) -> Result<Option<&'a Choice>> {
+    renderer.flush()?;
+    let Some(keyboard) = keyboard.filter(|_| !controls::locked()) else {
+        return Ok(choices.first());
+    };
+    loop {
+        match controls::read_key(keyboard, None) {
+            Some(Key::Choice(n)) if (1..=choices.len()).contains(&n) => {
+                return Ok(Some(&choices[n - 1]))
+            }
+            Some(Key::Quit) => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// フレームを 1 枚出力した直後の通知やベル、読み上げ
+fn frame_shown(renderer: &mut dyn Renderer, play_opts: &PlayOptions, text: &str) -> Result<()> {
+    if play_opts.notify {
+        notify::frame(text);
+    }
+    if play_opts.bell == Bell::Frame {
+        renderer.bell(&play_opts.bell_sequence)?;
+    }
+    if play_opts.speak {
+        // 読み終わってからインターバルを待つ
+        renderer.flush()?;
+        speak::frame(text);
+    }
+
+    Ok(())
+}
+
+/// 1 フレーム分の行を出力する (`--graphics` なら画像にする)
+fn write_frame<W: Write>(out: &mut W, lines: &[String], opts: &RenderOptions) -> Result<()> {
+    match &opts.graphics {
+        Some(graphics) => write!(out, "{}", graphics.frame(lines))?,
+        None => {
+            for line in narrow::fit(lines, opts).iter() {
+                writeln!(out, "{}", line)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 画面を消して 1 フレーム分を描き直す
+fn redraw<W: Write>(out: &mut W, lines: &[String], opts: &RenderOptions) -> Result<()> {
+    clear_screen(out)?;
+    write_frame(out, lines, opts)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+/// 画面を消さずに先頭から上書きしてちらつきを防ぐ
+fn redraw_in_place<W: Write>(out: &mut W, lines: &[String], opts: &RenderOptions) -> Result<()> {
+    terminal::home(out)?;
+    match opts.graphics {
+        Some(_) => write_frame(out, lines, opts)?,
+        None => {
+            for line in narrow::fit(lines, opts).iter() {
+                writeln!(out, "{}{}", line, terminal::clear_line_end())?;
+            }
+        }
+    }
+    terminal::clear_to_end(out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+fn clear_screen<W: Write>(out: &mut W) -> Result<()> {
+    terminal::clear_screen(out)?;
+    Ok(())
+}
+
+/// `--overflow wrap` のとき、吹き出しに収まるように分けたおかず
+fn pages(side_dish: &str, opts: &RenderOptions) -> Vec<String> {
+    let capacity = bubble_capacity(&template::Template::parse(&opts.art.template));
+    let styled = markup::Styled::parse(&opts.normalize.apply(&opts.expand(side_dish)));
+    let rows = bubble_rows(&styled.plain, opts.bubble_chars, opts.overflow);
+    let bubble = opts.layout == Placement::Bubble;
+    if !(bubble && opts.overflow == Overflow::Wrap && rows.len() > capacity && capacity > 0) {
+        return vec![side_dish.to_string()];
+    }
+    // 分けた後も飾りが残るように、行ごとに指定を付け直す
+    let rows: Vec<String> = styled
+        .offsets(&rows)
+        .into_iter()
+        .zip(&rows)
+        .map(|(start, row)| styled.markup(start, row.chars().count()))
+        .collect();
+    rows.chunks(capacity).map(|rows| rows.join("\n")).collect()
+}
+
+/// アートが端末に入らなければ小さなドラゴンにする
+fn create_dragon(
+    side_dish: &str,
+    terminal_width: usize,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    let lines = compose_dragon(side_dish, terminal_width, opts)?;
+    if !narrow::too_narrow(&lines, terminal_width) {
+        return Ok(lines);
+    }
+    log::info!(
+        "端末の幅 ({} 桁) にアートが入らないので、小さなドラゴンで描きます",
+        terminal_width
+    );
+    let side_dish = opts.expand(side_dish);
+    let styled = markup::Styled::parse(&opts.normalize.apply(&side_dish));
+    Ok(narrow::mini(&styled, terminal_width, opts))
+}
+
+fn compose_dragon(
+    side_dish: &str,
+    terminal_width: usize,
+    opts: &RenderOptions,
+) -> Result<Vec<String>> {
+    let side_dish = opts.expand(side_dish);
+    let styled = markup::Styled::parse(&opts.normalize.apply(&side_dish));
+    if opts.layout == Placement::Side {
+        return Ok(side::dragon(&styled, terminal_width, opts));
+    }
+    let side_dish = &styled.plain;
+    let template = template::Template::parse(&opts.art.template);
+    let capacity = bubble_capacity(&template);
+    let rows = bubble_rows(side_dish, opts.bubble_chars, opts.overflow);
+    log::trace!(
+        "おかず '{}' は吹き出しで {} 行 (最大 {} 文字 x {} 行)",
+        side_dish.replace('\n', " "),
+        rows.len(),
+        opts.bubble_chars,
+        capacity
+    );
+    for row in rows.iter().take(capacity) {
+        if row.chars().count() > opts.bubble_chars {
+            log::info!(
+                "'{}' は吹き出しの幅 ({} 文字) を超えています",
+                row,
+                opts.bubble_chars
+            );
+        }
+    }
+    if rows.len() > capacity && opts.overflow != Overflow::Error {
+        log::info!(
+            "おかず '{}' は {} 行になるので、吹き出しの {} 行に切り詰めます (--overflow wrap で次のフレームへ送れます)",
+            side_dish.replace('\n', " "),
+            rows.len(),
+            capacity
+        );
+    }
+    if opts.overflow == Overflow::Error {
+        layout::check(&rows, side_dish, opts.bubble_chars, capacity)?;
+    }
+    let fields = field_rows(opts);
+    if styled.is_plain() {
+        let rows = layout::fit_rows(rows, &template, opts.bubble_chars, opts.overflow);
+        return Ok(layout::compose(
+            &template,
+            &rows,
+            &fields,
+            terminal_width,
+            |art| opts.art_style.apply_to(art).to_string(),
+            |slot| opts.bubble_style.apply_to(slot).to_string(),
+        ));
+    }
+
+    // 幅は飾りを除いた行で決めて、埋めた後の行に飾りを付ける
+    let spans: Vec<(usize, usize)> = styled
+        .offsets(&rows)
+        .into_iter()
+        .zip(&rows)
+        .map(|(start, row)| (start, row.chars().count()))
+        .collect();
+    let rows: Vec<String> = layout::fit_rows(rows, &template, opts.bubble_chars, opts.overflow)
+        .iter()
+        .enumerate()
+        .map(|(i, row)| match spans.get(i) {
+            Some(&(start, len)) => styled.restyle(row, start, len, &opts.bubble_style),
+            None => opts.bubble_style.apply_to(row).to_string(),
+        })
+        .collect();
+    let fields = fields
+        .into_iter()
+        .map(|(name, rows)| {
+            let rows = rows
+                .iter()
+                .map(|row| opts.bubble_style.apply_to(row).to_string())
+                .collect();
+            (name, rows)
+        })
+        .collect();
+    Ok(layout::compose(
+        &template,
+        &rows,
+        &fields,
+        terminal_width,
+        |art| opts.art_style.apply_to(art).to_string(),
+        |slot| slot.to_string(),
+    ))
+}
+
+/// キャラクターの名前付きの欄に入れる行 (文言が無い欄は空白で埋める)
+fn field_rows(opts: &RenderOptions) -> BTreeMap<String, Vec<String>> {
+    let fields = opts.art.fields();
+    for name in opts.fields.keys() {
+        if !fields.iter().any(|(field, _)| field == name) {
+            log::info!("キャラクター {} に欄 {} はありません", opts.character, name);
+        }
+    }
+    fields
+        .into_iter()
+        .map(|(name, field)| {
+            let text = opts.fields.get(&name).map_or("", String::as_str);
+            let text = opts.normalize.apply(&opts.expand(text)).into_owned();
+            let rows = layout::fit_field(&text, field.width, field.height);
+            (name, rows)
+        })
         .collect()
 }
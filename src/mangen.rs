@@ -0,0 +1,225 @@
+use clap::{Arg, Command};
+use std::fmt::Write;
+
+const SCRIPT_SCHEMA: &str = r#"pre_captions = ["好きな総菜発表ドラゴンが", "好きな総菜を発表します"]
+side_dishes = ["からあげ", "ハンバーグ", "肉を甘辛く\n炒めたやつ"]
+after_captions = ["好き　好き　大好き"]
+# 文言は { ja = "...", en = "..." } で言語ごとにも書ける (--lang で選ぶ)
+# side_dishes = [{ ja = "からあげ", en = "Fried chicken" }]
+# 省略可。コマンドラインの --interval が優先される ("1.5s" のようにも書ける)
+interval = 1000
+# 省略可。キャプションだけのフレームのインターバル (省略時は interval)
+caption_interval = 2000
+# 省略可。フレームの切り替え方 (cut か dissolve)。コマンドラインの --transition が優先される
+transition = "dissolve"
+# 省略可。キャラクターの名前付きの欄 ($sign1$ など) に入れる文言。コマンドラインの --field が優先される
+fields = { sign = "本日の総菜" }
+
+# 省略可。フレームの character で呼ぶ配役 (art はキャラクター、theme はテーマ。どちらも省略可)
+[characters.boss]
+art = "dragon-color"
+theme = "neon"
+
+# 省略可。アフターキャプションの後に続くフレーム。
+# choices があると一時停止し、番号のキーで goto のラベルへ進む
+[[frames]]
+side_dish = "どっちが好き？"
+# 省略可。このフレームを演じる配役 (characters に無ければキャラクターの名前)
+character = "boss"
+choices = [{ label = "からあげ", goto = "karaage" }, { label = "ハンバーグ", goto = "hamburg" }]
+
+[[frames]]
+label = "karaage"
+side_dish = "からあげ"
+# 省略可。このフレームだけの欄の文言
+fields = { sign = "からあげ派" }
+goto = "end"
+
+[[frames]]
+label = "hamburg"
+side_dish = "ハンバーグ"
+# 省略可。このフレームだけの表示時間 (ms。"2s" のようにも書ける)
+duration = 2000
+# 省略可。このフレームだけの機嫌 (happy, angry, sad, sleepy, surprised)。色と表情が変わる
+mood = "angry"
+# 省略可。このフレームだけのアートの色 (--color と同じ書き方。mood の色より優先)
+color = "red.bold"
+# 省略可。このフレームへの切り替え方 (cut か dissolve)。--transition より優先
+effect = "dissolve"
+
+[[frames]]
+label = "end"
+caption = "好き　好き　大好き""#;
+
+const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("{date}", "今日の日付 (2024-01-31)"),
+    ("{time}", "現在の時刻 (09:30)"),
+    ("{user}", "ユーザー名"),
+    ("{hostname}", "ホスト名"),
+    ("{cwd}", "作業ディレクトリ (ホームの下なら ~ から)"),
+];
+
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    text.lines()
+        .map(|line| {
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn arg_usage(arg: &Arg) -> String {
+    let value_name = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+    let takes_value = arg.get_num_args().is_some_and(|n| n.takes_values());
+
+    if arg.is_positional() {
+        let multiple = arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+        return format!("<{}>{}", value_name, if multiple { "..." } else { "" });
+    }
+
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut usage = flags.join(", ");
+    if takes_value {
+        write!(usage, " \\fI{}\\fR", value_name).unwrap();
+    }
+    usage
+}
+
+fn arguments(s: &mut String, cmd: &Command) {
+    for arg in cmd.get_arguments() {
+        writeln!(s, ".TP").unwrap();
+        writeln!(s, "{}", arg_usage(arg)).unwrap();
+        let mut help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+        let defaults: Vec<String> = arg
+            .get_default_values()
+            .iter()
+            .map(|v| v.to_string_lossy().into_owned())
+            .collect();
+        if !defaults.is_empty() && !arg.is_hide_default_value_set() {
+            write!(help, " [既定値: {}]", defaults.join(", ")).unwrap();
+        }
+        let values: Vec<String> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|v| !v.is_hide_set())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        if !values.is_empty() {
+            write!(help, " [候補: {}]", values.join(", ")).unwrap();
+        }
+        writeln!(s, "{}", escape(&help)).unwrap();
+    }
+}
+
+/// clap の定義から roff 形式の man ページを組み立てる
+pub fn generate(mut cmd: Command) -> String {
+    cmd.build();
+    let name = cmd.get_name().to_string();
+    let version = cmd.get_version().unwrap_or_default().to_string();
+    let about = cmd.get_about().map(|a| a.to_string()).unwrap_or_default();
+
+    let mut s = String::new();
+    writeln!(
+        s,
+        ".TH {} 1 \"\" \"{} {}\"",
+        name.to_uppercase(),
+        name,
+        version
+    )
+    .unwrap();
+    writeln!(s, ".SH NAME").unwrap();
+    writeln!(s, "{} \\- {}", escape(&name), escape(&about)).unwrap();
+
+    writeln!(s, ".SH SYNOPSIS").unwrap();
+    writeln!(s, "\\fB{}\\fR [OPTIONS] <COMMAND>", escape(&name)).unwrap();
+
+    writeln!(s, ".SH DESCRIPTION").unwrap();
+    writeln!(s, "{}", escape(&about)).unwrap();
+
+    writeln!(s, ".SH OPTIONS").unwrap();
+    arguments(&mut s, &cmd);
+
+    writeln!(s, ".SH COMMANDS").unwrap();
+    for sub in cmd.get_subcommands().filter(|sub| sub.get_name() != "help") {
+        writeln!(s, ".SS {} {}", escape(&name), escape(sub.get_name())).unwrap();
+        if let Some(about) = sub.get_about() {
+            writeln!(s, "{}", escape(&about.to_string())).unwrap();
+        }
+        let mut usage = vec!["[OPTIONS]".to_string()];
+        usage.extend(
+            sub.get_positionals()
+                .map(|arg| match arg.is_required_set() {
+                    true => arg_usage(arg),
+                    false => format!("[{}]", arg_usage(arg)),
+                }),
+        );
+        writeln!(s, ".PP").unwrap();
+        writeln!(
+            s,
+            "\\fB{} {}\\fR {}",
+            escape(&name),
+            escape(sub.get_name()),
+            usage.join(" ")
+        )
+        .unwrap();
+        arguments(&mut s, sub);
+    }
+
+    writeln!(s, ".SH SCRIPT FORMAT").unwrap();
+    writeln!(
+        s,
+        "{}",
+        escape("anime -f で読み込むスクリプトは TOML 形式で、以下のキーを持ちます。")
+    )
+    .unwrap();
+    writeln!(s, ".TP").unwrap();
+    writeln!(s, "\\fBpre_captions\\fR").unwrap();
+    writeln!(s, "{}", escape("おかずの前に表示するキャプションの配列")).unwrap();
+    writeln!(s, ".TP").unwrap();
+    writeln!(s, "\\fBside_dishes\\fR").unwrap();
+    writeln!(
+        s,
+        "{}",
+        escape("吹き出しに表示するおかずの配列 (改行で 2 行表示)")
+    )
+    .unwrap();
+    writeln!(s, ".TP").unwrap();
+    writeln!(s, "\\fBafter_captions\\fR").unwrap();
+    writeln!(s, "{}", escape("おかずの後に表示するキャプションの配列")).unwrap();
+    writeln!(s, ".PP").unwrap();
+    writeln!(s, ".nf").unwrap();
+    writeln!(s, "{}", escape(SCRIPT_SCHEMA)).unwrap();
+    writeln!(s, ".fi").unwrap();
+
+    writeln!(s, ".SH PLACEHOLDERS").unwrap();
+    writeln!(
+        s,
+        "{}",
+        escape("おかずとキャプションの中の以下の文字列は、表示するときに現在の値に置き換わります (serve で受け取った文言は除く)。")
+    )
+    .unwrap();
+    for (name, description) in PLACEHOLDERS {
+        writeln!(s, ".TP").unwrap();
+        writeln!(s, "\\fB{}\\fR", escape(name)).unwrap();
+        writeln!(s, "{}", escape(description)).unwrap();
+    }
+
+    writeln!(s, ".SH VERSION").unwrap();
+    writeln!(s, "v{}", escape(&version)).unwrap();
+    s
+}
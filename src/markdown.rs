@@ -0,0 +1,190 @@
+//! `say --markdown` で読む Markdown
+//!
+//! 見出し、太字、斜体、インラインコード、箇条書き、引用、コードブロックだけを
+//! `markup` の飾りの指定に置き換える。それ以外の書き方は文字のまま残す
+
+/// Markdown を飾りの指定を付けたおかずにする (前後の空行は除く)
+pub fn to_markup(source: &str, ascii: bool) -> String {
+    let bullet = match ascii {
+        true => "- ",
+        false => "• ",
+    };
+    let quote = match ascii {
+        true => "| ",
+        false => "┃ ",
+    };
+    let mut lines = Vec::new();
+    let mut fenced = false;
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            lines.push(format!("#cyan{{{}}}", escape(line)));
+            continue;
+        }
+        let indent = &line[..line.len() - trimmed.len()];
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        lines.push(
+            if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+                format!("#bold{{{}}}", inline(trimmed[level..].trim()))
+            } else if let Some(item) = ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|marker| trimmed.strip_prefix(marker))
+            {
+                format!("{}{}{}", indent, bullet, inline(item))
+            } else if let Some(quoted) = trimmed.strip_prefix('>') {
+                format!("{}#dim{{{}}}", quote, inline(quoted.trim_start()))
+            } else {
+                format!("{}{}", indent, inline(trimmed))
+            },
+        );
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let start = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    lines[start..].join("\n")
+}
+
+/// 1 行の中の強調とインラインコード
+fn inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::new();
+    // 開いている `**` / `__` と `*` / `_`
+    let mut open: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        let rest = &chars[i..];
+        match c {
+            '\\' if rest.get(1).is_some_and(|c| c.is_ascii_punctuation()) => {
+                out.push_str(&escape(&rest[1].to_string()));
+                i += 2;
+            }
+            '`' => match rest[1..].iter().position(|c| *c == '`') {
+                Some(end) => {
+                    let code: String = rest[1..=end].iter().collect();
+                    out.push_str(&format!("#cyan{{{}}}", escape(&code)));
+                    i += end + 2;
+                }
+                None => {
+                    out.push('`');
+                    i += 1;
+                }
+            },
+            '*' | '_' => {
+                let (single, double) = match c {
+                    '*' => ("*", "**"),
+                    _ => ("_", "__"),
+                };
+                // `***a***` の閉じる側は、内側の `*` を先に閉じる
+                let strong = rest.get(1) == Some(&c) && !open.ends_with(&[double, single]);
+                let marker = match strong {
+                    true => double,
+                    false => single,
+                };
+                // snake_case の `_` は強調にしない
+                let before = i.checked_sub(1).map(|j| chars[j]);
+                let after = chars.get(i + marker.len()).copied();
+                let word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+                let intraword = c == '_' && word(before) && word(after);
+                if open.last() == Some(&marker) && !intraword {
+                    open.pop();
+                    out.push('}');
+                } else if !intraword && closes(&chars[i + marker.len()..], marker) {
+                    open.push(marker);
+                    out.push_str(match strong {
+                        true => "#bold{",
+                        false => "#italic{",
+                    });
+                } else {
+                    out.push_str(&escape(marker));
+                }
+                i += marker.len();
+            }
+            c => {
+                out.push_str(&escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    out.push_str(&"}".repeat(open.len()));
+    out
+}
+
+/// 後ろに閉じる `marker` があるか
+fn closes(rest: &[char], marker: &str) -> bool {
+    let marker: Vec<char> = marker.chars().collect();
+    rest.windows(marker.len())
+        .any(|window| window == marker.as_slice())
+}
+
+/// 飾りの指定として読まれる文字を文字のまま出す
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        if matches!(c, '*' | '#' | '}' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_block_elements() {
+        let source = "\n# 見出し\n- からあげ\n  * ハンバーグ\n> 引用\n```\nlet *x* = 1;\n```\n\n";
+        assert_eq!(
+            to_markup(source, false),
+            "#bold{見出し}\n• からあげ\n  • ハンバーグ\n┃ #dim{引用}\n#cyan{let \\*x\\* = 1;}"
+        );
+        assert_eq!(to_markup("+ a\n> b", true), "- a\n| #dim{b}");
+        // `#` の後に空白が無いものや 7 個以上は見出しにしない
+        assert_eq!(
+            to_markup("#tag\n####### x", false),
+            "\\#tag\n\\#\\#\\#\\#\\#\\#\\# x"
+        );
+    }
+
+    #[test]
+    fn converts_inline_emphasis_and_code() {
+        assert_eq!(inline("**太字** と *斜体*"), "#bold{太字} と #italic{斜体}");
+        assert_eq!(inline("__a__ _b_"), "#bold{a} #italic{b}");
+        assert_eq!(inline("`a*b`"), "#cyan{a\\*b}");
+        assert_eq!(inline("***a***"), "#bold{#italic{a}}");
+    }
+
+    #[test]
+    fn keeps_literal_marks() {
+        assert_eq!(inline("snake_case_name"), "snake_case_name");
+        assert_eq!(inline("2 * 3"), "2 \\* 3");
+        assert_eq!(inline("`open"), "`open");
+        assert_eq!(inline(r"\*not\*"), "\\*not\\*");
+        assert_eq!(inline("a } # \\"), "a \\} \\# \\\\");
+    }
+
+    #[test]
+    fn output_parses_back_to_the_text() {
+        let markup = to_markup("## *a* `b` **c", false);
+        assert_eq!(crate::markup::Styled::parse(&markup).plain, "a b **c");
+    }
+
+    #[test]
+    fn does_not_panic_on_ragged_input() {
+        for source in [
+            "", "\n\n", "#", "# ", "```", "`", "*", "**", "_", "\\", ">", "- ", "あ*い",
+        ] {
+            to_markup(source, false);
+            to_markup(source, true);
+        }
+    }
+}
@@ -0,0 +1,534 @@
+//! アートとおかずに書ける飾りの指定
+//!
+//! アートのファイルでは `#rrggbb{...}` (または `#rgb{...}`) で囲んだ部分をその色の
+//! 24 ビットカラーにする。入れ子にでき、閉じると外側の色に戻る
+//!
+//! おかずとキャプションでは `*太字*` と `#red{色}` (black, red, green, yellow, blue, magenta,
+//! cyan, white) が使える。`#bold{...}`, `#italic{...}`, `#dim{...}` も同じように囲める。吹き出しの幅は飾りを除いた文字で数える。`\*` のように `\` を前に
+//! 付けるとその文字のまま出す
+//!
+//! キャプションではさらに `[名前](URL)` で OSC 8 のリンクを書ける。リンクに対応していない端末では
+//! `名前 (URL)` と出す
+//!
+//! どちらも、対応する開始の無い `}` や閉じる `*` が同じ行に無い `*` はそのまま出力する
+
+use crate::raster::{parse_color, Rgb};
+use console::{Color, Style};
+use std::borrow::Cow;
+
+/// 色の指定をエスケープシーケンスにする。`enabled` が false なら指定を取り除くだけ
+pub fn art(source: &str, enabled: bool) -> String {
+    let mut out = String::new();
+    let mut stack: Vec<Rgb> = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if let Some((rgb, len)) = open(rest) {
+            stack.push(rgb);
+            if enabled {
+                out.push_str(&foreground(Some(rgb)));
+            }
+            rest = &rest[len..];
+            continue;
+        }
+        match c {
+            '}' if !stack.is_empty() => {
+                stack.pop();
+                if enabled {
+                    out.push_str(&foreground(stack.last().copied()));
+                }
+            }
+            // 行ごとに色を付け直すので、行をまたぐ色は行末でいったん戻す
+            '\n' if enabled && !stack.is_empty() => {
+                out.push_str(&foreground(None));
+                out.push('\n');
+                out.push_str(&foreground(stack.last().copied()));
+            }
+            c => out.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    if enabled && !stack.is_empty() {
+        out.push_str(&foreground(None));
+    }
+    out
+}
+
+/// `#rrggbb{` の色とバイト数
+pub fn open(s: &str) -> Option<(Rgb, usize)> {
+    let hex = s.strip_prefix('#')?;
+    let end = hex.find('{')?;
+    if !matches!(end, 3 | 6) || !hex[..end].chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let rgb = parse_color(&hex[..end]).ok()?;
+    Some((rgb, end + 2))
+}
+
+/// `None` なら既定の文字色に戻す
+fn foreground(rgb: Option<Rgb>) -> String {
+    match rgb {
+        Some([r, g, b]) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+        None => "\x1b[39m".to_string(),
+    }
+}
+
+const COLORS: [(&str, Color); 8] = [
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Attr {
+    Bold,
+    Italic,
+    Dim,
+    /// `COLORS` の番号
+    Color(usize),
+    /// `Styled::urls` の番号
+    Link(usize),
+}
+
+/// `#名前{...}` の名前
+fn attr(name: &str) -> Option<Attr> {
+    match name {
+        "bold" => Some(Attr::Bold),
+        "italic" => Some(Attr::Italic),
+        "dim" => Some(Attr::Dim),
+        _ => COLORS
+            .iter()
+            .position(|(color, _)| *color == name)
+            .map(Attr::Color),
+    }
+}
+
+/// 飾りを取り除いた文字とそれぞれの文字の飾り
+#[derive(Clone, Debug)]
+pub struct Styled {
+    pub plain: String,
+    chars: Vec<char>,
+    attrs: Vec<Vec<Attr>>,
+    urls: Vec<String>,
+}
+
+/// 読んでいる途中の `[名前](URL)`
+struct Link {
+    /// `]` の位置
+    close: usize,
+    /// `)` の次の位置
+    end: usize,
+    url: String,
+    /// 名前の最初の文字が `chars` の何文字目か
+    start: usize,
+}
+
+impl Styled {
+    pub fn parse(source: &str) -> Self {
+        Self::parse_with(source, None)
+    }
+
+    /// キャプションとして読む。`hyperlinks` が false ならリンクを `名前 (URL)` にする
+    pub fn caption(source: &str, hyperlinks: bool) -> Self {
+        Self::parse_with(source, Some(hyperlinks))
+    }
+
+    fn parse_with(source: &str, links: Option<bool>) -> Self {
+        let source: Vec<char> = source.chars().collect();
+        let mut chars = Vec::new();
+        let mut attrs = Vec::new();
+        let mut urls = Vec::new();
+        let mut spans: Vec<Attr> = Vec::new();
+        let mut bold = false;
+        let mut link: Option<Link> = None;
+        let mut i = 0;
+        while let Some(&c) = source.get(i) {
+            let rest = &source[i + 1..];
+            if let Some(open) = link.take_if(|link| link.close == i) {
+                spans.retain(|attr| !matches!(attr, Attr::Link(_)));
+                let label: String = chars[open.start..].iter().collect();
+                if links == Some(false) && label != open.url {
+                    for c in format!(" ({})", open.url).chars() {
+                        chars.push(c);
+                        attrs.push(current(bold, &spans));
+                    }
+                }
+                i = open.end;
+                continue;
+            }
+            match c {
+                '\\' if links.is_some() && matches!(rest.first(), Some('[' | ']')) => {
+                    chars.push(rest[0]);
+                    attrs.push(current(bold, &spans));
+                    i += 2;
+                    continue;
+                }
+                '[' if links.is_some() && link.is_none() => {
+                    if let Some((label, url)) = link_target(rest) {
+                        if links == Some(true) {
+                            urls.push(url.clone());
+                            spans.push(Attr::Link(urls.len() - 1));
+                        }
+                        link = Some(Link {
+                            close: i + 1 + label,
+                            end: i + 1 + label + url.chars().count() + 3,
+                            url,
+                            start: chars.len(),
+                        });
+                        i += 1;
+                        continue;
+                    }
+                }
+                '\\' if matches!(rest.first(), Some('*' | '#' | '}' | '\\')) => {
+                    chars.push(rest[0]);
+                    attrs.push(current(bold, &spans));
+                    i += 2;
+                    continue;
+                }
+                '*' if bold => {
+                    bold = false;
+                    i += 1;
+                    continue;
+                }
+                '*' if closes(rest) => {
+                    bold = true;
+                    i += 1;
+                    continue;
+                }
+                '#' => {
+                    let name: String = rest
+                        .iter()
+                        .take_while(|c| c.is_ascii_alphabetic())
+                        .collect();
+                    if let (Some(attr), Some('{')) = (attr(&name), rest.get(name.len())) {
+                        spans.push(attr);
+                        i += name.len() + 2;
+                        continue;
+                    }
+                }
+                '}' if !spans.is_empty() => {
+                    spans.pop();
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            chars.push(c);
+            attrs.push(current(bold, &spans));
+            i += 1;
+        }
+        Self {
+            plain: chars.iter().collect(),
+            chars,
+            attrs,
+            urls,
+        }
+    }
+
+    /// 飾りが 1 つも無い
+    pub fn is_plain(&self) -> bool {
+        self.attrs.iter().all(Vec::is_empty)
+    }
+
+    /// `plain` を分けた各行 (改行は除く) がそれぞれ何文字目から始まるか
+    pub fn offsets(&self, rows: &[String]) -> Vec<usize> {
+        let mut offsets = Vec::new();
+        let mut cursor = 0;
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 && self.chars.get(cursor) == Some(&'\n') {
+                cursor += 1;
+            }
+            offsets.push(cursor);
+            cursor += row.chars().count();
+        }
+        offsets
+    }
+
+    /// `start` 文字目から `len` 文字を両側の空白で埋めた `padded` を、`base` に飾りを重ねて描く
+    ///
+    /// 埋めた後で中身と違う文字 (切り詰めた印など) は `base` だけで描く
+    pub fn restyle(&self, padded: &str, start: usize, len: usize, base: &Style) -> String {
+        let leading =
+            |chars: &mut dyn Iterator<Item = char>| chars.take_while(|c| *c == ' ').count();
+        let content: Vec<char> = self.chars.iter().skip(start).take(len).copied().collect();
+        let padding =
+            leading(&mut padded.chars()).saturating_sub(leading(&mut content.iter().copied()));
+
+        let mut out = String::new();
+        let mut run = String::new();
+        let mut run_attrs: &[Attr] = &[];
+        let flush = |out: &mut String, run: &str, attrs: &[Attr]| {
+            let styled = style(base, attrs).apply_to(run).to_string();
+            let url = attrs.iter().find_map(|attr| match attr {
+                Attr::Link(n) => Some(&self.urls[*n]),
+                _ => None,
+            });
+            match url {
+                Some(url) => out.push_str(&hyperlink(url, &styled)),
+                None => out.push_str(&styled),
+            }
+        };
+        for (i, c) in padded.chars().enumerate() {
+            let attrs = i
+                .checked_sub(padding)
+                .filter(|k| content.get(*k) == Some(&c))
+                .map_or(&[][..], |k| self.attrs[start + k].as_slice());
+            if attrs != run_attrs && !run.is_empty() {
+                flush(&mut out, &run, run_attrs);
+                run.clear();
+            }
+            run_attrs = attrs;
+            run.push(c);
+        }
+        flush(&mut out, &run, run_attrs);
+        out
+    }
+
+    /// `start` 文字目から `len` 文字をもう一度飾りの指定を付けた文字列にする
+    pub fn markup(&self, start: usize, len: usize) -> String {
+        let mut out = String::new();
+        let mut open: Vec<Attr> = Vec::new();
+        let end = (start + len).min(self.chars.len());
+        for i in start..end {
+            // リンクは `#名前{...}` では書けないので落とす
+            let attrs: Vec<Attr> = self.attrs[i]
+                .iter()
+                .filter(|attr| !matches!(attr, Attr::Link(_)))
+                .copied()
+                .collect();
+            if attrs != open {
+                out.push_str(&"}".repeat(open.len()));
+                for attr in &attrs {
+                    out.push('#');
+                    out.push_str(match attr {
+                        Attr::Bold => "bold",
+                        Attr::Italic => "italic",
+                        Attr::Dim => "dim",
+                        Attr::Color(color) => COLORS[*color].0,
+                        Attr::Link(_) => unreachable!(),
+                    });
+                    out.push('{');
+                }
+                open = attrs;
+            }
+            let c = self.chars[i];
+            if matches!(c, '*' | '#' | '}' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push_str(&"}".repeat(open.len()));
+        out
+    }
+}
+
+/// `[` に続く `名前](URL)` の名前の文字数と URL
+///
+/// 名前は空でなく改行と `[` を含まず、URL は空白と制御文字を含まない
+fn link_target(rest: &[char]) -> Option<(usize, String)> {
+    let close = rest.iter().position(|c| matches!(c, ']' | '[' | '\n'))?;
+    if close == 0 || rest[close] != ']' || rest.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let url: String = rest[close + 2..]
+        .iter()
+        .take_while(|c| **c != ')' && !c.is_whitespace() && !c.is_control())
+        .collect();
+    let after = rest.get(close + 2 + url.chars().count());
+    (!url.is_empty() && after == Some(&')')).then_some((close, url))
+}
+
+/// `text` を `url` を指す OSC 8 のリンクにする
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// OSC 8 のリンクのシーケンスを取り除く (console は OSC を読み飛ばさない)
+pub fn strip_links(line: &str) -> Cow<'_, str> {
+    if !line.contains("\x1b]8;") {
+        return Cow::Borrowed(line);
+    }
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("\x1b]8;") {
+        out.push_str(&rest[..start]);
+        let sequence = &rest[start..];
+        let end = match (sequence.find("\x1b\\"), sequence.find('\x07')) {
+            (Some(st), Some(bel)) if bel < st => bel + 1,
+            (Some(st), _) => st + 2,
+            (None, Some(bel)) => bel + 1,
+            (None, None) => sequence.len(),
+        };
+        rest = &sequence[end..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// リンクのシーケンスも数えない表示幅
+pub fn width(line: &str) -> usize {
+    console::measure_text_width(&strip_links(line))
+}
+
+/// 色とリンクのシーケンスを取り除く
+pub fn strip(line: &str) -> String {
+    console::strip_ansi_codes(&strip_links(line)).into_owned()
+}
+
+/// 同じ行に `\\` の付いていない閉じる `*` があるか
+fn closes(rest: &[char]) -> bool {
+    let mut chars = rest.iter();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return false,
+            '*' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// 囲みは外側から順に、`*` の太字は最後
+fn current(bold: bool, spans: &[Attr]) -> Vec<Attr> {
+    let mut attrs = spans.to_vec();
+    if bold {
+        attrs.push(Attr::Bold);
+    }
+    attrs
+}
+
+fn style(base: &Style, attrs: &[Attr]) -> Style {
+    attrs.iter().fold(base.clone(), |style, attr| match attr {
+        Attr::Bold => style.bold(),
+        Attr::Italic => style.italic(),
+        Attr::Dim => style.dim(),
+        Attr::Color(color) => style.fg(COLORS[*color].1),
+        Attr::Link(_) => style,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_art_and_restores_outer_colors() {
+        assert_eq!(
+            art("a#f00{b#00ff00{c}d}e", true),
+            "a\x1b[38;2;255;0;0mb\x1b[38;2;0;255;0mc\x1b[38;2;255;0;0md\x1b[39me"
+        );
+        assert_eq!(art("a#f00{b\nc}", false), "ab\nc");
+        assert_eq!(
+            art("#f00{a\nb", true),
+            "\x1b[38;2;255;0;0ma\x1b[39m\n\x1b[38;2;255;0;0mb\x1b[39m"
+        );
+        // 色でない `#` や対応する開始の無い `}` はそのまま
+        assert_eq!(art("#ff{a} #xyz{b}", false), "#ff{a} #xyz{b}");
+    }
+
+    #[test]
+    fn parses_bold_colors_and_attributes() {
+        let styled = Styled::parse("*太字* と #red{赤#bold{太}}");
+        assert_eq!(styled.plain, "太字 と 赤太");
+        assert_eq!(styled.attrs[0], [Attr::Bold]);
+        assert_eq!(styled.attrs[2], []);
+        assert_eq!(styled.attrs[5], [Attr::Color(1)]);
+        assert_eq!(styled.attrs[6], [Attr::Color(1), Attr::Bold]);
+        assert!(!styled.is_plain());
+        assert!(Styled::parse("からあげ").is_plain());
+    }
+
+    #[test]
+    fn keeps_unmatched_and_escaped_marks() {
+        assert_eq!(Styled::parse("a * b\n*").plain, "a * b\n*");
+        assert_eq!(Styled::parse("} #nope{x}").plain, "} #nope{x}");
+        assert_eq!(Styled::parse(r"\*a\* \#red{b\} \\").plain, r"*a* #red{b} \");
+        assert_eq!(Styled::parse("#red{").plain, "");
+        assert_eq!(Styled::parse("\\").plain, "\\");
+    }
+
+    #[test]
+    fn parses_links_in_captions() {
+        let styled = Styled::caption("[公式](https://example.com) です", true);
+        assert_eq!(styled.plain, "公式 です");
+        assert_eq!(styled.urls, ["https://example.com"]);
+        assert_eq!(styled.attrs[0], [Attr::Link(0)]);
+
+        let plain = Styled::caption("[公式](https://example.com)", false);
+        assert_eq!(plain.plain, "公式 (https://example.com)");
+        let same = Styled::caption("[https://a.b](https://a.b)", false);
+        assert_eq!(same.plain, "https://a.b");
+
+        // おかずでは読まない。閉じていないものや空白の入った URL もそのまま
+        assert_eq!(Styled::parse("[a](b)").plain, "[a](b)");
+        for source in ["[a](b", "[](b)", "[a](b c)", "[a\n](b)", r"\[a](b)"] {
+            let expected = source.replacen('\\', "", 1);
+            assert_eq!(Styled::caption(source, true).plain, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn writes_markup_back() {
+        let source = "*a*#red{b}\\*";
+        let styled = Styled::parse(source);
+        let markup = styled.markup(0, styled.chars.len());
+        assert_eq!(markup, "#bold{a}#red{b}\\*");
+        assert_eq!(Styled::parse(&markup).plain, styled.plain);
+        assert_eq!(styled.markup(1, 100), "#red{b}\\*");
+    }
+
+    #[test]
+    fn strips_links_and_measures_width() {
+        let line = format!("{}です", hyperlink("https://example.com", "公式"));
+        assert_eq!(strip_links(&line), "公式です");
+        assert_eq!(strip_links("\x1b]8;;u\x07a\x1b]8;;\x07"), "a");
+        assert_eq!(strip_links("a\x1b]8;;"), "a");
+        assert_eq!(width(&line), 8);
+        assert_eq!(strip("\x1b[31mred\x1b[0m"), "red");
+    }
+
+    #[test]
+    fn offsets_rows_after_newlines() {
+        let styled = Styled::parse("ab\ncde");
+        let rows = ["ab".to_string(), "cde".to_string()];
+        assert_eq!(styled.offsets(&rows), [0, 3]);
+    }
+
+    #[test]
+    fn does_not_panic_on_ragged_input() {
+        for source in [
+            "",
+            "#",
+            "#{",
+            "#fff",
+            "#fff{",
+            "}}}",
+            "*",
+            "**",
+            "\\",
+            "[",
+            "[]",
+            "[a]",
+            "[a](",
+            "[a]()",
+            "#ああ{い}",
+            "[あ](い)う",
+            "*[a](b)*",
+            "#red{[a](b)}",
+            "\n*\n",
+        ] {
+            art(source, true);
+            let styled = Styled::caption(source, true);
+            styled.markup(0, source.len());
+            styled.restyle(&styled.plain, 0, styled.chars.len(), &Style::new());
+            Styled::caption(source, false);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! スクリプトのフレームの `mood` と `anime --rhai` の `set_mood("angry")` の機嫌
+//!
+//! そのフレームだけ (`set_mood` なら次に変えるまで) キャラクターの色と表情を変える。表情は
+//! キャラクターに閉じた目や開いた口のコマがあるときだけ変わる
+
+use crate::RenderOptions;
+use anyhow::anyhow;
+use console::Style;
+use serde::de::value::{Error, StrDeserializer};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Mood {
+    /// 黄色
+    Happy,
+    /// 赤く太字にして口を開ける
+    Angry,
+    /// 暗い青
+    Sad,
+    /// 青くして目を閉じる
+    Sleepy,
+    /// マゼンタにして口を開ける
+    Surprised,
+}
+
+/// `set_mood("angry")` のようにスクリプトから名前で選ぶ
+impl FromStr for Mood {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> anyhow::Result<Self> {
+        let deserializer: StrDeserializer<Error> = name.into_deserializer();
+        Self::deserialize(deserializer).map_err(|_| {
+            anyhow!(
+                "機嫌 '{}' はありません (happy, angry, sad, sleepy, surprised)",
+                name
+            )
+        })
+    }
+}
+
+impl Mood {
+    /// アートの色 (`--color` と同じ書き方)
+    fn color(self) -> &'static str {
+        match self {
+            Mood::Happy => "yellow",
+            Mood::Angry => "red.bold",
+            Mood::Sad => "blue.dim",
+            Mood::Sleepy => "blue",
+            Mood::Surprised => "magenta",
+        }
+    }
+
+    /// `opts` の色と表情をこの機嫌のものにする (吹き出しの文字は色だけ変える)
+    pub fn apply(self, opts: &RenderOptions) -> RenderOptions {
+        let mut opts = opts.clone();
+        opts.art_style = Style::from_dotted_str(self.color());
+        opts.bubble_style = match self {
+            Mood::Happy => opts.bubble_style.yellow(),
+            Mood::Angry => opts.bubble_style.red(),
+            Mood::Sad | Mood::Sleepy => opts.bubble_style.blue(),
+            Mood::Surprised => opts.bubble_style.magenta(),
+        };
+        let face = match self {
+            Mood::Sleepy => opts.art.idle.first().cloned(),
+            Mood::Angry | Mood::Surprised => opts.art.mouth.clone(),
+            Mood::Happy | Mood::Sad => None,
+        };
+        if let Some(face) = face {
+            opts.art.template = face;
+        }
+        opts
+    }
+}
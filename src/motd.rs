@@ -0,0 +1,19 @@
+use crate::{render_say, RenderOptions};
+use anyhow::Result;
+
+pub fn motd(
+    side_dish: &str,
+    caption: Option<&str>,
+    width: usize,
+    mut opts: RenderOptions,
+) -> Result<()> {
+    // motd はファイルへ書き出されるので色指定があれば常に出力する
+    opts.art_style = opts.art_style.force_styling(true);
+    opts.bubble_style = opts.bubble_style.force_styling(true);
+    opts.caption_style = opts.caption_style.force_styling(true);
+    for line in render_say(side_dish, caption, width, &opts)? {
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! 端末がフレームより狭いときの描き方
+//!
+//! 折り返してアートが崩れないように、アートが入らなければ 1 行の小さなドラゴンとおかずにする。
+//! 中央に寄せた行 (キャプションなど) は左の余白を詰め、それでも入らない行があれば、フレームの
+//! 代わりに必要な桁数を知らせる
+
+use crate::transition::{self, Cell};
+use crate::{i18n, log, markup, side, terminal, RenderOptions};
+use console::Term;
+use std::borrow::Cow;
+
+/// 描き直しの埋め草を除いた行の幅
+fn width(line: &str) -> usize {
+    markup::width(line.trim_end_matches(' '))
+}
+
+/// 端末に出すときの桁数 (端末でなければ `None`)
+fn columns() -> Option<usize> {
+    let term = Term::stdout();
+    term.is_term().then(|| terminal::width(&term))
+}
+
+/// 端末に出す `terminal_width` 桁に `lines` の入らない行があるか
+pub fn too_narrow(lines: &[String], terminal_width: usize) -> bool {
+    terminal_width > 0
+        && columns().is_some()
+        && lines.iter().any(|line| width(line) > terminal_width)
+}
+
+/// アートの代わりの `🐉 < おかず` (おかずは残りの幅で折り返す)
+pub fn mini(styled: &markup::Styled, terminal_width: usize, opts: &RenderOptions) -> Vec<String> {
+    let glyph = match opts.ascii {
+        true => "<:3= ",
+        false => "🐉 ",
+    };
+    let indent = console::measure_text_width(glyph);
+    let chars: Vec<char> = styled.plain.chars().collect();
+    side::wrap(&chars, terminal_width.saturating_sub(indent).max(1))
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, len))| {
+            let row: String = chars[start..start + len].iter().collect();
+            let row = match styled.is_plain() {
+                true => opts.bubble_style.apply_to(row).to_string(),
+                false => styled.restyle(&row, start, len, &opts.bubble_style),
+            };
+            match i {
+                0 => format!("{}{}", opts.art_style.apply_to(glyph), row),
+                _ => format!("{}{}", " ".repeat(indent), row),
+            }
+        })
+        .collect()
+}
+
+/// 左の余白を詰めて `columns` 桁の中央に置き直す (中身が入らなければ `None`)
+fn recenter(line: &str, columns: usize) -> Option<String> {
+    let cells = transition::columns(line);
+    let blank = |cell: &Option<Cell>| cell.as_ref().is_some_and(|cell| cell.c == ' ');
+    let leading = cells.iter().take_while(|cell| blank(cell)).count();
+    let content = width(line).saturating_sub(leading);
+    if content > columns {
+        return None;
+    }
+    let drop = leading.saturating_sub((columns - content) / 2);
+    let cells: Vec<Cell> = cells[drop..].iter().flatten().cloned().collect();
+    Some(transition::join(&cells))
+}
+
+/// 端末に入らない行があれば余白を詰め、それでも入らなければ必要な桁数を知らせる行を返す
+pub fn fit<'a>(lines: &'a [String], opts: &RenderOptions) -> Cow<'a, [String]> {
+    let Some(columns) = columns().filter(|_| opts.graphics.is_none() && !opts.a11y) else {
+        return Cow::Borrowed(lines);
+    };
+    let need = lines.iter().map(|line| width(line)).max().unwrap_or(0);
+    if need <= columns || columns == 0 {
+        return Cow::Borrowed(lines);
+    }
+    let recentered: Option<Vec<String>> = lines
+        .iter()
+        .map(|line| match width(line) <= columns {
+            true => Some(line.clone()),
+            false => recenter(line, columns),
+        })
+        .collect();
+    if let Some(lines) = recentered {
+        return Cow::Owned(lines);
+    }
+    log::debug!(
+        "端末の幅 {} 桁にフレーム ({} 桁) が入りません",
+        columns,
+        need
+    );
+    let notice = i18n::tr(&format!("端末の幅が足りません ({} 桁以上必要です)", need)).into_owned();
+    let chars: Vec<char> = notice.chars().collect();
+    Cow::Owned(
+        side::wrap(&chars, columns)
+            .into_iter()
+            .map(|(start, len)| chars[start..start + len].iter().collect())
+            .collect(),
+    )
+}
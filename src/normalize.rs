@@ -0,0 +1,113 @@
+//! おかずとキャプションの全角/半角をそろえる
+//!
+//! Unicode の正規化表を持たずに済むよう、NFKC のうち端末の表示幅に効く
+//! 全角英数記号・全角スペース・半角カナと濁点/半濁点の合成だけを行う
+
+use std::borrow::Cow;
+
+#[derive(clap::ValueEnum, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalize {
+    /// 全角英数記号を半角に、半角カナを全角にする
+    Nfkc,
+    /// そのまま
+    #[default]
+    None,
+}
+
+/// 半角カナ (U+FF61-U+FF9F) に対応する文字
+#[rustfmt::skip]
+const HALFWIDTH_KANA: [char; 63] = [
+    '。', '「', '」', '、', '・', 'ヲ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ュ', 'ョ', 'ッ',
+    'ー', 'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ',
+    'ソ', 'タ', 'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ',
+    'ホ', 'マ', 'ミ', 'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ',
+    'ン', '\u{3099}', '\u{309a}',
+];
+
+const DAKUTEN: char = '\u{3099}';
+const HANDAKUTEN: char = '\u{309a}';
+
+impl Normalize {
+    pub fn apply(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Normalize::None => Cow::Borrowed(text),
+            Normalize::Nfkc => Cow::Owned(nfkc(text)),
+        }
+    }
+}
+
+fn nfkc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars().map(width_fold) {
+        let composed = match (out.chars().last(), c) {
+            (Some(base), DAKUTEN) => voiced(base),
+            (Some(base), HANDAKUTEN) => semi_voiced(base),
+            _ => None,
+        };
+        match composed {
+            Some(composed) => {
+                out.pop();
+                out.push(composed);
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+fn width_fold(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+        '\u{ff61}'..='\u{ff9f}' => HALFWIDTH_KANA[(c as u32 - 0xff61) as usize],
+        '\u{ffe0}' => '¢',
+        '\u{ffe1}' => '£',
+        '\u{ffe2}' => '¬',
+        '\u{ffe5}' => '¥',
+        '\u{ffe6}' => '₩',
+        _ => c,
+    }
+}
+
+/// ひらがなに直した文字 (カタカナでなければそのまま)
+fn to_hiragana(c: char) -> (char, bool) {
+    match c {
+        'ァ'..='ヶ' => (char::from_u32(c as u32 - 0x60).unwrap_or(c), true),
+        _ => (c, false),
+    }
+}
+
+fn from_hiragana(c: char, katakana: bool) -> Option<char> {
+    match katakana {
+        true => char::from_u32(c as u32 + 0x60),
+        false => Some(c),
+    }
+}
+
+fn voiced(base: char) -> Option<char> {
+    match base {
+        'ウ' => return Some('ヴ'),
+        'ワ' => return Some('ヷ'),
+        'ヰ' => return Some('ヸ'),
+        'ヱ' => return Some('ヹ'),
+        'ヲ' => return Some('ヺ'),
+        'ゝ' => return Some('ゞ'),
+        'ヽ' => return Some('ヾ'),
+        'う' => return Some('ゔ'),
+        _ => {}
+    }
+    let (hiragana, katakana) = to_hiragana(base);
+    match "かきくけこさしすせそたちつてとはひふへほ".contains(hiragana) {
+        true => from_hiragana(char::from_u32(hiragana as u32 + 1)?, katakana),
+        false => None,
+    }
+}
+
+fn semi_voiced(base: char) -> Option<char> {
+    let (hiragana, katakana) = to_hiragana(base);
+    match "はひふへほ".contains(hiragana) {
+        true => from_hiragana(char::from_u32(hiragana as u32 + 2)?, katakana),
+        false => None,
+    }
+}
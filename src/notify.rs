@@ -0,0 +1,78 @@
+use crate::i18n;
+use anyhow::{bail, Context, Result};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const SUMMARY: &str = "好きな総菜発表ドラゴン";
+
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// 表示したフレームのテキストを通知する
+///
+/// 通知に失敗しても描画は続け、警告は最初の一度だけ出す
+pub fn frame(text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    if let Err(e) = send(text) {
+        if !WARNED.swap(true, Ordering::Relaxed) {
+            eprintln!(
+                "{}",
+                i18n::tr(&format!("通知を送れませんでした: {:#}", i18n::error(e)))
+            );
+        }
+    }
+}
+
+/// OS 標準の通知コマンドでデスクトップ通知を出す
+pub fn send(body: &str) -> Result<()> {
+    let body = body.replace('\n', " ");
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            applescript_escape(&body),
+            applescript_escape(SUMMARY)
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else if cfg!(windows) {
+        let script = format!(
+            "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+             $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+             $text = $xml.GetElementsByTagName('text'); \
+             $text.Item(0).AppendChild($xml.CreateTextNode('{}')) > $null; \
+             $text.Item(1).AppendChild($xml.CreateTextNode('{}')) > $null; \
+             [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('fav_say_dragon').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+            powershell_escape(SUMMARY),
+            powershell_escape(&body)
+        );
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-Command", &script]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(SUMMARY).arg(&body);
+        command
+    };
+
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("通知コマンドを実行できません")?;
+    if !status.success() {
+        bail!("通知コマンドが失敗しました ({})", status);
+    }
+
+    Ok(())
+}
+
+fn applescript_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn powershell_escape(text: &str) -> String {
+    text.replace('\'', "''")
+}
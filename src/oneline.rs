@@ -0,0 +1,52 @@
+//! `say --oneline` の tmux のステータスライン向けの 1 行
+//!
+//! `status-right` に `#(fav_say_dragon say --oneline からあげ)` のように埋め込む
+
+use crate::RenderOptions;
+
+/// tmux の既定の `status-right-length`
+pub const DEFAULT_WIDTH: usize = 40;
+
+/// 小さなドラゴンとおかず (とキャプション) を、改行の無い表示幅 `width` までの 1 行にする
+pub fn line(side_dish: &str, caption: Option<&str>, width: usize, opts: &RenderOptions) -> String {
+    let glyph = match opts.ascii {
+        true => "<:3=",
+        false => "🐉",
+    };
+    let text = |styled: crate::markup::Styled| flatten(&styled.plain);
+    let prepare = |text: &str| opts.normalize.apply(&opts.expand(text)).into_owned();
+    let mut line = format!(
+        "{} {}",
+        glyph,
+        text(crate::markup::Styled::parse(&prepare(side_dish)))
+    );
+    let caption =
+        caption.map(|caption| text(crate::markup::Styled::caption(&prepare(caption), false)));
+    if let Some(caption) = caption.filter(|caption| !caption.is_empty()) {
+        line.push_str(" - ");
+        line.push_str(&caption);
+    }
+    let ellipsis = match opts.ascii {
+        true => "...",
+        false => "…",
+    };
+    // 省略記号も入らない幅では省略記号を付けずに切る
+    let ellipsis = match console::measure_text_width(ellipsis) <= width {
+        true => ellipsis,
+        false => "",
+    };
+    escape(&console::truncate_str(&line, width, ellipsis))
+}
+
+/// 改行や制御文字を空白にし、続く空白を 1 つにまとめる
+fn flatten(text: &str) -> String {
+    text.split(|c: char| c.is_whitespace() || c.is_control())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// tmux が書式として読む `#` を `##` にする
+fn escape(line: &str) -> String {
+    line.replace('#', "##")
+}
@@ -0,0 +1,586 @@
+//! `~/.local/share/fav_say_dragon/packs/<パック>/pack.toml` で定義するアートパック
+//!
+//! ```toml
+//! name = "cats"
+//! description = "ねこのアート"
+//! # テンプレート中でおかずの 1 行目と 2 行目を置く印 (省略時は $line1$, $line2$)
+//! placeholders = ["{{1}}", "{{2}}"]
+//! # 推奨する吹き出しの中の大きさ
+//! bubble_size = { columns = 20, rows = 2 }
+//!
+//! [[characters]]
+//! name = "cat"
+//! template = "cat.txt"
+//! # 吹き出しの範囲 (テーマの枠線を描き直すのに使う)
+//! # (tail はしっぽのある行と向き。--tail で描き直すのに使う)
+//! bubble = { top = 0, bottom = 3, width = 22, tail = { row = 2, side = "right" } }
+//! # say --idle でときどき差し替える待機中のテンプレート (行数は template と同じにする)
+//! idle = ["cat-blink.txt"]
+//! # anime --typewriter で文字を出しながら交互に見せる口を開けたテンプレート (行数は同じ)
+//! mouth = "cat-open.txt"
+//! # テンプレートの名前付きの欄 ($sign1$, $sign2$, ...) の表示幅と行数
+//! # (省略時は幅が $sign1$ の印の幅、行数が印の数)
+//! fields = { sign = { width = 12, height = 2 } }
+//! ```
+//!
+//! テンプレートの中では `#ff8800{...}` で囲んだ部分をその色 (24 ビットカラー) で描く
+//!
+//! git や URL から入れたパックは、ファイルごとの SHA-256 を `packs.lock` に記録する。読み込む
+//! ときに中身が変わっていれば描かない (`--trust` なら警告だけ出して使う)
+
+use crate::art::{Art, Bubble, Field};
+use crate::{config, fetch, log, sha256};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const MANIFEST: &str = "pack.toml";
+const PLACEHOLDERS: [&str; 2] = ["$line1$", "$line2$"];
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub name: String,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    #[serde(default = "default_placeholders")]
+    pub placeholders: [String; 2],
+    pub bubble_size: Option<BubbleSize>,
+    pub characters: Vec<Character>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct BubbleSize {
+    pub columns: usize,
+    pub rows: usize,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Character {
+    pub name: String,
+    /// パックのディレクトリからの相対パス
+    pub template: PathBuf,
+    pub bubble: Option<Bubble>,
+    #[serde(default)]
+    pub idle: Vec<PathBuf>,
+    pub mouth: Option<PathBuf>,
+    #[serde(default)]
+    pub fields: BTreeMap<String, Field>,
+}
+
+fn default_placeholders() -> [String; 2] {
+    PLACEHOLDERS.map(String::from)
+}
+
+pub fn packs_dir() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("packs"))
+}
+
+/// インストールしたときのハッシュを記録するファイル
+const LOCK_FILE: &str = "packs.lock";
+
+static TRUSTED: AtomicBool = AtomicBool::new(false);
+
+/// `--trust` のとき、ロックファイルと中身が違うパックも使う
+pub fn set_trusted(trusted: bool) {
+    TRUSTED.store(trusted, Ordering::Relaxed);
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Lock {
+    #[serde(default)]
+    packs: BTreeMap<String, LockedPack>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockedPack {
+    source: String,
+    /// パックのディレクトリからの相対パス (`/` 区切り) と SHA-256
+    files: BTreeMap<String, String>,
+}
+
+impl Lock {
+    fn path() -> Option<PathBuf> {
+        config::data_dir().map(|dir| dir.join(LOCK_FILE))
+    }
+
+    fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| format!("{} を読み込めません", path.display()))?;
+        toml::from_str(&source)
+            .with_context(|| format!("{} の読み込みに失敗しました", path.display()))
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            bail!("データディレクトリを特定できません (HOME が未設定です)");
+        };
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// `dir` の下のファイル (インストール元の記録は除く) の SHA-256
+fn hashes(dir: &Path) -> Result<BTreeMap<String, String>> {
+    fn walk(dir: &Path, prefix: &str, out: &mut BTreeMap<String, String>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            match entry.file_type()?.is_dir() {
+                true => walk(&entry.path(), &format!("{}/", name), out)?,
+                false if name == SOURCE_FILE => {}
+                false => {
+                    out.insert(name, sha256::hex(&std::fs::read(entry.path())?));
+                }
+            }
+        }
+        Ok(())
+    }
+    let mut out = BTreeMap::new();
+    walk(dir, "", &mut out)?;
+    Ok(out)
+}
+
+/// ロックファイルに記録があれば、`dir` の中身が記録と同じか確かめる
+fn verify(dir: &Path, name: &str) -> Result<()> {
+    let lock = Lock::load()?;
+    let Some(locked) = lock.packs.get(name) else {
+        return Ok(());
+    };
+    let current = hashes(dir)?;
+    let changed = locked
+        .files
+        .keys()
+        .chain(current.keys())
+        .find(|file| locked.files.get(*file) != current.get(*file));
+    let Some(changed) = changed else {
+        return Ok(());
+    };
+    if TRUSTED.load(Ordering::Relaxed) {
+        log::info!(
+            "アートパック '{}' の {} はインストールしたときと違いますが、--trust なので使います",
+            name,
+            changed
+        );
+        return Ok(());
+    }
+    bail!(
+        "アートパック '{}' の {} がインストールしたときと違います (改ざんされているかもしれません)。\
+         art update で入れ直すか、--trust を付けると使えます",
+        name,
+        changed
+    );
+}
+
+/// `dir` の pack.toml を読み、テンプレートがそろっているか確かめる
+pub fn read_manifest(dir: &Path) -> Result<Manifest> {
+    let path = dir.join(MANIFEST);
+    let manifest = std::fs::read_to_string(&path)
+        .with_context(|| format!("{} を読み込めません", path.display()))?;
+    let manifest: Manifest = toml::from_str(&manifest)
+        .with_context(|| format!("{} の読み込みに失敗しました", path.display()))?;
+
+    // ディレクトリの名前にするので、`.` や `..` のような名前も通さない
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    if manifest.name.is_empty() || !manifest.name.chars().all(valid) {
+        bail!(
+            "{}: name '{}' は使えません (英数字と _ と - だけ)",
+            path.display(),
+            manifest.name
+        );
+    }
+    if manifest.characters.is_empty() {
+        bail!("{}: characters がありません", path.display());
+    }
+    for (i, character) in manifest.characters.iter().enumerate() {
+        if manifest.characters[..i]
+            .iter()
+            .any(|other| other.name == character.name)
+        {
+            bail!(
+                "{}: キャラクター '{}' が重複しています",
+                path.display(),
+                character.name
+            );
+        }
+        let source = template(dir, &manifest, &character.template)?;
+        let rows = source.lines().count();
+        let slots = crate::template::Template::parse(&source).fields();
+        for (name, field) in &character.fields {
+            match slots.iter().find(|(slot, _)| slot == name) {
+                None => bail!(
+                    "{}: '{}' のテンプレートに欄 {} がありません",
+                    path.display(),
+                    character.name,
+                    name
+                ),
+                Some(&(_, height)) if field.height > height || field.height == 0 => bail!(
+                    "{}: '{}' の欄 {} の height は 1 から {} にしてください",
+                    path.display(),
+                    character.name,
+                    name,
+                    height
+                ),
+                Some(_) => {}
+            }
+        }
+        for variant in character.idle.iter().chain(&character.mouth) {
+            if template(dir, &manifest, variant)?.lines().count() != rows {
+                bail!(
+                    "{}: '{}' の {} はテンプレートと行数が違います",
+                    path.display(),
+                    character.name,
+                    variant.display()
+                );
+            }
+        }
+        if let Some(bubble) = character.bubble {
+            if bubble.top >= bubble.bottom || bubble.bottom >= rows {
+                bail!(
+                    "{}: '{}' の bubble がテンプレートの範囲外です",
+                    path.display(),
+                    character.name
+                );
+            }
+            if let Some(tail) = bubble.tail {
+                if tail.row <= bubble.top || tail.row >= bubble.bottom {
+                    bail!(
+                        "{}: '{}' の bubble の tail が吹き出しの枠の中にありません",
+                        path.display(),
+                        character.name
+                    );
+                }
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// `placeholders` の印を `$line1$`, `$line2$` に置き換えたテンプレート
+fn template(dir: &Path, manifest: &Manifest, file: &Path) -> Result<String> {
+    if file.is_absolute()
+        || file
+            .components()
+            .any(|c| c == std::path::Component::ParentDir)
+    {
+        bail!("テンプレート {} はパックの外を指しています", file.display());
+    }
+    let path = dir.join(file);
+    let mut template = std::fs::read_to_string(&path)
+        .with_context(|| format!("テンプレート {} を読み込めません", path.display()))?;
+    for (placeholder, slot) in manifest.placeholders.iter().zip(PLACEHOLDERS) {
+        template = template.replace(placeholder.as_str(), slot);
+    }
+    let parsed = crate::template::Template::parse(&template);
+    if parsed.line_slots() == 0 && !parsed.has_repeat() {
+        bail!(
+            "テンプレート {} におかずを置く印がありません",
+            path.display()
+        );
+    }
+    Ok(template.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// インストール済みのパックのディレクトリとマニフェスト (壊れたものは除く)
+pub fn list() -> Result<Vec<(PathBuf, Manifest)>> {
+    let mut packs = Vec::new();
+    let Some(root) = packs_dir().filter(|dir| dir.is_dir()) else {
+        return Ok(packs);
+    };
+    for entry in std::fs::read_dir(root)? {
+        let dir = entry?.path();
+        if dir.join(MANIFEST).is_file() {
+            match read_manifest(&dir) {
+                Ok(manifest) => packs.push((dir, manifest)),
+                Err(e) => log::warn!("壊れたパックを飛ばします: {:#}", crate::i18n::error(e)),
+            }
+        }
+    }
+    packs.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    Ok(packs)
+}
+
+/// `パック/キャラクター` または `キャラクター` でパックのアートを探す
+pub fn load(name: &str) -> Result<Option<Art>> {
+    let (pack, character) = match name.split_once('/') {
+        Some((pack, character)) => (Some(pack), character),
+        None => (None, name),
+    };
+    for (dir, manifest) in list()? {
+        if pack.is_some_and(|pack| pack != manifest.name) {
+            continue;
+        }
+        if let Some(found) = manifest.characters.iter().find(|c| c.name == character) {
+            verify(&dir, &manifest.name)?;
+            return Ok(Some(Art {
+                template: template(&dir, &manifest, &found.template)?,
+                bubble: found.bubble,
+                idle: found
+                    .idle
+                    .iter()
+                    .map(|idle| template(&dir, &manifest, idle))
+                    .collect::<Result<_>>()?,
+                mouth: found
+                    .mouth
+                    .as_ref()
+                    .map(|mouth| template(&dir, &manifest, mouth))
+                    .transpose()?,
+                fields: found.fields.clone(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// インストール元を記録するファイル (`art update` で使う)
+const SOURCE_FILE: &str = ".source";
+
+/// git の URL、tar.gz のアーカイブ (URL かローカル)、ローカルのディレクトリからパックを入れる
+pub fn install(source: &str) -> Result<Manifest> {
+    let root = packs_dir()
+        .ok_or_else(|| anyhow::anyhow!("データディレクトリを特定できません (HOME が未設定です)"))?;
+    let work = std::env::temp_dir().join(format!("fav_say_dragon-pack-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&work);
+    std::fs::create_dir_all(&work)?;
+    let result = fetch(source, &work).and_then(|dir| {
+        let manifest = read_manifest(&dir)?;
+        std::fs::create_dir_all(&root)?;
+        let dest = root.join(&manifest.name);
+        // 消すのは packs の直下のディレクトリだけ
+        if dest.parent() != Some(root.as_path()) || dest.file_name().is_none() {
+            bail!("パックを {} には入れられません", dest.display());
+        }
+        if dest.exists() {
+            std::fs::remove_dir_all(&dest)?;
+        }
+        copy_dir(&dir, &dest)?;
+        let _ = std::fs::remove_dir_all(dest.join(".git"));
+        std::fs::write(dest.join(SOURCE_FILE), source)?;
+        // 取ってきたパックだけ中身を記録する (ローカルのディレクトリは手元で直すことがある)
+        let mut lock = Lock::load()?;
+        match remote(source) {
+            true => {
+                let files = hashes(&dest)?;
+                log::debug!(
+                    "{} のハッシュを {} に記録しました",
+                    manifest.name,
+                    LOCK_FILE
+                );
+                let locked = LockedPack {
+                    source: source.to_string(),
+                    files,
+                };
+                lock.packs.insert(manifest.name.clone(), locked);
+            }
+            false => {
+                lock.packs.remove(&manifest.name);
+            }
+        }
+        lock.save()?;
+        Ok(manifest)
+    });
+    let _ = std::fs::remove_dir_all(&work);
+    result
+}
+
+pub fn remove(name: &str) -> Result<()> {
+    let (dir, _) = find(name)?;
+    std::fs::remove_dir_all(&dir)?;
+    let mut lock = Lock::load()?;
+    if lock.packs.remove(name).is_some() {
+        lock.save()?;
+    }
+    Ok(())
+}
+
+/// 記録したインストール元から入れ直す (`name` が無ければすべて)
+pub fn update(name: Option<&str>) -> Result<Vec<Manifest>> {
+    let packs = match name {
+        Some(name) => vec![find(name)?],
+        None => list()?,
+    };
+    let mut updated = Vec::new();
+    for (dir, manifest) in packs {
+        let source = match std::fs::read_to_string(dir.join(SOURCE_FILE)) {
+            Ok(source) => source,
+            Err(_) if name.is_none() => continue,
+            Err(_) => bail!("{} のインストール元がわかりません", manifest.name),
+        };
+        updated.push(install(source.trim())?);
+    }
+    Ok(updated)
+}
+
+fn find(name: &str) -> Result<(PathBuf, Manifest)> {
+    list()?
+        .into_iter()
+        .find(|(_, manifest)| manifest.name == name)
+        .ok_or_else(|| anyhow::anyhow!("パック '{}' はインストールされていません", name))
+}
+
+/// git か URL から取ってくるインストール元
+fn remote(source: &str) -> bool {
+    source.contains("://") || source.starts_with("git@")
+}
+
+/// URL から取ってくる tar.gz の上限 (バイト)
+const MAX_ARCHIVE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// `source` を `work` に取り出し、pack.toml のあるディレクトリを返す
+fn fetch(source: &str, work: &Path) -> Result<PathBuf> {
+    let is_archive = source.ends_with(".tar.gz") || source.ends_with(".tgz");
+    let is_url = source.contains("://");
+    let extracted = work.join("pack");
+    if is_archive {
+        let archive = match is_url {
+            true => {
+                let archive = work.join("pack.tar.gz");
+                fetch::download(source, &archive, MAX_ARCHIVE_BYTES)?;
+                archive
+            }
+            false => PathBuf::from(source),
+        };
+        std::fs::create_dir_all(&extracted)?;
+        run(std::process::Command::new("tar")
+            .arg("-xzf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&extracted))?;
+    } else if is_url || source.starts_with("git@") || source.ends_with(".git") {
+        run(std::process::Command::new("git")
+            // `-` で始まる source を git のオプションとして読ませない
+            .args(["clone", "--quiet", "--depth", "1", "--", source])
+            .arg(&extracted))?;
+    } else {
+        let dir = Path::new(source);
+        if !dir.is_dir() {
+            bail!(
+                "{} は git の URL、tar.gz、ディレクトリのどれでもありません",
+                source
+            );
+        }
+        return Ok(dir.to_path_buf());
+    }
+
+    // アーカイブの中身が 1 つのディレクトリにまとまっていることが多い
+    if extracted.join(MANIFEST).is_file() {
+        return Ok(extracted);
+    }
+    let entries: Vec<PathBuf> = std::fs::read_dir(&extracted)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    match &entries[..] {
+        [dir] if dir.join(MANIFEST).is_file() => Ok(dir.clone()),
+        _ => bail!("{} に {} が見つかりません", source, MANIFEST),
+    }
+}
+
+fn run(command: &mut std::process::Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .status()
+        .with_context(|| format!("{} を実行できません", program))?;
+    if !status.success() {
+        bail!("{} が失敗しました ({})", program, status);
+    }
+    Ok(())
+}
+
+/// シンボリックリンクはパックの外を指すことがあるのでたどらずに飛ばす
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            log::info!("シンボリックリンク {} は入れません", entry.path().display());
+        } else if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テスト用の一時ディレクトリに pack.toml とテンプレートを書く
+    fn pack(test: &str, manifest: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fav_say_dragon-pack-{}-{}",
+            std::process::id(),
+            test
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(MANIFEST), manifest).unwrap();
+        std::fs::write(dir.join("cat.txt"), "$line1$\n$line2$\n =^.^=\n").unwrap();
+        dir
+    }
+
+    fn manifest(name: &str) -> String {
+        format!(
+            "name = {:?}\n[[characters]]\nname = \"cat\"\ntemplate = \"cat.txt\"\n",
+            name
+        )
+    }
+
+    #[test]
+    fn reads_a_valid_manifest() {
+        let dir = pack("valid", &manifest("cats_2-x"));
+        let manifest = read_manifest(&dir).unwrap();
+        assert_eq!(manifest.name, "cats_2-x");
+        assert_eq!(manifest.characters[0].name, "cat");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_names_outside_the_pack_directory() {
+        for (i, name) in ["", ".", "..", "a/b", "a\\b", "../x", "ねこ", "a b"]
+            .iter()
+            .enumerate()
+        {
+            let dir = pack(&format!("name{}", i), &manifest(name));
+            assert!(read_manifest(&dir).is_err(), "{:?}", name);
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_templates_outside_the_pack() {
+        let dir = pack(
+            "escape",
+            "name = \"cats\"\n[[characters]]\nname = \"cat\"\ntemplate = \"../cat.txt\"\n",
+        );
+        assert!(read_manifest(&dir).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_broken_manifests() {
+        let broken = [
+            "name = \"cats\"\ncharacters = []\n",
+            "name = \"cats\"\nunknown = 1\n[[characters]]\nname = \"cat\"\ntemplate = \"cat.txt\"\n",
+            "name = \"cats\"\n[[characters]]\nname = \"cat\"\ntemplate = \"cat.txt\"\n\
+             [[characters]]\nname = \"cat\"\ntemplate = \"cat.txt\"\n",
+            "name = \"cats\"\n[[characters]]\nname = \"cat\"\ntemplate = \"cat.txt\"\n\
+             bubble = { top = 1, bottom = 9, width = 10 }\n",
+        ];
+        for (i, source) in broken.iter().enumerate() {
+            let dir = pack(&format!("broken{}", i), source);
+            assert!(read_manifest(&dir).is_err(), "{}", source);
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+}
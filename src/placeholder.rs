@@ -0,0 +1,167 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 置き換える値の作り方
+type Value = fn() -> String;
+
+const PLACEHOLDERS: &[(&str, Value)] = &[
+    ("{date}", || LocalTime::now().format("%Y-%m-%d")),
+    ("{time}", || LocalTime::now().format("%H:%M")),
+    ("{user}", user),
+    ("{hostname}", hostname),
+    ("{cwd}", cwd),
+];
+
+/// `{date}`, `{time}`, `{user}`, `{hostname}`, `{cwd}` を現在の値に置き換える
+pub fn expand(text: &str) -> String {
+    let mut expanded = text.to_string();
+    for (name, value) in PLACEHOLDERS {
+        if expanded.contains(name) {
+            expanded = expanded.replace(name, &value());
+        }
+    }
+    expanded
+}
+
+pub struct LocalTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0 が日曜日
+    pub weekday: u32,
+}
+
+impl LocalTime {
+    pub fn now() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Self::from_unix(secs)
+    }
+
+    #[cfg(unix)]
+    pub fn from_unix(secs: i64) -> Self {
+        let time = secs as libc::time_t;
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+            return Self::from_unix_utc(secs);
+        }
+        Self {
+            year: tm.tm_year as i64 + 1900,
+            month: tm.tm_mon as u32 + 1,
+            day: tm.tm_mday as u32,
+            hour: tm.tm_hour as u32,
+            minute: tm.tm_min as u32,
+            second: tm.tm_sec as u32,
+            weekday: tm.tm_wday as u32,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn from_unix(secs: i64) -> Self {
+        Self::from_unix_utc(secs)
+    }
+
+    /// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    fn from_unix_utc(secs: i64) -> Self {
+        let days = secs.div_euclid(86400);
+        let rem = secs.rem_euclid(86400) as u32;
+
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year,
+            month,
+            day,
+            hour: rem / 3600,
+            minute: rem / 60 % 60,
+            second: rem % 60,
+            // 1970-01-01 は木曜日
+            weekday: (days + 4).rem_euclid(7) as u32,
+        }
+    }
+
+    /// `%Y %m %d %H %M %S %a %%` を置き換える
+    pub fn format(&self, fmt: &str) -> String {
+        let mut s = String::new();
+        let mut chars = fmt.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                s.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => s.push_str(&format!("{:04}", self.year)),
+                Some('m') => s.push_str(&format!("{:02}", self.month)),
+                Some('d') => s.push_str(&format!("{:02}", self.day)),
+                Some('H') => s.push_str(&format!("{:02}", self.hour)),
+                Some('M') => s.push_str(&format!("{:02}", self.minute)),
+                Some('S') => s.push_str(&format!("{:02}", self.second)),
+                Some('a') => {
+                    let weekdays = match crate::i18n::current() {
+                        crate::i18n::Lang::Ja => JA_WEEKDAYS,
+                        crate::i18n::Lang::En => EN_WEEKDAYS,
+                    };
+                    s.push_str(weekdays[self.weekday as usize % 7]);
+                }
+                Some('%') => s.push('%'),
+                Some(c) => {
+                    s.push('%');
+                    s.push(c);
+                }
+                None => s.push('%'),
+            }
+        }
+        s
+    }
+}
+
+/// `%a` の曜日 (`--lang` で選ぶ)
+const JA_WEEKDAYS: [&str; 7] = ["日", "月", "火", "水", "木", "金", "土"];
+const EN_WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+#[cfg(unix)]
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return "localhost".to_string();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "localhost".to_string())
+}
+
+pub fn user() -> String {
+    ["USER", "LOGNAME", "USERNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 作業ディレクトリ (ホームの下なら `~` から)
+pub fn cwd() -> String {
+    let Ok(cwd) = std::env::current_dir() else {
+        return String::new();
+    };
+    let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+    match home.as_deref().and_then(|home| cwd.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~{}{}", std::path::MAIN_SEPARATOR, rest.display()),
+        None => cwd.display().to_string(),
+    }
+}
@@ -0,0 +1,402 @@
+use crate::raster::{Image, Rgb};
+
+/// 無圧縮 deflate ブロック 1 つの最大長
+const STORED_BLOCK: usize = 65535;
+/// deflate で後ろを参照できる距離
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// 一致を探すときにたどる候補の数 (多いほど縮むが遅くなる)
+const MAX_CHAIN: usize = 64;
+
+/// 長さの符号 257.. の最小の長さと追加ビット数
+#[rustfmt::skip]
+const LENGTHS: [(usize, u32); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    (11, 1), (13, 1), (15, 1), (17, 1), (19, 2), (23, 2), (27, 2), (31, 2),
+    (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4), (115, 4),
+    (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+
+/// 距離の符号の最小の距離と追加ビット数
+#[rustfmt::skip]
+const DISTANCES: [(usize, u32); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2),
+    (17, 3), (25, 3), (33, 4), (49, 4), (65, 5), (97, 5), (129, 6), (193, 6),
+    (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9), (2049, 10), (3073, 10),
+    (4097, 11), (6145, 11), (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+
+/// 8bit RGB の PNG にする
+pub fn encode(image: &Image) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((image.width * 3 + 1) * image.height);
+    for row in image.pixels.chunks(image.width.max(1)) {
+        // フィルタなし
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut ihdr = Vec::new();
+    ihdr.extend((image.width as u32).to_be_bytes());
+    ihdr.extend((image.height as u32).to_be_bytes());
+    // ビット深度 8, RGB, deflate, 標準フィルタ, インターレースなし
+    ihdr.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &ihdr);
+    chunk(&mut png, b"IDAT", &zlib(&raw));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// 色の表 `colors` (256 色まで) とその番号 `indices` でパレット形式の PNG にする。16 色までなら
+/// 1 画素を 4 ビットに詰める
+pub fn encode_indexed(image: &Image, colors: &[Rgb], indices: &[u8]) -> Vec<u8> {
+    let depth: u8 = if colors.len() <= 16 { 4 } else { 8 };
+    let mut raw = Vec::new();
+    for row in indices.chunks(image.width.max(1)) {
+        raw.push(0);
+        match depth {
+            4 => raw.extend(
+                row.chunks(2)
+                    .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0)),
+            ),
+            _ => raw.extend(row),
+        }
+    }
+
+    let mut ihdr = Vec::new();
+    ihdr.extend((image.width as u32).to_be_bytes());
+    ihdr.extend((image.height as u32).to_be_bytes());
+    // パレット, deflate, 標準フィルタ, インターレースなし
+    ihdr.extend([depth, 3, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &ihdr);
+    chunk(&mut png, b"PLTE", &colors.concat());
+    chunk(&mut png, b"IDAT", &zlib(&raw));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// 固定ハフマン符号の deflate で圧縮する (縮まなければ stored ブロックにする)
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let compressed = deflate_fixed(data);
+    match compressed.len() < data.len() + data.len() / STORED_BLOCK * 5 + 5 {
+        true => out.extend(compressed),
+        false => stored(&mut out, data),
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/// 下位ビットから詰める deflate のビット列
+#[derive(Default)]
+struct Bits {
+    out: Vec<u8>,
+    buffer: u64,
+    len: u32,
+}
+
+impl Bits {
+    fn push(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.len;
+        self.len += bits;
+        while self.len >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// ハフマン符号は上位ビットから書く
+    fn code(&mut self, code: u32, bits: u32) {
+        self.push(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    /// 固定ハフマン符号のリテラルと長さの記号
+    fn symbol(&mut self, symbol: usize) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+/// 3 バイトの並びのハッシュで前の出現をたどり、一致を長さと距離にする
+fn deflate_fixed(data: &[u8]) -> Vec<u8> {
+    const HASH_BITS: u32 = 15;
+    let hash = |i: usize| {
+        let key = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], 0]);
+        (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+    };
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let insert = |i: usize, head: &mut Vec<usize>, prev: &mut Vec<usize>| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(i);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut bits = Bits::default();
+    // 最後のブロックで、固定ハフマン符号
+    bits.push(1, 1);
+    bits.push(1, 2);
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let limit = (data.len() - i).min(MAX_MATCH);
+            let mut candidate = head[hash(i)];
+            let mut chain = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN {
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + limit])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best.0 {
+                    best = (len, i - candidate);
+                    if len == limit {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW];
+                // 窓から外れて上書きされた位置はたどらない
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+        match best {
+            (len, distance) if len >= MIN_MATCH => {
+                let code = LENGTHS
+                    .iter()
+                    .rposition(|(base, _)| *base <= len)
+                    .unwrap_or(0);
+                let (base, extra) = LENGTHS[code];
+                bits.symbol(257 + code);
+                bits.push((len - base) as u32, extra);
+                let code = DISTANCES
+                    .iter()
+                    .rposition(|(base, _)| *base <= distance)
+                    .unwrap_or(0);
+                let (base, extra) = DISTANCES[code];
+                bits.code(code as u32, 5);
+                bits.push((distance - base) as u32, extra);
+                for j in i..i + len {
+                    insert(j, &mut head, &mut prev);
+                }
+                i += len;
+            }
+            _ => {
+                bits.symbol(data[i] as usize);
+                insert(i, &mut head, &mut prev);
+                i += 1;
+            }
+        }
+    }
+    bits.symbol(256);
+    bits.finish()
+}
+
+/// 無圧縮の stored ブロックで格納する
+fn stored(out: &mut Vec<u8>, data: &[u8]) {
+    let mut blocks = data.chunks(STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none() as u8;
+        let len = block.len() as u16;
+        out.push(last);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => 0xedb8_8320 ^ (crc >> 1),
+                _ => crc >> 1,
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 下位ビットから読む deflate のビット列
+    struct Reader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl Reader<'_> {
+        fn bits(&mut self, n: u32) -> u32 {
+            let mut value = 0;
+            for i in 0..n {
+                value |= ((self.data[self.pos / 8] >> (self.pos % 8)) as u32 & 1) << i;
+                self.pos += 1;
+            }
+            value
+        }
+    }
+
+    /// 固定ハフマン符号と stored ブロックだけを読む zlib の展開
+    fn inflate(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        let mut reader = Reader {
+            data: &zlib[2..zlib.len() - 4],
+            pos: 0,
+        };
+        let mut out = Vec::new();
+        loop {
+            let last = reader.bits(1);
+            match reader.bits(2) {
+                0 => {
+                    reader.pos = reader.pos.div_ceil(8) * 8;
+                    let len = reader.bits(16) as usize;
+                    assert_eq!(reader.bits(16) as usize, !len & 0xffff);
+                    for _ in 0..len {
+                        out.push(reader.bits(8) as u8);
+                    }
+                }
+                1 => loop {
+                    // 上位ビットから 1 ビットずつ読んで固定ハフマン符号を引く
+                    let mut code = 0;
+                    for _ in 0..7 {
+                        code = code << 1 | reader.bits(1);
+                    }
+                    let symbol = match code {
+                        0..=0x17 => code + 256,
+                        _ => {
+                            code = code << 1 | reader.bits(1);
+                            match code {
+                                0x30..=0xbf => code - 0x30,
+                                0xc0..=0xc7 => code - 0xc0 + 280,
+                                _ => (code << 1 | reader.bits(1)) - 0x190 + 144,
+                            }
+                        }
+                    } as usize;
+                    match symbol {
+                        0..=255 => out.push(symbol as u8),
+                        256 => break,
+                        _ => {
+                            let (base, extra) = LENGTHS[symbol - 257];
+                            let len = base + reader.bits(extra) as usize;
+                            let code = (reader.bits(5).reverse_bits() >> 27) as usize;
+                            let (base, extra) = DISTANCES[code];
+                            let distance = base + reader.bits(extra) as usize;
+                            for _ in 0..len {
+                                out.push(out[out.len() - distance]);
+                            }
+                        }
+                    }
+                },
+                btype => panic!("unexpected block type {btype}"),
+            }
+            if last == 1 {
+                break;
+            }
+        }
+        assert_eq!(zlib[zlib.len() - 4..], adler32(&out).to_be_bytes());
+        out
+    }
+
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x1234_5678u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_through_inflate() {
+        let mut scanlines = Vec::new();
+        for y in 0..300u32 {
+            scanlines.push(0);
+            for x in 0..500u32 {
+                scanlines.extend([(x / 40) as u8, (y / 30) as u8, 0x80]);
+            }
+        }
+        let cases = [
+            Vec::new(),
+            b"a".to_vec(),
+            b"abcabcabcabcabcabcabc".to_vec(),
+            vec![0; 100_000],
+            (0..=255).cycle().take(70_000).collect(),
+            noise(1_000),
+            scanlines,
+        ];
+        for data in cases {
+            assert_eq!(inflate(&zlib(&data)), data, "len {}", data.len());
+        }
+    }
+
+    #[test]
+    fn compresses_repeated_pixels() {
+        let image = Image {
+            width: 504,
+            height: 272,
+            pixels: vec![[0x10, 0x20, 0x30]; 504 * 272],
+        };
+        let raw = 272 * (1 + 504 * 3);
+        assert!(encode(&image).len() < raw / 50);
+    }
+
+    #[test]
+    fn falls_back_to_stored_blocks_for_noise() {
+        let data = noise(10_000);
+        let out = zlib(&data);
+        assert_eq!(out[2] & 0b110, 0);
+        assert_eq!(out.len(), 2 + 5 + data.len() + 4);
+        assert_eq!(inflate(&out), data);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{countdown, duration, redraw, render_say, RenderOptions};
+use anyhow::Result;
+use console::Term;
+use std::io::Write;
+use std::time::Duration;
+
+struct Session {
+    side_dish: &'static str,
+    label: &'static str,
+    length: Duration,
+}
+
+pub fn pomodoro(
+    work: Duration,
+    rest: Duration,
+    cycles: Option<u32>,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let mut term = Term::stdout();
+    let work = Session {
+        side_dish: "作業開始！",
+        label: "作業",
+        length: work,
+    };
+    let rest = Session {
+        side_dish: "休憩しよう",
+        label: "休憩",
+        length: rest,
+    };
+
+    let mut cycle = 1;
+    while cycles.is_none_or(|cycles| cycle <= cycles) {
+        let progress = match cycles {
+            Some(cycles) => format!("{}/{}", cycle, cycles),
+            None => format!("{}", cycle),
+        };
+        run_session(&mut term, &work, &progress, opts)?;
+        if cycles != Some(cycle) {
+            run_session(&mut term, &rest, &progress, opts)?;
+        }
+        cycle += 1;
+    }
+
+    let terminal_width = crate::terminal::width(&term);
+    let lines = render_say(
+        "おつかれさま！",
+        Some("ポモドーロ完了"),
+        terminal_width,
+        opts,
+    )?;
+    redraw(&mut term, &lines, opts)?;
+    ring_bell(&mut term)?;
+
+    Ok(())
+}
+
+fn run_session(
+    term: &mut Term,
+    session: &Session,
+    progress: &str,
+    opts: &RenderOptions,
+) -> Result<()> {
+    ring_bell(term)?;
+    countdown::tick(session.length, |remaining| {
+        let caption = format!(
+            "{} 残り {} ({})",
+            session.label,
+            duration::format_clock(remaining),
+            progress
+        );
+        let terminal_width = crate::terminal::width(term);
+        redraw(
+            term,
+            &render_say(session.side_dish, Some(&caption), terminal_width, opts)?,
+            opts,
+        )
+    })
+}
+
+fn ring_bell(term: &mut Term) -> Result<()> {
+    write!(term, "\x07")?;
+    term.flush()?;
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! ponysay のポニーファイル (`.pony`) をキャラクターとして読む
+//!
+//! `$$$` で囲まれたメタデータを読み飛ばし、`$balloon…$` の位置に吹き出しを置き、
+//! 吹き出しへの線 `$\$`, `$/$` を文字に戻す
+
+use crate::art::{Art, Bubble};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// ponysay がポニーを置く場所
+const PONY_DIRS: &[&str] = &[
+    "/usr/share/ponysay/ponies",
+    "/usr/local/share/ponysay/ponies",
+    "/opt/homebrew/share/ponysay/ponies",
+];
+
+/// 吹き出しの枠を含む表示幅
+const BALLOON_WIDTH: usize = 22;
+
+#[rustfmt::skip]
+const BALLOON: [&str; 4] = [
+    "r'￣￣￣￣￣￣￣￣￣ヽ",
+    "|$line1$|",
+    "|$line2$|",
+    "ゝ＿＿＿＿＿＿＿＿__ノ",
+];
+
+/// `name` が .pony ファイルのパスか、ponysay のディレクトリにあるポニーの名前ならアートにする
+pub fn load(name: &str) -> Result<Option<Art>> {
+    let path = match name.ends_with(".pony") {
+        true => Some(PathBuf::from(name)),
+        false => pony_dirs()
+            .map(|dir| dir.join(format!("{}.pony", name)))
+            .find(|p| p.is_file()),
+    };
+    match path.filter(|path| path.is_file()) {
+        Some(path) => parse_file(&path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn pony_dirs() -> impl Iterator<Item = PathBuf> {
+    let user = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".local/share/ponysay/ponies"));
+    user.into_iter().chain(PONY_DIRS.iter().map(PathBuf::from))
+}
+
+fn parse_file(path: &Path) -> Result<Art> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("{} を読み込めません", path.display()))?;
+    Ok(parse(&source))
+}
+
+fn parse(source: &str) -> Art {
+    let mut lines = source.lines().peekable();
+    // メタデータ
+    if lines.peek().is_some_and(|line| line.trim() == "$$$") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line.trim() == "$$$" {
+                break;
+            }
+        }
+    }
+
+    let mut template = Vec::new();
+    let mut bubble = None;
+    for line in lines {
+        // `$変数=値$` の定義行
+        let trimmed = line.trim();
+        if trimmed.starts_with('$') && trimmed.ends_with('$') && trimmed.contains('=') {
+            continue;
+        }
+        match line.find("$balloon") {
+            Some(i) if bubble.is_none() => {
+                let indent = console::measure_text_width(&line[..i]);
+                if indent == 0 {
+                    bubble = Some(Bubble {
+                        top: template.len(),
+                        bottom: template.len() + BALLOON.len() - 1,
+                        width: BALLOON_WIDTH,
+                        tail: None,
+                    });
+                }
+                let rest = line[i + 1..]
+                    .find('$')
+                    .map_or("", |end| &line[i + end + 2..]);
+                for (n, balloon) in BALLOON.iter().enumerate() {
+                    let tail = if n == 0 { escape(rest) } else { String::new() };
+                    template.push(format!("{}{}{}", " ".repeat(indent), balloon, tail));
+                }
+            }
+            _ => template.push(escape(&line.replace("$\\$", "\\").replace("$/$", "/"))),
+        }
+    }
+    while template.last().is_some_and(|line| line.trim().is_empty()) {
+        template.pop();
+    }
+
+    Art {
+        template: template.join("\n"),
+        bubble,
+        idle: Vec::new(),
+        mouth: None,
+        fields: std::collections::BTreeMap::new(),
+    }
+}
+
+/// アート中の `$` をテンプレートの差し込み口と見なされないようにする
+fn escape(line: &str) -> String {
+    line.replace('$', "$$")
+}
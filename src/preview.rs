@@ -0,0 +1,120 @@
+use crate::{art, i18n, render_say, template, write_frame, RenderOptions, Script};
+use anyhow::Result;
+use console::Term;
+use std::io::Write;
+use std::path::Path;
+
+/// キャラクターの確認に使うおかずとキャプション
+const DUMMY_SIDE_DISH: &str = "からあげ\nハンバーグ";
+const DUMMY_CAPTION: &str = "キャプション";
+
+/// `target` が既存のファイルならスクリプト、そうでなければキャラクター名として最初のフレームを表示する
+pub fn preview(target: &str, opts: &RenderOptions) -> Result<()> {
+    let path = Path::new(target);
+    let (script, opts) = match path.is_file() {
+        true => (Some(Script::load(path)?), opts.clone()),
+        false => (None, opts.with_character(target)?),
+    };
+    let (side_dish, caption) = match script
+        .as_ref()
+        .and_then(|s| s.timeline().into_iter().next())
+    {
+        Some(frame) => (
+            frame.side_dish.get(opts.lang).to_string(),
+            frame.caption.get(opts.lang).to_string(),
+        ),
+        None => (DUMMY_SIDE_DISH.to_string(), DUMMY_CAPTION.to_string()),
+    };
+
+    let mut term = Term::stdout();
+    write_frame(
+        &mut term,
+        &render_say(&side_dish, Some(&caption), 0, &opts)?,
+        &opts,
+    )?;
+    writeln!(term)?;
+
+    let template = template::Template::parse(&opts.art.template);
+    // 吹き出しの 1 行に入る表示幅
+    let bubble_columns = opts.bubble_chars + art::BUBBLE_PADDING;
+    let blank = vec![" ".repeat(bubble_columns); template.line_slots().max(1)];
+    let lines = template.render(&blank);
+    let width = lines
+        .iter()
+        .map(|pieces| {
+            pieces
+                .iter()
+                .map(|piece| match piece {
+                    template::Piece::Art(text) | template::Piece::Slot(text) => {
+                        console::measure_text_width(text)
+                    }
+                })
+                .sum::<usize>()
+        })
+        .max()
+        .unwrap_or(0);
+    let mut info = vec![format!("アート: 幅 {} x 高さ {}", width, lines.len())];
+    info.push(match opts.art.bubble {
+        Some(bubble) => format!(
+            "吹き出し: {}-{} 行目, 幅 {}",
+            bubble.top + 1,
+            bubble.bottom + 1,
+            bubble.width
+        ),
+        None => "吹き出し: なし (テーマの枠線は使われません)".to_string(),
+    });
+    info.push(match template.has_repeat() {
+        true => format!(
+            "容量: {} 行 + 繰り返し x 表示幅 {}",
+            template.line_slots(),
+            bubble_columns
+        ),
+        false => format!(
+            "容量: {} 行 x 表示幅 {} (1 行のおかずは {} 文字で折り返し)",
+            template.line_slots(),
+            bubble_columns,
+            opts.bubble_chars
+        ),
+    });
+    for (name, field) in opts.art.fields() {
+        info.push(format!(
+            "欄 {}: 幅 {} x {} 行",
+            name, field.width, field.height
+        ));
+    }
+    for (row, column, name) in template.slot_positions() {
+        info.push(format!("${}$: {} 行目 {} 列目", name, row + 1, column + 1));
+    }
+    info.push(format!("キャプション: 幅 {}", opts.caption_width));
+    for line in &info {
+        writeln!(term, "{}", i18n::tr(line))?;
+    }
+
+    if let Some(script) = script {
+        let frames = script.timeline();
+        let interval = script.interval.map_or("指定なし".to_string(), |interval| {
+            format!("{} ms", interval)
+        });
+        let summary = format!("フレーム: {} (インターバル {})", frames.len(), interval);
+        writeln!(term, "{}", i18n::tr(&summary))?;
+        for (i, frame) in frames.iter().enumerate() {
+            let side_dish = frame.side_dish.get(opts.lang);
+            let too_wide = side_dish
+                .lines()
+                .any(|line| console::measure_text_width(line) > bubble_columns);
+            let too_many =
+                !template.has_repeat() && side_dish.lines().count() > template.line_slots();
+            if too_many || too_wide {
+                let warning = format!("警告: {} 枚目のおかずは吹き出しに収まりません", i + 1);
+                writeln!(term, "{}", i18n::tr(&warning))?;
+            }
+            if console::measure_text_width(frame.caption.get(opts.lang)) > opts.caption_width {
+                let warning = format!("警告: {} 枚目のキャプションは幅を超えます", i + 1);
+                writeln!(term, "{}", i18n::tr(&warning))?;
+            }
+        }
+    }
+    term.flush()?;
+
+    Ok(())
+}
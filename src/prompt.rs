@@ -0,0 +1,89 @@
+//! `prompt-segment` のシェルのプロンプト向けの短い断片
+//!
+//! プロンプトのたびに呼ばれるので、端末への問い合わせやテーマの読み込みをせずにすぐ返す。
+//! おかずは時刻から決めるので、同じ時間帯のプロンプトでは同じおかずが出る
+
+use crate::rng::Rng;
+use console::Style;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// PS1 用に色のシーケンスを `\[ \]` で囲む
+    Bash,
+    /// PROMPT 用に色のシーケンスを `%{ %}` で囲む
+    Zsh,
+    /// 囲まない (starship の custom コマンドなど)
+    Plain,
+}
+
+impl Shell {
+    fn wrap(self, sequence: &str) -> String {
+        match self {
+            Shell::Bash => format!("\\[{}\\]", sequence),
+            Shell::Zsh => format!("%{{{}%}}", sequence),
+            Shell::Plain => sequence.to_string(),
+        }
+    }
+}
+
+pub struct Segment<'a> {
+    pub dishes: &'a [String],
+    /// この時間ごとにおかずを替える
+    pub rotate: Duration,
+    pub shell: Shell,
+    pub ascii: bool,
+    pub art_color: &'a str,
+    pub dish_color: &'a str,
+}
+
+impl Segment<'_> {
+    pub fn render(&self) -> String {
+        let glyph = match self.ascii {
+            true => "<:3=",
+            false => "🐉",
+        };
+        let dish = self.pick();
+        // 出力先は端末ではないので、色は常に付ける
+        let paint = |color: &str, text: &str| {
+            let styled = Style::from_dotted_str(color)
+                .force_styling(true)
+                .apply_to(text)
+                .to_string();
+            self.protect(&styled)
+        };
+        format!(
+            "{} {}",
+            paint(self.art_color, glyph),
+            paint(self.dish_color, &dish)
+        )
+    }
+
+    fn pick(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let slot = now / self.rotate.as_secs().max(1);
+        let dish = match self.dishes.len() {
+            0 => "",
+            n => &self.dishes[Rng::new(slot).below(n)],
+        };
+        dish.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// 色のシーケンスを表示幅に数えないようにシェルの印で囲む
+    fn protect(&self, styled: &str) -> String {
+        let mut out = String::new();
+        let mut rest = styled;
+        while let Some(start) = rest.find('\x1b') {
+            out.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('m')
+                .map_or(rest.len(), |end| start + end + 1);
+            out.push_str(&self.shell.wrap(&rest[start..end]));
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
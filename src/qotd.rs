@@ -0,0 +1,59 @@
+//! 今日の名言
+//!
+//! 日付から選ぶので、同じ日のうちは何度開いても同じ名言になる
+
+use crate::{config, placeholder::LocalTime, rng::Rng, say, RenderOptions, Text};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// 組み込みの名言
+const BUILTIN: &str = include_str!("quotes.toml");
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Quotes {
+    quotes: Vec<Quote>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Quote {
+    text: Text,
+    author: Option<Text>,
+}
+
+/// `path` が無ければ設定ディレクトリの quotes.toml があればそれも候補に加える
+pub fn qotd(path: Option<&Path>, builtin: bool, opts: &RenderOptions) -> Result<()> {
+    let mut quotes = match builtin {
+        true => parse(BUILTIN)?,
+        false => Vec::new(),
+    };
+    let user = path
+        .map(Path::to_path_buf)
+        .or_else(|| Some(config::config_dir()?.join("quotes.toml")).filter(|path| path.exists()));
+    if let Some(user) = user {
+        let source = std::fs::read_to_string(&user)
+            .with_context(|| format!("{} を読み込めません", user.display()))?;
+        quotes.extend(
+            parse(&source)
+                .with_context(|| format!("{} の読み込みに失敗しました", user.display()))?,
+        );
+    }
+    if quotes.is_empty() {
+        bail!("名言がありません");
+    }
+
+    let today = LocalTime::now();
+    let seed = today.year as u64 * 10000 + today.month as u64 * 100 + today.day as u64;
+    let quote = &quotes[Rng::new(seed).below(quotes.len())];
+    let caption = quote
+        .author
+        .as_ref()
+        .map(|author| format!("— {}", author.get(opts.lang)));
+    say(quote.text.get(opts.lang), caption.as_deref(), None, opts)
+}
+
+fn parse(source: &str) -> Result<Vec<Quote>> {
+    Ok(toml::from_str::<Quotes>(source)?.quotes)
+}
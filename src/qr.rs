@@ -0,0 +1,558 @@
+use crate::compositor::{z, Compositor, Layer};
+use anyhow::{bail, Result};
+use console::{Alignment, Style};
+
+/// `(ブロックあたりの誤り訂正コード語数, [(ブロック数, データコード語数)])`
+type Version = (usize, &'static [(usize, usize)]);
+
+/// 誤り訂正レベル M のバージョン 1-10
+const VERSIONS: [Version; 10] = [
+    (10, &[(1, 16)]),
+    (16, &[(1, 28)]),
+    (26, &[(1, 44)]),
+    (18, &[(2, 32)]),
+    (24, &[(2, 43)]),
+    (16, &[(4, 27)]),
+    (18, &[(4, 31)]),
+    (22, &[(2, 38), (2, 39)]),
+    (22, &[(3, 36), (2, 37)]),
+    (26, &[(4, 43), (1, 44)]),
+];
+
+/// 位置合わせパターンの中心座標
+const ALIGNMENTS: [&[usize]; 10] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+/// 周囲の余白 (モジュール数)
+const QUIET_ZONE: usize = 2;
+
+/// 上下 2 モジュールを半角ブロック 1 文字にまとめた QR コード
+pub fn render(text: &str) -> Result<Vec<String>> {
+    let modules = encode(text.as_bytes())?;
+    let size = modules.len();
+    let dark = |x: usize, y: usize| {
+        x >= QUIET_ZONE
+            && y >= QUIET_ZONE
+            && x - QUIET_ZONE < size
+            && y - QUIET_ZONE < size
+            && modules[y - QUIET_ZONE][x - QUIET_ZONE]
+    };
+
+    // 端末の背景色に関係なく読めるよう白地に黒で描く
+    let style = Style::new().black().on_white();
+    let full = size + QUIET_ZONE * 2;
+    Ok((0..full)
+        .step_by(2)
+        .map(|y| {
+            let line: String = (0..full)
+                .map(|x| match (dark(x, y), dark(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                })
+                .collect();
+            style.apply_to(line).to_string()
+        })
+        .collect())
+}
+
+/// ドラゴンと QR コードの間の桁数
+const GAP: usize = 2;
+
+/// ドラゴンの右に QR コードを並べ、その下にラベルを置く
+pub fn beside(art: Vec<String>, qr: Vec<String>, label: Option<&str>) -> Compositor {
+    let qr_width = qr
+        .first()
+        .map(|line| console::measure_text_width(line))
+        .unwrap_or(0);
+    let mut right = qr;
+    if let Some(label) = label {
+        right.push(console::pad_str(label, qr_width, Alignment::Center, None).to_string());
+    }
+
+    let mut frame = Compositor::new();
+    let character = Layer::new(z::CHARACTER, art);
+    let x = character.right() + GAP;
+    frame.push(character);
+    frame.push(Layer::new(z::BESIDE, right).at(x, 0).opaque());
+    frame
+}
+
+/// バイトモード・誤り訂正レベル M で符号化し、モジュールの行列を返す
+fn encode(data: &[u8]) -> Result<Vec<Vec<bool>>> {
+    let Some(version) = (1..=VERSIONS.len()).find(|v| data.len() <= capacity(*v)) else {
+        bail!(
+            "QR コードにするには長すぎます (最大 {} バイト)",
+            capacity(VERSIONS.len())
+        );
+    };
+    let codewords = codewords(data, version);
+
+    let mut qr = Matrix::new(version);
+    qr.draw_function_patterns();
+    qr.draw_codewords(&codewords);
+
+    let mask = (0..8)
+        .min_by_key(|mask| {
+            let mut candidate = qr.clone();
+            candidate.apply_mask(*mask);
+            candidate.draw_format(*mask);
+            candidate.penalty()
+        })
+        .unwrap_or(0);
+    qr.apply_mask(mask);
+    qr.draw_format(mask);
+
+    Ok(qr.modules)
+}
+
+fn data_codewords(version: usize) -> usize {
+    VERSIONS[version - 1]
+        .1
+        .iter()
+        .map(|(blocks, len)| blocks * len)
+        .sum()
+}
+
+/// モード指示子 4 ビットと文字数指示子を除いた容量
+fn capacity(version: usize) -> usize {
+    let count_bits = if version < 10 { 8 } else { 16 };
+    (data_codewords(version) * 8 - 4 - count_bits) / 8
+}
+
+/// データを詰めて誤り訂正コード語を付け、ブロックを交互に並べる
+fn codewords(data: &[u8], version: usize) -> Vec<u8> {
+    let mut bits = BitBuffer::default();
+    bits.push(0b0100, 4);
+    bits.push(data.len() as u32, if version < 10 { 8 } else { 16 });
+    for byte in data {
+        bits.push(*byte as u32, 8);
+    }
+    let capacity = data_codewords(version) * 8;
+    bits.push(0, (capacity - bits.len).min(4));
+    bits.push(0, (8 - bits.len % 8) % 8);
+    for pad in [0xec, 0x11].into_iter().cycle() {
+        if bits.len >= capacity {
+            break;
+        }
+        bits.push(pad, 8);
+    }
+
+    let (ec_len, groups) = VERSIONS[version - 1];
+    let generator = rs_generator(ec_len);
+    let mut blocks = Vec::new();
+    let mut rest = bits.bytes.as_slice();
+    for (count, len) in groups {
+        for _ in 0..*count {
+            let (block, tail) = rest.split_at(*len);
+            blocks.push((block.to_vec(), rs_remainder(block, &generator)));
+            rest = tail;
+        }
+    }
+
+    let mut result = Vec::new();
+    let longest = blocks.iter().map(|(data, _)| data.len()).max().unwrap_or(0);
+    for i in 0..longest {
+        result.extend(blocks.iter().filter_map(|(data, _)| data.get(i)));
+    }
+    for i in 0..ec_len {
+        result.extend(blocks.iter().map(|(_, ec)| ec[i]));
+    }
+    result
+}
+
+#[derive(Default)]
+struct BitBuffer {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value >> i & 1 != 0 {
+                let last = self.bytes.len() - 1;
+                self.bytes[last] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// GF(256) (既約多項式 0x11d) での積
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut result = 0u8;
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1d } else { 0 };
+        b >>= 1;
+    }
+    result
+}
+
+/// (x - α^0)(x - α^1)...(x - α^(n-1)) の係数 (最高次を除く)
+fn rs_generator(degree: usize) -> Vec<u8> {
+    let mut generator = vec![0u8; degree];
+    generator[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            generator[j] = gf_mul(generator[j], root);
+            if j + 1 < degree {
+                generator[j] ^= generator[j + 1];
+            }
+        }
+        root = gf_mul(root, 2);
+    }
+    generator
+}
+
+fn rs_remainder(data: &[u8], generator: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0u8; generator.len()];
+    for byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (r, g) in remainder.iter_mut().zip(generator) {
+            *r ^= gf_mul(*g, factor);
+        }
+    }
+    remainder
+}
+
+#[derive(Clone)]
+struct Matrix {
+    version: usize,
+    size: usize,
+    modules: Vec<Vec<bool>>,
+    /// 機能パターンなどマスクをかけない場所
+    reserved: Vec<Vec<bool>>,
+}
+
+impl Matrix {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        Self {
+            version,
+            size,
+            modules: vec![vec![false; size]; size],
+            reserved: vec![vec![false; size]; size],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.reserved[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+        for i in 0..size {
+            self.set(6, i, i % 2 == 0);
+            self.set(i, 6, i % 2 == 0);
+        }
+
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if x < 0 || y < 0 || x >= size as i32 || y >= size as i32 {
+                        continue;
+                    }
+                    let distance = dx.abs().max(dy.abs());
+                    self.set(x as usize, y as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+
+        let positions = ALIGNMENTS[self.version - 1];
+        let last = positions.len().saturating_sub(1);
+        for (i, cx) in positions.iter().enumerate() {
+            for (j, cy) in positions.iter().enumerate() {
+                // 位置検出パターンと重なる 3 隅には置かない
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set(
+                            (*cx as i32 + dx) as usize,
+                            (*cy as i32 + dy) as usize,
+                            distance != 1,
+                        );
+                    }
+                }
+            }
+        }
+
+        // 形式情報の場所を確保する
+        self.draw_format(0);
+
+        if self.version >= 7 {
+            let mut remainder = self.version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+            }
+            let bits = (self.version as u32) << 12 | remainder;
+            for i in 0..18 {
+                let dark = bits >> i & 1 != 0;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set(a, b, dark);
+                self.set(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format(&mut self, mask: u32) {
+        // 誤り訂正レベル M は 00
+        let data = mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 != 0;
+
+        let size = self.size;
+        for i in 0..=5 {
+            self.set(8, i, bit(i));
+        }
+        self.set(8, 7, bit(6));
+        self.set(8, 8, bit(7));
+        self.set(7, 8, bit(8));
+        for i in 9..15 {
+            self.set(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set(8, size - 15 + i, bit(i));
+        }
+        self.set(8, size - 8, true);
+    }
+
+    /// 右下から 2 列ずつジグザグに詰める
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.reserved[y][x] && i < codewords.len() * 8 {
+                        self.modules[y][x] = codewords[i / 8] >> (7 - i % 8) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.reserved[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    /// 読み取りにくさの評価値 (小さいほど良い)
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let rows: Vec<Vec<bool>> = self.modules.clone();
+        let columns: Vec<Vec<bool>> = (0..size)
+            .map(|x| (0..size).map(|y| self.modules[y][x]).collect())
+            .collect();
+
+        let mut penalty = 0;
+        for line in rows.iter().chain(columns.iter()) {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += 3 + run - 5;
+                }
+                run = 1;
+            }
+
+            const FINDER: [bool; 11] = [
+                true, false, true, true, true, false, true, false, false, false, false,
+            ];
+            for window in line.windows(FINDER.len()) {
+                if window == FINDER || window.iter().rev().eq(FINDER.iter()) {
+                    penalty += 40;
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = rows[y][x];
+                if rows[y][x + 1] == color && rows[y + 1][x] == color && rows[y + 1][x + 1] == color
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = rows.iter().flatten().filter(|dark| **dark).count();
+        let k = (dark * 20)
+            .abs_diff(total * 10)
+            .div_ceil(total)
+            .saturating_sub(1);
+        penalty + k * 10
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_smallest_version() {
+        assert_eq!(capacity(1), 14);
+        assert_eq!(capacity(10), 213);
+        assert_eq!(encode(b"").unwrap().len(), 21);
+        assert_eq!(encode(&[b'a'; 14]).unwrap().len(), 21);
+        assert_eq!(encode(&[b'a'; 15]).unwrap().len(), 25);
+        assert_eq!(encode(&[b'a'; 213]).unwrap().len(), 57);
+        assert!(encode(&[b'a'; 214]).is_err());
+        assert!(render(&"a".repeat(214)).is_err());
+    }
+
+    #[test]
+    fn encodes_every_length_without_panicking() {
+        for len in 0..=capacity(VERSIONS.len()) {
+            let modules = encode(&vec![b'x'; len]).unwrap();
+            assert!(modules.iter().all(|row| row.len() == modules.len()));
+        }
+    }
+
+    #[test]
+    fn error_correction_divides_by_the_generator() {
+        for version in 1..=VERSIONS.len() {
+            let data = vec![0xa5; capacity(version)];
+            let (ec_len, groups) = VERSIONS[version - 1];
+            let generator = rs_generator(ec_len);
+            let (_, len) = groups[0];
+            let interleaved = codewords(&data, version);
+            let blocks: usize = groups.iter().map(|(n, _)| n).sum();
+            assert_eq!(interleaved.len(), data_codewords(version) + ec_len * blocks);
+            // 1 つ目のブロックのデータと誤り訂正コード語をつなげると生成多項式で割り切れる
+            let first: Vec<u8> = (0..len)
+                .map(|i| interleaved[i * blocks])
+                .chain((0..ec_len).map(|i| interleaved[data_codewords(version) + i * blocks]))
+                .collect();
+            assert!(
+                rs_remainder(&first, &generator).iter().all(|r| *r == 0),
+                "version {}",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn multiplies_in_gf256() {
+        assert_eq!(gf_mul(0x80, 2), 0x1d);
+        assert_eq!(gf_mul(0, 0x53), 0);
+        for a in 0..=255u8 {
+            assert_eq!(gf_mul(a, 1), a);
+            assert_eq!(gf_mul(a, 3), gf_mul(3, a));
+        }
+    }
+
+    #[test]
+    fn draws_finder_timing_and_format_patterns() {
+        let modules = encode(b"https://example.com").unwrap();
+        let size = modules.len();
+        // 位置検出パターンの外枠と中心
+        for (x, y) in [(0, 0), (size - 7, 0), (0, size - 7)] {
+            assert!(modules[y][x] && modules[y + 6][x + 6] && modules[y + 3][x + 3]);
+            assert!(!modules[y + 1][x + 1]);
+        }
+        for (i, row) in modules.iter().enumerate().take(size - 8).skip(8) {
+            assert_eq!(modules[6][i], i % 2 == 0);
+            assert_eq!(row[6], i % 2 == 0);
+        }
+        assert!(modules[size - 8][8]);
+
+        // 形式情報の 2 つの写しは同じで、誤り訂正レベル M のもの
+        let first: u32 = (0..15)
+            .map(|i| match i {
+                0..=5 => modules[i][8],
+                6 => modules[7][8],
+                7 => modules[8][8],
+                8 => modules[8][7],
+                _ => modules[8][14 - i],
+            })
+            .enumerate()
+            .map(|(i, dark)| (dark as u32) << i)
+            .sum();
+        let second: u32 = (0..15)
+            .map(|i| match i {
+                0..=7 => modules[8][size - 1 - i],
+                _ => modules[size - 15 + i][8],
+            })
+            .enumerate()
+            .map(|(i, dark)| (dark as u32) << i)
+            .sum();
+        assert_eq!(first, second);
+        assert_eq!((first ^ 0x5412) >> 13, 0b00);
+    }
+
+    #[test]
+    fn renders_two_rows_per_line_with_a_quiet_zone() {
+        let lines = render("a").unwrap();
+        assert_eq!(lines.len(), (21 + QUIET_ZONE * 2).div_ceil(2));
+        assert!(lines
+            .iter()
+            .all(|line| console::measure_text_width(line) == 25));
+        assert_eq!(console::strip_ansi_codes(&lines[0]).trim(), "");
+        assert!(lines[1].contains('█'));
+    }
+}
@@ -0,0 +1,134 @@
+//! 書き出す画像の色数を減らす (`--palette`, `--dither`)
+//!
+//! 16 色と 256 色は xterm の色表に寄せ、PNG はパレット形式で書き出して小さくする
+
+use crate::raster::{self, Image, Rgb};
+use std::collections::HashMap;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Depth {
+    /// 端末の基本の 16 色
+    #[value(name = "16")]
+    Basic,
+    /// xterm の 256 色
+    #[value(name = "256")]
+    Xterm,
+    /// 減らさない (24 ビットカラー)
+    #[default]
+    Truecolor,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// いちばん近い色にする
+    #[default]
+    None,
+    /// 4x4 の格子模様で中間の色を表す
+    Ordered,
+    /// 誤差を周りの画素に散らす (Floyd-Steinberg)
+    Diffusion,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Quantize {
+    pub depth: Depth,
+    pub dither: Dither,
+}
+
+/// 4x4 のベイヤー行列
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+impl Quantize {
+    /// 使う色の表 (`Truecolor` なら `None`)
+    pub fn colors(self) -> Option<Vec<Rgb>> {
+        match self.depth {
+            Depth::Basic => Some((0..16).map(raster::indexed).collect()),
+            Depth::Xterm => Some((0..=255).map(raster::indexed).collect()),
+            Depth::Truecolor => None,
+        }
+    }
+
+    /// `image` の各画素を色の表の番号にする (`Truecolor` なら `None`)
+    pub fn apply(self, image: &Image) -> Option<(Vec<Rgb>, Vec<u8>)> {
+        let colors = self.colors()?;
+        let mut nearest = Nearest::new(&colors);
+        // 格子模様の濃さは色の表の粗さに合わせる
+        let spread = match self.depth {
+            Depth::Basic => 64.0,
+            _ => 24.0,
+        };
+        let mut error = vec![[0.0f32; 3]; image.pixels.len()];
+        let indices = (0..image.pixels.len())
+            .map(|i| {
+                let (x, y) = (i % image.width.max(1), i / image.width.max(1));
+                let pixel = image.pixels[i].map(f32::from);
+                // 色の表にある色 (背景や基本の色の文字) はそのまま残す
+                let exact = nearest.exact(image.pixels[i]);
+                let target: [f32; 3] = match self.dither {
+                    _ if exact => pixel,
+                    Dither::None => pixel,
+                    Dither::Ordered => {
+                        let offset = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+                        pixel.map(|c| c + offset * spread)
+                    }
+                    Dither::Diffusion => [0, 1, 2].map(|c| pixel[c] + error[i][c]),
+                };
+                let index = nearest.find(target.map(|c| c.round().clamp(0.0, 255.0) as u8));
+                if self.dither == Dither::Diffusion {
+                    let chosen = colors[index as usize];
+                    let diff = [0, 1, 2].map(|c| target[c] - f32::from(chosen[c]));
+                    let mut spread = |dx: isize, dy: usize, weight: f32| {
+                        let nx = x as isize + dx;
+                        if nx < 0 || nx as usize >= image.width || y + dy >= image.height {
+                            return;
+                        }
+                        let j = (y + dy) * image.width + nx as usize;
+                        for c in 0..3 {
+                            error[j][c] += diff[c] * weight;
+                        }
+                    };
+                    spread(1, 0, 7.0 / 16.0);
+                    spread(-1, 1, 3.0 / 16.0);
+                    spread(0, 1, 5.0 / 16.0);
+                    spread(1, 1, 1.0 / 16.0);
+                }
+                index
+            })
+            .collect();
+        Some((colors, indices))
+    }
+}
+
+/// 色の表からいちばん近い色 (一度調べた色は覚えておく)
+struct Nearest<'a> {
+    colors: &'a [Rgb],
+    found: HashMap<Rgb, u8>,
+}
+
+impl<'a> Nearest<'a> {
+    fn new(colors: &'a [Rgb]) -> Self {
+        Self {
+            colors,
+            found: HashMap::new(),
+        }
+    }
+
+    fn exact(&mut self, rgb: Rgb) -> bool {
+        let index = self.find(rgb);
+        self.colors[index as usize] == rgb
+    }
+
+    fn find(&mut self, rgb: Rgb) -> u8 {
+        let colors = self.colors;
+        *self.found.entry(rgb).or_insert_with(|| {
+            let distance = |color: &Rgb| -> u32 {
+                (0..3)
+                    .map(|c| (i32::from(color[c]) - i32::from(rgb[c])).pow(2) as u32)
+                    .sum()
+            };
+            (0..colors.len())
+                .min_by_key(|i| distance(&colors[*i]))
+                .unwrap_or(0) as u8
+        })
+    }
+}
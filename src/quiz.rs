@@ -0,0 +1,136 @@
+use crate::{redraw, render_say, RenderOptions};
+use anyhow::{bail, Context, Result};
+use console::Term;
+use serde::Deserialize;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// 答え合わせの結果を見せておく時間
+const REACTION: Duration = Duration::from_millis(1500);
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Quiz {
+    questions: Vec<Question>,
+    /// 正解/不正解のときに使うキャラクター (省略時はいつものキャラクター)
+    correct_character: Option<String>,
+    wrong_character: Option<String>,
+    #[serde(default = "default_correct")]
+    correct: String,
+    #[serde(default = "default_wrong")]
+    wrong: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Question {
+    question: String,
+    /// 正解として受け付ける答え (大文字小文字と前後の空白は無視する)
+    answers: Vec<String>,
+    /// この問題だけ反応を変える
+    correct: Option<String>,
+    wrong: Option<String>,
+}
+
+fn default_correct() -> String {
+    "正解！".to_string()
+}
+
+fn default_wrong() -> String {
+    "ざんねん…".to_string()
+}
+
+pub fn quiz(path: &Path, opts: &RenderOptions) -> Result<()> {
+    let quiz = std::fs::read_to_string(path)?;
+    let quiz: Quiz = toml::from_str(&quiz)
+        .with_context(|| format!("{} の読み込みに失敗しました", path.display()))?;
+    if quiz.questions.is_empty() {
+        bail!("{}: questions が空です", path.display());
+    }
+    if let Some(question) = quiz.questions.iter().find(|q| q.answers.is_empty()) {
+        bail!(
+            "{}: 「{}」に answers がありません",
+            path.display(),
+            question.question
+        );
+    }
+
+    let correct_opts = match &quiz.correct_character {
+        Some(character) => opts.with_character(character)?,
+        None => opts.clone(),
+    };
+    let wrong_opts = match &quiz.wrong_character {
+        Some(character) => opts.with_character(character)?,
+        None => opts.clone(),
+    };
+
+    let mut term = Term::stdout();
+    let mut stdin = std::io::stdin().lock();
+    let total = quiz.questions.len();
+    let mut score = 0;
+    for (i, question) in quiz.questions.iter().enumerate() {
+        let terminal_width = crate::terminal::width(&term);
+        let caption = format!("第 {} 問 / 全 {} 問", i + 1, total);
+        redraw(
+            &mut term,
+            &render_say(&question.question, Some(&caption), terminal_width, opts)?,
+            opts,
+        )?;
+        write!(term, "> ")?;
+        term.flush()?;
+
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            break;
+        }
+        let answer = normalize(&answer);
+        let (text, opts) = match question.answers.iter().any(|a| normalize(a) == answer) {
+            true => {
+                score += 1;
+                (
+                    question
+                        .correct
+                        .as_deref()
+                        .unwrap_or(&quiz.correct)
+                        .to_string(),
+                    &correct_opts,
+                )
+            }
+            false => (
+                format!(
+                    "{}\n正解は {}",
+                    question.wrong.as_deref().unwrap_or(&quiz.wrong),
+                    question.answers[0]
+                ),
+                &wrong_opts,
+            ),
+        };
+        let caption = format!("{} / {} 問正解", score, i + 1);
+        redraw(
+            &mut term,
+            &render_say(&text, Some(&caption), terminal_width, opts)?,
+            opts,
+        )?;
+        sleep(REACTION);
+    }
+
+    let terminal_width = crate::terminal::width(&term);
+    let (text, result_opts) = match score == total {
+        true => ("全問正解！".to_string(), &correct_opts),
+        false => (format!("{} 問正解", score), opts),
+    };
+    let caption = format!("スコア {} / {}", score, total);
+    redraw(
+        &mut term,
+        &render_say(&text, Some(&caption), terminal_width, result_opts)?,
+        result_opts,
+    )?;
+
+    Ok(())
+}
+
+fn normalize(answer: &str) -> String {
+    answer.trim().to_lowercase()
+}
@@ -0,0 +1,267 @@
+use crate::font::Font;
+
+pub type Rgb = [u8; 3];
+
+/// 色指定の無い文字と背景に使う色
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub foreground: Rgb,
+    pub background: Rgb,
+}
+
+impl Palette {
+    pub const LIGHT: Palette = Palette {
+        foreground: [0, 0, 0],
+        background: [255, 255, 255],
+    };
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            foreground: [229, 229, 229],
+            background: [0, 0, 0],
+        }
+    }
+}
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, color: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width * height],
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        for y in y..(y + height).min(self.height) {
+            for x in x..(x + width).min(self.width) {
+                self.pixels[y * self.width + x] = color;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    Default,
+    Indexed(u8),
+    Rgb(Rgb),
+}
+
+pub struct Cell {
+    pub c: char,
+    pub width: usize,
+    pub foreground: Rgb,
+    pub background: Option<Rgb>,
+}
+
+/// ANSI の色指定を解釈しながら行をビットマップフォントで描く
+pub fn render(lines: &[String], font: &Font, palette: Palette, padding: usize) -> Image {
+    let rows: Vec<Vec<Cell>> = lines.iter().map(|line| cells(line, palette)).collect();
+    let columns = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| cell.width).sum::<usize>())
+        .max()
+        .unwrap_or(0);
+
+    let mut image = Image::new(
+        columns * font.cell_width + padding * 2,
+        rows.len() * font.height + padding * 2,
+        palette.background,
+    );
+    for (row_index, row) in rows.iter().enumerate() {
+        let top = padding + row_index * font.height;
+        let mut left = padding;
+        for cell in row {
+            let cell_width = cell.width * font.cell_width;
+            if let Some(background) = cell.background {
+                image.fill(left, top, cell_width, font.height, background);
+            }
+            match font.glyph(cell.c) {
+                Some(glyph) => {
+                    for y in 0..font.height {
+                        for x in 0..glyph.width.min(cell_width) {
+                            if glyph.get(x, y) {
+                                image.fill(left + x, top + y, 1, 1, cell.foreground);
+                            }
+                        }
+                    }
+                }
+                // グリフが無い文字は枠で示す
+                None if !cell.c.is_whitespace() => {
+                    let (width, height) =
+                        (cell_width.saturating_sub(2), font.height.saturating_sub(2));
+                    let (right, bottom) = (left + width, top + height);
+                    image.fill(left + 1, top + 1, width, 1, cell.foreground);
+                    image.fill(left + 1, bottom, width, 1, cell.foreground);
+                    image.fill(left + 1, top + 1, 1, height, cell.foreground);
+                    image.fill(right, top + 1, 1, height, cell.foreground);
+                }
+                None => {}
+            }
+            left += cell_width;
+        }
+    }
+
+    image
+}
+
+/// 行を文字ごとの色付きセルに分解し、末尾の空白を落とす
+pub fn cells(line: &str, palette: Palette) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let mut foreground = Color::Default;
+    let mut background = Color::Default;
+    let mut bold = false;
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.peek() != Some(&'[') {
+                continue;
+            }
+            chars.next();
+            let mut params = String::new();
+            let mut terminator = None;
+            for c in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&c) {
+                    terminator = Some(c);
+                    break;
+                }
+                params.push(c);
+            }
+            if terminator == Some('m') {
+                apply_sgr(&params, &mut foreground, &mut background, &mut bold);
+            }
+            continue;
+        }
+        let width = console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+        if width == 0 {
+            continue;
+        }
+
+        let foreground = match foreground {
+            Color::Indexed(i) if bold && i < 8 => Color::Indexed(i + 8),
+            color => color,
+        };
+        cells.push(Cell {
+            c,
+            width,
+            foreground: resolve(foreground).unwrap_or(palette.foreground),
+            background: resolve(background),
+        });
+    }
+
+    while cells
+        .last()
+        .is_some_and(|cell| cell.c == ' ' && cell.background.is_none())
+    {
+        cells.pop();
+    }
+    cells
+}
+
+fn apply_sgr(params: &str, foreground: &mut Color, background: &mut Color, bold: &mut bool) {
+    let codes: Vec<u16> = match params.is_empty() {
+        true => vec![0],
+        false => params.split(';').map(|p| p.parse().unwrap_or(0)).collect(),
+    };
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        match code {
+            0 => {
+                *foreground = Color::Default;
+                *background = Color::Default;
+                *bold = false;
+            }
+            1 => *bold = true,
+            22 => *bold = false,
+            30..=37 => *foreground = Color::Indexed((code - 30) as u8),
+            39 => *foreground = Color::Default,
+            40..=47 => *background = Color::Indexed((code - 40) as u8),
+            49 => *background = Color::Default,
+            90..=97 => *foreground = Color::Indexed((code - 90 + 8) as u8),
+            100..=107 => *background = Color::Indexed((code - 100 + 8) as u8),
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(|i| Color::Indexed(i as u8)),
+                    Some(2) => {
+                        let rgb: Vec<u8> = codes.by_ref().take(3).map(|c| c as u8).collect();
+                        rgb.try_into().ok().map(Color::Rgb)
+                    }
+                    _ => None,
+                };
+                if let Some(color) = color {
+                    match code {
+                        38 => *foreground = color,
+                        _ => *background = color,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve(color: Color) -> Option<Rgb> {
+    const BASIC: [Rgb; 16] = [
+        [0, 0, 0],
+        [205, 0, 0],
+        [0, 205, 0],
+        [205, 205, 0],
+        [0, 0, 238],
+        [205, 0, 205],
+        [0, 205, 205],
+        [229, 229, 229],
+        [127, 127, 127],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [92, 92, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match color {
+        Color::Default => None,
+        Color::Rgb(rgb) => Some(rgb),
+        Color::Indexed(i @ 0..=15) => Some(BASIC[i as usize]),
+        Color::Indexed(i @ 16..=231) => {
+            let i = (i - 16) as usize;
+            Some([LEVELS[i / 36], LEVELS[i / 6 % 6], LEVELS[i % 6]])
+        }
+        Color::Indexed(i) => {
+            let gray = 8 + (i - 232) * 10;
+            Some([gray, gray, gray])
+        }
+    }
+}
+
+/// 端末の 256 色の `i` 番目の色
+pub fn indexed(i: u8) -> Rgb {
+    resolve(Color::Indexed(i)).unwrap_or_default()
+}
+
+/// `#rrggbb` または `#rgb` を解釈する
+pub fn parse_color(s: &str) -> anyhow::Result<Rgb> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()
+        .ok_or_else(|| anyhow::anyhow!("色 '{}' を解釈できません (#rrggbb)", s))?;
+    match digits[..] {
+        [r, g, b] => Ok([r * 17, g * 17, b * 17]),
+        [r1, r2, g1, g2, b1, b2] => Ok([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2]),
+        _ => anyhow::bail!("色 '{}' を解釈できません (#rrggbb)", s),
+    }
+}
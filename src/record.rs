@@ -0,0 +1,74 @@
+use crate::{i18n, redraw, render_say, RenderOptions, Script};
+use anyhow::Result;
+use console::Term;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// 標準入力から 1 行ずつ `say` しながらスクリプトに書き留める
+///
+/// `pre:` / `after:` で始まる行はプレ/アフターキャプションになる。
+/// 入力の間隔の平均をインターバルとして保存する
+pub fn record(output: &Path, append: bool, opts: &RenderOptions) -> Result<()> {
+    let mut script = match append && output.exists() {
+        true => Script::load(output)?,
+        false => Script::default(),
+    };
+
+    let mut term = Term::stdout();
+    let mut gaps = Vec::new();
+    let mut last_shown: Option<Instant> = None;
+    prompt()?;
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let line = opts.sanitize(&line);
+        let line = line.trim();
+        if line.is_empty() {
+            prompt()?;
+            continue;
+        }
+
+        let (side_dish, caption) = if let Some(caption) = line.strip_prefix("pre:") {
+            script.pre_captions.push(caption.trim().to_string().into());
+            ("", Some(caption.trim()))
+        } else if let Some(caption) = line.strip_prefix("after:") {
+            script
+                .after_captions
+                .push(caption.trim().to_string().into());
+            ("", Some(caption.trim()))
+        } else {
+            script.side_dishes.push(line.to_string().into());
+            (line, None)
+        };
+        let terminal_width = crate::terminal::width(&term);
+        redraw(
+            &mut term,
+            &render_say(side_dish, caption, terminal_width, opts)?,
+            opts,
+        )?;
+
+        if let Some(last_shown) = last_shown {
+            gaps.push(last_shown.elapsed().as_millis() as u64);
+        }
+        last_shown = Some(Instant::now());
+        prompt()?;
+    }
+
+    if !gaps.is_empty() {
+        script.interval = Some((gaps.iter().sum::<u64>() / gaps.len() as u64).max(10));
+    }
+    script.save(output)?;
+    eprintln!(
+        "{}",
+        i18n::tr(&format!("{} に保存しました", output.display()))
+    );
+
+    Ok(())
+}
+
+fn prompt() -> Result<()> {
+    let mut stderr = std::io::stderr();
+    write!(stderr, "> ")?;
+    stderr.flush()?;
+    Ok(())
+}
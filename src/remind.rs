@@ -0,0 +1,151 @@
+//! `remind` の時刻を決めたお知らせ
+//!
+//! `--at` と設定ファイルの `[[reminders]]` の時刻 (ローカル時刻の HH:MM) まで待ち、
+//! おかずを出すかスクリプトを再生する。時刻は毎日くり返す
+
+use crate::{i18n, log, placeholder::LocalTime};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Deserializer};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 待っている間に時計を見直す間隔 (スリープや時刻の変更に追いつくため)
+const POLL: u64 = 60;
+const DAY: i64 = 24 * 60 * 60;
+
+/// 1 日のうちの時刻
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct At {
+    pub hour: u32,
+    pub minute: u32,
+}
+
+/// `12:00` のような時刻
+pub fn parse_at(s: &str) -> Result<At> {
+    let parsed = s.trim().split_once(':').and_then(|(hour, minute)| {
+        let at = At {
+            hour: hour.parse().ok()?,
+            minute: minute.parse().ok()?,
+        };
+        (at.hour < 24 && at.minute < 60 && minute.len() == 2).then_some(at)
+    });
+    match parsed {
+        Some(at) => Ok(at),
+        None => bail!("時刻 '{}' を解釈できません (HH:MM)", s),
+    }
+}
+
+fn deserialize_at<'de, D: Deserializer<'de>>(deserializer: D) -> Result<At, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_at(&s).map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Reminder {
+    #[serde(deserialize_with = "deserialize_at")]
+    pub at: At,
+    /// 吹き出しに出すおかず (空なら「時間だよ！」)
+    #[serde(default)]
+    pub message: String,
+    pub caption: Option<String>,
+    /// おかずの代わりに再生するスクリプト
+    pub script: Option<PathBuf>,
+    /// デスクトップ通知も出す
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl Reminder {
+    /// 吹き出しと通知に使うおかず
+    pub fn text(&self) -> String {
+        match self.message.is_empty() {
+            true => i18n::tr("時間だよ！").into_owned(),
+            false => self.message.clone(),
+        }
+    }
+}
+
+/// `at` の次の時刻までの秒数 (ちょうどその時刻なら 0)
+fn until(at: At, now: &LocalTime) -> i64 {
+    let target = i64::from(at.hour * 3600 + at.minute * 60);
+    let now = i64::from(now.hour * 3600 + now.minute * 60 + now.second);
+    (target - now).rem_euclid(DAY)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// それぞれの時刻が来るたびに `fire` を呼ぶ。`once` ならすべて一度ずつ呼んだら終わる
+pub fn run(
+    reminders: &[Reminder],
+    once: bool,
+    mut fire: impl FnMut(&Reminder) -> Result<()>,
+) -> Result<()> {
+    if reminders.is_empty() {
+        bail!("知らせる時刻がありません (--at か設定ファイルの [[reminders]] で指定します)");
+    }
+    let now = unix_now();
+    let local = LocalTime::now();
+    let mut next: Vec<Option<i64>> = reminders
+        .iter()
+        .map(|reminder| Some(now + until(reminder.at, &local)))
+        .collect();
+    loop {
+        let now = unix_now();
+        for (reminder, next) in reminders.iter().zip(&mut next) {
+            if next.is_some_and(|next| next <= now) {
+                log::info!(
+                    "{:02}:{:02} のお知らせ",
+                    reminder.at.hour,
+                    reminder.at.minute
+                );
+                fire(reminder)?;
+                // 知らせている間に過ぎた分も数えて、次の日の同じ時刻にする
+                *next = match once {
+                    true => None,
+                    false => {
+                        let wait = until(reminder.at, &LocalTime::now());
+                        Some(unix_now() + if wait == 0 { DAY } else { wait })
+                    }
+                };
+            }
+        }
+        let Some(soonest) = next.iter().flatten().min() else {
+            return Ok(());
+        };
+        let wait = (soonest - unix_now()).clamp(0, POLL as i64);
+        sleep(Duration::from_secs(wait as u64));
+    }
+}
+
+/// 端末から切り離して子プロセスで待つ。親プロセスでは true を返す
+#[cfg(unix)]
+pub fn detach() -> Result<bool> {
+    match unsafe { libc::fork() } {
+        -1 => bail!(
+            "バックグラウンドに移れません: {}",
+            std::io::Error::last_os_error()
+        ),
+        0 => {
+            unsafe { libc::setsid() };
+            Ok(false)
+        }
+        pid => {
+            println!(
+                "{}",
+                i18n::tr(&format!("バックグラウンドで待ちます (PID {})", pid))
+            );
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn detach() -> Result<bool> {
+    bail!("remind --background は Unix 系 OS でのみ使えます")
+}
@@ -0,0 +1,433 @@
+//! フレームの出力先
+//!
+//! `say` と `anime` は描いたフレームの行を `Renderer` に渡すだけにして、端末、テキスト、HTML、
+//! 画像、asciicast の違いはそれぞれの実装が受け持つ
+
+use crate::font::Font;
+use crate::quantize::Quantize;
+use crate::raster::{self, Palette};
+use crate::{clear_screen, json, png, redraw_in_place, terminal, write_frame, RenderOptions};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub trait Renderer {
+    /// 1 フレームを出力する
+    fn frame(&mut self, lines: &[String]) -> Result<()>;
+
+    /// 直前のフレームを同じ場所で描き直す (タイプライター表示の途中など)
+    fn redraw(&mut self, lines: &[String]) -> Result<()> {
+        self.frame(lines)
+    }
+
+    /// 直前のフレームを出してから `duration` 経った (待つのは呼び出す側)
+    fn elapse(&mut self, _duration: Duration) {}
+
+    /// ベルを鳴らす
+    fn bell(&mut self, _sequence: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// ここまでのフレームを見えるようにする (キー入力を待つ前など)
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 全部のフレームを出力し終えた
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+/// 拡張子 (.txt, .html, .png, .cast) で形式を決めて `path` に書き出す。フォントと `quantize` は
+/// PNG のときだけ使う
+pub fn open(
+    path: &Path,
+    font: impl FnOnce() -> Result<Font>,
+    quantize: Quantize,
+) -> Result<Box<dyn Renderer>> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    Ok(match extension.as_deref() {
+        Some("txt") => Box::new(Plain::new(BufWriter::new(create(path)?))),
+        Some("html" | "htm") => Box::new(Html::new(path, Palette::default())),
+        Some("png") => Box::new(Image::new(
+            path,
+            font()?,
+            Palette::default(),
+            crate::graphics::PADDING,
+            quantize,
+        )),
+        Some("cast") => Box::new(Cast::new(path)),
+        _ => bail!(
+            "{} の形式が分かりません (.txt, .html, .png, .cast のどれかにしてください)",
+            path.display()
+        ),
+    })
+}
+
+fn create(path: &Path) -> Result<File> {
+    File::create(path).with_context(|| format!("{} に書き込めません", path.display()))
+}
+
+fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    std::fs::write(path, contents).with_context(|| format!("{} に書き込めません", path.display()))
+}
+
+/// 端末 (と telnet などの接続先)。`--graphics` なら画像にする
+pub struct Terminal<'a, W: Write> {
+    out: W,
+    /// フレームごとに画面を消して描き直す (false なら空行で区切って続けて出力する)
+    animate: bool,
+    /// 画面を消さず、前のフレームを出した行だけを描き直す
+    inline: bool,
+    opts: &'a RenderOptions,
+    shown: bool,
+    /// 前のフレームの行数 (`inline` で戻る行数)
+    printed: usize,
+}
+
+impl<'a, W: Write> Terminal<'a, W> {
+    pub fn new(out: W, animate: bool, opts: &'a RenderOptions) -> Self {
+        Self {
+            out,
+            animate,
+            inline: false,
+            opts,
+            shown: false,
+            printed: 0,
+        }
+    }
+
+    /// カーソルの位置から下だけを使ってアニメーションする (前からある出力は消さない)
+    pub fn inline(out: W, opts: &'a RenderOptions) -> Self {
+        Self {
+            inline: true,
+            ..Self::new(out, true, opts)
+        }
+    }
+
+    /// 前のフレームの先頭の行に戻って上書きし、残った行を消す
+    fn overwrite(&mut self, lines: &[String]) -> Result<()> {
+        let lines = crate::narrow::fit(lines, self.opts);
+        terminal::up(&mut self.out, self.printed)?;
+        for line in lines.iter() {
+            writeln!(self.out, "{}{}", line, terminal::clear_line_end())?;
+        }
+        terminal::clear_to_end(&mut self.out)?;
+        self.printed = lines.len();
+        self.shown = true;
+        Ok(())
+    }
+}
+
+impl<W: Write> Renderer for Terminal<'_, W> {
+    fn frame(&mut self, lines: &[String]) -> Result<()> {
+        if self.inline {
+            self.overwrite(lines)?;
+            return self.flush();
+        }
+        match (self.animate, self.shown) {
+            (true, _) => clear_screen(&mut self.out)?,
+            (false, true) => writeln!(self.out)?,
+            (false, false) => {}
+        }
+        write_frame(&mut self.out, lines, self.opts)?;
+        self.shown = true;
+        self.flush()
+    }
+
+    fn redraw(&mut self, lines: &[String]) -> Result<()> {
+        if self.inline {
+            self.overwrite(lines)?;
+            return self.flush();
+        }
+        redraw_in_place(&mut self.out, lines, self.opts)?;
+        self.shown = true;
+        Ok(())
+    }
+
+    fn bell(&mut self, sequence: &str) -> Result<()> {
+        write!(self.out, "{}", sequence)?;
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// 色と行末の空白を除いたテキスト。フレームは空行で区切る
+pub struct Plain<W: Write> {
+    out: W,
+    shown: bool,
+}
+
+impl<W: Write> Plain<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, shown: false }
+    }
+}
+
+impl<W: Write> Renderer for Plain<W> {
+    fn frame(&mut self, lines: &[String]) -> Result<()> {
+        if self.shown {
+            writeln!(self.out)?;
+        }
+        for line in lines {
+            writeln!(self.out, "{}", crate::markup::strip(line).trim_end())?;
+        }
+        self.shown = true;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// 色を `<span>` にした `<pre>` を並べた HTML。2 フレーム以上ならその時間どおりに切り替える
+pub struct Html {
+    path: PathBuf,
+    palette: Palette,
+    /// それぞれのフレームと次のフレームまでの時間
+    frames: Vec<(Vec<String>, Duration)>,
+}
+
+impl Html {
+    pub fn new(path: &Path, palette: Palette) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            palette,
+            frames: Vec::new(),
+        }
+    }
+
+    fn document(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str("<title>fav_say_dragon</title>\n");
+        out.push_str(&format!(
+            "<style>body {{ background: {}; color: {}; }} pre {{ font-family: monospace; }}</style>\n",
+            hex(self.palette.background),
+            hex(self.palette.foreground)
+        ));
+        out.push_str("</head>\n<body>\n");
+        for (i, (lines, duration)) in self.frames.iter().enumerate() {
+            out.push_str(&format!(
+                "<pre data-duration=\"{}\"{}>",
+                duration.as_millis(),
+                match i {
+                    0 => "",
+                    _ => " hidden",
+                }
+            ));
+            for line in lines {
+                out.push_str(&html_line(line, self.palette));
+                out.push('\n');
+            }
+            out.push_str("</pre>\n");
+        }
+        if self.frames.len() > 1 {
+            out.push_str(
+                "<script>
+const frames = document.querySelectorAll('pre');
+let i = 0;
+function next() {
+  if (i + 1 >= frames.length) return;
+  setTimeout(() => {
+    frames[i].hidden = true;
+    i += 1;
+    frames[i].hidden = false;
+    next();
+  }, Number(frames[i].dataset.duration));
+}
+next();
+</script>
+",
+            );
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+impl Renderer for Html {
+    fn frame(&mut self, lines: &[String]) -> Result<()> {
+        self.frames.push((lines.to_vec(), Duration::ZERO));
+        Ok(())
+    }
+
+    fn elapse(&mut self, duration: Duration) {
+        if let Some((_, shown)) = self.frames.last_mut() {
+            *shown += duration;
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        write(&self.path, self.document())
+    }
+}
+
+/// ANSI の色を `<span>` にした 1 行
+fn html_line(line: &str, palette: Palette) -> String {
+    let mut out = String::new();
+    let mut open: Option<(raster::Rgb, Option<raster::Rgb>)> = None;
+    for cell in raster::cells(line, palette) {
+        let colors = (cell.foreground, cell.background);
+        if open != Some(colors) {
+            if open.is_some_and(|colors| colors != (palette.foreground, None)) {
+                out.push_str("</span>");
+            }
+            if colors != (palette.foreground, None) {
+                out.push_str(&format!("<span style=\"color: {}", hex(cell.foreground)));
+                if let Some(background) = cell.background {
+                    out.push_str(&format!("; background: {}", hex(background)));
+                }
+                out.push_str("\">");
+            }
+            open = Some(colors);
+        }
+        match cell.c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+    if open.is_some_and(|colors| colors != (palette.foreground, None)) {
+        out.push_str("</span>");
+    }
+    out
+}
+
+fn hex([r, g, b]: raster::Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// ビットマップフォントで描いた PNG。2 フレーム以上なら `名前-01.png` のように番号を付ける
+pub struct Image {
+    path: PathBuf,
+    font: Font,
+    palette: Palette,
+    padding: usize,
+    quantize: Quantize,
+    frames: Vec<Vec<String>>,
+}
+
+impl Image {
+    pub fn new(
+        path: &Path,
+        font: Font,
+        palette: Palette,
+        padding: usize,
+        quantize: Quantize,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            font,
+            palette,
+            padding,
+            quantize,
+            frames: Vec::new(),
+        }
+    }
+
+    /// `i` 番目のフレームの書き出し先
+    fn path(&self, i: usize) -> PathBuf {
+        if self.frames.len() == 1 {
+            return self.path.clone();
+        }
+        let digits = self.frames.len().to_string().len();
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        self.path
+            .with_file_name(format!("{}-{:0digits$}.png", stem, i + 1))
+    }
+}
+
+impl Renderer for Image {
+    fn frame(&mut self, lines: &[String]) -> Result<()> {
+        self.frames.push(lines.to_vec());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        for (i, lines) in self.frames.iter().enumerate() {
+            let image = raster::render(lines, &self.font, self.palette, self.padding);
+            let png = match self.quantize.apply(&image) {
+                Some((colors, indices)) => png::encode_indexed(&image, &colors, &indices),
+                None => png::encode(&image),
+            };
+            write(&self.path(i), png)?;
+        }
+        Ok(())
+    }
+}
+
+/// asciinema で再生できる asciicast v2
+pub struct Cast {
+    path: PathBuf,
+    /// 出力した時刻とフレーム
+    frames: Vec<(Duration, Vec<String>)>,
+    elapsed: Duration,
+}
+
+#[derive(Serialize)]
+struct CastHeader {
+    version: u32,
+    width: usize,
+    height: usize,
+}
+
+impl Cast {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            frames: Vec::new(),
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl Renderer for Cast {
+    fn frame(&mut self, lines: &[String]) -> Result<()> {
+        self.frames.push((self.elapsed, lines.to_vec()));
+        Ok(())
+    }
+
+    fn elapse(&mut self, duration: Duration) {
+        self.elapsed += duration;
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let lines = || self.frames.iter().flat_map(|(_, lines)| lines);
+        let header = CastHeader {
+            version: 2,
+            width: lines()
+                .map(|line| console::measure_text_width(line))
+                .max()
+                .unwrap_or(0),
+            height: self
+                .frames
+                .iter()
+                .map(|(_, lines)| lines.len())
+                .max()
+                .unwrap_or(0),
+        };
+        let mut out = json::to_string(&header)?;
+        out.push('\n');
+        for (at, lines) in &self.frames {
+            let data = format!("\x1b[H\x1b[2J{}\r\n", lines.join("\r\n"));
+            out.push_str(&json::to_string(&(at.as_secs_f64(), "o", data))?);
+            out.push('\n');
+        }
+        write(&self.path, out)
+    }
+}
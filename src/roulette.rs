@@ -0,0 +1,66 @@
+use crate::{clear_screen, redraw_in_place, render_say, rng::Rng, write_frame, RenderOptions};
+use anyhow::{bail, Result};
+use console::Term;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// 最初の切り替え間隔
+const FIRST_DELAY: Duration = Duration::from_millis(40);
+/// 切り替えるたびに間隔を伸ばす倍率
+const SLOWDOWN: f64 = 1.12;
+/// この間隔を超えたら止まる
+const LAST_DELAY: Duration = Duration::from_millis(500);
+
+/// 候補を切り替えながらだんだん遅くし、ランダムに選んだ候補で止まる
+pub fn roulette(
+    items: &[String],
+    caption: Option<&str>,
+    animate: bool,
+    opts: &RenderOptions,
+) -> Result<()> {
+    if items.is_empty() {
+        bail!("候補を 1 つ以上指定してください");
+    }
+    let mut rng = Rng::stream(opts.seed, "roulette");
+    let winner = rng.below(items.len());
+
+    let mut term = Term::stdout();
+    let terminal_width = crate::terminal::width(&term);
+    if animate {
+        let delays = delays();
+        // 切り替えの次がちょうど当選した候補になるよう開始位置を逆算する
+        let start = (winner + items.len() - delays.len() % items.len()) % items.len();
+        clear_screen(&mut term)?;
+        for (step, delay) in delays.iter().enumerate() {
+            let item = &items[(start + step) % items.len()];
+            redraw_in_place(
+                &mut term,
+                &render_say(item, Some("…"), terminal_width, opts)?,
+                opts,
+            )?;
+            sleep(*delay);
+        }
+        clear_screen(&mut term)?;
+    }
+
+    write_frame(
+        &mut term,
+        &render_say(
+            &items[winner],
+            Some(caption.unwrap_or("決定！")),
+            terminal_width,
+            opts,
+        )?,
+        opts,
+    )?;
+    term.flush()?;
+
+    Ok(())
+}
+
+/// 当選した候補を出すまでの各フレームの表示時間
+fn delays() -> Vec<Duration> {
+    std::iter::successors(Some(FIRST_DELAY), |delay| Some(delay.mul_f64(SLOWDOWN)))
+        .take_while(|delay| *delay <= LAST_DELAY)
+        .collect()
+}
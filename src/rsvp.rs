@@ -0,0 +1,113 @@
+//! `rsvp` の速読 (Rapid Serial Visual Presentation)
+//!
+//! 文章を単語ごと (日本語は簡単な規則で文節ごと) に区切り、1 つずつ吹き出しに出すスクリプトに
+//! する。句読点で終わる区切りは少し長く出す
+
+use crate::{Frame, Script, Text, STDIN_PATH};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// 既定の 1 分あたりの単語 (文節) の数
+pub const DEFAULT_WPM: u16 = 300;
+
+pub fn load(path: &Path, wpm: u16) -> Result<Script> {
+    let source = match path == Path::new(STDIN_PATH) {
+        true => std::io::read_to_string(std::io::stdin()).context("標準入力を読み込めません")?,
+        false => std::fs::read_to_string(path)
+            .with_context(|| format!("{} を読み込めません", path.display()))?,
+    };
+    Ok(script(&source, wpm))
+}
+
+/// `source` の区切りを 1 つずつ `wpm` の速さで出すスクリプト
+pub fn script(source: &str, wpm: u16) -> Script {
+    let base = 60_000.0 / f64::from(wpm.max(1));
+    let frames = chunks(source)
+        .into_iter()
+        .map(|chunk| Frame {
+            duration: Some((base * pause(&chunk)).round() as u64),
+            side_dish: Text::Plain(chunk),
+            ..Frame::default()
+        })
+        .collect();
+    Script {
+        frames,
+        ..Script::default()
+    }
+}
+
+/// 空白で単語に分け、日本語を含む単語はさらに文節に分ける
+pub fn chunks(text: &str) -> Vec<String> {
+    text.split_whitespace().flat_map(bunsetsu).collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Hiragana,
+    Katakana,
+    Kanji,
+    /// 、。！？ (前の文節に付ける)
+    Punctuation,
+    /// 閉じかっこ (前の文節に付け、続く助詞もその文節に付ける)
+    Closing,
+    /// 開きかっこ (次の文節に付ける)
+    Opening,
+    Other,
+}
+
+impl Kind {
+    fn of(c: char) -> Self {
+        match c {
+            'ぁ'..='ゖ' | 'ゝ' | 'ゞ' => Kind::Hiragana,
+            'ァ'..='ヺ' | 'ヽ' | 'ヾ' | 'ｦ'..='ﾝ' => Kind::Katakana,
+            '一'..='鿿' | '々' | '〆' | '〇' => Kind::Kanji,
+            '、' | '。' | '，' | '．' | '！' | '？' | '…' | '・' => Kind::Punctuation,
+            '」' | '』' | '）' | '】' | '〉' | '》' => Kind::Closing,
+            '「' | '『' | '（' | '【' | '〈' | '《' => Kind::Opening,
+            _ => Kind::Other,
+        }
+    }
+}
+
+/// 助詞や送り仮名 (ひらがな)、句読点、閉じかっこの後に自立語が来たところで区切る
+fn bunsetsu(word: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    let mut previous: Option<Kind> = None;
+    for c in word.chars() {
+        // 長音は前の文字の続き
+        let kind = match (c, previous) {
+            ('ー' | 'ｰ', Some(previous)) => previous,
+            _ => Kind::of(c),
+        };
+        let split = match (previous, kind) {
+            (_, Kind::Punctuation | Kind::Closing) | (Some(Kind::Opening), _) | (None, _) => false,
+            (Some(Kind::Hiragana | Kind::Closing), Kind::Hiragana) => false,
+            (Some(Kind::Hiragana | Kind::Punctuation | Kind::Closing), _) => true,
+            (_, Kind::Opening) => true,
+            _ => false,
+        };
+        if split && !chunk.is_empty() {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+        previous = Some(kind);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// 区切りの最後の文字に合わせて表示時間を延ばす倍率
+fn pause(chunk: &str) -> f64 {
+    let last = chunk
+        .trim_end_matches(['」', '』', '）', ')', '"', '\''])
+        .chars()
+        .last();
+    match last {
+        Some('。' | '．' | '！' | '？' | '.' | '!' | '?' | '…') => 2.0,
+        Some('、' | '，' | ',' | ';' | ':') => 1.5,
+        _ => 1.0,
+    }
+}
@@ -0,0 +1,98 @@
+//! 外から受け取った文言のエスケープシーケンスと制御文字を取り除く
+//!
+//! 標準入力や URL、ソケットから来た文言で端末のタイトルを書き換えたりカーソルを
+//! 動かしたりできないようにする。`--allow-ansi` ならそのまま使う
+
+use crate::log;
+use std::borrow::Cow;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// 改行以外の制御文字とエスケープシーケンスを取り除く (タブは空白にする)
+pub fn strip(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| c.is_control() && c != '\n') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => out.push(c),
+            '\t' => out.push(' '),
+            ESC => match chars.next() {
+                Some('[') => csi(&mut chars),
+                Some(']' | 'P' | 'X' | '^' | '_') => string(&mut chars),
+                _ => {}
+            },
+            // 8 ビットの CSI と OSC などの文字列
+            '\u{9b}' => csi(&mut chars),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => string(&mut chars),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    log::info!("外から受け取った文言のエスケープシーケンスと制御文字を取り除きました (--allow-ansi でそのまま使えます)");
+    Cow::Owned(out)
+}
+
+/// `ESC [` の後の引数と終わりの文字
+fn csi(chars: &mut impl Iterator<Item = char>) {
+    for c in chars {
+        if ('\x40'..='\x7e').contains(&c) {
+            break;
+        }
+    }
+}
+
+/// BEL か ST (`ESC \`) で終わる文字列
+fn string(chars: &mut std::iter::Peekable<impl Iterator<Item = char>>) {
+    while let Some(c) = chars.next() {
+        match c {
+            BEL | '\u{9c}' => break,
+            ESC => {
+                chars.next_if_eq(&'\\');
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrows_clean_text() {
+        assert!(matches!(strip("からあげ\nおいしい"), Cow::Borrowed(_)));
+        assert!(matches!(strip(""), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn removes_csi_sequences() {
+        assert_eq!(strip("a\x1b[2Jb"), "ab");
+        assert_eq!(strip("\x1b[38;2;255;0;0mred\x1b[0m"), "red");
+        assert_eq!(strip("a\u{9b}2Jb"), "ab");
+        assert_eq!(strip("a\x1b["), "a");
+    }
+
+    #[test]
+    fn removes_strings_ended_by_bel_or_st() {
+        assert_eq!(strip("a\x1b]0;title\x07b"), "ab");
+        assert_eq!(strip("a\x1b]0;title\x1b\\b"), "ab");
+        assert_eq!(strip("a\u{9d}0;title\u{9c}b"), "ab");
+        assert_eq!(strip("a\u{9d}0;title\x07b"), "ab");
+        assert_eq!(strip("a\x1bPq#0\x1b\\b"), "ab");
+        // 閉じていない文字列は最後まで捨てる
+        assert_eq!(strip("a\x1b]0;pwned"), "a");
+    }
+
+    #[test]
+    fn keeps_newlines_and_turns_tabs_into_spaces() {
+        assert_eq!(strip("a\tb\nc"), "a b\nc");
+        assert_eq!(strip("a\r\n\x08b\x00"), "a\nb");
+        assert_eq!(strip("a\x1bcb"), "ab");
+        assert_eq!(strip("\x1b"), "");
+    }
+}
@@ -0,0 +1,31 @@
+//! フレームを出す予定の時刻
+//!
+//! 待つ時間をインターバルそのものではなく「前の予定の時刻 + インターバル」までの残りにして、
+//! 描くのにかかった時間や sleep の遅れがフレームごとにたまらないようにする
+
+use std::time::{Duration, Instant};
+
+pub struct Schedule {
+    /// 今のフレームを出す予定だった時刻
+    due: Instant,
+}
+
+impl Schedule {
+    /// 今を最初のフレームの予定の時刻にする
+    pub fn start() -> Self {
+        Self {
+            due: Instant::now(),
+        }
+    }
+
+    /// 次の予定を `duration` 後にして、それまでの残りの時間を返す (遅れていれば 0)
+    pub fn advance(&mut self, duration: Duration) -> Duration {
+        self.due += duration;
+        self.due.saturating_duration_since(Instant::now())
+    }
+
+    /// 一時停止やキー操作、読み上げなどで予定から外れたら、今から数え直す
+    pub fn reset(&mut self) {
+        self.due = Instant::now();
+    }
+}
@@ -0,0 +1,108 @@
+//! アニメーション中の端末の状態 (カーソルを隠す、代替画面) とその後始末
+//!
+//! `Screen` を drop すると元に戻す。パニックやシグナルで途中で終わっても、フックとハンドラーで
+//! 同じように色とカーソルと画面を戻すので、壊れた端末を残さない
+
+use crate::terminal;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// カーソルを隠している
+static HIDDEN: AtomicBool = AtomicBool::new(false);
+/// 代替画面に切り替えている
+static ALTERNATE: AtomicBool = AtomicBool::new(false);
+
+const RESET_COLORS: &str = "\x1b[0m";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const ENTER_ALTERNATE: &str = "\x1b[?1049h";
+const LEAVE_ALTERNATE: &str = "\x1b[?1049l";
+
+/// 描いている間だけカーソルを隠す (`alternate` なら代替画面にも切り替える)
+pub struct Screen(());
+
+impl Screen {
+    /// 標準出力が端末でない、または互換の描き方なら何もしない
+    pub fn enter(alternate: bool) -> Option<Self> {
+        if !std::io::stdout().is_terminal() || terminal::legacy() {
+            return None;
+        }
+        let mut out = std::io::stdout();
+        if alternate {
+            ALTERNATE.store(true, Ordering::SeqCst);
+            let _ = write!(out, "{}", ENTER_ALTERNATE);
+        }
+        HIDDEN.store(true, Ordering::SeqCst);
+        let _ = write!(out, "{}", HIDE_CURSOR);
+        let _ = out.flush();
+        Some(Self(()))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// 切り替えたものだけを戻す。何度呼んでもよい
+fn restore() {
+    let hidden = HIDDEN.swap(false, Ordering::SeqCst);
+    let alternate = ALTERNATE.swap(false, Ordering::SeqCst);
+    if !hidden && !alternate {
+        return;
+    }
+    let mut out = std::io::stdout();
+    let _ = write!(out, "{}", RESET_COLORS);
+    if alternate {
+        let _ = write!(out, "{}", LEAVE_ALTERNATE);
+    }
+    let _ = write!(out, "{}", SHOW_CURSOR);
+    let _ = out.flush();
+}
+
+/// パニックのメッセージを出す前と、SIGINT / SIGTERM / SIGHUP で終わる前に端末を戻す
+pub fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        previous(info);
+    }));
+    #[cfg(unix)]
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        unsafe { libc::signal(signal, on_signal as *const () as libc::sighandler_t) };
+    }
+}
+
+/// `kiosk` のために端末からの割り込み (Ctrl-C、Ctrl-\、Ctrl-Z) を無視する。SIGTERM では終われる
+pub fn ignore_interrupts() {
+    #[cfg(unix)]
+    for signal in [libc::SIGINT, libc::SIGQUIT, libc::SIGTSTP] {
+        unsafe { libc::signal(signal, libc::SIG_IGN) };
+    }
+}
+
+/// シグナルのハンドラーの中ではロックを取れないので、`write` だけで戻してから同じシグナルで終わる
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    let write = |sequence: &str| unsafe {
+        libc::write(
+            libc::STDOUT_FILENO,
+            sequence.as_ptr().cast(),
+            sequence.len(),
+        );
+    };
+    let hidden = HIDDEN.swap(false, Ordering::SeqCst);
+    let alternate = ALTERNATE.swap(false, Ordering::SeqCst);
+    if hidden || alternate {
+        write(RESET_COLORS);
+        if alternate {
+            write(LEAVE_ALTERNATE);
+        }
+        write(SHOW_CURSOR);
+    }
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
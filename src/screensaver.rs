@@ -0,0 +1,90 @@
+//! `screensaver` のスクリーンセーバー
+//!
+//! 代替画面で、ランダムに選んだおかずとキャラクターを画面のランダムな位置に、ランダムな
+//! 切り替え方で出し続ける。何かキーを押すと終わり、元の画面に戻る
+
+use crate::compositor::{z, Compositor, Layer};
+use crate::renderer::{Renderer, Terminal};
+use crate::{art, render_say, rng::Rng, screen::Screen, transition, tty, RenderOptions};
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use console::Term;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// 次を出すまでの既定の間隔 (ms)
+pub const DEFAULT_INTERVAL: u64 = 5000;
+
+pub fn screensaver(side_dishes: &[String], interval: u64, opts: &RenderOptions) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        bail!("screensaver は端末でだけ使えます");
+    }
+    if opts.graphics.is_some() {
+        bail!("screensaver は --graphics と一緒に使えません");
+    }
+    // --ascii のときは ASCII のキャラクターだけを使う
+    let cast = art::BUILTIN
+        .iter()
+        .filter(|(name, _)| !opts.ascii || *name == art::ASCII_CHARACTER)
+        .map(|(name, _)| opts.with_character(name))
+        .collect::<Result<Vec<_>>>()?;
+
+    let _screen = Screen::enter(true);
+    let term = Term::stdout();
+    let mut renderer = Terminal::new(Term::stdout(), true, opts);
+    let mut keyboard = tty::RawTty::open();
+    let mut rng = Rng::stream(opts.seed, "screensaver");
+    let mut previous: Option<Vec<String>> = None;
+    loop {
+        let opts = &cast[rng.below(cast.len())];
+        let side_dish = &side_dishes[rng.below(side_dishes.len())];
+        let (rows, columns) = term.size();
+        let lines = place(
+            &render_say(side_dish, None, 0, opts)?,
+            rows as usize,
+            columns as usize,
+            &mut rng,
+        );
+        let kinds = transition::Kind::value_variants();
+        let effect = kinds[rng.below(kinds.len())].build(rng.next_u64());
+        match (effect, &previous) {
+            (Some(effect), Some(previous)) => {
+                transition::run(&mut renderer, effect.as_ref(), previous, &lines, 1.0)?;
+                renderer.redraw(&lines)?;
+            }
+            _ => renderer.frame(&lines)?,
+        }
+        renderer.flush()?;
+        previous = Some(lines);
+        if pressed(keyboard.as_mut(), Duration::from_millis(interval)) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 端末の `rows` 行 `columns` 桁の中のランダムな位置に置いた画面全体の行
+///
+/// 最後の行まで書くと画面が 1 行ずれるので、最後の行は使わない
+fn place(lines: &[String], rows: usize, columns: usize, rng: &mut Rng) -> Vec<String> {
+    let layer = Layer::new(z::CHARACTER, lines.to_vec());
+    // 末尾の空白は埋め草なので幅に数えない
+    let width = layer.width();
+    let rows = rows.saturating_sub(1);
+    let top = rng.below(rows.saturating_sub(lines.len()) + 1);
+    let left = rng.below(columns.saturating_sub(width) + 1);
+    let mut screen = Compositor::new().with_height(rows);
+    screen.push(layer.at(left, top));
+    screen.render(0)
+}
+
+/// `timeout` まで待ち、その間に何かキーが押されたら true
+fn pressed(keyboard: Option<&mut tty::RawTty>, timeout: Duration) -> bool {
+    match keyboard {
+        Some(keyboard) => keyboard.read_timeout(&mut [0; 16], Some(timeout)) > 0,
+        None => {
+            std::thread::sleep(timeout);
+            false
+        }
+    }
+}
@@ -0,0 +1,962 @@
+//! `anime --rhai` で読み込む Rhai 風の小さなスクリプト言語
+//!
+//! 使えるのは `let`, 代入, `if`/`else`, `while`, `loop`, `for x in 0..n` / `for x in 配列`,
+//! `break`, `continue` と整数・文字列・真偽値・配列の式、組み込み関数だけ
+
+use crate::mood::Mood;
+use crate::schedule::Schedule;
+use crate::screen::Screen;
+use crate::{clear_screen, redraw, render_say, rng::Rng, write_frame, RenderOptions};
+use anyhow::{anyhow, bail, Result};
+use console::Term;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// おかずの指定が無いときに `random_dish()` が選ぶ候補
+pub const DEFAULT_DISHES: &[&str] = &["からあげ", "ハンバーグ", "肉を甘辛く\n炒めたやつ"];
+
+pub fn run(path: &Path, dishes: Vec<String>, animate: bool, opts: &RenderOptions) -> Result<()> {
+    let source = std::fs::read_to_string(path)?;
+    let program = Parser::new(tokenize(&source)?)
+        .program()
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+
+    let _screen = animate.then(|| Screen::enter(false)).flatten();
+    let mut term = Term::stdout();
+    if animate {
+        clear_screen(&mut term)?;
+    }
+    let mut interpreter = Interpreter::new(term, dishes, animate, opts);
+    interpreter.block(&program)?;
+    interpreter.out.flush()?;
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    Punct(&'static str),
+    Eof,
+}
+
+/// 長いものから順に照合する記号
+const PUNCTS: &[&str] = &[
+    "..", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "+", "-", "*", "/", "%", "<", ">", "=",
+    "!", "(", ")", "{", "}", "[", "]", ",", ";",
+];
+
+/// 字句と行番号の並び
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = source;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+        if c == '\n' {
+            line += 1;
+            rest = &rest[1..];
+        } else if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |i| &comment[i..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment
+                .find("*/")
+                .ok_or_else(|| anyhow!("{} 行目: コメントが閉じていません", line))?;
+            line += comment[..end].matches('\n').count();
+            rest = &comment[end + 2..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let n = rest[..end]
+                .parse()
+                .map_err(|_| anyhow!("{} 行目: 数が大きすぎます", line))?;
+            tokens.push((Token::Int(n), line));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push((Token::Ident(rest[..end].to_string()), line));
+            rest = &rest[end..];
+        } else if c == '"' {
+            let mut text = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, c)) => text.push(c),
+                        None => bail!("{} 行目: 文字列が閉じていません", line),
+                    },
+                    Some((_, c)) => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        text.push(c);
+                    }
+                    None => bail!("{} 行目: 文字列が閉じていません", line),
+                }
+            };
+            tokens.push((Token::Str(text), line));
+            rest = &rest[end..];
+        } else {
+            let punct = PUNCTS
+                .iter()
+                .find(|punct| rest.starts_with(*punct))
+                .ok_or_else(|| anyhow!("{} 行目: 使えない文字 '{}' があります", line, c))?;
+            tokens.push((Token::Punct(punct), line));
+            rest = &rest[punct.len()..];
+        }
+    }
+    tokens.push((Token::Eof, line));
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Expr {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+    Array(Vec<Expr>),
+    Var(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    Index(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Let(String, Expr),
+    Assign(String, Option<&'static str>, Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    Loop(Vec<Stmt>),
+    ForRange(String, Expr, Expr, Vec<Stmt>),
+    ForEach(String, Expr, Vec<Stmt>),
+    Break,
+    Continue,
+    Expr(Expr),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+/// 二項演算子の優先順位 (低い順)
+const PRECEDENCE: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+impl Parser {
+    fn new(tokens: Vec<(Token, usize)>) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!("{} 行目: {}", self.tokens[self.pos].1, message)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Token::Punct(p) if *p == punct)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(name) if name == keyword)
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<()> {
+        match self.is_punct(punct) {
+            true => {
+                self.next();
+                Ok(())
+            }
+            false => Err(self.error(&format!("'{}' が必要です", punct))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Ident(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => Err(self.error("名前が必要です")),
+        }
+    }
+
+    fn program(&mut self) -> Result<Vec<Stmt>> {
+        let mut stmts = Vec::new();
+        while *self.peek() != Token::Eof {
+            match self.is_punct(";") {
+                true => drop(self.next()),
+                false => stmts.push(self.statement()?),
+            }
+        }
+        Ok(stmts)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>> {
+        self.expect("{")?;
+        let mut stmts = Vec::new();
+        while !self.is_punct("}") {
+            match self.peek() {
+                Token::Eof => return Err(self.error("'}' が必要です")),
+                Token::Punct(";") => drop(self.next()),
+                _ => stmts.push(self.statement()?),
+            }
+        }
+        self.next();
+        Ok(stmts)
+    }
+
+    /// 式の文の後の `;` (ブロックの最後なら省略できる)
+    fn end_of_statement(&mut self) -> Result<()> {
+        match self.is_punct("}") || *self.peek() == Token::Eof {
+            true => Ok(()),
+            false => self.expect(";"),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Stmt> {
+        let keyword = match self.peek() {
+            Token::Ident(name) => name.clone(),
+            _ => String::new(),
+        };
+        let stmt = match keyword.as_str() {
+            "let" => {
+                self.next();
+                let name = self.ident()?;
+                self.expect("=")?;
+                let stmt = Stmt::Let(name, self.expr()?);
+                self.end_of_statement()?;
+                stmt
+            }
+            "if" => self.if_statement()?,
+            "while" => {
+                self.next();
+                let condition = self.expr()?;
+                Stmt::While(condition, self.block()?)
+            }
+            "loop" => {
+                self.next();
+                Stmt::Loop(self.block()?)
+            }
+            "for" => {
+                self.next();
+                let name = self.ident()?;
+                if !self.is_keyword("in") {
+                    return Err(self.error("'in' が必要です"));
+                }
+                self.next();
+                let from = self.expr()?;
+                match self.is_punct("..") {
+                    true => {
+                        self.next();
+                        let to = self.expr()?;
+                        Stmt::ForRange(name, from, to, self.block()?)
+                    }
+                    false => Stmt::ForEach(name, from, self.block()?),
+                }
+            }
+            "break" | "continue" => {
+                self.next();
+                self.end_of_statement()?;
+                match keyword.as_str() {
+                    "break" => Stmt::Break,
+                    _ => Stmt::Continue,
+                }
+            }
+            _ => {
+                let is_assign = matches!(
+                    self.tokens.get(self.pos + 1),
+                    Some((Token::Punct("=" | "+=" | "-="), _))
+                ) && matches!(self.peek(), Token::Ident(_));
+                let stmt = match is_assign {
+                    true => {
+                        let name = self.ident()?;
+                        let op = match self.next() {
+                            Token::Punct("+=") => Some("+"),
+                            Token::Punct("-=") => Some("-"),
+                            _ => None,
+                        };
+                        Stmt::Assign(name, op, self.expr()?)
+                    }
+                    false => Stmt::Expr(self.expr()?),
+                };
+                self.end_of_statement()?;
+                stmt
+            }
+        };
+        Ok(stmt)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt> {
+        self.next();
+        let condition = self.expr()?;
+        let then = self.block()?;
+        let otherwise = match self.is_keyword("else") {
+            true => {
+                self.next();
+                match self.is_keyword("if") {
+                    true => vec![self.if_statement()?],
+                    false => self.block()?,
+                }
+            }
+            false => Vec::new(),
+        };
+        Ok(Stmt::If(condition, then, otherwise))
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        let Some(ops) = PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+        let mut left = self.binary(level + 1)?;
+        while let Token::Punct(op) = *self.peek() {
+            if !ops.contains(&op) {
+                break;
+            }
+            self.next();
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match *self.peek() {
+            Token::Punct(op @ ("!" | "-")) => {
+                self.next();
+                Ok(Expr::Unary(op, Box::new(self.unary()?)))
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        while self.is_punct("[") {
+            self.next();
+            let index = self.expr()?;
+            self.expect("]")?;
+            expr = Expr::Index(Box::new(expr), Box::new(index));
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        if matches!(self.peek(), Token::Eof | Token::Punct(_))
+            && !(self.is_punct("(") || self.is_punct("["))
+        {
+            return Err(self.error("式が必要です"));
+        }
+        match self.next() {
+            Token::Int(n) => Ok(Expr::Int(n)),
+            Token::Str(s) => Ok(Expr::Str(s)),
+            Token::Ident(name) if name == "true" => Ok(Expr::Bool(true)),
+            Token::Ident(name) if name == "false" => Ok(Expr::Bool(false)),
+            Token::Ident(name) => match self.is_punct("(") {
+                true => {
+                    self.next();
+                    Ok(Expr::Call(name, self.list(")")?))
+                }
+                false => Ok(Expr::Var(name)),
+            },
+            Token::Punct("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            _ => Ok(Expr::Array(self.list("]")?)),
+        }
+    }
+
+    /// `,` 区切りの式を `close` まで読む
+    fn list(&mut self, close: &str) -> Result<Vec<Expr>> {
+        let mut items = Vec::new();
+        while !self.is_punct(close) {
+            items.push(self.expr()?);
+            if !self.is_punct(close) {
+                self.expect(",")?;
+            }
+        }
+        self.next();
+        Ok(items)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Unit,
+    Int(i64),
+    Str(String),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
+
+impl Value {
+    fn truthy(&self) -> Result<bool> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            value => bail!("条件が真偽値ではありません: {}", value),
+        }
+    }
+
+    fn int(&self) -> Result<i64> {
+        match self {
+            Value::Int(n) => Ok(*n),
+            value => bail!("整数ではありません: {}", value),
+        }
+    }
+}
+
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
+
+struct Interpreter<'a, W: Write> {
+    scopes: Vec<HashMap<String, Value>>,
+    out: W,
+    /// `set_character` と `set_mood` を反映した現在の表示設定
+    opts: RenderOptions,
+    /// `set_character` で差し替えた表示設定 (機嫌を反映する前)
+    cast: RenderOptions,
+    mood: Option<Mood>,
+    base_opts: &'a RenderOptions,
+    dishes: Vec<String>,
+    rng: Rng,
+    animate: bool,
+    /// 非アニメーション時にフレームの間を空けるため
+    shown: bool,
+    /// wait の待ち時間から、その前の計算と描画にかかった時間を引く
+    schedule: Schedule,
+}
+
+impl<'a, W: Write> Interpreter<'a, W> {
+    fn new(out: W, dishes: Vec<String>, animate: bool, opts: &'a RenderOptions) -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            out,
+            opts: opts.clone(),
+            cast: opts.clone(),
+            mood: None,
+            base_opts: opts,
+            dishes: match dishes.is_empty() {
+                true => DEFAULT_DISHES.iter().map(|s| s.to_string()).collect(),
+                false => dishes,
+            },
+            rng: Rng::stream(opts.seed, "rhai"),
+            animate,
+            shown: false,
+            schedule: Schedule::start(),
+        }
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> Result<Flow> {
+        self.scopes.push(HashMap::new());
+        let flow = self.statements(stmts);
+        self.scopes.pop();
+        flow
+    }
+
+    fn statements(&mut self, stmts: &[Stmt]) -> Result<Flow> {
+        for stmt in stmts {
+            match self.statement(stmt)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<Flow> {
+        match stmt {
+            Stmt::Let(name, expr) => {
+                let value = self.eval(expr)?;
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(name.clone(), value);
+                }
+            }
+            Stmt::Assign(name, op, expr) => {
+                let value = self.eval(expr)?;
+                let value = match op {
+                    Some(op) => binary(op, self.var(name)?, value)?,
+                    None => value,
+                };
+                let slot = self
+                    .scopes
+                    .iter_mut()
+                    .rev()
+                    .find_map(|scope| scope.get_mut(name))
+                    .ok_or_else(|| anyhow!("変数 {} は let で宣言されていません", name))?;
+                *slot = value;
+            }
+            Stmt::If(condition, then, otherwise) => {
+                let branch = match self.eval(condition)?.truthy()? {
+                    true => then,
+                    false => otherwise,
+                };
+                return self.block(branch);
+            }
+            Stmt::While(condition, body) => {
+                while self.eval(condition)?.truthy()? {
+                    if let Flow::Break = self.block(body)? {
+                        break;
+                    }
+                }
+            }
+            Stmt::Loop(body) => loop {
+                if let Flow::Break = self.block(body)? {
+                    break;
+                }
+            },
+            Stmt::ForRange(name, from, to, body) => {
+                let (from, to) = (self.eval(from)?.int()?, self.eval(to)?.int()?);
+                for i in from..to {
+                    if let Flow::Break = self.iteration(name, Value::Int(i), body)? {
+                        break;
+                    }
+                }
+            }
+            Stmt::ForEach(name, items, body) => {
+                let items = match self.eval(items)? {
+                    Value::Array(items) => items,
+                    value => bail!("for で回せるのは範囲か配列です: {}", value),
+                };
+                for item in items {
+                    if let Flow::Break = self.iteration(name, item, body)? {
+                        break;
+                    }
+                }
+            }
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Continue => return Ok(Flow::Continue),
+            Stmt::Expr(expr) => {
+                self.eval(expr)?;
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn iteration(&mut self, name: &str, value: Value, body: &[Stmt]) -> Result<Flow> {
+        self.scopes.push(HashMap::from([(name.to_string(), value)]));
+        let flow = self.block(body);
+        self.scopes.pop();
+        flow
+    }
+
+    fn var(&self, name: &str) -> Result<Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .ok_or_else(|| anyhow!("変数 {} は定義されていません", name))
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value> {
+        Ok(match expr {
+            Expr::Int(n) => Value::Int(*n),
+            Expr::Str(s) => Value::Str(s.clone()),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(item))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Var(name) => self.var(name)?,
+            Expr::Unary(op, expr) => match (*op, self.eval(expr)?) {
+                ("!", Value::Bool(b)) => Value::Bool(!b),
+                ("-", Value::Int(n)) => Value::Int(n.wrapping_neg()),
+                (op, value) => bail!("{} は {} に使えません", op, value),
+            },
+            // && と || は右辺を必要なときだけ評価する
+            Expr::Binary(op @ ("&&" | "||"), left, right) => {
+                let left = self.eval(left)?.truthy()?;
+                match (*op, left) {
+                    ("&&", false) => Value::Bool(false),
+                    ("||", true) => Value::Bool(true),
+                    _ => Value::Bool(self.eval(right)?.truthy()?),
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                binary(op, left, self.eval(right)?)?
+            }
+            Expr::Index(array, index) => {
+                let index = self.eval(index)?.int()?;
+                match self.eval(array)? {
+                    Value::Array(items) => usize::try_from(index)
+                        .ok()
+                        .and_then(|i| items.get(i).cloned())
+                        .ok_or_else(|| anyhow!("添字 {} が範囲外です", index))?,
+                    value => bail!("配列ではありません: {}", value),
+                }
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                self.call(name, &args)?
+            }
+        })
+    }
+
+    fn call(&mut self, name: &str, args: &[Value]) -> Result<Value> {
+        match (name, args) {
+            ("say", [text]) => self.say(&text.to_string(), None)?,
+            ("say", [text, caption]) => self.say(&text.to_string(), Some(&caption.to_string()))?,
+            ("wait", [ms]) => {
+                // wait(1500) のほか wait("1.5s") も書ける
+                let ms = match ms {
+                    Value::Str(text) => crate::duration::parse_millis(text)? as i64,
+                    ms => ms.int()?,
+                };
+                if self.animate && ms > 0 {
+                    self.out.flush()?;
+                    sleep(self.schedule.advance(Duration::from_millis(ms as u64)));
+                }
+            }
+            ("set_character", [character]) => {
+                self.cast = match character.to_string().as_str() {
+                    "" => self.base_opts.clone(),
+                    character => self.base_opts.with_character(character)?,
+                };
+                self.opts = self.looks();
+            }
+            ("set_mood", [mood]) => {
+                self.mood = match mood.to_string().as_str() {
+                    "" => None,
+                    mood => Some(mood.parse()?),
+                };
+                self.opts = self.looks();
+            }
+            ("random_dish", []) => {
+                let i = self.rng.below(self.dishes.len());
+                return Ok(Value::Str(self.dishes[i].clone()));
+            }
+            ("rand", [n]) => {
+                let n = n.int()?;
+                if n <= 0 {
+                    bail!("rand の引数は正の整数にしてください");
+                }
+                return Ok(Value::Int(self.rng.below(n as usize) as i64));
+            }
+            ("len", [Value::Array(items)]) => return Ok(Value::Int(items.len() as i64)),
+            ("len", [Value::Str(s)]) => return Ok(Value::Int(s.chars().count() as i64)),
+            ("to_string", [value]) => return Ok(Value::Str(value.to_string())),
+            _ => bail!("関数 {}({} 個の引数) はありません", name, args.len()),
+        }
+        Ok(Value::Unit)
+    }
+
+    /// 今のキャラクターに今の機嫌を反映した表示設定
+    fn looks(&self) -> RenderOptions {
+        match self.mood {
+            Some(mood) => mood.apply(&self.cast),
+            None => self.cast.clone(),
+        }
+    }
+
+    fn say(&mut self, text: &str, caption: Option<&str>) -> Result<()> {
+        let terminal_width = crate::terminal::width(&Term::stdout());
+        let lines = render_say(text, caption, terminal_width, &self.opts)?;
+        match self.animate {
+            true => redraw(&mut self.out, &lines, &self.opts)?,
+            false => {
+                if self.shown {
+                    writeln!(self.out)?;
+                }
+                write_frame(&mut self.out, &lines, &self.opts)?;
+            }
+        }
+        self.shown = true;
+        Ok(())
+    }
+}
+
+fn binary(op: &str, left: Value, right: Value) -> Result<Value> {
+    Ok(match (op, left, right) {
+        ("+", Value::Int(a), Value::Int(b)) => Value::Int(a.wrapping_add(b)),
+        ("-", Value::Int(a), Value::Int(b)) => Value::Int(a.wrapping_sub(b)),
+        ("*", Value::Int(a), Value::Int(b)) => Value::Int(a.wrapping_mul(b)),
+        ("/" | "%", Value::Int(_), Value::Int(0)) => bail!("0 で割ろうとしました"),
+        ("/", Value::Int(a), Value::Int(b)) => Value::Int(a.wrapping_div(b)),
+        ("%", Value::Int(a), Value::Int(b)) => Value::Int(a.wrapping_rem(b)),
+        ("+", Value::Str(a), b) => Value::Str(a + &b.to_string()),
+        ("+", a, Value::Str(b)) => Value::Str(a.to_string() + &b),
+        ("==", a, b) => Value::Bool(a == b),
+        ("!=", a, b) => Value::Bool(a != b),
+        ("<", Value::Int(a), Value::Int(b)) => Value::Bool(a < b),
+        ("<=", Value::Int(a), Value::Int(b)) => Value::Bool(a <= b),
+        (">", Value::Int(a), Value::Int(b)) => Value::Bool(a > b),
+        (">=", Value::Int(a), Value::Int(b)) => Value::Bool(a >= b),
+        (op, a, b) => bail!("{} {} {} は計算できません", a, op, b),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser as _;
+
+    fn opts() -> RenderOptions {
+        let args = crate::Args::try_parse_from(["fav_say_dragon", "--ascii", "say", "x"]).unwrap();
+        RenderOptions::resolve(&args.render, &crate::Config::default(), 0, false).unwrap()
+    }
+
+    /// 非アニメーションで実行して、トップレベルの変数を残したまま返す
+    fn interpret<'a>(source: &str, opts: &'a RenderOptions) -> Result<Interpreter<'a, Vec<u8>>> {
+        let program = Parser::new(tokenize(source)?).program()?;
+        let dishes = vec!["からあげ".to_string(), "ハンバーグ".to_string()];
+        let mut interpreter = Interpreter::new(Vec::new(), dishes, false, opts);
+        interpreter.statements(&program)?;
+        Ok(interpreter)
+    }
+
+    fn eval(expr: &str) -> Result<Value> {
+        let opts = opts();
+        interpret(&format!("let x = {};", expr), &opts)?.var("x")
+    }
+
+    fn error(source: &str) -> String {
+        let opts = opts();
+        match interpret(source, &opts) {
+            Ok(_) => panic!("{} が失敗しませんでした", source),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn follows_operator_precedence() {
+        assert_eq!(eval("1 + 2 * 3").unwrap(), Value::Int(7));
+        assert_eq!(eval("(1 + 2) * 3").unwrap(), Value::Int(9));
+        assert_eq!(eval("10 - 4 - 3").unwrap(), Value::Int(3));
+        assert_eq!(eval("7 % 4 * 2").unwrap(), Value::Int(6));
+        assert_eq!(eval("-2 * 3").unwrap(), Value::Int(-6));
+        assert_eq!(eval("1 < 2 == 2 < 3").unwrap(), Value::Bool(true));
+        assert_eq!(eval("true || false && false").unwrap(), Value::Bool(true));
+        assert_eq!(eval("!true || true").unwrap(), Value::Bool(true));
+        assert_eq!(eval("\"a\" + 1 + 2").unwrap(), Value::Str("a12".into()));
+        assert_eq!(eval("1 + 2 + \"a\"").unwrap(), Value::Str("3a".into()));
+        assert_eq!(eval("[1, [2, 3]][1][0]").unwrap(), Value::Int(2));
+    }
+
+    #[test]
+    fn short_circuits_logical_operators() {
+        assert_eq!(eval("false && 1 / 0 == 0").unwrap(), Value::Bool(false));
+        assert_eq!(eval("true || rand(0) == 0").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn wraps_integer_overflow() {
+        let min = "(0 - 9223372036854775807 - 1)";
+        assert_eq!(eval(&format!("-{}", min)).unwrap(), Value::Int(i64::MIN));
+        assert_eq!(eval(&format!("{} - 1", min)).unwrap(), Value::Int(i64::MAX));
+        assert_eq!(
+            eval(&format!("{} / -1", min)).unwrap(),
+            Value::Int(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn runs_loops_and_scopes() {
+        let opts = opts();
+        let source = "
+            let sum = 0;
+            for i in 0..5 { if i == 3 { continue; } sum += i; }
+            let n = 0;
+            while n < 10 { n += 3; }
+            let items = 0;
+            for item in [\"a\", \"b\"] { items += len(item); }
+            let count = 0;
+            loop { count += 1; if count >= 4 { break } }
+            let outer = 1;
+            if true { let outer = 2; outer = 3; }
+        ";
+        let interpreter = interpret(source, &opts).unwrap();
+        assert_eq!(interpreter.var("sum").unwrap(), Value::Int(7));
+        assert_eq!(interpreter.var("n").unwrap(), Value::Int(12));
+        assert_eq!(interpreter.var("items").unwrap(), Value::Int(2));
+        assert_eq!(interpreter.var("count").unwrap(), Value::Int(4));
+        assert_eq!(interpreter.var("outer").unwrap(), Value::Int(1));
+        assert!(interpreter.var("i").is_err());
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        assert_eq!(error("let a = 1;\nlet b = ;"), "2 行目: 式が必要です");
+        assert_eq!(error("let a = 1\nlet b = 2;"), "2 行目: ';' が必要です");
+        assert_eq!(error("if true {"), "1 行目: '}' が必要です");
+        assert_eq!(error("for i 0..3 {}"), "1 行目: 'in' が必要です");
+        assert_eq!(error("\n\"abc"), "2 行目: 文字列が閉じていません");
+        assert_eq!(error("/* abc"), "1 行目: コメントが閉じていません");
+        assert_eq!(
+            error("let a = 1 @ 2;"),
+            "1 行目: 使えない文字 '@' があります"
+        );
+        assert_eq!(error("99999999999999999999"), "1 行目: 数が大きすぎます");
+    }
+
+    #[test]
+    fn reports_runtime_errors() {
+        assert_eq!(error("x = 1;"), "変数 x は let で宣言されていません");
+        assert_eq!(error("say(y);"), "変数 y は定義されていません");
+        assert_eq!(error("1 / 0;"), "0 で割ろうとしました");
+        assert_eq!(error("1 % 0;"), "0 で割ろうとしました");
+        assert_eq!(error("[1][1];"), "添字 1 が範囲外です");
+        assert_eq!(error("[1][-1];"), "添字 -1 が範囲外です");
+        assert_eq!(error("1[0];"), "配列ではありません: 1");
+        assert_eq!(error("if 1 {}"), "条件が真偽値ではありません: 1");
+        assert_eq!(error("for i in 3 {}"), "for で回せるのは範囲か配列です: 3");
+        assert_eq!(error("!1;"), "! は 1 に使えません");
+        assert_eq!(error("true < 1;"), "true < 1 は計算できません");
+        assert_eq!(error("nope(1);"), "関数 nope(1 個の引数) はありません");
+        assert_eq!(error("say();"), "関数 say(0 個の引数) はありません");
+    }
+
+    #[test]
+    fn says_text_and_captions() {
+        let opts = opts();
+        let interpreter = interpret("say(\"からあげ\"); say(1 + 1, \"おいしい\");", &opts).unwrap();
+        let out = String::from_utf8(interpreter.out).unwrap();
+        assert!(out.contains("からあげ"), "{}", out);
+        assert!(out.contains('2'), "{}", out);
+        assert!(out.contains("おいしい"), "{}", out);
+        assert!(
+            out.contains("\n\n"),
+            "フレームの間が空いていません: {}",
+            out
+        );
+    }
+
+    #[test]
+    fn waits_only_when_animating() {
+        let opts = opts();
+        let start = std::time::Instant::now();
+        interpret("wait(60000); wait(\"1m\"); wait(-1);", &opts).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(interpret("wait(\"abc\");", &opts).is_err());
+        assert!(interpret("wait(true);", &opts).is_err());
+    }
+
+    #[test]
+    fn picks_random_dishes_and_numbers() {
+        let opts = opts();
+        let source = "
+            let dish = random_dish();
+            let ok = true;
+            for i in 0..50 { let n = rand(3); ok = ok && n >= 0 && n < 3; }
+        ";
+        let interpreter = interpret(source, &opts).unwrap();
+        let dish = interpreter.var("dish").unwrap().to_string();
+        assert!(
+            ["からあげ", "ハンバーグ"].contains(&dish.as_str()),
+            "{}",
+            dish
+        );
+        assert_eq!(interpreter.var("ok").unwrap(), Value::Bool(true));
+        assert_eq!(error("rand(0);"), "rand の引数は正の整数にしてください");
+    }
+
+    #[test]
+    fn measures_and_converts_values() {
+        assert_eq!(eval("len([1, 2, 3])").unwrap(), Value::Int(3));
+        assert_eq!(eval("len(\"からあげ\")").unwrap(), Value::Int(4));
+        assert_eq!(
+            eval("to_string([1, \"a\", true])").unwrap(),
+            Value::Str("[1, a, true]".into())
+        );
+        assert!(eval("len(1)").is_err());
+    }
+
+    #[test]
+    fn switches_characters() {
+        let opts = opts();
+        let interpreter = interpret("set_character(\"dragon-sign\");", &opts).unwrap();
+        assert_eq!(interpreter.opts.character, "dragon-sign");
+        let interpreter = interpret(
+            "set_character(\"dragon-sign\"); set_character(\"\");",
+            &opts,
+        )
+        .unwrap();
+        assert_eq!(interpreter.opts.character, opts.character);
+        assert_eq!(
+            error("set_character(\"nobody\");"),
+            "キャラクター 'nobody' が見つかりません"
+        );
+    }
+
+    #[test]
+    fn sets_moods() {
+        let opts = opts();
+        let angry = crate::mood::Mood::Angry.apply(&opts);
+        let interpreter = interpret("set_mood(\"angry\");", &opts).unwrap();
+        assert_eq!(interpreter.opts.art_style, angry.art_style);
+        assert_eq!(interpreter.opts.character, opts.character);
+        let interpreter = interpret("set_mood(\"angry\"); set_mood(\"\");", &opts).unwrap();
+        assert_eq!(interpreter.opts.art_style, opts.art_style);
+        // キャラクターを変えても機嫌は続く
+        let interpreter = interpret(
+            "set_mood(\"angry\"); set_character(\"dragon-sign\");",
+            &opts,
+        )
+        .unwrap();
+        assert_eq!(interpreter.opts.art_style, angry.art_style);
+        assert_eq!(interpreter.opts.character, "dragon-sign");
+        assert!(error("set_mood(\"hungry\");").starts_with("機嫌 'hungry' はありません"));
+    }
+}
@@ -0,0 +1,244 @@
+//! `self-update` の GitHub のリリースからの更新
+//!
+//! 最新のリリースのうちこの OS と CPU 向けの成果物を取ってきて、同じリリースの
+//! チェックサム (`<成果物>.sha256` か `SHA256SUMS`) と SHA-256 が合ったときだけ
+//! 実行中のバイナリと入れ替える。チェックサムの無いリリースは入れない
+//!
+//! 署名の検証はまだしない。チェックサムは成果物と同じ場所から取るので、
+//! 転送の途中で壊れたものは弾けるが、リリースそのものの改ざんは防げない
+
+use crate::{fetch, i18n, json, log, sanitize, sha256};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 最新のリリースを返す GitHub の API
+const LATEST: &str =
+    "https://api.github.com/repos/anotherhollow1125/fav_say_dragon/releases/latest";
+/// API の応答の上限 (バイト)
+const MAX_RELEASE_BYTES: u64 = 1024 * 1024;
+/// 成果物の上限 (バイト)
+const MAX_ASSET_BYTES: u64 = 64 * 1024 * 1024;
+/// チェックサムをまとめたファイルの名前 (大文字小文字は区別しない)
+const SUMS: &[&str] = &["SHA256SUMS", "SHA256SUMS.txt", "checksums.txt"];
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// `v1.2.3` のようなタグの数の並び (数でない部分は無視する)
+fn version(tag: &str) -> Vec<u64> {
+    let tag = tag.trim_start_matches(['v', 'V']);
+    let core = tag.split(['-', '+']).next().unwrap_or(tag);
+    core.split('.').map(|n| n.parse().unwrap_or(0)).collect()
+}
+
+/// この OS と CPU 向けの成果物 (チェックサムのファイルは除く)
+fn pick(assets: &[Asset]) -> Option<&Asset> {
+    let os: &[&str] = match std::env::consts::OS {
+        "macos" => &["apple", "darwin", "macos"],
+        "windows" => &["windows"],
+        os => &[os][..],
+    };
+    let arch: &[&str] = match std::env::consts::ARCH {
+        "x86_64" => &["x86_64", "amd64"],
+        "aarch64" => &["aarch64", "arm64"],
+        arch => &[arch][..],
+    };
+    assets.iter().find(|asset| {
+        let name = asset.name.to_lowercase();
+        !name.ends_with(".sha256")
+            && !SUMS.iter().any(|sums| sums.eq_ignore_ascii_case(&name))
+            && os.iter().any(|os| name.contains(os))
+            && arch.iter().any(|arch| name.contains(arch))
+    })
+}
+
+/// `asset` のチェックサム (16 進数、小文字)
+fn checksum(release: &Release, asset: &Asset) -> Result<String> {
+    let single = format!("{}.sha256", asset.name);
+    let found = release.assets.iter().find_map(|candidate| {
+        if candidate.name == single {
+            return Some((candidate, true));
+        }
+        SUMS.iter()
+            .any(|sums| sums.eq_ignore_ascii_case(&candidate.name))
+            .then_some((candidate, false))
+    });
+    let Some((sums, single)) = found else {
+        bail!(
+            "{} のチェックサムがリリースにありません ({} か SHA256SUMS)",
+            asset.name,
+            single
+        );
+    };
+    let body = fetch::text(&sums.browser_download_url, fetch::MAX_BYTES)?;
+    // `sha256sum` の出力の形 (`<ハッシュ>  <名前>`、バイナリモードなら名前の前に `*`)
+    let hash = body.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let hash = fields.next()?;
+        let name = fields.next().map(|name| name.trim_start_matches('*'));
+        match (single, name) {
+            (true, _) => Some(hash),
+            (false, Some(name)) if name == asset.name => Some(hash),
+            _ => None,
+        }
+    });
+    match hash {
+        Some(hash) if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            Ok(hash.to_ascii_lowercase())
+        }
+        _ => bail!(
+            "{} に {} のチェックサムが見つかりません",
+            sums.name,
+            asset.name
+        ),
+    }
+}
+
+/// アーカイブなら `work` に展開して中の実行ファイルを、そうでなければそのまま返す
+fn unpack(download: &Path, name: &str, work: &Path) -> Result<PathBuf> {
+    let lower = name.to_lowercase();
+    let is_archive = [".tar.gz", ".tgz", ".tar.xz", ".zip"]
+        .iter()
+        .any(|ext| lower.ends_with(ext));
+    if !is_archive {
+        return Ok(download.to_path_buf());
+    }
+    let extracted = work.join("release");
+    std::fs::create_dir_all(&extracted)?;
+    // Windows の tar (bsdtar) は zip も展開できる
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(download)
+        .arg("-C")
+        .arg(&extracted)
+        .status()
+        .context("tar を実行できません")?;
+    if !status.success() {
+        bail!("tar が失敗しました ({})", status);
+    }
+    let binary = format!("fav_say_dragon{}", std::env::consts::EXE_SUFFIX);
+    find(&extracted, &binary)?
+        .with_context(|| format!("{} の中に {} が見つかりません", name, binary))
+}
+
+fn find(dir: &Path, file: &str) -> Result<Option<PathBuf>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if let Some(found) = find(&path, file)? {
+                return Ok(Some(found));
+            }
+        } else if path.file_name().is_some_and(|name| name == file) {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// 実行中のバイナリを `new` と入れ替える
+///
+/// 同じディレクトリに書いてから名前を変えるので、途中で失敗しても元のバイナリは残る。
+/// Windows では実行中のファイルを上書きできないため、元のものを `.old` に退ける
+fn replace(new: &Path) -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("実行ファイルのパスを特定できません")?;
+    let exe = std::fs::canonicalize(&exe).unwrap_or(exe);
+    let staged = exe.with_extension("new");
+    std::fs::copy(new, &staged)
+        .with_context(|| format!("{} に書き込めません", staged.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    if cfg!(windows) {
+        let old = exe.with_extension("old");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(&exe, &old)
+            .with_context(|| format!("{} を退けられません", exe.display()))?;
+    }
+    if let Err(e) = std::fs::rename(&staged, &exe) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e).with_context(|| format!("{} を置き換えられません", exe.display()));
+    }
+    Ok(exe)
+}
+
+/// 最新のリリースを確かめ、`check` でなければ入れ替える。`force` なら同じ版でも入れ直す
+pub fn self_update(check: bool, force: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let mut release: Release = json::from_str(&fetch::text(LATEST, MAX_RELEASE_BYTES)?)
+        .context("リリースの情報を読めません")?;
+    // タグと成果物の名前は端末に出すので、エスケープシーケンスを取り除く
+    release.tag_name = sanitize::strip(&release.tag_name).into_owned();
+    for asset in &mut release.assets {
+        asset.name = sanitize::strip(&asset.name).into_owned();
+    }
+    log::info!("最新のリリース: {}", release.tag_name);
+    let newer = version(&release.tag_name) > version(current);
+    if !newer && (check || !force) {
+        println!("{}", i18n::tr(&format!("最新版です ({})", current)));
+        return Ok(());
+    }
+    if check {
+        println!(
+            "{}",
+            i18n::tr(&format!(
+                "{} が出ています (いまは {})",
+                release.tag_name, current
+            ))
+        );
+        return Ok(());
+    }
+    let Some(asset) = pick(&release.assets) else {
+        bail!(
+            "{} に {}-{} 向けの成果物がありません",
+            release.tag_name,
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+    };
+    let expected = checksum(&release, asset)?;
+
+    let work = std::env::temp_dir().join(format!("fav_say_dragon-update-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&work);
+    std::fs::create_dir_all(&work)?;
+    let result = (|| {
+        let download = work.join(&asset.name);
+        log::info!("{} を取得します", asset.browser_download_url);
+        fetch::download(&asset.browser_download_url, &download, MAX_ASSET_BYTES)?;
+        let actual = sha256::hex(&std::fs::read(&download)?);
+        if actual != expected {
+            bail!(
+                "{} のチェックサムが合いません (期待: {}, 実際: {})",
+                asset.name,
+                expected,
+                actual
+            );
+        }
+        log::debug!("チェックサムが合いました: {}", actual);
+        replace(&unpack(&download, &asset.name, &work)?)
+    })();
+    let _ = std::fs::remove_dir_all(&work);
+    let exe = result?;
+    println!(
+        "{}",
+        i18n::tr(&format!(
+            "{} を {} に更新しました",
+            exe.display(),
+            release.tag_name
+        ))
+    );
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use crate::renderer::Terminal;
+use crate::{fit_frames, i18n, json, play, render_say, PlayOptions, RenderOptions, Script};
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+/// リクエスト行とヘッダーの合計の上限 (バイト)
+const MAX_HEADER_SIZE: usize = 8 * 1024;
+/// リクエストを読み終えるまでの上限 (遅いクライアントに接続を握られ続けないため)
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// GET /say の width の上限
+const MAX_WIDTH: usize = 1000;
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    body: String,
+}
+
+pub fn serve(host: &str, port: u16, opts: RenderOptions) -> Result<()> {
+    let opts = Arc::new(opts);
+    let listener = TcpListener::bind((host, port))?;
+    eprintln!(
+        "{}",
+        i18n::tr(&format!("http://{}:{} で待ち受けています", host, port))
+    );
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{}", i18n::tr(&format!("接続エラー: {}", e)));
+                continue;
+            }
+        };
+        let opts = Arc::clone(&opts);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &opts) {
+                eprintln!(
+                    "{}",
+                    i18n::tr(&format!("リクエスト処理エラー: {}", i18n::error(e)))
+                );
+            }
+        });
+    }
+
+    Ok(())
+}
+
+pub fn serve_tcp(
+    host: &str,
+    port: u16,
+    script: Script,
+    play_opts: PlayOptions,
+    opts: RenderOptions,
+) -> Result<()> {
+    let opts = Arc::new(opts);
+    let listener = TcpListener::bind((host, port))?;
+    eprintln!(
+        "{}",
+        i18n::tr(&format!("telnet {} {} で配信しています", host, port))
+    );
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{}", i18n::tr(&format!("接続エラー: {}", e)));
+                continue;
+            }
+        };
+        let script = script.clone();
+        let play_opts = play_opts.clone();
+        let opts = Arc::clone(&opts);
+        thread::spawn(move || {
+            let peer = stream.peer_addr().ok();
+            let mut out = Terminal::new(CrlfWriter(stream), play_opts.animate, &opts);
+            if let Err(e) = play(&mut out, script, play_opts, || 0, &opts) {
+                eprintln!(
+                    "{}",
+                    i18n::tr(&format!("{:?} への配信を中断しました: {}", peer, e))
+                );
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// telnet クライアント向けに改行を CRLF へ変換する
+struct CrlfWriter<W: Write>(W);
+
+impl<W: Write> Write for CrlfWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for line in buf.split_inclusive(|&b| b == b'\n') {
+            match line.strip_suffix(b"\n") {
+                Some(line) => {
+                    self.0.write_all(line)?;
+                    self.0.write_all(b"\r\n")?;
+                }
+                None => self.0.write_all(line)?,
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+fn handle(mut stream: TcpStream, opts: &RenderOptions) -> Result<()> {
+    let request = match read_request(&mut stream) {
+        Ok(request) => request,
+        Err(e) => return respond(&mut stream, 400, "Bad Request", &format!("{}\n", e)),
+    };
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/say") => {
+            let text = opts.sanitize(request.query.get("text").map(String::as_str).unwrap_or(""));
+            let caption = request
+                .query
+                .get("caption")
+                .map(|caption| opts.sanitize(caption));
+            let width = match request.query.get("width") {
+                Some(width) => match width.parse() {
+                    Ok(width) if width <= MAX_WIDTH => width,
+                    _ => {
+                        let message = format!("width は {} 以下の整数です\n", MAX_WIDTH);
+                        return respond(&mut stream, 400, "Bad Request", &message);
+                    }
+                },
+                None => 0,
+            };
+            let mut frame = match render_say(&text, caption.as_deref(), width, opts) {
+                Ok(lines) => lines.join("\n"),
+                Err(e) => return respond(&mut stream, 400, "Bad Request", &format!("{}\n", e)),
+            };
+            frame.push('\n');
+            respond(&mut stream, 200, "OK", &frame)
+        }
+        ("POST", "/anime") => {
+            let mut script: Script = match json::from_str(&request.body) {
+                Ok(script) => script,
+                Err(e) => return respond(&mut stream, 400, "Bad Request", &format!("{}\n", e)),
+            };
+            script.sanitize(opts);
+            if let Err(e) = untrusted(&script)
+                .and_then(|_| script.validate())
+                .and_then(|_| fit_frames(script.timeline(), opts).map(drop))
+            {
+                return respond(&mut stream, 400, "Bad Request", &format!("{}\n", e));
+            }
+            // クエリの指定を本文の interval より優先する
+            let interval = match request.query.get("interval").map(|i| i.parse::<u64>()) {
+                Some(Ok(interval)) => Some(interval),
+                Some(Err(_)) => Some(0),
+                None => script.interval,
+            };
+            let interval = match interval {
+                Some(interval) if interval >= 10 => interval,
+                Some(_) => {
+                    return respond(
+                        &mut stream,
+                        400,
+                        "Bad Request",
+                        "interval は 10 以上の整数です\n",
+                    )
+                }
+                None => 1000,
+            };
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nConnection: close\r\n\r\n"
+            )?;
+            let play_opts = PlayOptions {
+                interval,
+                transition: script.transition,
+                ..PlayOptions::default()
+            };
+            let mut out = Terminal::new(&mut stream, play_opts.animate, opts);
+            play(&mut out, script, play_opts, || 0, opts)
+        }
+        (_, "/say" | "/anime") => respond(
+            &mut stream,
+            405,
+            "Method Not Allowed",
+            "Method Not Allowed\n",
+        ),
+        _ => respond(&mut stream, 404, "Not Found", "Not Found\n"),
+    }
+}
+
+/// HTTP で受け取ったスクリプトにはキャラクター、テーマ、音のファイルを選ばせない
+/// (サーバーの手元のファイルを読んだり鳴らしたりできてしまうため)
+fn untrusted(script: &Script) -> Result<()> {
+    if !script.characters.is_empty() {
+        bail!(
+            "HTTP で受け取ったスクリプトでは {} は使えません",
+            "characters"
+        );
+    }
+    for frame in &script.frames {
+        if frame.character.is_some() {
+            bail!(
+                "HTTP で受け取ったスクリプトでは {} は使えません",
+                "frames[].character"
+            );
+        }
+        if frame.sound.is_some() {
+            bail!(
+                "HTTP で受け取ったスクリプトでは {} は使えません",
+                "frames[].sound"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 読み始めてから `READ_TIMEOUT` を過ぎたら読み込みを失敗させる
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    until: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.until.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// `MAX_HEADER_SIZE` の残りから 1 行読む
+fn read_header_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    budget: &mut usize,
+) -> Result<usize> {
+    let n = reader.take(*budget as u64).read_line(line)?;
+    *budget -= n;
+    if n > 0 && !line.ends_with('\n') && *budget == 0 {
+        bail!("ヘッダーが大きすぎます (最大 {} バイト)", MAX_HEADER_SIZE);
+    }
+    Ok(n)
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut reader = BufReader::new(Deadline {
+        stream,
+        until: Instant::now() + READ_TIMEOUT,
+    });
+    let mut budget = MAX_HEADER_SIZE;
+
+    let mut request_line = String::new();
+    read_header_line(&mut reader, &mut request_line, &mut budget)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| anyhow!("リクエスト行が不正です"))?;
+    let target = parts
+        .next()
+        .ok_or_else(|| anyhow!("リクエスト行が不正です"))?;
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if read_header_line(&mut reader, &mut header, &mut budget)? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        bail!("本文が大きすぎます (最大 {} バイト)", MAX_BODY_SIZE);
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((percent_decode(key)?, percent_decode(value)?))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query,
+        body: String::from_utf8(body)?,
+    })
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = iter.by_ref().take(2).collect();
+                let hex = std::str::from_utf8(&hex)?;
+                bytes.push(
+                    u8::from_str_radix(hex, 16)
+                        .map_err(|_| anyhow!("不正なパーセントエンコーディングです"))?,
+                );
+            }
+            b => bytes.push(b),
+        }
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn respond(stream: &mut TcpStream, status: u16, reason: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! `--layout side` の横並びのレイアウト
+//!
+//! 吹き出しを消したアートを左に置き、おかずを右の列に端末の残りの幅で折り返して並べる。
+//! 長いお知らせをバナーのように出すときに使う
+
+use crate::compositor::{z, Compositor, Layer};
+use crate::transition::{self, Cell};
+use crate::{art, field_rows, layout, markup, template, RenderOptions};
+
+/// アートと文の列の間の桁数
+const GAP: usize = 2;
+/// 文の列の最小の幅 (端末が狭くてもこれだけは取る)
+const MIN_COLUMN: usize = 20;
+
+/// `styled` のおかずをアートの右に並べた行。各行は `terminal_width` まで空白で埋める
+pub fn dragon(styled: &markup::Styled, terminal_width: usize, opts: &RenderOptions) -> Vec<String> {
+    let art = art(opts);
+    let art_width = art
+        .iter()
+        .map(|line| markup::width(line))
+        .max()
+        .unwrap_or(0);
+    let column = match terminal_width {
+        0 => opts.caption_width,
+        width => width.saturating_sub(art_width + GAP),
+    }
+    .max(MIN_COLUMN);
+
+    let chars: Vec<char> = styled.plain.chars().collect();
+    let rows: Vec<String> = wrap(&chars, column)
+        .into_iter()
+        .map(|(start, len)| {
+            let row: String = chars[start..start + len].iter().collect();
+            match styled.is_plain() {
+                true => opts.bubble_style.apply_to(row).to_string(),
+                false => styled.restyle(&row, start, len, &opts.bubble_style),
+            }
+        })
+        .collect();
+
+    // 文が短ければアートの高さの中央に置く
+    let top = art.len().saturating_sub(rows.len()) / 2;
+    let mut frame = Compositor::new();
+    frame.push(Layer::new(z::CHARACTER, art));
+    frame.push(Layer::new(z::BESIDE, rows).at(art_width + GAP, top));
+    frame.render(terminal_width)
+}
+
+/// 吹き出しを空白にして、左の余白と上下の空行を詰めたアート
+fn art(opts: &RenderOptions) -> Vec<String> {
+    let template = template::Template::parse(&opts.art.template);
+    let rows = layout::fit_rows(Vec::new(), &template, opts.bubble_chars, opts.overflow);
+    let lines = layout::compose(
+        &template,
+        &rows,
+        &field_rows(opts),
+        0,
+        |art| opts.art_style.apply_to(art).to_string(),
+        |slot| opts.bubble_style.apply_to(slot).to_string(),
+    );
+    let mut lines: Vec<Vec<Option<Cell>>> =
+        lines.iter().map(|line| transition::columns(line)).collect();
+    if let Some(bubble) = &opts.art.bubble {
+        for (row, line) in lines.iter_mut().enumerate() {
+            if !(bubble.top..=bubble.bottom).contains(&row) {
+                continue;
+            }
+            // 右のしっぽは枠のすぐ外の 1 文字。枠の端をまたぐ全角文字も消す
+            let tail = bubble
+                .tail
+                .is_some_and(|tail| tail.row == row && tail.side == art::TailSide::Right);
+            let from = (bubble.width + usize::from(tail)).min(line.len());
+            let end = (from..line.len())
+                .find(|&i| line[i].is_some())
+                .unwrap_or(line.len());
+            line[..end].fill(Some(Cell::blank()));
+        }
+    }
+
+    let blank = |cell: &Option<Cell>| cell.as_ref().is_some_and(|cell| cell.c == ' ');
+    let is_empty = |line: &Vec<Option<Cell>>| line.iter().all(blank);
+    let first = lines.iter().position(|line| !is_empty(line)).unwrap_or(0);
+    let last = lines
+        .iter()
+        .rposition(|line| !is_empty(line))
+        .map_or(0, |i| i + 1);
+    let lines = &lines[first..last.max(first)];
+    let indent = lines
+        .iter()
+        .filter(|line| !is_empty(line))
+        .map(|line| line.iter().take_while(|cell| blank(cell)).count())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| {
+            let cells: Vec<Cell> = line.iter().skip(indent).flatten().cloned().collect();
+            let end = cells
+                .iter()
+                .rposition(|cell| cell.c != ' ')
+                .map_or(0, |i| i + 1);
+            transition::join(&cells[..end])
+        })
+        .collect()
+}
+
+/// `chars` を表示幅 `width` の行に分けた、それぞれの行の開始位置と文字数
+///
+/// 改行で行を分け、語の途中で幅を超えるときは直前の空白で折り返す。空白が無ければ文字で切る。
+/// 折り返した位置の空白はどちらの行にも入れない
+pub fn wrap(chars: &[char], width: usize) -> Vec<(usize, usize)> {
+    let char_width = |c: char| console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut row_width = 0;
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        if c == '\n' {
+            rows.push((start, i - start));
+            start = i + 1;
+            row_width = 0;
+            i += 1;
+            continue;
+        }
+        let c_width = char_width(c);
+        if row_width + c_width > width && i > start {
+            let space = chars[start..i].iter().rposition(|c| *c == ' ');
+            match (c, space) {
+                (' ', _) => {
+                    rows.push((start, i - start));
+                    while chars.get(i) == Some(&' ') {
+                        i += 1;
+                    }
+                    start = i;
+                    row_width = 0;
+                    continue;
+                }
+                (_, Some(space)) if space > 0 => {
+                    rows.push((start, space));
+                    start += space + 1;
+                    row_width = chars[start..i].iter().map(|c| char_width(*c)).sum();
+                }
+                _ => {
+                    rows.push((start, i - start));
+                    start = i;
+                    row_width = 0;
+                }
+            }
+        }
+        row_width += c_width;
+        i += 1;
+    }
+    rows.push((start, chars.len() - start));
+    rows
+}
@@ -0,0 +1,85 @@
+//! スクリプトのフレームの `sound` の効果音
+//!
+//! 鳴らすのは `audio` 機能を付けてビルドしたときだけ。OS の再生コマンドを起動し、
+//! 鳴り終わるのは待たずに次へ進む
+
+use crate::i18n;
+use anyhow::Result;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// `path` の音を鳴らし始める
+///
+/// 鳴らせなくても再生は続け、警告は最初の一度だけ出す
+pub fn play(path: &Path) {
+    if let Err(e) = spawn(path) {
+        if !WARNED.swap(true, Ordering::Relaxed) {
+            eprintln!(
+                "{}",
+                i18n::tr(&format!("効果音を鳴らせませんでした: {:#}", i18n::error(e)))
+            );
+        }
+    }
+}
+
+/// Linux などで順に試す再生コマンド
+#[cfg(feature = "audio")]
+const UNIX_BACKENDS: &[&[&str]] = &[
+    &["paplay"],
+    &["aplay", "-q"],
+    &["ffplay", "-nodisp", "-autoexit", "-loglevel", "quiet"],
+];
+
+#[cfg(feature = "audio")]
+fn spawn(path: &Path) -> Result<()> {
+    use anyhow::{bail, Context};
+    use std::process::{Command, Stdio};
+
+    if !path.is_file() {
+        bail!("{} がありません", path.display());
+    }
+    let mut commands = Vec::new();
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("afplay");
+        command.arg(path);
+        commands.push(command);
+    } else if cfg!(windows) {
+        let script = format!(
+            "(New-Object Media.SoundPlayer '{}').PlaySync()",
+            path.display().to_string().replace('\'', "''")
+        );
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-Command", &script]);
+        commands.push(command);
+    } else {
+        for backend in UNIX_BACKENDS {
+            let mut command = Command::new(backend[0]);
+            command.args(&backend[1..]).arg(path);
+            commands.push(command);
+        }
+    }
+    for mut command in commands {
+        let spawned = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).context("再生コマンドを実行できません"),
+            Ok(mut child) => {
+                // 終わったプロセスを残さないよう、別のスレッドで待つ
+                std::thread::spawn(move || child.wait());
+                return Ok(());
+            }
+        }
+    }
+    bail!("再生コマンドが見つかりません (paplay, aplay, ffplay)")
+}
+
+#[cfg(not(feature = "audio"))]
+fn spawn(_path: &Path) -> Result<()> {
+    anyhow::bail!("audio 機能を付けずにビルドしたので音は鳴りません (cargo build --features audio)")
+}
@@ -0,0 +1,77 @@
+use crate::i18n;
+use anyhow::{bail, Context, Result};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Linux などで順に試す読み上げコマンド
+const UNIX_BACKENDS: &[&str] = &["espeak-ng", "espeak", "spd-say"];
+
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// フレームのテキストを読み上げ、終わるまで待つ
+///
+/// 読み上げに失敗しても描画は続け、警告は最初の一度だけ出す
+pub fn frame(text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    if let Err(e) = speak(text) {
+        if !WARNED.swap(true, Ordering::Relaxed) {
+            eprintln!(
+                "{}",
+                i18n::tr(&format!("読み上げできませんでした: {:#}", i18n::error(e)))
+            );
+        }
+    }
+}
+
+pub fn speak(text: &str) -> Result<()> {
+    let text = text.replace('\n', " ");
+    if cfg!(target_os = "macos") {
+        return run(Command::new("say").arg(&text));
+    }
+    if cfg!(windows) {
+        let script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('{}')",
+            text.replace('\'', "''")
+        );
+        return run(Command::new("powershell").args(["-NoProfile", "-Command", &script]));
+    }
+
+    for backend in UNIX_BACKENDS {
+        let mut command = Command::new(backend);
+        match *backend {
+            // 読み終わるまで待たせる
+            "spd-say" => command.arg("--wait").arg(&text),
+            _ => command.arg(&text),
+        };
+        match run(&mut command) {
+            Err(e) if is_not_found(&e) => continue,
+            result => return result,
+        }
+    }
+    bail!(
+        "読み上げコマンドが見つかりません ({})",
+        UNIX_BACKENDS.join(", ")
+    )
+}
+
+fn run(command: &mut Command) -> Result<()> {
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("読み上げコマンドを実行できません")?;
+    if !status.success() {
+        bail!("読み上げコマンドが失敗しました ({})", status);
+    }
+
+    Ok(())
+}
+
+fn is_not_found(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+}
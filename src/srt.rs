@@ -0,0 +1,118 @@
+//! `anime --srt` の SubRip 字幕の読み込み
+//!
+//! 字幕ごとに 1 フレームにして、表示時間を字幕の開始から終了までにする。字幕と字幕の間が
+//! あいていれば、その間は空の吹き出しを出す
+
+use crate::{Frame, Script, Text};
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+
+/// これより短い字幕の間は空けずに詰める (ms)
+const MIN_GAP: u64 = 10;
+
+struct Cue {
+    start: u64,
+    end: u64,
+    text: String,
+}
+
+pub fn load(path: &Path) -> Result<Script> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("{} を読み込めません", path.display()))?;
+    script(&source).with_context(|| format!("{} の読み込みに失敗しました", path.display()))
+}
+
+/// 字幕ごとに 1 フレームにしたスクリプト
+pub fn script(source: &str) -> Result<Script> {
+    Ok(Script {
+        frames: frames(&parse(source)?),
+        ..Script::default()
+    })
+}
+
+fn frames(cues: &[Cue]) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    let mut shown_until = 0;
+    for cue in cues {
+        if cue.start >= shown_until + MIN_GAP {
+            frames.push(Frame {
+                duration: Some(cue.start - shown_until),
+                ..Frame::default()
+            });
+        }
+        frames.push(Frame {
+            side_dish: Text::Plain(cue.text.clone()),
+            // 前の字幕と重なっていれば、重なった分だけ短くする
+            duration: Some(
+                cue.end
+                    .saturating_sub(cue.start.max(shown_until))
+                    .max(MIN_GAP),
+            ),
+            ..Frame::default()
+        });
+        shown_until = shown_until.max(cue.end);
+    }
+    frames
+}
+
+fn parse(source: &str) -> Result<Vec<Cue>> {
+    let source = source.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    for block in source
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+    {
+        let mut lines = block.trim_matches('\n').lines();
+        let mut timing = lines.next().unwrap_or_default();
+        // 番号の行は省略されていてもよい
+        if !timing.contains("-->") {
+            timing = lines.next().unwrap_or_default();
+        }
+        let Some((start, end)) = timing.split_once("-->") else {
+            bail!(
+                "時刻の行がありません: {}",
+                block.lines().next().unwrap_or_default()
+            );
+        };
+        let (start, end) = (timestamp(start)?, timestamp(end)?);
+        if end < start {
+            bail!("終了が開始より前です: {}", timing.trim());
+        }
+        let text = lines.map(strip_tags).collect::<Vec<_>>().join("\n");
+        cues.push(Cue { start, end, text });
+    }
+    cues.sort_by_key(|cue| cue.start);
+    Ok(cues)
+}
+
+/// `00:01:02,500` (`.` 区切りも可) をミリ秒にする。後ろの位置指定は無視する
+fn timestamp(s: &str) -> Result<u64> {
+    let s = s.split_whitespace().next().unwrap_or_default();
+    let invalid = || anyhow!("時刻 '{}' を解釈できません", s);
+    let (clock, millis) = s.split_once([',', '.']).ok_or_else(invalid)?;
+    let fields: Vec<u64> = clock
+        .split(':')
+        .map(|field| field.parse().map_err(|_| invalid()))
+        .collect::<Result<_>>()?;
+    let [hours, minutes, seconds] = fields[..] else {
+        return Err(invalid());
+    };
+    let millis: u64 = millis.parse().map_err(|_| invalid())?;
+    Ok(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
+
+/// `<i>` や `{\an8}` のような書式の指定を取り除く
+fn strip_tags(line: &str) -> String {
+    let mut text = String::new();
+    let mut closing = None;
+    for c in line.chars() {
+        match (closing, c) {
+            (None, '<') => closing = Some('>'),
+            (None, '{') => closing = Some('}'),
+            (None, c) => text.push(c),
+            (Some(end), c) if c == end => closing = None,
+            (Some(_), _) => {}
+        }
+    }
+    text
+}
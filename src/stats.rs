@@ -0,0 +1,62 @@
+//! `anime --stats` の再生の記録
+
+use crate::i18n;
+use std::time::{Duration, Instant};
+
+pub struct Stats {
+    started: Instant,
+    /// フレームごとの描画と出力にかかった時間
+    renders: Vec<Duration>,
+    /// 描画がインターバルに間に合わなかったフレーム `(番号, 描画時間, インターバル)`
+    late: Vec<(usize, Duration, Duration)>,
+}
+
+impl Stats {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            renders: Vec::new(),
+            late: Vec::new(),
+        }
+    }
+
+    /// `index` 番目 (0 始まり) のフレームの描画時間。`interval` はその後に待つ時間
+    pub fn frame(&mut self, index: usize, render: Duration, interval: Option<Duration>) {
+        self.renders.push(render);
+        if let Some(interval) = interval.filter(|interval| render > *interval) {
+            self.late.push((index, render, interval));
+        }
+    }
+
+    /// stderr に出す報告の行
+    pub fn report(&self) -> Vec<String> {
+        let ms = |duration: &Duration| duration.as_secs_f64() * 1000.0;
+        let total = self.renders.iter().sum::<Duration>();
+        let average = match self.renders.len() {
+            0 => 0.0,
+            n => ms(&total) / n as f64,
+        };
+        let max = self.renders.iter().max().map_or(0.0, ms);
+
+        let mut lines = vec![
+            format!(
+                "再生: {} フレーム, 合計 {:.2} 秒",
+                self.renders.len(),
+                self.started.elapsed().as_secs_f64()
+            ),
+            format!("描画: 平均 {:.2} ms, 最大 {:.2} ms", average, max),
+        ];
+        lines.extend(self.late.iter().map(|(index, render, interval)| {
+            format!(
+                "警告: {} フレーム目の描画 ({:.2} ms) がインターバル ({:.2} ms) に間に合いませんでした",
+                index + 1,
+                ms(render),
+                ms(interval)
+            )
+        }));
+        lines
+            .iter()
+            .map(|line| i18n::tr(line).into_owned())
+            .collect()
+    }
+}
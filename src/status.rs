@@ -0,0 +1,188 @@
+//! `status` のシステムの状態の表示
+//!
+//! CPU とメモリとバッテリーの使用率を `/proc` と `/sys` から読んでキャプションに出し、
+//! CPU の負荷に合わせてドラゴンの機嫌 (セリフと吹き出しの色) を変える
+
+use crate::{clear_screen, i18n, redraw_in_place, render_say, say, RenderOptions};
+use anyhow::{Context, Result};
+use console::{Color, Term};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// 既定の更新の間隔 (ms)
+pub const DEFAULT_INTERVAL: u64 = 2000;
+/// 最初の CPU の使用率を測る間隔
+const FIRST_SAMPLE: Duration = Duration::from_millis(250);
+
+/// 負荷ごとのドラゴンの機嫌
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mood {
+    Sleepy,
+    Calm,
+    Busy,
+    Panicked,
+}
+
+impl Mood {
+    /// `sleepy` 未満なら眠く、`panic` 以上ならあわてる
+    fn from_load(load: f64, sleepy: f64, panic: f64) -> Self {
+        match load {
+            _ if load >= panic => Self::Panicked,
+            _ if load < sleepy => Self::Sleepy,
+            _ if load >= (sleepy + panic) / 2.0 => Self::Busy,
+            _ => Self::Calm,
+        }
+    }
+
+    fn comment(self) -> &'static str {
+        match self {
+            Self::Sleepy => "ひまだなあ… Zzz",
+            Self::Calm => "順調だよ",
+            Self::Busy => "けっこう忙しい！",
+            Self::Panicked => "もう無理！ たすけて！",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Sleepy => Color::Blue,
+            Self::Calm => Color::Green,
+            Self::Busy => Color::Yellow,
+            Self::Panicked => Color::Red,
+        }
+    }
+}
+
+/// `/proc/stat` の CPU 全体の時間 (すべて, 何もしていない)
+#[derive(Clone, Copy)]
+struct CpuTimes {
+    total: u64,
+    idle: u64,
+}
+
+impl CpuTimes {
+    fn read() -> Result<Self> {
+        let stat = std::fs::read_to_string("/proc/stat")
+            .context("CPU の使用率を読めません (/proc/stat)")?;
+        let times: Vec<u64> = stat
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("cpu "))
+            .context("CPU の使用率を読めません (/proc/stat)")?
+            .split_whitespace()
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        // idle と iowait
+        let idle = times.get(3).copied().unwrap_or(0) + times.get(4).copied().unwrap_or(0);
+        Ok(Self {
+            total: times.iter().sum(),
+            idle,
+        })
+    }
+
+    /// `earlier` からの使用率 (%)
+    fn usage_since(self, earlier: Self) -> f64 {
+        let total = self.total.saturating_sub(earlier.total);
+        let idle = self.idle.saturating_sub(earlier.idle);
+        match total {
+            0 => 0.0,
+            total => (total - idle.min(total)) as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// メモリの使用率 (%)
+fn memory() -> Option<f64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<f64> {
+        let line = meminfo.lines().find(|line| line.starts_with(name))?;
+        line[name.len()..].split_whitespace().next()?.parse().ok()
+    };
+    let total = field("MemTotal:")?;
+    let available = field("MemAvailable:")?;
+    (total > 0.0).then(|| (total - available) * 100.0 / total)
+}
+
+/// 最初に見つかったバッテリーの残量 (%) と充電中か
+fn battery() -> Option<(u8, bool)> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    entries.flatten().find_map(|entry| {
+        let path = entry.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).ok();
+        if read("type")?.trim() != "Battery" {
+            return None;
+        }
+        let capacity = read("capacity")?.trim().parse().ok()?;
+        let charging = read("status").is_some_and(|status| status.trim() == "Charging");
+        Some((capacity, charging))
+    })
+}
+
+/// キャプションに出す `CPU 12% | MEM 40% | BAT 80%`
+fn summary(cpu: f64, memory: Option<f64>, battery: Option<(u8, bool)>) -> String {
+    let mut parts = vec![format!("CPU {:.0}%", cpu)];
+    if let Some(memory) = memory {
+        parts.push(format!("MEM {:.0}%", memory));
+    }
+    if let Some((capacity, charging)) = battery {
+        parts.push(format!(
+            "BAT {}%{}",
+            capacity,
+            if charging { "+" } else { "" }
+        ));
+    }
+    parts.join(" | ")
+}
+
+/// `once` なら一度だけ出して終わる。そうでなければ `interval` ごとに描き直す
+pub fn status(
+    interval: Duration,
+    sleepy: f64,
+    panic: f64,
+    once: bool,
+    opts: &RenderOptions,
+) -> Result<()> {
+    if !Path::new("/proc/stat").exists() {
+        anyhow::bail!("status は /proc のある環境 (Linux) でだけ使えます");
+    }
+    let mut previous = CpuTimes::read()?;
+    sleep(FIRST_SAMPLE);
+    let mood_opts = |cpu: f64| {
+        let mood = Mood::from_load(cpu, sleepy, panic);
+        let mut opts = opts.clone();
+        opts.bubble_style = opts.bubble_style.fg(mood.color());
+        // 眠いときは目を閉じたコマを使う
+        if let (Mood::Sleepy, Some(closed)) = (mood, opts.art.idle.first()) {
+            opts.art.template = closed.clone();
+        }
+        (i18n::tr(mood.comment()), opts)
+    };
+    if once {
+        let cpu = CpuTimes::read()?.usage_since(previous);
+        let (comment, opts) = mood_opts(cpu);
+        return say(
+            &comment,
+            Some(&summary(cpu, memory(), battery())),
+            None,
+            &opts,
+        );
+    }
+
+    let mut term = Term::stdout();
+    clear_screen(&mut term)?;
+    loop {
+        let now = CpuTimes::read()?;
+        let cpu = now.usage_since(previous);
+        previous = now;
+        let (comment, opts) = mood_opts(cpu);
+        let caption = summary(cpu, memory(), battery());
+        let terminal_width = crate::terminal::width(&term);
+        redraw_in_place(
+            &mut term,
+            &render_say(&comment, Some(&caption), terminal_width, &opts)?,
+            &opts,
+        )?;
+        sleep(interval);
+    }
+}
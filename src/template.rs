@@ -0,0 +1,231 @@
+//! アートのテンプレート
+//!
+//! - `$名前$` は差し込み口。`$line1$`, `$line2$`, ... におかずの各行が入る
+//! - `$sign1$`, `$sign2$`, ... のように `line` 以外の名前に番号を付けた口は名前付きの欄。
+//!   おかずとは別の文言 (ドラゴンが持つ看板など) が 1 行ずつ入る
+//! - `$repeat$` だけの行から `$end$` だけの行までは、番号付きの口に入りきらなかった
+//!   おかずの行の数だけ繰り返し、その中の `$line$` に 1 行ずつ入る
+//! - `$$` は `$` そのもの。口として解釈できない `$` もそのまま出力する
+
+use std::collections::BTreeMap;
+
+/// 差し込んだ後の 1 行の部品
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Piece {
+    Art(String),
+    Slot(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Slot(String),
+}
+
+#[derive(Clone, Debug)]
+enum Block {
+    Line(Vec<Segment>),
+    Repeat(Vec<Vec<Segment>>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Template {
+    blocks: Vec<Block>,
+}
+
+const REPEAT: &str = "$repeat$";
+const END: &str = "$end$";
+
+impl Template {
+    pub fn parse(source: &str) -> Self {
+        let mut blocks = Vec::new();
+        let mut repeat: Option<Vec<Vec<Segment>>> = None;
+        for line in source.lines() {
+            match (line.trim(), &mut repeat) {
+                (REPEAT, None) => repeat = Some(Vec::new()),
+                (END, Some(_)) => blocks.extend(repeat.take().map(Block::Repeat)),
+                (_, Some(lines)) => lines.push(segments(line)),
+                (_, None) => blocks.push(Block::Line(segments(line))),
+            }
+        }
+        // 閉じていない繰り返しは最後までとみなす
+        blocks.extend(repeat.map(Block::Repeat));
+        Self { blocks }
+    }
+
+    /// `$line1$` から数えた番号付きの口の数 (最大の番号)
+    pub fn line_slots(&self) -> usize {
+        self.segments()
+            .filter_map(|segment| match segment {
+                Segment::Slot(name) => name.strip_prefix("line")?.parse::<usize>().ok(),
+                Segment::Text(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 名前付きの欄の名前と行数 (最大の番号) を最初に出てくる順に
+    pub fn fields(&self) -> Vec<(String, usize)> {
+        let mut fields: Vec<(String, usize)> = Vec::new();
+        for segment in self.segments() {
+            let Segment::Slot(name) = segment else {
+                continue;
+            };
+            let Some((field, n)) = numbered(name).filter(|(field, _)| *field != "line") else {
+                continue;
+            };
+            match fields.iter_mut().find(|(name, _)| name == field) {
+                Some((_, rows)) => *rows = (*rows).max(n),
+                None => fields.push((field.to_string(), n)),
+            }
+        }
+        fields
+    }
+
+    /// 繰り返しの範囲があれば、おかずの行数に上限が無い
+    pub fn has_repeat(&self) -> bool {
+        self.blocks
+            .iter()
+            .any(|block| matches!(block, Block::Repeat(_)))
+    }
+
+    /// 口の `(テンプレートの行, 表示幅での列, 名前)` (行と列は 0 始まり)
+    pub fn slot_positions(&self) -> Vec<(usize, usize, String)> {
+        let mut positions = Vec::new();
+        let mut row = 0;
+        for block in &self.blocks {
+            let lines = match block {
+                Block::Line(line) => std::slice::from_ref(line),
+                Block::Repeat(lines) => {
+                    // 開始の印の行
+                    row += 1;
+                    lines.as_slice()
+                }
+            };
+            for line in lines {
+                let mut column = 0;
+                for segment in line {
+                    match segment {
+                        Segment::Text(text) => column += console::measure_text_width(text),
+                        Segment::Slot(name) => positions.push((row, column, name.clone())),
+                    }
+                }
+                row += 1;
+            }
+            if matches!(block, Block::Repeat(_)) {
+                row += 1;
+            }
+        }
+        positions
+    }
+
+    /// `rows` を番号付きの口、続いて繰り返しの範囲へ順に差し込む
+    ///
+    /// 値の無い番号付きの口は空、知らない名前の口はそのまま残す
+    pub fn render(&self, rows: &[String]) -> Vec<Vec<Piece>> {
+        self.render_fields(rows, &BTreeMap::new())
+    }
+
+    /// `render` に加えて、名前付きの欄に `fields` の行を差し込む (`fields` に無い欄はそのまま残す)
+    pub fn render_fields(
+        &self,
+        rows: &[String],
+        fields: &BTreeMap<String, Vec<String>>,
+    ) -> Vec<Vec<Piece>> {
+        let fixed = self.line_slots();
+        let extra = rows.get(fixed..).unwrap_or_default();
+        let fill = |line: &[Segment], current: Option<&String>| -> Vec<Piece> {
+            line.iter()
+                .map(|segment| match segment {
+                    Segment::Text(text) => Piece::Art(text.clone()),
+                    Segment::Slot(name) if name == "line" => {
+                        Piece::Slot(current.cloned().unwrap_or_default())
+                    }
+                    Segment::Slot(name) => match numbered(name) {
+                        Some(("line", n)) => {
+                            Piece::Slot(rows.get(n - 1).cloned().unwrap_or_default())
+                        }
+                        Some((field, n)) if fields.contains_key(field) => {
+                            Piece::Slot(fields[field].get(n - 1).cloned().unwrap_or_default())
+                        }
+                        _ => Piece::Art(format!("${}$", name)),
+                    },
+                })
+                .collect()
+        };
+
+        let mut lines = Vec::new();
+        for block in &self.blocks {
+            match block {
+                Block::Line(line) => lines.push(fill(line, None)),
+                Block::Repeat(region) => {
+                    for row in extra {
+                        lines.extend(region.iter().map(|line| fill(line, Some(row))));
+                    }
+                }
+            }
+        }
+        lines
+    }
+
+    fn segments(&self) -> impl Iterator<Item = &Segment> {
+        self.blocks
+            .iter()
+            .flat_map(|block| match block {
+                Block::Line(line) => std::slice::from_ref(line),
+                Block::Repeat(lines) => lines.as_slice(),
+            })
+            .flatten()
+    }
+}
+
+/// `line` が口で始まっていればその長さ (`$line1$` なら 7)
+pub fn slot_at(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix('$')?;
+    let end = rest.find('$')?;
+    is_slot_name(&rest[..end]).then_some(end + 2)
+}
+
+/// `sign2` を `("sign", 2)` に分ける (番号は 1 以上)
+fn numbered(name: &str) -> Option<(&str, usize)> {
+    let field = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let n = name[field.len()..].parse::<usize>().ok()?;
+    (!field.is_empty() && n >= 1).then_some((field, n))
+}
+
+fn is_slot_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn segments(line: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = line;
+    while let Some(i) = rest.find('$') {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(escaped) = rest.strip_prefix("$$") {
+            text.push('$');
+            rest = escaped;
+            continue;
+        }
+        match slot_at(rest) {
+            Some(len) => {
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Slot(rest[1..len - 1].to_string()));
+                rest = &rest[len..];
+            }
+            None => {
+                text.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
@@ -0,0 +1,145 @@
+//! 端末ごとの違いの吸収
+//!
+//! Windows では仮想端末処理 (VT) を有効にしてエスケープシーケンスを使う。有効にできない古い
+//! conhost や `--legacy-console` では色を付けず、画面の消去とカーソル移動をコンソールの API に任せる
+
+use crate::log;
+use console::Term;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static LEGACY: AtomicBool = AtomicBool::new(false);
+
+/// 出力の前に一度だけ呼ぶ。`legacy` でなくても VT を使えなければ互換の描き方にする
+pub fn init(legacy: bool) {
+    let legacy = legacy || !virtual_terminal();
+    if legacy {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+    match legacy {
+        true => log::debug!("コンソール: 色とエスケープシーケンスを使わない互換の描き方"),
+        false => log::debug!("コンソール: VT"),
+    }
+    LEGACY.store(legacy, Ordering::Relaxed);
+}
+
+pub fn legacy() -> bool {
+    LEGACY.load(Ordering::Relaxed)
+}
+
+/// 端末ならエスケープシーケンスを解釈できるか
+///
+/// Windows では console が色を判定するときに VT を有効にするので、その結果を使う
+#[cfg(windows)]
+fn virtual_terminal() -> bool {
+    let term = Term::stdout();
+    !term.is_term() || term.features().colors_supported()
+}
+
+#[cfg(not(windows))]
+fn virtual_terminal() -> bool {
+    true
+}
+
+/// 描くときに使う端末の幅
+///
+/// conhost は最後の桁まで書くと勝手に改行するので、Windows では 1 桁残す
+pub fn width(term: &Term) -> usize {
+    let columns = term.size().1 as usize;
+    match cfg!(windows) {
+        true => columns.saturating_sub(1),
+        false => columns,
+    }
+}
+
+/// 画面を消してカーソルを左上に置く
+pub fn clear_screen<W: Write>(out: &mut W) -> std::io::Result<()> {
+    match legacy() {
+        true => {
+            out.flush()?;
+            Term::stdout().clear_screen()
+        }
+        false => write!(out, "\r\x1b[2J\r\x1b[H"),
+    }
+}
+
+/// カーソルを左上に戻す
+pub fn home<W: Write>(out: &mut W) -> std::io::Result<()> {
+    match legacy() {
+        true => {
+            out.flush()?;
+            Term::stdout().move_cursor_to(0, 0)
+        }
+        false => write!(out, "\x1b[H"),
+    }
+}
+
+/// カーソルを `lines` 行上の行頭に戻す
+pub fn up<W: Write>(out: &mut W, lines: usize) -> std::io::Result<()> {
+    match (legacy(), lines) {
+        (_, 0) => write!(out, "\r"),
+        (true, lines) => {
+            out.flush()?;
+            let term = Term::stdout();
+            term.move_cursor_up(lines)?;
+            term.move_cursor_left(usize::from(u16::MAX))
+        }
+        (false, lines) => write!(out, "\x1b[{}A\r", lines),
+    }
+}
+
+/// カーソルから行末までを消すシーケンス (互換の描き方では行を端末の幅まで空白で埋めてあるので不要)
+pub fn clear_line_end() -> &'static str {
+    match legacy() {
+        true => "",
+        false => "\x1b[K",
+    }
+}
+
+/// カーソルから画面の最後までを消す
+pub fn clear_to_end<W: Write>(out: &mut W) -> std::io::Result<()> {
+    match legacy() {
+        true => {
+            out.flush()?;
+            Term::stdout().clear_to_end_of_screen()
+        }
+        false => write!(out, "\x1b[J"),
+    }
+}
+
+/// OSC 8 のリンクを解釈すると分かっている端末のプログラム (`TERM_PROGRAM`) と種類 (`TERM`)
+const HYPERLINK_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"];
+const HYPERLINK_TERMS: &[&str] = &[
+    "xterm-kitty",
+    "alacritty",
+    "foot",
+    "xterm-ghostty",
+    "wezterm",
+];
+
+/// 標準出力の端末が OSC 8 のリンクを解釈できるか
+///
+/// `FORCE_HYPERLINK` があればそれに従う (`0` なら使わない)。無ければ色を付けられる端末のうち、
+/// 対応していると分かっているものだけで使う
+pub fn hyperlinks() -> bool {
+    if legacy() {
+        return false;
+    }
+    if let Some(force) = std::env::var_os("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if !console::colors_enabled() || !Term::stdout().is_term() {
+        return false;
+    }
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    let vte = var("VTE_VERSION").parse::<u32>().unwrap_or(0);
+    ["WT_SESSION", "KONSOLE_VERSION", "DOMTERM"]
+        .iter()
+        .any(|name| std::env::var_os(name).is_some())
+        || vte >= 5000
+        || HYPERLINK_PROGRAMS.contains(&var("TERM_PROGRAM").as_str())
+        || HYPERLINK_TERMS
+            .iter()
+            .any(|term| var("TERM").starts_with(term))
+}
@@ -0,0 +1,187 @@
+use crate::art::Bubble;
+use crate::config;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub colors: ThemeColors,
+    pub border: Option<Border>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeColors {
+    pub art: Option<String>,
+    pub caption: Option<String>,
+    /// 吹き出しの中の文字
+    pub bubble: Option<String>,
+}
+
+/// 吹き出しの枠線
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Border {
+    pub top_left: String,
+    pub top: String,
+    pub top_right: String,
+    pub left: String,
+    pub right: String,
+    pub bottom_left: String,
+    pub bottom: String,
+    pub bottom_right: String,
+}
+
+impl Border {
+    pub fn is_ascii(&self) -> bool {
+        [
+            &self.top_left,
+            &self.top,
+            &self.top_right,
+            &self.left,
+            &self.right,
+            &self.bottom_left,
+            &self.bottom,
+            &self.bottom_right,
+        ]
+        .iter()
+        .all(|part| part.is_ascii())
+    }
+}
+
+const RETRO: &str = r#"
+[colors]
+art = "green"
+caption = "green.bold"
+bubble = "green.bold"
+
+[border]
+top_left = "+"
+top = "-"
+top_right = "+"
+left = "|"
+right = "|"
+bottom_left = "+"
+bottom = "-"
+bottom_right = "+"
+"#;
+
+const NEON: &str = r#"
+[colors]
+art = "magenta.bold"
+caption = "cyan.bold"
+bubble = "yellow.bold"
+
+[border]
+top_left = "╔"
+top = "═"
+top_right = "╗"
+left = "║"
+right = "║"
+bottom_left = "╚"
+bottom = "═"
+bottom_right = "╝"
+"#;
+
+const MONO: &str = r#"
+[colors]
+art = "white"
+caption = "white.bold"
+bubble = "white.bold"
+"#;
+
+pub const BUILTIN: &[(&str, &str)] = &[("retro", RETRO), ("neon", NEON), ("mono", MONO)];
+
+pub fn themes_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("themes"))
+}
+
+impl Theme {
+    /// 設定ディレクトリのテーマを組み込みテーマより優先する
+    pub fn load(name: &str) -> Result<Self> {
+        if let Some(path) = themes_dir().map(|dir| dir.join(format!("{}.toml", name))) {
+            if path.exists() {
+                let theme = std::fs::read_to_string(&path)?;
+                return toml::from_str(&theme)
+                    .with_context(|| format!("{} の読み込みに失敗しました", path.display()));
+            }
+        }
+        match BUILTIN.iter().find(|(builtin, _)| *builtin == name) {
+            Some((_, theme)) => Ok(toml::from_str(theme)?),
+            None => bail!("テーマ '{}' が見つかりません", name),
+        }
+    }
+}
+
+/// `(名前, 組み込みかどうか)` の一覧
+pub fn list() -> Result<Vec<(String, bool)>> {
+    let mut themes: Vec<(String, bool)> = BUILTIN
+        .iter()
+        .map(|(name, _)| (name.to_string(), true))
+        .collect();
+    if let Some(dir) = themes_dir().filter(|dir| dir.is_dir()) {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                if let Some(name) = path.file_stem() {
+                    let name = name.to_string_lossy().into_owned();
+                    themes.retain(|(theme, _)| *theme != name);
+                    themes.push((name, false));
+                }
+            }
+        }
+    }
+    themes.sort();
+    Ok(themes)
+}
+
+/// テンプレート中の吹き出しの枠線を描き直す
+pub fn apply_border(template: &str, bubble: &Bubble, border: &Border) -> String {
+    let fill = |left: &str, fill: &str, right: &str| {
+        let inner = bubble
+            .width
+            .saturating_sub(console::measure_text_width(left) + console::measure_text_width(right));
+        let fill_width = console::measure_text_width(fill).max(1);
+        let mut line = left.to_string();
+        line.push_str(&fill.repeat(inner / fill_width));
+        line.push_str(&" ".repeat(inner % fill_width));
+        line.push_str(right);
+        line
+    };
+
+    template
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let rest = console::truncate_str(line, bubble.width, "");
+            let rest = &line[rest.len()..];
+            if i == bubble.top {
+                fill(&border.top_left, &border.top, &border.top_right) + rest
+            } else if i == bubble.bottom {
+                fill(&border.bottom_left, &border.bottom, &border.bottom_right) + rest
+            } else if i > bubble.top && i < bubble.bottom {
+                let mut chars = line.chars();
+                chars.next();
+                let inner = chars.as_str();
+                let (interior, rest) = match crate::template::slot_at(inner) {
+                    Some(len) => (&inner[..len], &inner[len..]),
+                    None => {
+                        let interior = console::truncate_str(inner, bubble.width - 2, "");
+                        let len = interior.len();
+                        (&inner[..len], &inner[len..])
+                    }
+                };
+                let rest = match rest.strip_prefix('|') {
+                    Some(rest) => format!("{}{}", border.right, rest),
+                    None => rest.to_string(),
+                };
+                format!("{}{}{}", border.left, interior, rest)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,221 @@
+//! フレームからフレームへの切り替え方
+//!
+//! `anime --transition` とスクリプトの `transition` で選ぶ。切り替えは次のフレームの表示時間の
+//! 始めに入る。新しい効果は `Transition` を実装して `Kind` に足す
+
+use crate::renderer::Renderer;
+use crate::rng::Rng;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub trait Transition {
+    /// 切り替えにかける時間
+    fn duration(&self) -> Duration;
+
+    /// `from` から `to` へ `t` (0.0-1.0) だけ進んだときの行
+    fn blend(&self, from: &[String], to: &[String], t: f64) -> Vec<String>;
+}
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// すぐに切り替える
+    Cut,
+    /// 前のフレームの文字をランダムに次のフレームの文字へ置き換えていく
+    Dissolve,
+}
+
+impl Kind {
+    /// `seed` はランダムに切り替える効果の乱数のシード
+    pub fn build(self, seed: u64) -> Option<Box<dyn Transition>> {
+        match self {
+            Kind::Cut => None,
+            Kind::Dissolve => Some(Box::new(Dissolve { seed })),
+        }
+    }
+}
+
+/// 切り替えの途中を描く間隔
+const STEP: Duration = Duration::from_millis(30);
+
+/// `from` の上に `to` へ切り替わっていく途中を描く (`to` そのものは描かない)
+pub fn run(
+    renderer: &mut dyn Renderer,
+    transition: &dyn Transition,
+    from: &[String],
+    to: &[String],
+    speed: f64,
+) -> Result<()> {
+    let duration = transition.duration().div_f64(speed);
+    let steps = (duration.as_millis() / STEP.as_millis()).max(1) as u32;
+    for n in 1..steps {
+        renderer.redraw(&transition.blend(from, to, n as f64 / steps as f64))?;
+        std::thread::sleep(duration / steps);
+    }
+    Ok(())
+}
+
+/// 文字ごとにランダムな順番で置き換える
+struct Dissolve {
+    seed: u64,
+}
+
+impl Dissolve {
+    /// 行 `row` の `column` 桁目が置き換わる進み具合 (0.0-1.0)
+    fn threshold(&self, row: usize, column: usize) -> f64 {
+        let cell = ((row as u64) << 32 | column as u64).wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (Rng::new(self.seed ^ cell).next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Transition for Dissolve {
+    fn duration(&self) -> Duration {
+        Duration::from_millis(300)
+    }
+
+    fn blend(&self, from: &[String], to: &[String], t: f64) -> Vec<String> {
+        (0..from.len().max(to.len()))
+            .map(|row| {
+                let from = columns(from.get(row).map_or("", String::as_str));
+                let to = columns(to.get(row).map_or("", String::as_str));
+                let width = from.len().max(to.len());
+                let mut cells = Vec::new();
+                let mut column = 0;
+                while column < width {
+                    let source = match self.threshold(row, column) < t {
+                        true => &to,
+                        false => &from,
+                    };
+                    // 全角文字の右半分だけが選ばれたら空白にする
+                    let cell = match source.get(column) {
+                        Some(Some(cell)) => cell.clone(),
+                        _ => Cell::blank(),
+                    };
+                    column += cell.width;
+                    cells.push(cell);
+                }
+                join(&cells)
+            })
+            .collect()
+    }
+}
+
+/// 色の指定と 1 文字
+#[derive(Clone)]
+pub struct Cell {
+    /// この文字に効いている SGR のシーケンス
+    pub style: String,
+    pub c: char,
+    pub width: usize,
+    /// `c` に続く幅の無い文字 (結合文字など)
+    pub combining: String,
+    /// この文字が入っている OSC 8 のリンクの URL (無ければ空)
+    pub link: String,
+}
+
+impl Cell {
+    pub fn blank() -> Self {
+        Self {
+            style: String::new(),
+            c: ' ',
+            width: 1,
+            combining: String::new(),
+            link: String::new(),
+        }
+    }
+}
+
+/// 行を桁ごとに分ける。全角文字の右半分の桁は `None`
+pub fn columns(line: &str) -> Vec<Option<Cell>> {
+    let mut columns: Vec<Option<Cell>> = Vec::new();
+    let mut style = String::new();
+    let mut link = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        // OSC は BEL か ST までまとめて読み、リンク (OSC 8) の URL だけを覚える
+        if c == '\x1b' && chars.clone().next() == Some(']') {
+            let mut osc = String::new();
+            for c in chars.by_ref() {
+                if c == '\x07' || c == '\\' && osc.ends_with('\x1b') {
+                    break;
+                }
+                osc.push(c);
+            }
+            let osc = osc.trim_end_matches('\x1b');
+            if let Some(params) = osc.strip_prefix("]8;") {
+                link = params
+                    .split_once(';')
+                    .map_or("", |(_, url)| url)
+                    .to_string();
+            }
+            continue;
+        }
+        if c == '\x1b' {
+            let mut sequence = String::from(c);
+            for c in chars.by_ref() {
+                sequence.push(c);
+                if c != '[' && ('\x40'..='\x7e').contains(&c) {
+                    break;
+                }
+            }
+            match sequence.as_str() {
+                "\x1b[0m" | "\x1b[m" => style.clear(),
+                sequence if sequence.ends_with('m') => style.push_str(sequence),
+                // 色以外のシーケンスは桁を持たないので落とす
+                _ => {}
+            }
+            continue;
+        }
+        let width = console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+        if width == 0 {
+            // 前の文字にまとめる (行頭なら落とす)
+            if let Some(Some(cell)) = columns.iter_mut().rev().find(|cell| cell.is_some()) {
+                cell.combining.push(c);
+            }
+            continue;
+        }
+        columns.push(Some(Cell {
+            style: style.clone(),
+            c,
+            width,
+            combining: String::new(),
+            link: link.clone(),
+        }));
+        columns.extend((1..width).map(|_| None));
+    }
+    columns
+}
+
+pub fn join(cells: &[Cell]) -> String {
+    let mut out = String::new();
+    let mut style = "";
+    let mut link = "";
+    for cell in cells {
+        if cell.link != link {
+            if !link.is_empty() {
+                out.push_str("\x1b]8;;\x1b\\");
+            }
+            if !cell.link.is_empty() {
+                out.push_str(&format!("\x1b]8;;{}\x1b\\", cell.link));
+            }
+            link = &cell.link;
+        }
+        if cell.style != style {
+            if !style.is_empty() {
+                out.push_str("\x1b[0m");
+            }
+            out.push_str(&cell.style);
+            style = &cell.style;
+        }
+        out.push(cell.c);
+        out.push_str(&cell.combining);
+    }
+    if !style.is_empty() {
+        out.push_str("\x1b[0m");
+    }
+    if !link.is_empty() {
+        out.push_str("\x1b]8;;\x1b\\");
+    }
+    out
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+/// 行バッファとエコーを止めた制御端末 (drop で元に戻す)
+///
+/// Ctrl-C もシグナルにせず 1 バイトとして読むので、端末の設定を戻し損ねない
+#[cfg(unix)]
+pub struct RawTty {
+    tty: std::fs::File,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl RawTty {
+    pub fn open() -> Option<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let tty = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .ok()?;
+        let fd = tty.as_raw_fd();
+
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+            return None;
+        }
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+            return None;
+        }
+
+        Some(Self { tty, original })
+    }
+
+    pub fn write_all(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        use std::io::Write;
+
+        self.tty.write_all(bytes)?;
+        self.tty.flush()
+    }
+
+    /// `timeout` (None なら無期限) まで待って読めた分を返す
+    pub fn read_timeout(&mut self, buf: &mut [u8], timeout: Option<Duration>) -> usize {
+        use std::io::Read;
+        use std::os::unix::io::AsRawFd;
+
+        let mut pollfd = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout.map_or(-1, |timeout| {
+            timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int
+        });
+        if unsafe { libc::poll(&mut pollfd, 1, timeout) } <= 0 {
+            return 0;
+        }
+        self.tty.read(buf).unwrap_or(0)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawTty {
+    fn drop(&mut self) {
+        use std::os::unix::io::AsRawFd;
+
+        unsafe { libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSANOW, &self.original) };
+    }
+}
+
+#[cfg(not(unix))]
+pub struct RawTty;
+
+#[cfg(not(unix))]
+impl RawTty {
+    pub fn open() -> Option<Self> {
+        None
+    }
+
+    pub fn write_all(&mut self, _bytes: &[u8]) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    pub fn read_timeout(&mut self, _buf: &mut [u8], _timeout: Option<Duration>) -> usize {
+        0
+    }
+}
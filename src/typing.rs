@@ -0,0 +1,90 @@
+use crate::{redraw, render_say, RenderOptions, Script};
+use anyhow::{bail, Result};
+use console::Term;
+use std::io::{BufRead, Write};
+use std::time::Instant;
+
+/// 1 単語とみなす文字数 (WPM の慣習)
+const CHARS_PER_WORD: f64 = 5.0;
+
+/// おかずを 1 つずつ表示して打ち込ませ、速さと正確さを測る
+pub fn typing(script: &Script, opts: &RenderOptions) -> Result<()> {
+    let targets: Vec<String> = script
+        .side_dishes
+        .iter()
+        .map(|side_dish| {
+            opts.expand(side_dish.get(opts.lang))
+                .lines()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|target| !target.trim().is_empty())
+        .collect();
+    if targets.is_empty() {
+        bail!("練習するおかずがありません");
+    }
+
+    let mut term = Term::stdout();
+    let mut stdin = std::io::stdin().lock();
+    let mut correct = 0;
+    let mut total = 0;
+    let mut typed_chars = 0;
+    let start = Instant::now();
+    for (i, target) in targets.iter().enumerate() {
+        let terminal_width = crate::terminal::width(&term);
+        let caption = format!("{} / {} を入力してください", i + 1, targets.len());
+        redraw(
+            &mut term,
+            &render_say(target, Some(&caption), terminal_width, opts)?,
+            opts,
+        )?;
+        write!(term, "> ")?;
+        term.flush()?;
+
+        let mut typed = String::new();
+        if stdin.read_line(&mut typed)? == 0 {
+            break;
+        }
+        let typed = typed.trim_end_matches(['\r', '\n']);
+        let (matched, length) = compare(target, typed);
+        correct += matched;
+        total += length;
+        typed_chars += typed.chars().count();
+    }
+
+    let minutes = start.elapsed().as_secs_f64() / 60.0;
+    let wpm = match minutes > 0.0 {
+        true => typed_chars as f64 / CHARS_PER_WORD / minutes,
+        false => 0.0,
+    };
+    let accuracy = match total {
+        0 => 0.0,
+        _ => correct as f64 * 100.0 / total as f64,
+    };
+    let terminal_width = crate::terminal::width(&term);
+    redraw(
+        &mut term,
+        &render_say(
+            &format!("{:.1} WPM\n正確さ {:.1}%", wpm, accuracy),
+            Some(&format!(
+                "{} 文字中 {} 文字正解 / {:.1} 秒",
+                total,
+                correct,
+                minutes * 60.0
+            )),
+            terminal_width,
+            opts,
+        )?,
+        opts,
+    )?;
+
+    Ok(())
+}
+
+/// 同じ位置の文字が一致した数と、長い方の文字数
+fn compare(target: &str, typed: &str) -> (usize, usize) {
+    let target: Vec<char> = target.chars().collect();
+    let typed: Vec<char> = typed.chars().collect();
+    let matched = target.iter().zip(&typed).filter(|(a, b)| a == b).count();
+    (matched, target.len().max(typed.len()))
+}
@@ -0,0 +1,23 @@
+//! wasm32 向けに書き出す関数
+//!
+//! 描くのは `ffi` と同じ `fsd_render` と `fsd_free`。JS 側は `fsd_alloc` で確保した領域に
+//! おかずを NUL 終端の UTF-8 で書いて `fsd_render` に渡し、戻り値から NUL までを読む
+
+/// `len` バイトの領域を確保する
+#[no_mangle]
+pub extern "C" fn fsd_alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+/// `fsd_alloc` で確保した領域を返す
+///
+/// # Safety
+///
+/// `ptr` と `len` は `fsd_alloc` の戻り値と引数そのままであること
+#[no_mangle]
+pub unsafe extern "C" fn fsd_dealloc(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
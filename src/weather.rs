@@ -0,0 +1,171 @@
+//! wttr.in の JSON (`?format=j1`) から今日の天気を読み上げる
+
+use crate::{fetch, i18n, i18n::Lang, json, say, RenderOptions};
+use anyhow::{anyhow, Result};
+use console::Color;
+use serde::Deserialize;
+
+/// 天気の応答の上限 (バイト)
+const MAX_BYTES: u64 = 256 * 1024;
+
+#[derive(Deserialize)]
+struct Report {
+    current_condition: Vec<Current>,
+    #[serde(default)]
+    nearest_area: Vec<Area>,
+    #[serde(default)]
+    weather: Vec<Day>,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    #[serde(rename = "temp_C")]
+    temp: String,
+    #[serde(rename = "weatherCode")]
+    code: String,
+    #[serde(rename = "weatherDesc", default)]
+    description: Vec<Named>,
+    #[serde(default)]
+    lang_ja: Vec<Named>,
+}
+
+#[derive(Deserialize)]
+struct Area {
+    #[serde(rename = "areaName", default)]
+    name: Vec<Named>,
+}
+
+#[derive(Deserialize)]
+struct Day {
+    #[serde(rename = "maxtempC")]
+    max: String,
+    #[serde(rename = "mintempC")]
+    min: String,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    value: String,
+}
+
+/// 天気ごとのドラゴンの機嫌
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mood {
+    Sunny,
+    Cloudy,
+    Foggy,
+    Rainy,
+    Snowy,
+    Stormy,
+}
+
+impl Mood {
+    /// wttr.in (World Weather Online) の天気コードから
+    fn from_code(code: u32) -> Self {
+        match code {
+            113 => Self::Sunny,
+            143 | 248 | 260 => Self::Foggy,
+            200 | 386 | 389 | 392 | 395 => Self::Stormy,
+            176 | 263 | 266 | 281 | 284 | 293..=314 | 353..=359 => Self::Rainy,
+            179 | 182 | 185 | 227 | 230 | 317..=350 | 362..=377 => Self::Snowy,
+            _ => Self::Cloudy,
+        }
+    }
+
+    fn mark(self) -> &'static str {
+        match self {
+            Self::Sunny => "☀",
+            Self::Cloudy => "☁",
+            Self::Foggy => "≋",
+            Self::Rainy => "☂",
+            Self::Snowy => "☃",
+            Self::Stormy => "⚡",
+        }
+    }
+
+    fn comment(self) -> &'static str {
+        match self {
+            Self::Sunny => "いい天気だ！",
+            Self::Cloudy => "今日はくもりだね",
+            Self::Foggy => "霧に気をつけて",
+            Self::Rainy => "傘を忘れずに",
+            Self::Snowy => "あったかくしてね",
+            Self::Stormy => "今日はおうちにいよう…",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Sunny => Color::Yellow,
+            Self::Cloudy => Color::White,
+            Self::Foggy => Color::Cyan,
+            Self::Rainy => Color::Blue,
+            Self::Snowy => Color::White,
+            Self::Stormy => Color::Magenta,
+        }
+    }
+}
+
+pub fn weather(location: Option<&str>, api: &str, mut opts: RenderOptions) -> Result<()> {
+    let url = format!(
+        "{}/{}?format=j1&lang={}",
+        api.trim_end_matches('/'),
+        encode(location.unwrap_or_default()),
+        opts.lang.code()
+    );
+    let report: Report = json::from_str(&fetch::text(&url, MAX_BYTES)?)?;
+    let current = report
+        .current_condition
+        .first()
+        .ok_or_else(|| anyhow!("天気の応答に現在の天気がありません"))?;
+
+    let mood = Mood::from_code(current.code.parse().unwrap_or(0));
+    let description = match opts.lang {
+        Lang::Ja => current.lang_ja.first().or(current.description.first()),
+        Lang::En => current.description.first(),
+    }
+    .map(|named| named.value.trim())
+    .unwrap_or_default();
+
+    let mut side_dish = format!("{} {}℃", mood.mark(), current.temp);
+    if let Some(today) = report.weather.first() {
+        side_dish.push_str(&format!(" ↑{} ↓{}", today.max, today.min));
+    }
+    if !description.is_empty() {
+        side_dish.push('\n');
+        side_dish.push_str(description);
+    }
+    let area = location
+        .map(str::to_string)
+        .or_else(|| {
+            let area = report.nearest_area.first()?.name.first()?;
+            Some(area.value.clone())
+        })
+        .unwrap_or_default();
+    let caption = match area.is_empty() {
+        true => mood.comment().to_string(),
+        false => format!("{}の天気: {}", area, mood.comment()),
+    };
+    let caption = i18n::tr(&caption);
+
+    opts.bubble_style = opts.bubble_style.fg(mood.color());
+    // 応答の文言でエスケープシーケンスを送れないようにする
+    let side_dish = opts.sanitize(&side_dish).into_owned();
+    let caption = opts.sanitize(&caption).into_owned();
+    say(&side_dish, Some(&caption), None, &opts)
+}
+
+/// URL のパスに入れられない文字を % で符号化する
+fn encode(text: &str) -> String {
+    let mut out = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => {
+                out.push(byte as char)
+            }
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
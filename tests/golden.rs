@@ -0,0 +1,116 @@
+//! 吹き出しのレイアウトのゴールデンテスト
+//!
+//! 期待する出力は `tests/golden/<名前>.txt`。レイアウトを意図して変えたときは
+//! `UPDATE_GOLDEN=1 cargo test --test golden` で書き直して差分を確かめる
+
+use fav_say_dragon::layout::{Layout, Overflow};
+use std::path::PathBuf;
+
+/// 端末の幅の代わり (行末の埋め方も固定する)
+const WIDTH: usize = 72;
+
+fn assert_golden(name: &str, lines: &[String]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", name));
+    let actual = lines.join("\n") + "\n";
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{} を読み込めません: {}", path.display(), e));
+    assert_eq!(actual, expected, "{} と違います", path.display());
+}
+
+fn frame(side_dish: &str, caption: &str, overflow: Overflow) -> Vec<String> {
+    let layout = Layout {
+        overflow,
+        ..Layout::default()
+    };
+    layout.frame(side_dish, caption, WIDTH).unwrap()
+}
+
+#[test]
+fn ascii() {
+    assert_golden(
+        "ascii",
+        &frame("karaage", "fried chicken", Overflow::Truncate),
+    );
+}
+
+#[test]
+fn cjk() {
+    assert_golden(
+        "cjk",
+        &frame("からあげ", "好きな総菜発表ドラゴン", Overflow::Truncate),
+    );
+}
+
+#[test]
+fn emoji() {
+    assert_golden("emoji", &frame("🍤えびフライ🍤", "🐉", Overflow::Truncate));
+}
+
+#[test]
+fn multi_line() {
+    assert_golden(
+        "multi_line",
+        &frame("えび\nフライ\nタルタル", "", Overflow::Truncate),
+    );
+}
+
+#[test]
+fn wraps_long_line() {
+    assert_golden(
+        "wraps_long_line",
+        &frame(
+            "いろはにほへとちりぬるをわかよたれそつねならむ",
+            "",
+            Overflow::Truncate,
+        ),
+    );
+}
+
+#[test]
+fn truncates_overflow() {
+    let side_dish = "一\n二\n三\n四\n五\n六\n七\n八\n九";
+    assert_golden(
+        "truncates_overflow",
+        &frame(side_dish, "", Overflow::Truncate),
+    );
+}
+
+#[test]
+fn ellipsis_overflow() {
+    let side_dish = "一\n二\n三\n四\n五\n六\n七\n八\n九";
+    assert_golden(
+        "ellipsis_overflow",
+        &frame(side_dish, "", Overflow::Ellipsis),
+    );
+}
+
+#[test]
+fn error_overflow() {
+    let layout = Layout {
+        overflow: Overflow::Error,
+        ..Layout::default()
+    };
+    assert!(layout
+        .dragon("一\n二\n三\n四\n五\n六\n七\n八\n九", WIDTH)
+        .is_err());
+    assert!(layout.dragon("からあげ", WIDTH).is_ok());
+}
+
+#[test]
+fn empty() {
+    assert_golden("empty", &frame("", "", Overflow::Truncate));
+}
+
+#[test]
+fn render_without_padding() {
+    assert_golden(
+        "render_without_padding",
+        &fav_say_dragon::render("からあげ", 0),
+    );
+}